@@ -1,9 +1,9 @@
 use std::num::Bounded;
 use std::rc::Rc;
 use std::cell::RefCell;
-use nalgebra::na::Translation;
+use nalgebra::na::{Translation, Indexable};
 use nalgebra::na;
-use bounding_volume::{HasBoundingVolume, BoundingVolume, LooseBoundingVolume};
+use bounding_volume::{HasBoundingVolume, BoundingVolume, LooseBoundingVolume, SurfaceArea};
 use math::{Scalar, Vect, Matrix};
 
 /// Trait of objects that can be bounded by an AABB.
@@ -109,6 +109,29 @@ impl LooseBoundingVolume for AABB {
     }
 }
 
+impl SurfaceArea for AABB {
+    #[inline]
+    fn surface_area(&self) -> Scalar {
+        let extents = self.maxs - self.mins;
+        let mut res = na::zero::<Scalar>();
+
+        // Sum of the areas of each pair of opposite faces.
+        for i in range(0, na::dim::<Vect>()) {
+            let mut face: Scalar = na::one();
+
+            for j in range(0, na::dim::<Vect>()) {
+                if i != j {
+                    face = face * extents.at(j)
+                }
+            }
+
+            res = res + face
+        }
+
+        res * na::cast::<f64, Scalar>(2.0)
+    }
+}
+
 impl Translation<Vect> for AABB
 {
     #[inline]
@@ -7,7 +7,11 @@ use math::{Scalar, Matrix};
 impl HasAABB for Compound {
     #[inline]
     fn aabb(&self, m: &Matrix) -> AABB {
-        let bv              = self.bvt().root_bounding_volume().unwrap();
+        let bv = match self.bvt().root_bounding_volume() {
+            Some(bv) => bv,
+            None     => return AABB::new_invalid() // The geometry is empty.
+        };
+
         let ls_center       = bv.translation();
         let center          = m.transform(&ls_center);
         let half_extents    = (bv.maxs() - *bv.mins()) / na::cast::<f32, Scalar>(2.0);
@@ -0,0 +1,61 @@
+use nalgebra::na::{Vec3, Translation, Rotate};
+use nalgebra::na;
+use bounding_volume::{HasAABB, AABB};
+use geom::Torus;
+use math::{Scalar, Matrix};
+
+impl HasAABB for Torus {
+    fn aabb(&self, m: &Matrix) -> AABB {
+        // The core circle extends by `R * sqrt(1 - n_i²)` along the i-th axis, `n` being the torus
+        // axis.
+        let axis   = m.rotate(&Vec3::new(na::zero(), na::one(), na::zero()));
+        let extent = |n: Scalar| -> Scalar {
+            let sq_sin = (na::one::<Scalar>() - n * n).max(na::zero());
+
+            self.major_radius() * sq_sin.sqrt() + self.minor_radius()
+        };
+
+        let half_extents = Vec3::new(extent(axis.x), extent(axis.y), extent(axis.z));
+        let center       = m.translation();
+
+        AABB::new(center - half_extents, center + half_extents)
+    }
+}
+
+#[cfg(test, f64)]
+mod test {
+    use std::num::Float;
+    use nalgebra::na::{Vec3, Iso3, Transform};
+    use nalgebra::na;
+    use bounding_volume::HasAABB;
+    use geom::Torus;
+
+    #[test]
+    fn test_torus_aabb() {
+        let torus = Torus::new(2.0f64, 0.5);
+        let m     = Iso3::new(Vec3::new(1.0f64, -2.0, 3.0), Vec3::new(0.3f64, -0.5, 0.7));
+        let aabb  = torus.aabb(&m);
+
+        // Sample the torus surface, and check the AABB is tight around it.
+        let two_pi: f64 = Float::two_pi();
+        let n           = 200u;
+        let mut mins    = Vec3::new(Float::infinity(), Float::infinity(), Float::infinity());
+        let mut maxs    = -mins;
+
+        for i in range(0u, n) {
+            let (s1, c1) = (two_pi * (i as f64) / (n as f64)).sin_cos();
+
+            for j in range(0u, n) {
+                let (s2, c2) = (two_pi * (j as f64) / (n as f64)).sin_cos();
+                let radial   = 2.0 + 0.5 * c2;
+                let pt       = m.transform(&Vec3::new(radial * c1, 0.5 * s2, radial * s1));
+
+                mins = na::inf(&mins, &pt);
+                maxs = na::sup(&maxs, &pt);
+            }
+        }
+
+        assert!(na::approx_eq_eps(aabb.mins(), &mins, &1.0e-3));
+        assert!(na::approx_eq_eps(aabb.maxs(), &maxs, &1.0e-3));
+    }
+}
@@ -2,7 +2,7 @@ use std::num::Zero;
 use nalgebra::na::{Translation, Norm};
 use nalgebra::na;
 use math::{Scalar, Vect, Matrix};
-use bounding_volume::{BoundingVolume, LooseBoundingVolume};
+use bounding_volume::{BoundingVolume, LooseBoundingVolume, SurfaceArea};
 use bounding_volume;
 
 /// Trait implemented by objects having a bounding sphere.
@@ -128,6 +128,36 @@ impl LooseBoundingVolume for BoundingSphere {
     }
 }
 
+#[dim2]
+impl SurfaceArea for BoundingSphere {
+    #[inline]
+    fn surface_area(&self) -> Scalar {
+        let two_pi: Scalar = Float::two_pi();
+
+        two_pi * self.radius
+    }
+}
+
+#[dim3]
+impl SurfaceArea for BoundingSphere {
+    #[inline]
+    fn surface_area(&self) -> Scalar {
+        let pi: Scalar = Float::pi();
+
+        na::cast::<f64, Scalar>(4.0) * pi * self.radius * self.radius
+    }
+}
+
+#[dim4]
+impl SurfaceArea for BoundingSphere {
+    #[inline]
+    fn surface_area(&self) -> Scalar {
+        let pi: Scalar = Float::pi();
+
+        na::cast::<f64, Scalar>(2.0) * pi * pi * self.radius * self.radius * self.radius
+    }
+}
+
 impl Translation<Vect> for BoundingSphere {
     #[inline]
     fn translation(&self) -> Vect {
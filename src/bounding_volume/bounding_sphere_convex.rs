@@ -1,14 +1,38 @@
-use nalgebra::na::Transform;
 use bounding_volume::{BoundingSphere, HasBoundingSphere};
-use bounding_volume;
 use geom::Convex;
 use math::Matrix;
 
 impl HasBoundingSphere for Convex {
     #[inline]
     fn bounding_sphere(&self, m: &Matrix) -> BoundingSphere {
-        let (center, radius) = bounding_volume::point_cloud_bounding_sphere(self.pts());
+        self.local_bounding_sphere().transform_by(m)
+    }
+}
+
+#[cfg(test, dim3, f64)]
+mod test {
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use bounding_volume::HasBoundingSphere;
+    use geom::Convex;
+
+    #[test]
+    fn test_convex_bounding_sphere() {
+        // The corners of a cube.
+        let mut pts = Vec::new();
+
+        for i in range(0u, 8) {
+            pts.push(Vec3::new(if i & 1 == 0 { -1.0f64 } else { 1.0 },
+                               if i & 2 == 0 { -1.0f64 } else { 1.0 },
+                               if i & 4 == 0 { -1.0f64 } else { 1.0 }));
+        }
+
+        let convex = Convex::new_with_margin(pts.as_slice(), 0.1);
+        let m      = Iso3::new(Vec3::new(1.0f64, 2.0, 3.0), Vec3::new(0.0, 1.0, 0.0));
+        let bs     = convex.bounding_sphere(&m);
 
-        BoundingSphere::new(m.transform(&center), radius + self.margin())
+        assert!(na::approx_eq(bs.center(), &Vec3::new(1.0, 2.0, 3.0)));
+        assert!(na::approx_eq(&bs.radius(), &(3.0f64.sqrt() + 0.1)));
+        assert!(bs == convex.local_bounding_sphere().transform_by(&m));
     }
 }
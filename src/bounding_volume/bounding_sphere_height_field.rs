@@ -0,0 +1,17 @@
+use nalgebra::na::{Translation, Transform};
+use nalgebra::na;
+use bounding_volume::{BoundingSphere, HasBoundingSphere};
+use geom::HeightField;
+use math::{Scalar, Matrix};
+
+impl HasBoundingSphere for HeightField {
+    #[inline]
+    fn bounding_sphere(&self, m: &Matrix) -> BoundingSphere {
+        // The margin is already included in the AABB.
+        let aabb   = self.local_aabb();
+        let center = aabb.translation();
+        let radius = na::norm(&(*aabb.maxs() - *aabb.mins())) / na::cast::<f64, Scalar>(2.0);
+
+        BoundingSphere::new(m.transform(&center), radius)
+    }
+}
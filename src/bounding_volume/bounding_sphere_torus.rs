@@ -0,0 +1,15 @@
+use nalgebra::na::Translation;
+use math::Matrix;
+use bounding_volume::{HasBoundingSphere, BoundingSphere};
+use geom::Torus;
+
+
+impl HasBoundingSphere for Torus {
+    #[inline]
+    fn bounding_sphere(&self, m: &Matrix) -> BoundingSphere {
+        let center = m.translation();
+        let radius = self.major_radius() + self.minor_radius();
+
+        BoundingSphere::new(center, radius)
+    }
+}
@@ -26,3 +26,206 @@ pub fn point_cloud_bounding_sphere_with_center<N: Float, V: FloatVec<N>>(pts: &[
 pub fn point_cloud_bounding_sphere<N: Float + Cast<f64>, V: FloatVec<N>>(pts: &[V]) -> (V, N) {
     point_cloud_bounding_sphere_with_center(pts, utils::center(pts))
 }
+
+
+/// Computes the minimal bounding sphere of a set of point.
+///
+/// This uses Welzl's algorithm with the move-to-front heuristic. Its expected running time is
+/// linear wrt. the number of points but with a large constant.
+pub fn point_cloud_minimal_bounding_sphere<N: Float + Cast<f64>, V: FloatVec<N> + Clone>(pts: &[V]) -> (V, N) {
+    assert!(pts.len() > 0, "Cannot compute the bounding sphere of an empty point cloud.");
+
+    let mut order   = Vec::from_fn(pts.len(), |i| i);
+    let mut support = Vec::with_capacity(na::dim::<V>() + 1);
+    let empty       = (pts[0].clone(), -na::one::<N>());
+
+    let (center, _) = welzl_mtf(pts, &mut order, pts.len(), &mut support, empty);
+
+    // The radius is recomputed to make sure the rounding errors did not leave any point out.
+    point_cloud_bounding_sphere_with_center(pts, center)
+}
+
+// Computes the minimal sphere enclosing the first `end` points of `pts` (in the order given by
+// `order`) and having the points of `support` on its boundary. `ball` is the sphere having only
+// the points of `support` on its boundary. Spheres are represented by their center and squared
+// radius.
+fn welzl_mtf<N: Float + Cast<f64>, V: FloatVec<N> + Clone>(pts:     &[V],
+                                                          order:   &mut Vec<uint>,
+                                                          end:     uint,
+                                                          support: &mut Vec<V>,
+                                                          ball:    (V, N))
+                                                          -> (V, N) {
+    let mut ball = ball;
+
+    if support.len() == na::dim::<V>() + 1 {
+        return ball
+    }
+
+    let _eps: N = Float::epsilon();
+    let _tol    = _eps * na::cast(100.0f64);
+
+    for i in range(0u, end) {
+        let pt = &pts[*order.get(i)];
+
+        if na::sqnorm(&(*pt - *ball.ref0())) > *ball.ref1() * (na::one::<N>() + _tol) {
+            support.push(pt.clone());
+
+            match support_ball(support.as_slice()) {
+                Some(sball) => {
+                    ball = welzl_mtf(pts, order, i, support, sball);
+
+                    // Move to front, so that this point is tested first by the next calls.
+                    let id = order.remove(i).unwrap();
+                    order.insert(0, id);
+                },
+                None => {
+                    // The support is affinely dependent: this might happen with degenerate
+                    // inputs (e.g. more than `dim + 1` cospherical points). Ignoring the point
+                    // is fine since the final radius is recomputed from every point.
+                }
+            }
+
+            let _ = support.pop();
+        }
+    }
+
+    ball
+}
+
+// Computes the smallest sphere having all the points of `support` on its boundary. Returns `None`
+// if the points are affinely dependent.
+fn support_ball<N: Float + Cast<f64>, V: FloatVec<N> + Clone>(support: &[V]) -> Option<(V, N)> {
+    let p0 = &support[0];
+    let m  = support.len() - 1;
+
+    if m == 0 {
+        return Some((p0.clone(), na::zero()))
+    }
+
+    // The center is `p0 + sum(lambda_j * v_j)` where the `v_j` are the edges from `p0`. It is
+    // equidistant to all the points iff `sum(2 * (v_i . v_j) * lambda_j) = v_i . v_i` for all `i`.
+    let vs: Vec<V> = support.slice_from(1).iter().map(|p| *p - *p0).collect();
+    let _2: N      = na::cast(2.0f64);
+    let w          = m + 1;
+    let mut sys    = Vec::from_elem(m * w, na::zero::<N>());
+    let mut scale  = na::zero::<N>();
+
+    for i in range(0u, m) {
+        for j in range(0u, m) {
+            *sys.get_mut(i * w + j) = _2 * na::dot(&vs[i], &vs[j]);
+        }
+
+        let sqn = na::sqnorm(&vs[i]);
+        *sys.get_mut(i * w + m) = sqn;
+        scale = scale.max(sqn);
+    }
+
+    // Gaussian elimination with partial pivoting.
+    let _eps: N = Float::epsilon();
+    let tol     = _eps * na::cast(100.0f64) * scale;
+
+    for k in range(0u, m) {
+        let mut pivot = k;
+
+        for i in range(k + 1, m) {
+            if sys[i * w + k].abs() > sys[pivot * w + k].abs() {
+                pivot = i;
+            }
+        }
+
+        if sys[pivot * w + k].abs() <= tol {
+            return None
+        }
+
+        for j in range(k, w) {
+            sys.as_mut_slice().swap(k * w + j, pivot * w + j);
+        }
+
+        for i in range(k + 1, m) {
+            let factor = sys[i * w + k] / sys[k * w + k];
+
+            for j in range(k, w) {
+                let sub = factor * sys[k * w + j];
+                *sys.get_mut(i * w + j) = sys[i * w + j] - sub;
+            }
+        }
+    }
+
+    let mut lambdas = Vec::from_elem(m, na::zero::<N>());
+
+    for i in range(0u, m).rev() {
+        let mut rhs = sys[i * w + m];
+
+        for j in range(i + 1, m) {
+            rhs = rhs - sys[i * w + j] * lambdas[j];
+        }
+
+        *lambdas.get_mut(i) = rhs / sys[i * w + i];
+    }
+
+    let mut center = p0.clone();
+
+    for (v, lambda) in vs.iter().zip(lambdas.iter()) {
+        center = center + *v * *lambda;
+    }
+
+    let sqradius = na::sqnorm(&(center - *p0));
+
+    Some((center, sqradius))
+}
+
+#[cfg(test, dim3, f64)]
+mod test {
+    use nalgebra::na::Vec3;
+    use nalgebra::na;
+    use bounding_volume;
+
+    // The corners of a cube, with a dense cluster of points close to one of them.
+    fn skewed_cube() -> Vec<Vec3<f64>> {
+        let mut res = Vec::new();
+
+        for i in range(0u, 8) {
+            let x = if i & 1 == 0 { -1.0 } else { 1.0 };
+            let y = if i & 2 == 0 { -1.0 } else { 1.0 };
+            let z = if i & 4 == 0 { -1.0 } else { 1.0 };
+
+            res.push(Vec3::new(x, y, z));
+        }
+
+        for i in range(0u, 100) {
+            let t = i as f64 / 100.0;
+            res.push(Vec3::new(0.9, 0.9 - 0.1 * t, 0.8 + 0.1 * t));
+        }
+
+        res
+    }
+
+    #[test]
+    fn test_minimal_bounding_sphere() {
+        let pts = skewed_cube();
+
+        let (center, radius)  = bounding_volume::point_cloud_minimal_bounding_sphere(pts.as_slice());
+        let (_, naive_radius) = bounding_volume::point_cloud_bounding_sphere(pts.as_slice());
+
+        assert!(na::approx_eq(&center, &na::zero()));
+        assert!(na::approx_eq(&radius, &3.0f64.sqrt()));
+        assert!(naive_radius > radius * 1.1);
+    }
+
+    #[test]
+    fn test_minimal_bounding_sphere_degenerate() {
+        // Coplanar cospherical points, and duplicates.
+        let pts = [ Vec3::new(1.0f64, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0),
+                    Vec3::new(0.0, -1.0, 0.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0) ];
+
+        let (center, radius) = bounding_volume::point_cloud_minimal_bounding_sphere(pts.as_slice());
+
+        assert!(na::approx_eq(&center, &na::zero()));
+        assert!(na::approx_eq(&radius, &1.0));
+
+        let (center, radius) = bounding_volume::point_cloud_minimal_bounding_sphere([ Vec3::new(1.0f64, 2.0, 3.0) ].as_slice());
+
+        assert!(na::approx_eq(&center, &Vec3::new(1.0, 2.0, 3.0)));
+        assert!(radius == 0.0);
+    }
+}
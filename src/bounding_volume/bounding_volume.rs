@@ -33,3 +33,13 @@ pub trait LooseBoundingVolume : BoundingVolume {
     /// Creates a new, enlarged version, of this bounding volume.
     fn loosened(&self, Scalar) -> Self;
 }
+
+/// Trait of bounding volumes with a measurable boundary.
+///
+/// The surface area of a bounding volume is proportional to the probability of it being hit by a
+/// random ray. This makes it a good cost metric for bounding volume hierarchies. In 2D, this is
+/// the perimeter.
+pub trait SurfaceArea {
+    /// The area of the boundary of this bounding volume.
+    fn surface_area(&self) -> Scalar;
+}
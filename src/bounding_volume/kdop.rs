@@ -0,0 +1,397 @@
+use nalgebra::na::{Translation, Indexable};
+use nalgebra::na;
+use bounding_volume::{AABB, BoundingVolume, LooseBoundingVolume, KDOPKind};
+use math::{Scalar, Vect};
+
+/// The k-DOP kinds available in 2d.
+#[dim2]
+pub mod kind {
+    /// The set of directions bounding a `KDOP`.
+    ///
+    /// Only the 8-DOP is available in 2d.
+    #[deriving(Show, PartialEq, Clone, Encodable, Decodable)]
+    pub enum KDOPKind {
+        /// 8-DOP: bounded along the 2 coordinate axes and the 2 diagonals.
+        KDOP8
+    }
+}
+
+/// The k-DOP kinds available in 3d.
+#[dim3]
+pub mod kind {
+    /// The set of directions bounding a `KDOP`.
+    ///
+    /// The 14-, 18- and 26-DOP are available in 3d.
+    #[deriving(Show, PartialEq, Clone, Encodable, Decodable)]
+    pub enum KDOPKind {
+        /// 14-DOP: bounded along the 3 coordinate axes and the 4 cube diagonals.
+        KDOP14,
+        /// 18-DOP: bounded along the 3 coordinate axes and the 6 face diagonals.
+        KDOP18,
+        /// 26-DOP: bounded along the 3 coordinate axes, the 4 cube diagonals and the 6 face
+        /// diagonals.
+        KDOP26
+    }
+}
+
+/// The k-DOP kinds available in 4d.
+#[dim4]
+pub mod kind {
+    /// The set of directions bounding a `KDOP`.
+    ///
+    /// Only the 8-DOP is available in 4d.
+    #[deriving(Show, PartialEq, Clone, Encodable, Decodable)]
+    pub enum KDOPKind {
+        /// 8-DOP: bounded along the 4 coordinate axes only, i.e., it is equivalent to an AABB.
+        KDOP8
+    }
+}
+
+impl KDOPKind {
+    /// The directions bounding a k-DOP of this kind.
+    ///
+    /// The first directions are the coordinate axes. The directions are not normalized.
+    #[inline]
+    pub fn axes(&self) -> &'static [Vect] {
+        kdop_axes(*self)
+    }
+
+    /// The number of faces of a k-DOP of this kind, i.e., its `k`.
+    #[inline]
+    pub fn k(&self) -> uint {
+        self.axes().len() * 2
+    }
+}
+
+#[dim2]
+fn kdop_axes(kind: KDOPKind) -> &'static [Vect] {
+    use nalgebra::na::Vec2;
+    use bounding_volume::KDOP8;
+
+    static AXES_8: [Vec2<Scalar>, ..4] = [
+        Vec2 { x: 1.0, y: 0.0 }, Vec2 { x: 0.0, y: 1.0 },
+        Vec2 { x: 1.0, y: 1.0 }, Vec2 { x: 1.0, y: -1.0 }
+    ];
+
+    match kind {
+        KDOP8 => AXES_8.as_slice()
+    }
+}
+
+#[dim3]
+fn kdop_axes(kind: KDOPKind) -> &'static [Vect] {
+    use nalgebra::na::Vec3;
+    use bounding_volume::{KDOP14, KDOP18, KDOP26};
+
+    static AXES_14: [Vec3<Scalar>, ..7] = [
+        Vec3 { x: 1.0, y: 0.0, z: 0.0 }, Vec3 { x: 0.0, y: 1.0, z: 0.0 }, Vec3 { x: 0.0, y: 0.0, z: 1.0 },
+        Vec3 { x: 1.0, y: 1.0, z: 1.0 }, Vec3 { x: 1.0, y: 1.0, z: -1.0 },
+        Vec3 { x: 1.0, y: -1.0, z: 1.0 }, Vec3 { x: -1.0, y: 1.0, z: 1.0 }
+    ];
+
+    static AXES_18: [Vec3<Scalar>, ..9] = [
+        Vec3 { x: 1.0, y: 0.0, z: 0.0 }, Vec3 { x: 0.0, y: 1.0, z: 0.0 }, Vec3 { x: 0.0, y: 0.0, z: 1.0 },
+        Vec3 { x: 1.0, y: 1.0, z: 0.0 }, Vec3 { x: 1.0, y: 0.0, z: 1.0 }, Vec3 { x: 0.0, y: 1.0, z: 1.0 },
+        Vec3 { x: 1.0, y: -1.0, z: 0.0 }, Vec3 { x: 1.0, y: 0.0, z: -1.0 }, Vec3 { x: 0.0, y: 1.0, z: -1.0 }
+    ];
+
+    static AXES_26: [Vec3<Scalar>, ..13] = [
+        Vec3 { x: 1.0, y: 0.0, z: 0.0 }, Vec3 { x: 0.0, y: 1.0, z: 0.0 }, Vec3 { x: 0.0, y: 0.0, z: 1.0 },
+        Vec3 { x: 1.0, y: 1.0, z: 1.0 }, Vec3 { x: 1.0, y: 1.0, z: -1.0 },
+        Vec3 { x: 1.0, y: -1.0, z: 1.0 }, Vec3 { x: -1.0, y: 1.0, z: 1.0 },
+        Vec3 { x: 1.0, y: 1.0, z: 0.0 }, Vec3 { x: 1.0, y: 0.0, z: 1.0 }, Vec3 { x: 0.0, y: 1.0, z: 1.0 },
+        Vec3 { x: 1.0, y: -1.0, z: 0.0 }, Vec3 { x: 1.0, y: 0.0, z: -1.0 }, Vec3 { x: 0.0, y: 1.0, z: -1.0 }
+    ];
+
+    match kind {
+        KDOP14 => AXES_14.as_slice(),
+        KDOP18 => AXES_18.as_slice(),
+        KDOP26 => AXES_26.as_slice()
+    }
+}
+
+#[dim4]
+fn kdop_axes(kind: KDOPKind) -> &'static [Vect] {
+    use nalgebra::na::Vec4;
+    use bounding_volume::KDOP8;
+
+    static AXES_8: [Vec4<Scalar>, ..4] = [
+        Vec4 { x: 1.0, y: 0.0, z: 0.0, w: 0.0 }, Vec4 { x: 0.0, y: 1.0, z: 0.0, w: 0.0 },
+        Vec4 { x: 0.0, y: 0.0, z: 1.0, w: 0.0 }, Vec4 { x: 0.0, y: 0.0, z: 0.0, w: 1.0 }
+    ];
+
+    match kind {
+        KDOP8 => AXES_8.as_slice()
+    }
+}
+
+/// A Discrete Oriented Polytope.
+///
+/// This is the intersection of slabs orthogonal to a fixed set of directions. Those directions
+/// are given by the `KDOPKind` of the k-DOP. With diagonal directions, it fits tighter than an
+/// AABB around geometries which are not aligned with the coordinate axes.
+#[deriving(Show, PartialEq, Clone, Encodable, Decodable)]
+pub struct KDOP {
+    kind: KDOPKind,
+    mins: Vec<Scalar>,
+    maxs: Vec<Scalar>
+}
+
+impl KDOP {
+    /// Creates a new k-DOP.
+    ///
+    /// # Arguments:
+    ///   * `kind` - the set of directions bounding the k-DOP.
+    ///   * `mins` - the minimal projection of the k-DOP on each direction of `kind.axes()`.
+    ///   * `maxs` - the maximal projection of the k-DOP on each direction of `kind.axes()`.
+    pub fn new(kind: KDOPKind, mins: Vec<Scalar>, maxs: Vec<Scalar>) -> KDOP {
+        assert!(mins.len() == kind.axes().len() && maxs.len() == kind.axes().len(),
+                "The k-DOP bounds must have one element per axis.");
+
+        for (min, max) in mins.iter().zip(maxs.iter()) {
+            assert!(*min <= *max, "The k-DOP bounds must be ordered.");
+        }
+
+        KDOP {
+            kind: kind,
+            mins: mins,
+            maxs: maxs
+        }
+    }
+
+    /// The set of directions bounding this k-DOP.
+    #[inline]
+    pub fn kind(&self) -> KDOPKind {
+        self.kind
+    }
+
+    /// The directions bounding this k-DOP.
+    #[inline]
+    pub fn axes(&self) -> &'static [Vect] {
+        self.kind.axes()
+    }
+
+    /// The minimal projection of this k-DOP on each of its axes.
+    #[inline]
+    pub fn mins<'a>(&'a self) -> &'a [Scalar] {
+        self.mins.as_slice()
+    }
+
+    /// The maximal projection of this k-DOP on each of its axes.
+    #[inline]
+    pub fn maxs<'a>(&'a self) -> &'a [Scalar] {
+        self.maxs.as_slice()
+    }
+
+    /// The AABB of this k-DOP.
+    ///
+    /// This is given by the bounds along the coordinate axes, so it might not be tight.
+    pub fn aabb(&self) -> AABB {
+        let mut mins: Vect = na::zero();
+        let mut maxs: Vect = na::zero();
+
+        for i in range(0u, na::dim::<Vect>()) {
+            mins.set(i, self.mins[i]);
+            maxs.set(i, self.maxs[i]);
+        }
+
+        AABB::new(mins, maxs)
+    }
+}
+
+impl BoundingVolume for KDOP {
+    // NOTE: the k-DOPs are assumed to be of the same kind.
+    #[inline]
+    fn intersects(&self, other: &KDOP) -> bool {
+        for i in range(0u, self.mins.len()) {
+            if self.mins[i] > other.maxs[i] || other.mins[i] > self.maxs[i] {
+                return false
+            }
+        }
+
+        true
+    }
+
+    #[inline]
+    fn contains(&self, other: &KDOP) -> bool {
+        for i in range(0u, self.mins.len()) {
+            if self.mins[i] > other.mins[i] || other.maxs[i] > self.maxs[i] {
+                return false
+            }
+        }
+
+        true
+    }
+
+    fn merge(&mut self, other: &KDOP) {
+        assert!(self.kind == other.kind, "Cannot merge k-DOPs of different kinds.");
+
+        for (min, other_min) in self.mins.mut_iter().zip(other.mins.iter()) {
+            *min = (*min).min(*other_min);
+        }
+
+        for (max, other_max) in self.maxs.mut_iter().zip(other.maxs.iter()) {
+            *max = (*max).max(*other_max);
+        }
+    }
+
+    #[inline]
+    fn merged(&self, other: &KDOP) -> KDOP {
+        let mut res = self.clone();
+
+        res.merge(other);
+
+        res
+    }
+}
+
+impl LooseBoundingVolume for KDOP {
+    fn loosen(&mut self, amount: Scalar) {
+        let axes = self.kind.axes();
+
+        // The axes are not normalized.
+        for (i, axis) in axes.iter().enumerate() {
+            let shift = amount * na::norm(axis);
+
+            *self.mins.get_mut(i) -= shift;
+            *self.maxs.get_mut(i) += shift;
+        }
+    }
+
+    #[inline]
+    fn loosened(&self, amount: Scalar) -> KDOP {
+        let mut res = self.clone();
+
+        res.loosen(amount);
+
+        res
+    }
+}
+
+impl Translation<Vect> for KDOP {
+    /// The center of the AABB of this k-DOP.
+    #[inline]
+    fn translation(&self) -> Vect {
+        let _0_5: Scalar = na::cast(0.5f64);
+        let mut res: Vect = na::zero();
+
+        for i in range(0u, na::dim::<Vect>()) {
+            res.set(i, (self.mins[i] + self.maxs[i]) * _0_5);
+        }
+
+        res
+    }
+
+    #[inline]
+    fn inv_translation(&self) -> Vect {
+        -self.translation()
+    }
+
+    fn append_translation(&mut self, dv: &Vect) {
+        let axes = self.kind.axes();
+
+        for (i, axis) in axes.iter().enumerate() {
+            let shift = na::dot(dv, axis);
+
+            *self.mins.get_mut(i) += shift;
+            *self.maxs.get_mut(i) += shift;
+        }
+    }
+
+    #[inline]
+    fn append_translation_cpy(kdop: &KDOP, dv: &Vect) -> KDOP {
+        let mut res = kdop.clone();
+
+        res.append_translation(dv);
+
+        res
+    }
+
+    #[inline]
+    fn prepend_translation(&mut self, dv: &Vect) {
+        self.append_translation(dv)
+    }
+
+    #[inline]
+    fn prepend_translation_cpy(kdop: &KDOP, dv: &Vect) -> KDOP {
+        Translation::append_translation_cpy(kdop, dv)
+    }
+
+    #[inline]
+    fn set_translation(&mut self, v: Vect) {
+        let center = self.translation();
+
+        self.append_translation(&(v - center))
+    }
+}
+
+#[cfg(test, dim3, f64)]
+mod test {
+    use sync::Arc;
+    use nalgebra::na::{Vec3, Iso3, Translation};
+    use nalgebra::na;
+    use bounding_volume::{BoundingVolume, HasAABB, KDOP14, KDOP18, KDOP26};
+    use bounding_volume;
+    use partitioning::{BVT, BoundingVolumeInterferencesCollector};
+    use ray::{Ray, RayCast};
+    use geom::{Mesh, Cuboid};
+
+    #[test]
+    fn test_kdop_implicit() {
+        let cuboid = Cuboid::new(Vec3::new(1.0f64, 1.0, 1.0));
+        let m      = Iso3::new(Vec3::new(1.0, 2.0, 3.0), na::zero());
+
+        for kind in [ KDOP14, KDOP18, KDOP26 ].iter() {
+            let kdop = bounding_volume::implicit_shape_kdop(*kind, &m, &cuboid);
+
+            assert!(kind.k() == kdop.mins().len() * 2);
+            assert!(na::approx_eq(kdop.aabb().mins(), cuboid.aabb(&m).mins()));
+            assert!(na::approx_eq(kdop.aabb().maxs(), cuboid.aabb(&m).maxs()));
+            assert!(na::approx_eq(&kdop.translation(), &Vec3::new(1.0, 2.0, 3.0)));
+        }
+    }
+
+    // A strip of thin triangles along the `x = y` diagonal.
+    fn diagonal_mesh() -> Mesh {
+        let mut vertices = Vec::new();
+        let mut indices  = Vec::new();
+
+        for i in range(0u, 10) {
+            let p = Vec3::new(i as f64, i as f64, 0.0);
+
+            vertices.push(p);
+            vertices.push(p + Vec3::new(1.0, 1.0, 0.0));
+            vertices.push(p + Vec3::new(0.0, 0.0, 0.1));
+            indices.push_all(&[ 3 * i, 3 * i + 1, 3 * i + 2 ]);
+        }
+
+        Mesh::new(Arc::new(vertices), Arc::new(indices), None, None)
+    }
+
+    #[test]
+    fn test_kdop_mesh_bvt() {
+        let mesh   = diagonal_mesh();
+        let leaves = mesh.element_kdops(KDOP18);
+        let bvt    = BVT::new_kdtree(leaves.clone());
+        let query  = bounding_volume::point_cloud_kdop(KDOP18, &na::one(), &[ Vec3::new(5.0, 4.0, 0.0) ]);
+
+        // The point is inside of the AABB of the 5th triangle, but not of its 18-DOP.
+        assert!(mesh.bounding_volumes()[4].contains(&query.aabb()));
+
+        let mut inter = Vec::new();
+
+        {
+            let mut visitor = BoundingVolumeInterferencesCollector::new(&query, &mut inter);
+            bvt.visit(&mut visitor);
+        }
+
+        assert!(inter.is_empty());
+
+        let ray = Ray::new(Vec3::new(5.0, 4.0, 10.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(leaves.iter().all(|l| l.ref1().toi_with_ray(&ray, true).is_none()));
+
+        let ray = Ray::new(Vec3::new(5.5, 5.5, 10.0), Vec3::new(0.0, 0.0, -1.0));
+        let toi = leaves[5].ref1().toi_with_ray(&ray, true);
+        assert!(toi.is_some() && na::approx_eq(&toi.unwrap(), &(10.0 - 0.1 - mesh.margin())));
+    }
+}
@@ -0,0 +1,44 @@
+use nalgebra::na::Transform;
+use nalgebra::na;
+use implicit::Implicit;
+use bounding_volume::{KDOP, KDOPKind};
+use math::{Vect, Matrix};
+
+/// Computes the k-DOP of an implicit shape.
+pub fn implicit_shape_kdop<I: Implicit<Vect, Matrix>>(kind: KDOPKind, m: &Matrix, i: &I) -> KDOP {
+    let axes     = kind.axes();
+    let mut mins = Vec::with_capacity(axes.len());
+    let mut maxs = Vec::with_capacity(axes.len());
+
+    for axis in axes.iter() {
+        maxs.push(na::dot(&i.support_point(m, axis), axis));
+        mins.push(na::dot(&i.support_point(m, &-*axis), axis));
+    }
+
+    KDOP::new(kind, mins, maxs)
+}
+
+/// Computes the k-DOP of a set of point.
+pub fn point_cloud_kdop(kind: KDOPKind, m: &Matrix, pts: &[Vect]) -> KDOP {
+    let axes     = kind.axes();
+    let wp0      = m.transform(&pts[0]);
+    let mut mins = Vec::from_fn(axes.len(), |i| na::dot(&wp0, &axes[i]));
+    let mut maxs = mins.clone();
+
+    for pt in pts.slice_from(1).iter() {
+        let wpt = m.transform(pt);
+
+        for (i, axis) in axes.iter().enumerate() {
+            let proj = na::dot(&wpt, axis);
+
+            if proj < mins[i] {
+                *mins.get_mut(i) = proj;
+            }
+            else if proj > maxs[i] {
+                *maxs.get_mut(i) = proj;
+            }
+        }
+    }
+
+    KDOP::new(kind, mins, maxs)
+}
@@ -2,17 +2,25 @@
 
 // Types and traits
 #[doc(inline)]
-pub use bounding_volume::bounding_volume::{HasBoundingVolume, BoundingVolume, LooseBoundingVolume};
+pub use bounding_volume::bounding_volume::{HasBoundingVolume, BoundingVolume, LooseBoundingVolume,
+                                           SurfaceArea};
 #[doc(inline)]
 pub use bounding_volume::aabb::{HasAABB, AABB, WithAABB};
 pub use bounding_volume::bounding_sphere::{HasBoundingSphere, BoundingSphere};
+pub use bounding_volume::obb::{HasOBB, OBB};
+pub use bounding_volume::kdop::KDOP;
+// `KDOPKind` and the k-DOP kinds available in the current dimension.
+pub use bounding_volume::kdop::kind::*;
 pub use bounding_volume::spacialized_cone::SpacializedCone;
 
 
 // functions
 pub use bounding_volume::aabb_utils::{implicit_shape_aabb, point_cloud_aabb};
 pub use bounding_volume::aabb_ball::ball_aabb;
-pub use bounding_volume::bounding_sphere_utils::{point_cloud_bounding_sphere_with_center, point_cloud_bounding_sphere};
+pub use bounding_volume::bounding_sphere_utils::{point_cloud_bounding_sphere_with_center, point_cloud_bounding_sphere,
+                                                 point_cloud_minimal_bounding_sphere};
+pub use bounding_volume::obb_utils::point_cloud_obb;
+pub use bounding_volume::kdop_utils::{implicit_shape_kdop, point_cloud_kdop};
 
 // modules
 #[doc(hidden)]
@@ -31,6 +39,9 @@ mod aabb_compound;
 mod aabb_triangle;
 mod aabb_segment;
 mod aabb_mesh;
+mod aabb_height_field;
+#[dim3]
+mod aabb_torus;
 mod aabb_bezier_surface;
 mod aabb_utils;
 
@@ -46,7 +57,31 @@ mod bounding_sphere_compound;
 mod bounding_sphere_triangle;
 mod bounding_sphere_segment;
 mod bounding_sphere_mesh;
+mod bounding_sphere_height_field;
+#[dim3]
+mod bounding_sphere_torus;
 mod bounding_sphere_bezier_surface;
 mod bounding_sphere_utils;
 
+mod obb;
+mod obb_box;
+mod obb_cone;
+mod obb_ball;
+mod obb_cylinder;
+mod obb_capsule;
+mod obb_plane;
+mod obb_convex;
+mod obb_compound;
+mod obb_triangle;
+mod obb_segment;
+mod obb_mesh;
+mod obb_height_field;
+#[dim3]
+mod obb_torus;
+mod obb_bezier_surface;
+mod obb_utils;
+
+mod kdop;
+mod kdop_utils;
+
 mod spacialized_cone;
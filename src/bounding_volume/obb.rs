@@ -0,0 +1,335 @@
+use std::num::Bounded;
+use nalgebra::na::{Translation, Transform, Rotate, AbsoluteRotate, Indexable, Norm};
+use nalgebra::na;
+use bounding_volume::{HasAABB, AABB, BoundingVolume, LooseBoundingVolume, SurfaceArea};
+use math::{Scalar, Vect, Matrix};
+
+/// Trait of objects that can be bounded by an OBB.
+pub trait HasOBB {
+    /// The object’s OBB.
+    fn obb(&self, &Matrix) -> OBB;
+}
+
+/// An Oriented Bounding Box.
+///
+/// This is a box with an arbitrary orientation. Its center and axes are given by the
+/// transformation `m`, and its size by its half extents along each of those axes.
+#[deriving(Show, PartialEq, Clone, Encodable, Decodable)]
+pub struct OBB {
+    m:            Matrix,
+    half_extents: Vect
+}
+
+impl OBB {
+    /// Creates a new OBB.
+    ///
+    /// # Arguments:
+    ///   * `m` - the position and orientation of the OBB center.
+    ///   * `half_extents` - the half extents of the OBB along each axis of `m`. They must all be
+    ///   positive.
+    pub fn new(m: Matrix, half_extents: Vect) -> OBB {
+        assert!(na::partial_le(&na::zero(), &half_extents));
+
+        OBB {
+            m:            m,
+            half_extents: half_extents
+        }
+    }
+
+    /// Creates the OBB corresponding to an AABB transformed by `m`.
+    pub fn new_from_aabb(m: &Matrix, aabb: &AABB) -> OBB {
+        let mut obb_m = m.clone();
+
+        obb_m.set_translation(m.transform(&aabb.translation()));
+
+        OBB::new(obb_m, (*aabb.maxs() - *aabb.mins()) * na::cast::<f64, Scalar>(0.5))
+    }
+
+    /// The position and orientation of this OBB center.
+    #[inline]
+    pub fn m<'a>(&'a self) -> &'a Matrix {
+        &self.m
+    }
+
+    /// The half extents of this OBB along each of its axes.
+    #[inline]
+    pub fn half_extents<'a>(&'a self) -> &'a Vect {
+        &self.half_extents
+    }
+
+    /// This OBB expressed in its local frame, i.e., where it is axis-aligned.
+    #[inline]
+    pub fn local_aabb(&self) -> AABB {
+        AABB::new(-self.half_extents, self.half_extents)
+    }
+
+    /// Transforms this OBB by `m`.
+    #[inline]
+    pub fn transform_by(&self, m: &Matrix) -> OBB {
+        OBB::new(m * self.m, self.half_extents.clone())
+    }
+}
+
+/// Computes the OBB of a shape aligned with its local frame.
+///
+/// The OBB is the local-space AABB of the shape transformed by `m`.
+pub fn local_aabb_obb<G: HasAABB>(m: &Matrix, g: &G) -> OBB {
+    let id: Matrix = na::one();
+
+    OBB::new_from_aabb(m, &g.aabb(&id))
+}
+
+/// The transformation from the local frame of `b` to the local frame of `a`.
+#[inline]
+fn relative_transform(a: &OBB, b: &OBB) -> Matrix {
+    na::inv(&a.m).expect("An OBB transformation must be inversible.") * b.m
+}
+
+impl BoundingVolume for OBB {
+    fn intersects(&self, other: &OBB) -> bool {
+        // Separating axis theorem.
+        let axes1 = obb_axes(&self.m);
+        let axes2 = obb_axes(&other.m);
+        let delta = other.m.translation() - self.m.translation();
+
+        for axis in axes1.iter().chain(axes2.iter()) {
+            if separation(axis, &delta, axes1.as_slice(), &self.half_extents,
+                          axes2.as_slice(), &other.half_extents) > na::zero() {
+                return false
+            }
+        }
+
+        let mut separated = false;
+
+        edge_axes(axes1.as_slice(), axes2.as_slice(), |axis| {
+            if !separated && separation(&axis, &delta, axes1.as_slice(), &self.half_extents,
+                                        axes2.as_slice(), &other.half_extents) > na::zero() {
+                separated = true
+            }
+        });
+
+        !separated
+    }
+
+    fn contains(&self, other: &OBB) -> bool {
+        // `other` must be inside of `self` along each axis of `self`.
+        let rel    = relative_transform(self, other);
+        let center = rel.translation();
+        let extent = rel.absolute_rotate(&other.half_extents);
+
+        for i in range(0u, na::dim::<Vect>()) {
+            if center.at(i).abs() + extent.at(i) > self.half_extents.at(i) {
+                return false
+            }
+        }
+
+        true
+    }
+
+    fn merge(&mut self, other: &OBB) {
+        // Enlarge `self` along its own axes to enclose the AABB of `other` in the local frame of
+        // `self`.
+        let rel    = relative_transform(self, other);
+        let center = rel.translation();
+        let extent = rel.absolute_rotate(&other.half_extents);
+        let _0_5   = na::cast::<f64, Scalar>(0.5);
+
+        let mins = na::inf(&-self.half_extents, &(center - extent));
+        let maxs = na::sup(&self.half_extents, &(center + extent));
+
+        let ws_center = self.m.transform(&((mins + maxs) * _0_5));
+
+        self.m.set_translation(ws_center);
+        self.half_extents = (maxs - mins) * _0_5;
+    }
+
+    /// Merges this OBB with another one. The result has the orientation of `self`.
+    #[inline]
+    fn merged(&self, other: &OBB) -> OBB {
+        let mut res = self.clone();
+
+        res.merge(other);
+
+        res
+    }
+}
+
+/// The world-space axes of an OBB.
+fn obb_axes(m: &Matrix) -> Vec<Vect> {
+    let mut res = Vec::with_capacity(na::dim::<Vect>());
+
+    na::canonical_basis(|b: Vect| {
+        res.push(m.rotate(&b));
+
+        true
+    });
+
+    res
+}
+
+/// The separation of two OBBs along an axis. It is negative if their projections overlap.
+fn separation(axis: &Vect, delta: &Vect, axes1: &[Vect], he1: &Vect, axes2: &[Vect], he2: &Vect) -> Scalar {
+    let mut radius: Scalar = na::zero();
+
+    for i in range(0u, axes1.len()) {
+        radius = radius + he1.at(i) * na::dot(&axes1[i], axis).abs() +
+                          he2.at(i) * na::dot(&axes2[i], axis).abs();
+    }
+
+    na::dot(delta, axis).abs() - radius
+}
+
+/// Calls `f` with every axis orthogonal to one edge of each OBB.
+#[dim3]
+fn edge_axes(axes1: &[Vect], axes2: &[Vect], f: |Vect| -> ()) {
+    let _eps: Scalar = Float::epsilon();
+    let _eps_tol     = _eps * na::cast(100.0f64);
+
+    for i in range(0u, 3) {
+        for j in range(0u, 3) {
+            let mut axis = na::cross(&axes1[i], &axes2[j]);
+
+            // Parallel edges are already covered by the face axes.
+            if axis.normalize() > _eps_tol {
+                f(axis)
+            }
+        }
+    }
+}
+
+// NOTE: in 4d, this makes `intersects` conservative: it may return `true` for disjoint OBBs.
+#[not_dim3]
+fn edge_axes(_: &[Vect], _: &[Vect], _: |Vect| -> ()) {
+}
+
+impl LooseBoundingVolume for OBB {
+    #[inline]
+    fn loosen(&mut self, amount: Scalar) {
+        self.half_extents = self.half_extents + amount
+    }
+
+    #[inline]
+    fn loosened(&self, amount: Scalar) -> OBB {
+        OBB::new(self.m.clone(), self.half_extents + amount)
+    }
+}
+
+impl SurfaceArea for OBB {
+    #[inline]
+    fn surface_area(&self) -> Scalar {
+        self.local_aabb().surface_area()
+    }
+}
+
+impl Translation<Vect> for OBB {
+    #[inline]
+    fn translation(&self) -> Vect {
+        self.m.translation()
+    }
+
+    #[inline]
+    fn inv_translation(&self) -> Vect {
+        -self.translation()
+    }
+
+    #[inline]
+    fn append_translation(&mut self, dv: &Vect) {
+        self.m.append_translation(dv)
+    }
+
+    #[inline]
+    fn append_translation_cpy(obb: &OBB, dv: &Vect) -> OBB {
+        OBB::new(na::append_translation(&obb.m, dv), obb.half_extents.clone())
+    }
+
+    #[inline]
+    fn prepend_translation(&mut self, dv: &Vect) {
+        self.append_translation(dv)
+    }
+
+    #[inline]
+    fn prepend_translation_cpy(obb: &OBB, dv: &Vect) -> OBB {
+        Translation::append_translation_cpy(obb, dv)
+    }
+
+    #[inline]
+    fn set_translation(&mut self, v: Vect) {
+        self.m.set_translation(v)
+    }
+}
+
+impl Bounded for OBB {
+    /// An OBB enclosing the whole space.
+    #[inline]
+    fn max_value() -> OBB {
+        OBB::new(na::one(), Bounded::max_value())
+    }
+
+    /// An OBB enclosing the whole space.
+    #[inline]
+    fn min_value() -> OBB {
+        Bounded::max_value()
+    }
+}
+
+#[cfg(test, dim3, f64)]
+mod test {
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use bounding_volume::{BoundingVolume, HasAABB};
+    use partitioning::{BVT, BoundingVolumeInterferencesCollector};
+    use ray::{Ray, RayCast};
+    use geom::Cuboid;
+    use super::{OBB, HasOBB};
+
+    // A long thin box along the `x = y` diagonal.
+    fn diagonal_obb(t: Vec3<f64>) -> OBB {
+        let angle = Float::frac_pi_4();
+
+        OBB::new(Iso3::new(t, Vec3::new(0.0, 0.0, angle)), Vec3::new(5.0, 0.1, 0.1))
+    }
+
+    #[test]
+    fn test_obb_intersects() {
+        let a = diagonal_obb(na::zero());
+        let b = diagonal_obb(Vec3::new(0.5, -0.5, 0.0));
+        let c = diagonal_obb(Vec3::new(2.0, 2.0, 0.0));
+
+        // Their AABBs intersect but not the OBBs themselves.
+        assert!(!a.intersects(&b));
+        assert!(a.intersects(&c));
+        assert!(a.merged(&b).contains(&a));
+        assert!(a.merged(&b).contains(&b));
+        assert!(!a.contains(&c));
+    }
+
+    #[test]
+    fn test_obb_ray_cast() {
+        let a = diagonal_obb(na::zero());
+
+        assert!(a.toi_with_ray(&Ray::new(Vec3::new(-10.0, 3.0, 0.0), Vec3::x()), true).is_some());
+        assert!(a.toi_with_ray(&Ray::new(Vec3::new(-10.0, 4.0, 0.0), Vec3::x()), true).is_none());
+    }
+
+    #[test]
+    fn test_obb_bvt() {
+        let cuboid = Cuboid::new(Vec3::new(5.0, 0.1, 0.1));
+        let leaves = Vec::from_fn(10, |i| {
+            let m = Iso3::new(Vec3::new(0.0, i as f64, 0.0), Vec3::new(0.0, 0.0, Float::frac_pi_4()));
+
+            (i, cuboid.obb(&m))
+        });
+
+        let bvt       = BVT::new_kdtree(leaves.clone());
+        let mut inter = Vec::new();
+
+        {
+            let mut visitor = BoundingVolumeInterferencesCollector::new(leaves.get(5).ref1(), &mut inter);
+            bvt.visit(&mut visitor);
+        }
+
+        // The parallel thin boxes 1 apart along `y` are 1/sqrt(2) apart: they do not intersect.
+        assert_eq!(inter.len(), 1);
+        assert!(cuboid.aabb(leaves.get(5).ref1().m()).intersects(&cuboid.aabb(leaves.get(6).ref1().m())));
+    }
+}
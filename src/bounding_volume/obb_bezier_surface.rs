@@ -0,0 +1,11 @@
+use bounding_volume::{HasOBB, OBB};
+use bounding_volume::obb_utils;
+use geom::BezierSurface;
+use math::Matrix;
+
+impl HasOBB for BezierSurface {
+    #[inline]
+    fn obb(&self, m: &Matrix) -> OBB {
+        obb_utils::point_cloud_obb(m, self.control_points())
+    }
+}
@@ -0,0 +1,11 @@
+use bounding_volume::{HasOBB, OBB};
+use bounding_volume::obb;
+use geom::Cone;
+use math::Matrix;
+
+impl HasOBB for Cone {
+    #[inline]
+    fn obb(&self, m: &Matrix) -> OBB {
+        obb::local_aabb_obb(m, self)
+    }
+}
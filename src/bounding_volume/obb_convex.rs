@@ -0,0 +1,15 @@
+use bounding_volume::{HasOBB, OBB, LooseBoundingVolume};
+use bounding_volume::obb_utils;
+use geom::Convex;
+use math::Matrix;
+
+impl HasOBB for Convex {
+    #[inline]
+    fn obb(&self, m: &Matrix) -> OBB {
+        let mut res = obb_utils::point_cloud_obb(m, self.pts());
+
+        res.loosen(self.margin());
+
+        res
+    }
+}
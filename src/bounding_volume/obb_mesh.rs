@@ -0,0 +1,17 @@
+use bounding_volume::{HasOBB, OBB, LooseBoundingVolume};
+use bounding_volume::obb_utils;
+use geom::Mesh;
+use math::Matrix;
+
+impl HasOBB for Mesh {
+    #[inline]
+    fn obb(&self, m: &Matrix) -> OBB {
+        // NOTE: unlike the AABB, this is not cached on the BVT and is linear wrt. the number of
+        // vertices.
+        let mut res = obb_utils::point_cloud_obb(m, self.vertices().as_slice());
+
+        res.loosen(self.margin());
+
+        res
+    }
+}
@@ -0,0 +1,11 @@
+use std::num::Bounded;
+use bounding_volume::{HasOBB, OBB};
+use geom::Plane;
+use math::Matrix;
+
+impl HasOBB for Plane {
+    #[inline]
+    fn obb(&self, m: &Matrix) -> OBB {
+        OBB::new(m.clone(), Bounded::max_value())
+    }
+}
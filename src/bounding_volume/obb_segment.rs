@@ -0,0 +1,16 @@
+use bounding_volume::{HasOBB, OBB, LooseBoundingVolume};
+use bounding_volume::obb_utils;
+use geom::Segment;
+use math::Matrix;
+
+impl HasOBB for Segment {
+    #[inline]
+    fn obb(&self, m: &Matrix) -> OBB {
+        let pts     = [ self.a().clone(), self.b().clone() ];
+        let mut res = obb_utils::point_cloud_obb(m, pts.as_slice());
+
+        res.loosen(self.margin());
+
+        res
+    }
+}
@@ -0,0 +1,16 @@
+use bounding_volume::{HasOBB, OBB, LooseBoundingVolume};
+use bounding_volume::obb_utils;
+use geom::Triangle;
+use math::Matrix;
+
+impl HasOBB for Triangle {
+    #[inline]
+    fn obb(&self, m: &Matrix) -> OBB {
+        let pts     = [ self.a().clone(), self.b().clone(), self.c().clone() ];
+        let mut res = obb_utils::point_cloud_obb(m, pts.as_slice());
+
+        res.loosen(self.margin());
+
+        res
+    }
+}
@@ -0,0 +1,55 @@
+use nalgebra::na;
+use bounding_volume::{AABB, OBB};
+use bounding_volume::aabb_utils;
+use math::{Vect, Matrix};
+
+#[dim3]
+use nalgebra::na::{Mat3, Col};
+#[dim3]
+use math::Scalar;
+#[dim3]
+use utils;
+
+/// Computes an OBB of a set of point.
+///
+/// In 3d, the OBB axes are the principal axes of the point cloud, i.e., the eigenvectors of its
+/// covariance matrix. In other dimensions, the OBB is aligned with the local frame of the points.
+#[dim3]
+pub fn point_cloud_obb(m: &Matrix, pts: &[Vect]) -> OBB {
+    let (cov, center): (Mat3<Scalar>, Vect) = utils::cov_and_center(pts);
+    let (eigvec, eigval) = na::eigen_qr(&cov, &Float::epsilon(), 1000);
+    let mut eigpairs     = [ (eigvec.col(0), eigval.x), (eigvec.col(1), eigval.y), (eigvec.col(2), eigval.z) ];
+
+    // Sort in decreasing order wrt. eigenvalues.
+    eigpairs.sort_by(|a, b| {
+        if *a.ref1() > *b.ref1() {
+            Less
+        }
+        else if *a.ref1() < *b.ref1() {
+            Greater
+        }
+        else {
+            Equal
+        }
+    });
+
+    let mut frame: Matrix = na::one();
+    frame.look_at_z(&center, &(center + *eigpairs[0].ref0()), eigpairs[1].ref0());
+
+    let inv_frame    = na::inv(&frame).expect("The principal axes frame must be inversible.");
+    let (mins, maxs) = aabb_utils::point_cloud_aabb(&inv_frame, pts);
+
+    OBB::new_from_aabb(&(m * frame), &AABB::new(mins, maxs))
+}
+
+/// Computes an OBB of a set of point.
+///
+/// In 3d, the OBB axes are the principal axes of the point cloud, i.e., the eigenvectors of its
+/// covariance matrix. In other dimensions, the OBB is aligned with the local frame of the points.
+#[not_dim3]
+pub fn point_cloud_obb(m: &Matrix, pts: &[Vect]) -> OBB {
+    let id: Matrix   = na::one();
+    let (mins, maxs) = aabb_utils::point_cloud_aabb(&id, pts);
+
+    OBB::new_from_aabb(m, &AABB::new(mins, maxs))
+}
@@ -1,4 +1,5 @@
 use ray::Ray;
+use math::{Scalar, Vect};
 
 /// Trait all broad phase must implement.
 ///
@@ -35,6 +36,25 @@ pub trait InterferencesBroadPhase<B, DV> : BroadPhase<B> {
     fn for_each_pair_mut(&mut self, f: |&B, &B, &mut DV| -> ());
 }
 
+/// Trait of broad phases which report the interferences started and stopped since the last time
+/// they were queried.
+///
+/// This avoids diffing the set of interferences after each update to drive, e.g., the creation
+/// and destruction of narrow phases incrementally.
+pub trait PairEventsBroadPhase<B, DV> : InterferencesBroadPhase<B, DV> {
+    /// Enables or disables the recording of pair events.
+    ///
+    /// Recording is disabled by default. Disabling it discards the events not drained yet.
+    fn set_pair_events_enabled(&mut self, enabled: bool);
+
+    /// Executes `removed` on each pair removed, then `added` on each pair added, since the last
+    /// call to this method.
+    ///
+    /// A pair both added and removed in the meantime is not reported. The events are cleared
+    /// afterward.
+    fn drain_pair_events(&mut self, added: |&B, &B, &mut DV| -> (), removed: |&B, &B, DV| -> ());
+}
+
 /// Trait of broad phases working with bounding volume.
 pub trait BoundingVolumeBroadPhase<B, BV> : BroadPhase<B> {
     /// Collects every object which might intersect a given bounding volume.
@@ -46,3 +66,26 @@ pub trait RayCastBroadPhase<B> : BroadPhase<B> {
     /// Collects every object which might intersect a ray.
     fn interferences_with_ray(&mut self, &Ray, &mut Vec<B>);
 }
+
+/// Traits of broad phase able to run point queries.
+///
+/// Each query takes a function computing the exact distance between an object and a point. It
+/// must never be smaller than the distance between the point and the object bounding volume.
+pub trait PointQueryBroadPhase<B> : BroadPhase<B> {
+    /// Collects every object containing a point, i.e., with a zero distance to it.
+    fn interferences_with_point(&mut self, pt: &Vect, dist_fn: |&B, &Vect| -> Scalar, out: &mut Vec<B>);
+
+    /// Collects the `k` objects closest to a point, sorted by increasing distance.
+    fn k_nearest_to_point(&mut self,
+                          pt:      &Vect,
+                          k:       uint,
+                          dist_fn: |&B, &Vect| -> Scalar,
+                          out:     &mut Vec<(Scalar, B)>);
+
+    /// Finds the object closest to a point, among those closer than `max_dist`.
+    fn nearest_to_point_within_distance(&mut self,
+                                        pt:       &Vect,
+                                        max_dist: Scalar,
+                                        dist_fn:  |&B, &Vect| -> Scalar)
+                                        -> Option<(Scalar, B)>;
+}
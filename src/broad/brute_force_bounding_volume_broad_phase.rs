@@ -1,4 +1,3 @@
-use std::mem;
 use std::gc::{GC, Gc};
 use std::cell::RefCell;
 use data::hash_map::HashMap;
@@ -6,7 +5,8 @@ use data::hash::UintTWHash;
 use data::pair::{Pair, PairTWHash};
 use data::has_uid::HasUid;
 use nalgebra::na;
-use broad::Dispatcher;
+use broad::{BroadPhase, InterferencesBroadPhase, PairEventsBroadPhase, Dispatcher};
+use broad::pair_events::PairEvents;
 use bounding_volume::{HasBoundingVolume, LooseBoundingVolume};
 use math::Scalar;
 
@@ -60,6 +60,7 @@ pub struct BruteForceBoundingVolumeBroadPhase<B, BV, D, DV> {
     sobjects:   Vec<Gc<RefCell<BoundingVolumeProxy<B, BV>>>>, // inactive objects
     rb2bv:      HashMap<uint, uint, UintTWHash>,
     pairs:      HashMap<Pair<Gc<RefCell<BoundingVolumeProxy<B, BV>>>>, DV, PairTWHash>, // pair manager
+    events:     PairEvents<Gc<RefCell<BoundingVolumeProxy<B, BV>>>, DV>,
     dispatcher: D,
     margin:     Scalar,
     to_update:  Vec<Gc<RefCell<BoundingVolumeProxy<B, BV>>>>,
@@ -79,6 +80,7 @@ BruteForceBoundingVolumeBroadPhase<B, BV, D, DV> {
             to_update:  Vec::new(),
             rb2bv:      HashMap::new(UintTWHash::new()),
             pairs:      HashMap::new(PairTWHash::new()),
+            events:     PairEvents::new(),
             dispatcher: dispatcher,
             update_off: 0,
             margin:     margin
@@ -91,75 +93,92 @@ BruteForceBoundingVolumeBroadPhase<B, BV, D, DV> {
         self.pairs.len()
     }
 
-    /// Adds an element to this broad phase.
-    #[inline]
-    pub fn add(&mut self, rb: B) {
-        let proxy = box(GC) RefCell::new(BoundingVolumeProxy::new(rb, self.margin.clone()));
-        self.objects.push(proxy);
-        self.to_update.push(proxy);
-    }
-
-    /// Removes an element from this broad phase.
-    #[inline]
-    pub fn remove(&mut self, _: &B) {
-        fail!("Not yet implemented.");
-    }
-
     /// Marks and object as active or inactive. The bounding volume of an inactive object is never
     /// updated. Activating/deactivating an already active/inactive objects leads to undefined
     /// behaviour.
     pub fn set_active(&mut self, b: &B, active: bool) {
-        let (key, at) =
-            match self.rb2bv.find_mut(&b.uid()) {
+        let i =
+            match self.rb2bv.find(&b.uid()) {
                 None    => fail!("Unable to change the active state of an unknown object."),
-                Some(i) => {
-                    if active {
-                        // remove from sobjects…
-                        let proxy  = self.sobjects[*i];
-                        let lproxy = self.sobjects.pop().unwrap();
-                        *self.sobjects.get_mut(*i) = lproxy;
+                Some(i) => *i
+            };
 
-                        // … then add to objects
-                        self.objects.push(proxy);
+        let (from, to) =
+            if active {
+                (&mut self.sobjects, &mut self.objects)
+            }
+            else {
+                (&mut self.objects, &mut self.sobjects)
+            };
+
+        let proxy = from.swap_remove(i).unwrap();
 
-                        let mut at = self.objects.len() - 1;
+        // The last object of `from` took the place of `proxy`.
+        if i != from.len() {
+            let _ = self.rb2bv.insert(from[i].borrow().body.uid(), i);
+        }
 
-                        mem::swap(&mut at, i);
+        to.push(proxy);
+        let _ = self.rb2bv.insert(b.uid(), to.len() - 1);
+    }
 
-                        (lproxy, at)
+    /// Re-evaluates the validity of every pair involving `b`.
+    ///
+    /// This must be called whenever the result of the dispatcher `is_valid` method changes for
+    /// `b`, e.g., when its collision groups are modified. Pairs which became invalid are removed,
+    /// and pairs which became valid are added if the bounding volumes intersect.
+    pub fn revalidate(&mut self, b: &B) {
+        let (proxy, active) =
+            match self.objects.iter().find(|o| o.borrow().body.uid() == b.uid()) {
+                Some(o) => (*o, true),
+                None    => {
+                    match self.sobjects.iter().find(|o| o.borrow().body.uid() == b.uid()) {
+                        Some(o) => (*o, false),
+                        None    => return
                     }
-                    else {
-                        // remove from objects…
-                        let proxy  = self.objects[*i];
-                        let lproxy = self.objects.pop().unwrap();
-                        *self.objects.get_mut(*i) = lproxy;
+                }
+            };
+
+        let bproxy = proxy.borrow();
+
+        for (i, other) in self.objects.iter().chain(self.sobjects.iter()).enumerate() {
+            if other.uid() == proxy.uid() {
+                continue
+            }
 
-                        // … then add to sobjects
-                        self.sobjects.push(proxy);
+            let bother = other.borrow();
+            let key    = Pair::new(proxy, *other);
 
-                        let mut at = self.sobjects.len() - 1;
+            if self.dispatcher.is_valid(&bproxy.body, &bother.body) {
+                // pairs between two inactive objects are never detected.
+                let other_active = i < self.objects.len();
 
-                        mem::swap(&mut at, i);
+                if (active || other_active) &&
+                   bproxy.bounding_volume.intersects(&bother.bounding_volume) {
+                    let dispatcher = &mut self.dispatcher;
+                    let num_pairs  = self.pairs.len();
+                    let _ = self.pairs.find_or_insert_lazy(
+                        key.clone(),
+                        || dispatcher.dispatch(&bproxy.body, &bother.body)
+                    );
 
-                        (lproxy, at)
+                    if self.pairs.len() != num_pairs {
+                        self.events.record_added(key);
                     }
                 }
-            };
-
-        self.rb2bv.insert(key.uid(), at);
+            }
+            else {
+                match self.pairs.get_and_remove(&key) {
+                    Some(entry) => self.events.record_removed(entry.key, entry.value),
+                    None        => { }
+                }
+            }
+        }
     }
 
-    /// Updates the collision pairs based on the objects bounding volumes.
-    pub fn update(&mut self) {
+    fn update_updatable(&mut self) {
         let mut new_colls = 0u;
 
-        for b in self.objects.mut_iter() {
-            let margin = self.margin;
-            if b.borrow_mut().update(&margin) {
-                self.to_update.push(b.clone())
-            }
-        }
-
         for &b1 in self.to_update.iter() {
             for &b2 in self.objects.iter() {
                 let bb1 = b1.borrow();
@@ -167,13 +186,25 @@ BruteForceBoundingVolumeBroadPhase<B, BV, D, DV> {
                 if self.dispatcher.is_valid(&bb1.body, &bb2.body) {
                     if bb2.bounding_volume.intersects(&bb1.bounding_volume) {
                         let dispatcher = &mut self.dispatcher;
+                        let num_pairs  = self.pairs.len();
                         let _ = self.pairs.find_or_insert_lazy(
                             Pair::new(b1, b2),
                             || dispatcher.dispatch(&bb1.body, &bb2.body)
                         );
 
+                        if self.pairs.len() != num_pairs {
+                            self.events.record_added(Pair::new(b1, b2));
+                        }
+
                         new_colls = new_colls + 1;
                     }
+                    else {
+                        // Remove the outdated interferences of the updated objects right away.
+                        match self.pairs.get_and_remove(&Pair::new(b1, b2)) {
+                            Some(entry) => self.events.record_removed(entry.key, entry.value),
+                            None        => { }
+                        }
+                    }
                 }
             }
         }
@@ -185,22 +216,28 @@ BruteForceBoundingVolumeBroadPhase<B, BV, D, DV> {
             for i in range(self.update_off, self.update_off + num_removals) {
                 let id = i % self.pairs.len();
 
-                let remove = {
+                let to_remove = {
                     let elts  = self.pairs.elements();
                     let entry = &elts[id];
 
                     let bf = entry.key.first.borrow();
                     let bs = entry.key.second.borrow();
                     if !bf.bounding_volume.intersects(&bs.bounding_volume) {
-                        true
+                        Some(entry.key.clone())
                     }
                     else {
-                        false
+                        None
                     }
                 };
 
-                if remove {
-                    self.pairs.remove_elem_at(id);
+                match to_remove {
+                    Some(key) => {
+                        match self.pairs.get_and_remove(&key) {
+                            Some(entry) => self.events.record_removed(entry.key, entry.value),
+                            None        => { }
+                        }
+                    },
+                    None => { }
                 }
             }
 
@@ -211,6 +248,121 @@ BruteForceBoundingVolumeBroadPhase<B, BV, D, DV> {
     }
 }
 
+impl<B:  'static + HasBoundingVolume<BV> + HasUid + Clone,
+     BV: 'static + LooseBoundingVolume + Clone,
+     D:  Dispatcher<B, B, DV>,
+     DV>
+BroadPhase<B> for BruteForceBoundingVolumeBroadPhase<B, BV, D, DV> {
+    #[inline]
+    fn add(&mut self, rb: B) {
+        let _ = self.rb2bv.insert(rb.uid(), self.objects.len());
+
+        let proxy = box(GC) RefCell::new(BoundingVolumeProxy::new(rb, self.margin.clone()));
+        self.objects.push(proxy);
+        self.to_update.push(proxy);
+    }
+
+    #[inline]
+    fn remove(&mut self, _: &B) {
+        fail!("Not yet implemented.");
+    }
+
+    fn update(&mut self) {
+        for b in self.objects.mut_iter() {
+            let margin = self.margin;
+            if b.borrow_mut().update(&margin) {
+                self.to_update.push(b.clone())
+            }
+        }
+
+        self.update_updatable()
+    }
+
+    fn update_object(&mut self, object: &B) {
+        let margin = self.margin;
+
+        match self.objects.iter().find(|o| o.borrow().body.uid() == object.uid()) {
+            Some(b) => {
+                if b.borrow_mut().update(&margin) {
+                    self.to_update.push(b.clone())
+                }
+            },
+            None => { } // unknown or inactive
+        }
+
+        self.update_updatable()
+    }
+}
+
+impl<B:  'static + HasBoundingVolume<BV> + HasUid + Clone,
+     BV: 'static + LooseBoundingVolume + Clone,
+     D:  Dispatcher<B, B, DV>,
+     DV>
+InterferencesBroadPhase<B, DV> for BruteForceBoundingVolumeBroadPhase<B, BV, D, DV> {
+    /// Marks an object as active.
+    ///
+    /// The interferences of inactive objects are never removed, so `f` is called on every
+    /// interference between `body` and an inactive object.
+    fn activate(&mut self, body: &B, f: |&B, &B, &mut DV| -> ()) {
+        self.set_active(body, true);
+
+        for p in self.pairs.elements_mut().mut_iter() {
+            let bf = p.key.first.borrow();
+            let bs = p.key.second.borrow();
+
+            if bf.body.uid() == body.uid() && self.sobjects.iter().any(|o| o.uid() == p.key.second.uid()) ||
+               bs.body.uid() == body.uid() && self.sobjects.iter().any(|o| o.uid() == p.key.first.uid()) {
+                f(&bf.body, &bs.body, &mut p.value)
+            }
+        }
+    }
+
+    #[inline]
+    fn deactivate(&mut self, body: &B) {
+        self.set_active(body, false)
+    }
+
+    #[inline]
+    fn for_each_pair(&self, f: |&B, &B, &DV| -> ()) {
+        for p in self.pairs.elements().iter() {
+            let bf = p.key.first.borrow();
+            let bs = p.key.second.borrow();
+            f(&bf.body, &bs.body, &p.value)
+        }
+    }
+
+    #[inline]
+    fn for_each_pair_mut(&mut self, f: |&B, &B, &mut DV| -> ()) {
+        for p in self.pairs.elements_mut().mut_iter() {
+            let bf = p.key.first.borrow();
+            let bs = p.key.second.borrow();
+            f(&bf.body, &bs.body, &mut p.value)
+        }
+    }
+}
+
+impl<B:  'static + HasBoundingVolume<BV> + HasUid + Clone,
+     BV: 'static + LooseBoundingVolume + Clone,
+     D:  Dispatcher<B, B, DV>,
+     DV>
+PairEventsBroadPhase<B, DV> for BruteForceBoundingVolumeBroadPhase<B, BV, D, DV> {
+    #[inline]
+    fn set_pair_events_enabled(&mut self, enabled: bool) {
+        self.events.set_enabled(enabled)
+    }
+
+    fn drain_pair_events(&mut self, added: |&B, &B, &mut DV| -> (), removed: |&B, &B, DV| -> ()) {
+        // The interferences of inactive objects are kept with the others.
+        let mut spairs = HashMap::new(PairTWHash::new());
+
+        self.events.drain(
+            &mut self.pairs,
+            &mut spairs,
+            |p1, p2, dv| added(&p1.borrow().body, &p2.borrow().body, dv),
+            |p1, p2, dv| removed(&p1.borrow().body, &p2.borrow().body, dv))
+    }
+}
+
 #[cfg(test, dim3, f64)]
 mod test {
     use super::BruteForceBoundingVolumeBroadPhase;
@@ -221,7 +373,7 @@ mod test {
     use nalgebra::na;
     use geom::Ball;
     use bounding_volume::WithAABB;
-    use broad::NoIdDispatcher;
+    use broad::{NoIdDispatcher, BroadPhase, PairEventsBroadPhase};
 
     #[test]
     fn test_bfbv_empty() {
@@ -320,4 +472,41 @@ mod test {
 
         assert_eq!(bf.num_interferences(), (399 * (399 + 1)) / 2)
     }
+
+    #[test]
+    fn test_bfbv_pair_events() {
+        type Shape = Rc<RefCell<WithAABB<Ball>>>;
+        let dispatcher: NoIdDispatcher<Shape> = NoIdDispatcher;
+        let mut bf     = BruteForceBoundingVolumeBroadPhase::new(dispatcher, 0.2);
+        let ball       = Ball::new(0.3);
+
+        bf.set_pair_events_enabled(true);
+
+        let a = Rc::new(RefCell::new(WithAABB(Iso3::new(na::zero(), na::zero()), ball)));
+        let b = Rc::new(RefCell::new(WithAABB(Iso3::new(Vec3::new(0.5, 0.0, 0.0), na::zero()), ball)));
+
+        bf.add(a.clone());
+        bf.add(b.clone());
+        bf.update();
+
+        let mut num_added   = 0u;
+        let mut num_removed = 0u;
+
+        bf.drain_pair_events(|_, _, _| num_added = num_added + 1, |_, _, _| num_removed = num_removed + 1);
+        assert_eq!((num_added, num_removed), (1, 0));
+
+        {
+            let mut wa = b.borrow_mut();
+            let m      = wa.m().clone();
+            let g      = wa.g().clone();
+            *wa        = WithAABB(na::append_translation(&m, &Vec3::new(10.0, 0.0, 0.0)), g)
+        }
+
+        bf.update();
+
+        num_added   = 0;
+        num_removed = 0;
+        bf.drain_pair_events(|_, _, _| num_added = num_added + 1, |_, _, _| num_removed = num_removed + 1);
+        assert_eq!((num_added, num_removed), (0, 1));
+    }
 }
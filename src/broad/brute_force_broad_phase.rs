@@ -1,7 +1,8 @@
 use data::hash_map::HashMap;
 use data::pair::{Pair, PairTWHash};
 use data::has_uid::HasUid;
-use broad::Dispatcher;
+use broad::{BroadPhase, InterferencesBroadPhase, PairEventsBroadPhase, Dispatcher};
+use broad::pair_events::PairEvents;
 
 
 /**
@@ -13,6 +14,7 @@ use broad::Dispatcher;
 pub struct BruteForceBroadPhase<B, D, DV> {
     dispatcher: D,
     pairs:      HashMap<Pair<B>, DV, PairTWHash>,
+    events:     PairEvents<B, DV>,
     objects:    Vec<B>
 }
 
@@ -22,6 +24,7 @@ impl<B: HasUid + Clone, D: Dispatcher<B, B, DV>, DV> BruteForceBroadPhase<B, D,
         BruteForceBroadPhase {
             dispatcher: dispatcher,
             pairs:      HashMap::new(PairTWHash::new()),
+            events:     PairEvents::new(),
             objects:    Vec::new(),
         }
     }
@@ -32,13 +35,51 @@ impl<B: HasUid + Clone, D: Dispatcher<B, B, DV>, DV> BruteForceBroadPhase<B, D,
         &self.pairs
     }
 
-    /// Adds an element to this broad phase.
-    pub fn add(&mut self, b: B) {
+
+    /// Re-evaluates the validity of every pair involving `b`.
+    ///
+    /// This must be called whenever the result of the dispatcher `is_valid` method changes for
+    /// `b`, e.g., when its collision groups are modified. Pairs which became invalid are removed,
+    /// and pairs which became valid are added.
+    pub fn revalidate(&mut self, b: &B) {
+        for o in self.objects.iter() {
+            if o.uid() == b.uid() {
+                continue
+            }
+
+            let key = Pair::new(o.clone(), b.clone());
+
+            if self.dispatcher.is_valid(o, b) {
+                let dispatcher = &mut self.dispatcher;
+                let num_pairs  = self.pairs.len();
+                let _ = self.pairs.find_or_insert_lazy(key.clone(), || dispatcher.dispatch(o, b));
+
+                if self.pairs.len() != num_pairs {
+                    self.events.record_added(key);
+                }
+            }
+            else {
+                match self.pairs.get_and_remove(&key) {
+                    Some(entry) => self.events.record_removed(entry.key, entry.value),
+                    None        => { }
+                }
+            }
+        }
+    }
+}
+
+impl<B: HasUid + Clone, D: Dispatcher<B, B, DV>, DV> BroadPhase<B> for BruteForceBroadPhase<B, D, DV> {
+    fn add(&mut self, b: B) {
         for o in self.objects.iter() {
             if self.dispatcher.is_valid(o, &b) {
                 match self.dispatcher.dispatch(o, &b) {
-                    Some(nf) => { let _ = self.pairs.insert(Pair::new(o.clone(), b.clone()), nf); },
-                    None     => { }
+                    Some(nf) => {
+                        let key = Pair::new(o.clone(), b.clone());
+
+                        let _ = self.pairs.insert(key.clone(), nf);
+                        self.events.record_added(key);
+                    },
+                    None => { }
                 }
             }
         }
@@ -46,16 +87,67 @@ impl<B: HasUid + Clone, D: Dispatcher<B, B, DV>, DV> BruteForceBroadPhase<B, D,
         self.objects.push(b)
     }
 
-    /// Removes an element from this broad phase.
-    pub fn remove(&mut self, _: &B) {
+    fn remove(&mut self, _: &B) {
         fail!("Not yet implemented.");
     }
+
+    /// Does nothing: every object is always assumed to interfere with every other object.
+    #[inline]
+    fn update(&mut self) {
+    }
+
+    /// Does nothing: every object is always assumed to interfere with every other object.
+    #[inline]
+    fn update_object(&mut self, _: &B) {
+    }
+}
+
+impl<B: HasUid + Clone, D: Dispatcher<B, B, DV>, DV>
+InterferencesBroadPhase<B, DV> for BruteForceBroadPhase<B, D, DV> {
+    /// Does nothing: the interferences of inactive objects are never removed.
+    #[inline]
+    fn activate(&mut self, _: &B, _: |&B, &B, &mut DV| -> ()) {
+    }
+
+    /// Does nothing: the interferences of inactive objects are never removed.
+    #[inline]
+    fn deactivate(&mut self, _: &B) {
+    }
+
+    #[inline]
+    fn for_each_pair(&self, f: |&B, &B, &DV| -> ()) {
+        for p in self.pairs.elements().iter() {
+            f(&p.key.first, &p.key.second, &p.value)
+        }
+    }
+
+    #[inline]
+    fn for_each_pair_mut(&mut self, f: |&B, &B, &mut DV| -> ()) {
+        for p in self.pairs.elements_mut().mut_iter() {
+            f(&p.key.first, &p.key.second, &mut p.value)
+        }
+    }
+}
+
+impl<B: HasUid + Clone, D: Dispatcher<B, B, DV>, DV>
+PairEventsBroadPhase<B, DV> for BruteForceBroadPhase<B, D, DV> {
+    #[inline]
+    fn set_pair_events_enabled(&mut self, enabled: bool) {
+        self.events.set_enabled(enabled)
+    }
+
+    fn drain_pair_events(&mut self, added: |&B, &B, &mut DV| -> (), removed: |&B, &B, DV| -> ()) {
+        // There are no inactive pairs.
+        let mut spairs = HashMap::new(PairTWHash::new());
+
+        self.events.drain(&mut self.pairs, &mut spairs, added, removed)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::BruteForceBroadPhase;
-    use broad::NoIdDispatcher;
+    use broad::{NoIdDispatcher, BroadPhase, PairEventsBroadPhase};
     use data::pair::Pair;
 
     #[test]
@@ -87,4 +179,26 @@ mod test {
         assert!(!pairs.contains_key(&Pair::new(b, b)));
         assert!(!pairs.contains_key(&Pair::new(c, c)));
     }
+
+    #[test]
+    fn test_bf_pair_events() {
+        let dispatcher: NoIdDispatcher<int> = NoIdDispatcher;
+        let mut bf = BruteForceBroadPhase::new(dispatcher);
+
+        bf.set_pair_events_enabled(true);
+
+        bf.add(10);
+        bf.add(20);
+        bf.add(30);
+
+        let mut num_added   = 0u;
+        let mut num_removed = 0u;
+
+        bf.drain_pair_events(|_, _, _| num_added = num_added + 1, |_, _, _| num_removed = num_removed + 1);
+        assert_eq!((num_added, num_removed), (3, 0));
+
+        num_added = 0;
+        bf.drain_pair_events(|_, _, _| num_added = num_added + 1, |_, _, _| num_removed = num_removed + 1);
+        assert_eq!((num_added, num_removed), (0, 0));
+    }
 }
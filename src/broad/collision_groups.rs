@@ -0,0 +1,273 @@
+use std::gc::Gc;
+use std::rc::Rc;
+use std::cell::RefCell;
+use broad::Dispatcher;
+
+/// The maximum number of collision groups.
+pub static MAX_GROUPS: uint = 32;
+
+/// Collision groups of an object, used to filter the pairs detected by a broad phase.
+///
+/// An object is a member of some groups, and whitelists and blacklists some groups. Two objects
+/// can interact if each one is a member of at least one group whitelisted by the other one, and if
+/// neither is a member of a group blacklisted by the other one. Groups are identified by integers
+/// smaller than `MAX_GROUPS`.
+#[deriving(Show, PartialEq, Eq, Clone, Encodable, Decodable)]
+pub struct CollisionGroups {
+    membership: u32,
+    whitelist:  u32,
+    blacklist:  u32
+}
+
+impl CollisionGroups {
+    /// Creates collision groups which are members of every group and can interact with every
+    /// group.
+    #[inline]
+    pub fn new() -> CollisionGroups {
+        CollisionGroups {
+            membership: 0xFFFFFFFF,
+            whitelist:  0xFFFFFFFF,
+            blacklist:  0
+        }
+    }
+
+    #[inline]
+    fn bit(group: uint) -> u32 {
+        assert!(group < MAX_GROUPS, "The collision group index must be smaller than `MAX_GROUPS`.");
+
+        1u32 << group
+    }
+
+    #[inline]
+    fn mask(groups: &[uint]) -> u32 {
+        groups.iter().fold(0u32, |mask, g| mask | CollisionGroups::bit(*g))
+    }
+
+    /// Sets the groups these collision groups are members of.
+    #[inline]
+    pub fn set_membership(&mut self, groups: &[uint]) {
+        self.membership = CollisionGroups::mask(groups)
+    }
+
+    /// Sets the groups these collision groups can interact with.
+    #[inline]
+    pub fn set_whitelist(&mut self, groups: &[uint]) {
+        self.whitelist = CollisionGroups::mask(groups)
+    }
+
+    /// Sets the groups these collision groups can never interact with.
+    ///
+    /// The blacklist has precedence over the whitelist.
+    #[inline]
+    pub fn set_blacklist(&mut self, groups: &[uint]) {
+        self.blacklist = CollisionGroups::mask(groups)
+    }
+
+    /// Adds these collision groups to the group `group` if `add` is `true`, removes them from it
+    /// otherwise.
+    #[inline]
+    pub fn modify_membership(&mut self, group: uint, add: bool) {
+        if add {
+            self.membership = self.membership | CollisionGroups::bit(group)
+        }
+        else {
+            self.membership = self.membership & !CollisionGroups::bit(group)
+        }
+    }
+
+    /// Adds `group` to the whitelist if `add` is `true`, removes it otherwise.
+    #[inline]
+    pub fn modify_whitelist(&mut self, group: uint, add: bool) {
+        if add {
+            self.whitelist = self.whitelist | CollisionGroups::bit(group)
+        }
+        else {
+            self.whitelist = self.whitelist & !CollisionGroups::bit(group)
+        }
+    }
+
+    /// Adds `group` to the blacklist if `add` is `true`, removes it otherwise.
+    #[inline]
+    pub fn modify_blacklist(&mut self, group: uint, add: bool) {
+        if add {
+            self.blacklist = self.blacklist | CollisionGroups::bit(group)
+        }
+        else {
+            self.blacklist = self.blacklist & !CollisionGroups::bit(group)
+        }
+    }
+
+    /// Tests if these collision groups are members of `group`.
+    #[inline]
+    pub fn is_member_of(&self, group: uint) -> bool {
+        self.membership & CollisionGroups::bit(group) != 0
+    }
+
+    /// Tests if `group` is whitelisted.
+    #[inline]
+    pub fn is_group_whitelisted(&self, group: uint) -> bool {
+        self.whitelist & CollisionGroups::bit(group) != 0
+    }
+
+    /// Tests if `group` is blacklisted.
+    #[inline]
+    pub fn is_group_blacklisted(&self, group: uint) -> bool {
+        self.blacklist & CollisionGroups::bit(group) != 0
+    }
+
+    /// Tests if objects with these collision groups can interact with objects with the collision
+    /// groups `other`.
+    #[inline]
+    pub fn can_interact_with(&self, other: &CollisionGroups) -> bool {
+        self.membership  & other.whitelist != 0 &&
+        other.membership & self.whitelist  != 0 &&
+        self.membership  & other.blacklist == 0 &&
+        other.membership & self.blacklist  == 0
+    }
+}
+
+/// Trait of objects having collision groups.
+pub trait HasCollisionGroups {
+    /// The collision groups of this object.
+    fn collision_groups(&self) -> CollisionGroups;
+}
+
+impl HasCollisionGroups for CollisionGroups {
+    #[inline]
+    fn collision_groups(&self) -> CollisionGroups {
+        self.clone()
+    }
+}
+
+impl<T: HasCollisionGroups> HasCollisionGroups for RefCell<T> {
+    #[inline]
+    fn collision_groups(&self) -> CollisionGroups {
+        self.borrow().collision_groups()
+    }
+}
+
+impl<T: HasCollisionGroups> HasCollisionGroups for Rc<T> {
+    #[inline]
+    fn collision_groups(&self) -> CollisionGroups {
+        self.deref().collision_groups()
+    }
+}
+
+impl<T: 'static + HasCollisionGroups> HasCollisionGroups for Gc<T> {
+    #[inline]
+    fn collision_groups(&self) -> CollisionGroups {
+        self.deref().collision_groups()
+    }
+}
+
+/// Dispatcher which filters out the pairs of objects with incompatible collision groups.
+///
+/// The other pairs are handled by the wrapped dispatcher. When the collision groups of an object
+/// are modified, the broad phase must be notified with its `revalidate` method so that its pairs
+/// are added or removed accordingly.
+pub struct CollisionGroupsDispatcher<D> {
+    dispatcher: D
+}
+
+impl<D> CollisionGroupsDispatcher<D> {
+    /// Creates a new dispatcher filtering out incompatible collision groups before calling
+    /// `dispatcher`.
+    #[inline]
+    pub fn new(dispatcher: D) -> CollisionGroupsDispatcher<D> {
+        CollisionGroupsDispatcher {
+            dispatcher: dispatcher
+        }
+    }
+
+    /// The wrapped dispatcher.
+    #[inline]
+    pub fn dispatcher<'a>(&'a self) -> &'a D {
+        &self.dispatcher
+    }
+}
+
+impl<B: HasCollisionGroups, DV, D: Dispatcher<B, B, DV>>
+Dispatcher<B, B, DV> for CollisionGroupsDispatcher<D> {
+    #[inline]
+    fn dispatch(&self, a: &B, b: &B) -> Option<DV> {
+        self.dispatcher.dispatch(a, b)
+    }
+
+    #[inline]
+    fn is_valid(&self, a: &B, b: &B) -> bool {
+        a.collision_groups().can_interact_with(&b.collision_groups()) && self.dispatcher.is_valid(a, b)
+    }
+}
+
+#[cfg(test, dim3, f64)]
+mod test {
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use super::{CollisionGroups, CollisionGroupsDispatcher};
+    use broad::{BruteForceBroadPhase, NoIdDispatcher, BroadPhase, PairEventsBroadPhase};
+    use data::pair::Pair;
+
+    #[test]
+    fn test_groups_interaction() {
+        let mut a = CollisionGroups::new();
+        let mut b = CollisionGroups::new();
+
+        assert!(a.can_interact_with(&b));
+
+        a.set_membership(&[1]);
+        b.set_whitelist(&[0, 2]);
+        assert!(!a.can_interact_with(&b));
+        assert!(!b.can_interact_with(&a));
+
+        b.modify_whitelist(1, true);
+        assert!(a.can_interact_with(&b));
+
+        a.modify_blacklist(3, true);
+        assert!(!a.can_interact_with(&b));
+
+        b.set_membership(&[4]);
+        assert!(a.can_interact_with(&b));
+    }
+
+    #[test]
+    fn test_bf_revalidate() {
+        type Object = Rc<RefCell<CollisionGroups>>;
+        let dispatcher: NoIdDispatcher<Object> = NoIdDispatcher;
+        let mut bf = BruteForceBroadPhase::new(CollisionGroupsDispatcher::new(dispatcher));
+
+        let a = Rc::new(RefCell::new(CollisionGroups::new()));
+        let b = Rc::new(RefCell::new(CollisionGroups::new()));
+        let c = Rc::new(RefCell::new(CollisionGroups::new()));
+
+        b.borrow_mut().set_membership(&[2]);
+        c.borrow_mut().set_membership(&[1]);
+        a.borrow_mut().set_blacklist(&[1]);
+
+        bf.add(a.clone());
+        bf.add(b.clone());
+        bf.add(c.clone());
+
+        assert_eq!(bf.pairs().len(), 2);
+        assert!(!bf.pairs().contains_key(&Pair::new(a.clone(), c.clone())));
+
+        bf.set_pair_events_enabled(true);
+
+        a.borrow_mut().set_blacklist(&[]);
+        c.borrow_mut().set_whitelist(&[0]);
+        bf.revalidate(&c);
+
+        assert_eq!(bf.pairs().len(), 2);
+        assert!(bf.pairs().contains_key(&Pair::new(a.clone(), c.clone())));
+        assert!(!bf.pairs().contains_key(&Pair::new(b.clone(), c.clone())));
+
+        // The revalidation replaced the pair (b, c) by the pair (a, c).
+        let mut added   = Vec::new();
+        let mut removed = Vec::new();
+
+        bf.drain_pair_events(|o1, o2, _| added.push(Pair::new(o1.clone(), o2.clone())),
+                             |o1, o2, _| removed.push(Pair::new(o1.clone(), o2.clone())));
+
+        assert!(added == vec!(Pair::new(a.clone(), c.clone())));
+        assert!(removed == vec!(Pair::new(b.clone(), c.clone())));
+    }
+}
@@ -1,38 +1,67 @@
-use std::gc::{GC, Gc};
-use std::cell::RefCell;
 use nalgebra::na::Translation;
 use nalgebra::na;
-use broad::{BroadPhase, InterferencesBroadPhase, BoundingVolumeBroadPhase, RayCastBroadPhase};
-use partitioning::{DBVT, DBVTLeaf};
+use broad::{BroadPhase, InterferencesBroadPhase, BoundingVolumeBroadPhase, RayCastBroadPhase,
+            PairEventsBroadPhase, PointQueryBroadPhase};
+use broad::pair_events::PairEvents;
+use partitioning::{DBVT, DBVTLeaf, DBVTLeafId};
 use data::hash::UintTWHash;
 use data::hash_map::HashMap;
 use data::pair::{Pair, PairTWHash};
 use data::has_uid::HasUid;
 use broad::Dispatcher;
-use bounding_volume::{HasBoundingVolume, LooseBoundingVolume};
+use bounding_volume::{HasBoundingVolume, LooseBoundingVolume, SurfaceArea};
 use ray::{Ray, RayCast};
+use point::PointQuery;
 use partitioning::{BoundingVolumeInterferencesCollector, RayInterferencesCollector};
 use math::{Scalar, Vect};
 
+/// An object of the broad phase and the location of its leaf.
+struct DBVTProxy<B> {
+    object: B,
+    leaf:   DBVTLeafId,
+    active: bool // `true` if `leaf` is on the active tree.
+}
+
 /// Broad phase based on a Dynamic Bounding Volume Tree.
 ///
 /// It uses two separate trees: one for static objects and which is never updated, and one for
-/// moving objects.
+/// moving objects. The trees only store the objects identifiers so this broad phase does not
+/// rely on any managed box.
 pub struct DBVTBroadPhase<B, BV, D, DV> {
-    tree:        DBVT<B, BV>,
-    stree:       DBVT<B, BV>,
-    active2bv:   HashMap<uint, Gc<RefCell<DBVTLeaf<B, BV>>>, UintTWHash>,
-    inactive2bv: HashMap<uint, Gc<RefCell<DBVTLeaf<B, BV>>>, UintTWHash>,
-    pairs:       HashMap<Pair<Gc<RefCell<DBVTLeaf<B, BV>>>>, DV, PairTWHash>, // pair manager
-    spairs:      HashMap<Pair<Gc<RefCell<DBVTLeaf<B, BV>>>>, DV, PairTWHash>,
-    dispatcher:  D,
-    margin:      Scalar,
-    collector:   Vec<Gc<RefCell<DBVTLeaf<B, BV>>>>,
-    to_update:   Vec<Gc<RefCell<DBVTLeaf<B, BV>>>>,
-    update_off:  uint // incremental pairs removal index
+    tree:       DBVT<uint, BV>,
+    stree:      DBVT<uint, BV>,
+    proxies:    HashMap<uint, DBVTProxy<B>, UintTWHash>,
+    pairs:      HashMap<Pair<B>, DV, PairTWHash>, // pair manager
+    spairs:     HashMap<Pair<B>, DV, PairTWHash>,
+    events:     PairEvents<B, DV>,
+    dispatcher: D,
+    margin:     Scalar,
+    collector:  Vec<DBVTLeafId>,
+    to_update:  Vec<uint>,
+    update_off: uint // incremental pairs removal index
+}
+
+/// The leaf of the object identified by `uid`, on the tree it currently belongs to.
+fn leaf_of<'a, B, BV>(proxies: &HashMap<uint, DBVTProxy<B>, UintTWHash>,
+                      tree:    &'a DBVT<uint, BV>,
+                      stree:   &'a DBVT<uint, BV>,
+                      uid:     uint)
+                      -> &'a DBVTLeaf<uint, BV> {
+    match proxies.find(&uid) {
+        Some(p) => if p.active { tree.leaf(p.leaf) } else { stree.leaf(p.leaf) },
+        None    => fail!("Internal error: found a leaf without proxy.")
+    }
+}
+
+/// The object identified by `uid`.
+fn object_of<'a, B>(proxies: &'a HashMap<uint, DBVTProxy<B>, UintTWHash>, uid: uint) -> &'a B {
+    match proxies.find(&uid) {
+        Some(p) => &p.object,
+        None    => fail!("Internal error: found a leaf without proxy.")
+    }
 }
 
-impl<B:  'static + HasBoundingVolume<BV> + Clone,
+impl<B:  'static + HasBoundingVolume<BV> + HasUid + Clone,
      BV: 'static + LooseBoundingVolume + Translation<Vect> + Clone,
      D:  Dispatcher<B, B, DV>,
      DV>
@@ -40,17 +69,17 @@ DBVTBroadPhase<B, BV, D, DV> {
     /// Creates a new broad phase based on a Dynamic Bounding Volume Tree.
     pub fn new(dispatcher: D, margin: Scalar) -> DBVTBroadPhase<B, BV, D, DV> {
         DBVTBroadPhase {
-            tree:        DBVT::new(),
-            stree:       DBVT::new(),
-            active2bv:   HashMap::new(UintTWHash::new()),
-            inactive2bv: HashMap::new(UintTWHash::new()),
-            pairs:       HashMap::new(PairTWHash::new()),
-            spairs:      HashMap::new(PairTWHash::new()),
-            dispatcher:  dispatcher,
-            update_off:  0,
-            collector:   Vec::new(),
-            to_update:   Vec::new(),
-            margin:      margin
+            tree:       DBVT::new(),
+            stree:      DBVT::new(),
+            proxies:    HashMap::new(UintTWHash::new()),
+            pairs:      HashMap::new(PairTWHash::new()),
+            spairs:     HashMap::new(PairTWHash::new()),
+            events:     PairEvents::new(),
+            dispatcher: dispatcher,
+            update_off: 0,
+            collector:  Vec::new(),
+            to_update:  Vec::new(),
+            margin:     margin
         }
     }
 
@@ -62,33 +91,46 @@ DBVTBroadPhase<B, BV, D, DV> {
 
     fn update_updatable(&mut self) {
         /*
-         * Re-insert outdated nodes one by one and collect interferences at the same time.
+         * Collect the interferences of the nodes re-inserted since the last update.
          */
         let mut new_colls = 0u;
 
         for u in self.to_update.iter() {
-            { // scope to avoid dynamic borrow failur.
-                let bu = u.borrow();
-                self.tree.interferences_with_leaf(bu.deref(), &mut self.collector);
-                self.stree.interferences_with_leaf(bu.deref(), &mut self.collector);
-
-                // dispatch
-                for i in self.collector.iter() {
-                    let bi = i.borrow();
-                    if self.dispatcher.is_valid(&bu.object, &bi.object) {
-                        let dispatcher = &mut self.dispatcher;
-                        let _ = self.pairs.find_or_insert_lazy(
-                            Pair::new(u.clone(), i.clone()),
-                            || dispatcher.dispatch(&bu.object, &bi.object)
-                            );
-
-                        new_colls = new_colls + 1;
+            let leaf = leaf_of(&self.proxies, &self.tree, &self.stree, *u);
+
+            self.tree.interferences_with_leaf(leaf, &mut self.collector);
+            let num_active = self.collector.len();
+            self.stree.interferences_with_leaf(leaf, &mut self.collector);
+
+            // dispatch
+            for (k, i) in self.collector.iter().enumerate() {
+                let other = if k < num_active { self.tree.leaf(*i).object } else { self.stree.leaf(*i).object };
+
+                // The leaf of `u` is still in the tree so it interferes with itself.
+                if other == *u {
+                    continue
+                }
+
+                let bu    = object_of(&self.proxies, *u);
+                let bi    = object_of(&self.proxies, other);
+
+                if self.dispatcher.is_valid(bu, bi) {
+                    let dispatcher = &mut self.dispatcher;
+                    let num_pairs  = self.pairs.len();
+                    let _ = self.pairs.find_or_insert_lazy(
+                        Pair::new(bu.clone(), bi.clone()),
+                        || dispatcher.dispatch(bu, bi)
+                        );
+
+                    if self.pairs.len() != num_pairs {
+                        self.events.record_added(Pair::new(bu.clone(), bi.clone()));
                     }
+
+                    new_colls = new_colls + 1;
                 }
             }
 
             self.collector.clear();
-            self.tree.insert(*u);
         }
 
         /*
@@ -107,18 +149,19 @@ DBVTBroadPhase<B, BV, D, DV> {
                     let elts  = self.pairs.elements();
                     let entry = &elts[id];
 
-                    let bf = entry.key.first.borrow();
-                    let bs = entry.key.second.borrow();
-                    if !bf.bounding_volume.intersects(&bs.bounding_volume) {
-                        true
-                    }
-                    else {
-                        false
-                    }
+                    let lf = leaf_of(&self.proxies, &self.tree, &self.stree, entry.key.first.uid());
+                    let ls = leaf_of(&self.proxies, &self.tree, &self.stree, entry.key.second.uid());
+
+                    !lf.bounding_volume.intersects(&ls.bounding_volume)
                 };
 
                 if remove {
-                    self.pairs.remove_elem_at(id);
+                    let key = self.pairs.elements()[id].key.clone();
+
+                    match self.pairs.get_and_remove(&key) {
+                        Some(entry) => self.events.record_removed(entry.key, entry.value),
+                        None        => { }
+                    }
                 }
             }
 
@@ -127,6 +170,135 @@ DBVTBroadPhase<B, BV, D, DV> {
 
         self.to_update.clear();
     }
+
+    /// Removes every pair involving the object identified by `uid`, or only those rejected by the
+    /// dispatcher if `only_invalid` is `true`.
+    fn remove_pairs_involving(&mut self, uid: uint, only_invalid: bool) {
+        let mut keys_to_remove = Vec::new();
+
+        for elt in self.pairs.elements().iter() {
+            if (elt.key.first.uid() == uid || elt.key.second.uid() == uid) &&
+               (!only_invalid || !self.dispatcher.is_valid(&elt.key.first, &elt.key.second)) {
+                keys_to_remove.push(elt.key.clone());
+            }
+        }
+
+        for k in keys_to_remove.iter() {
+            match self.pairs.get_and_remove(k) {
+                Some(entry) => self.events.record_removed(entry.key, entry.value),
+                None        => { }
+            }
+        }
+
+        keys_to_remove.clear();
+
+        // "sleeping" pairs
+        for elt in self.spairs.elements().iter() {
+            if (elt.key.first.uid() == uid || elt.key.second.uid() == uid) &&
+               (!only_invalid || !self.dispatcher.is_valid(&elt.key.first, &elt.key.second)) {
+                keys_to_remove.push(elt.key.clone());
+            }
+        }
+
+        for k in keys_to_remove.iter() {
+            match self.spairs.get_and_remove(k) {
+                Some(entry) => self.events.record_removed(entry.key, entry.value),
+                None        => { }
+            }
+        }
+    }
+
+    /// Pushes the objects of the leaves collected from the active (resp. inactive) tree to `out`.
+    fn output_collected(&mut self, active: bool, out: &mut Vec<B>) {
+        for l in self.collector.iter() {
+            let uid = if active { self.tree.leaf(*l).object } else { self.stree.leaf(*l).object };
+
+            out.push(object_of(&self.proxies, uid).clone())
+        }
+
+        self.collector.clear()
+    }
+
+    /// Re-evaluates the validity of every pair involving `b`.
+    ///
+    /// This must be called whenever the result of the dispatcher `is_valid` method changes for
+    /// `b`, e.g., when its collision groups are modified. Pairs which became invalid are removed,
+    /// and pairs which became valid are added if the bounding volumes intersect.
+    pub fn revalidate(&mut self, b: &B) {
+        let uid    = b.uid();
+        let active =
+            match self.proxies.find(&uid) {
+                Some(p) => p.active,
+                None    => return
+            };
+
+        /*
+         * Remove the pairs which became invalid.
+         */
+        self.remove_pairs_involving(uid, true);
+
+        /*
+         * Add the pairs which became valid.
+         */
+        let leaf = leaf_of(&self.proxies, &self.tree, &self.stree, uid);
+
+        self.tree.interferences_with_leaf(leaf, &mut self.collector);
+        let num_active = self.collector.len();
+        self.stree.interferences_with_leaf(leaf, &mut self.collector);
+
+        for (k, i) in self.collector.iter().enumerate() {
+            let other = if k < num_active { self.tree.leaf(*i).object } else { self.stree.leaf(*i).object };
+
+            if other == uid {
+                continue
+            }
+
+            let bo = object_of(&self.proxies, other);
+
+            if self.dispatcher.is_valid(b, bo) {
+                // interferences between inactive objects are kept apart.
+                let pairs =
+                    if active || k < num_active {
+                        &mut self.pairs
+                    }
+                    else {
+                        &mut self.spairs
+                    };
+
+                let dispatcher = &mut self.dispatcher;
+                let num_pairs  = pairs.len();
+                let _ = pairs.find_or_insert_lazy(
+                    Pair::new(b.clone(), bo.clone()),
+                    || dispatcher.dispatch(b, bo)
+                    );
+
+                if pairs.len() != num_pairs {
+                    self.events.record_added(Pair::new(b.clone(), bo.clone()));
+                }
+            }
+        }
+
+        self.collector.clear();
+    }
+}
+
+impl<B:  'static + HasBoundingVolume<BV> + Clone + HasUid,
+     BV: 'static + LooseBoundingVolume + SurfaceArea + Translation<Vect> + Clone,
+     D:  Dispatcher<B, B, DV>,
+     DV>
+DBVTBroadPhase<B, BV, D, DV> {
+    /// Runs one step of the incremental optimization of the trees of this broad phase.
+    ///
+    /// At most `budget` internal nodes of each tree are visited. Returns the number of rotations
+    /// performed. See `DBVT::optimize_incremental`.
+    pub fn optimize_incremental(&mut self, budget: uint) -> uint {
+        self.tree.optimize_incremental(budget) + self.stree.optimize_incremental(budget)
+    }
+
+    /// The sum of the surface areas of the internal nodes of the trees of this broad phase.
+    pub fn total_internal_surface_area(&self) -> Scalar {
+        self.tree.total_internal_surface_area() + self.stree.total_internal_surface_area()
+    }
 }
 
 impl<B:  'static + HasBoundingVolume<BV> + Clone + HasUid,
@@ -136,84 +308,51 @@ impl<B:  'static + HasBoundingVolume<BV> + Clone + HasUid,
 BroadPhase<B> for DBVTBroadPhase<B, BV, D, DV> {
     #[inline]
     fn add(&mut self, b: B) {
-        let id   = b.uid();
-        let leaf = box(GC) RefCell::new(DBVTLeaf::new(b.bounding_volume().loosened(self.margin.clone()), b));
+        let uid  = b.uid();
+        let leaf = self.tree.insert(uid, b.bounding_volume().loosened(self.margin.clone()));
 
-        self.to_update.push(leaf);
+        self.proxies.insert(uid, DBVTProxy { object: b, leaf: leaf, active: true });
+        self.to_update.push(uid);
         self.update_updatable();
-
-        self.active2bv.insert(id, leaf);
     }
 
     fn remove(&mut self, b: &B) {
         // remove b from the dbvts
-        let key      = b.uid();
-        let leaf_opt = self.active2bv.get_and_remove(&key);
-        let mut leaf;
-
-        match leaf_opt {
-            Some(l) => {
-                leaf = l.value;
-                self.tree.remove(&mut leaf);
-            },
-            None => {
-                let leaf_opt = self.inactive2bv.get_and_remove(&key);
-                match leaf_opt {
-                    Some(l) => {
-                        leaf = l.value;
-                        self.stree.remove(&mut leaf);
-                    },
-                    None => return
+        let uid = b.uid();
+
+        match self.proxies.get_and_remove(&uid) {
+            Some(p) => {
+                if p.value.active {
+                    let _ = self.tree.remove(p.value.leaf);
                 }
-            }
+                else {
+                    let _ = self.stree.remove(p.value.leaf);
+                }
+            },
+            None => return
         }
 
-        let mut keys_to_remove = Vec::new();
-
         // remove every pair involving b
-        for elt in self.pairs.elements().iter() {
-            if elt.key.first.uid() == leaf.uid() || elt.key.second.uid() == leaf.uid() {
-                keys_to_remove.push(elt.key);
-            }
-        }
-
-        for k in keys_to_remove.iter() {
-            self.pairs.remove(k);
-        }
-
-        keys_to_remove.clear();
-
-        // remove every "sleeping" pair involving b
-        for elt in self.spairs.elements().iter() {
-            if elt.key.first.uid() == leaf.uid() || elt.key.second.uid() == leaf.uid() {
-                keys_to_remove.push(elt.key);
-            }
-        }
-
-        for k in keys_to_remove.iter() {
-            self.spairs.remove(k);
-        }
+        self.remove_pairs_involving(uid, false)
     }
 
     fn update(&mut self) {
         // NOTE: be careful not to add the same object twice!
         /*
-         * Remove all outdated nodes
+         * Move all outdated leaves
          */
-        for a in self.active2bv.elements_mut().mut_iter() {
-            let mut new_bv = a.value.borrow().object.bounding_volume();
+        for p in self.proxies.elements().iter() {
+            if !p.value.active {
+                continue
+            }
+
+            let mut new_bv = p.value.object.bounding_volume();
 
-            if !a.value.borrow().bounding_volume.contains(&new_bv) {
+            if !self.tree.leaf(p.value.leaf).bounding_volume.contains(&new_bv) {
                 // need an update!
                 new_bv.loosen(self.margin.clone());
-
-                {
-                    let mut bv = a.value.borrow_mut();
-                    bv.bounding_volume = new_bv;
-                }
-
-                self.tree.remove(&mut a.value);
-                self.to_update.push(a.value);
+                self.tree.set_bounding_volume(p.value.leaf, new_bv);
+                self.to_update.push(p.key);
             }
         }
 
@@ -221,21 +360,18 @@ BroadPhase<B> for DBVTBroadPhase<B, BV, D, DV> {
     }
 
     fn update_object(&mut self, object: &B) {
-        match self.active2bv.find_mut(&object.uid()) {
-            None       => { },
-            Some(leaf) => {
-                let mut new_bv = leaf.borrow().object.bounding_volume();
-                if !leaf.borrow().bounding_volume.contains(&new_bv) {
+        match self.proxies.find(&object.uid()) {
+            Some(p) if p.active => {
+                let mut new_bv = p.object.bounding_volume();
+
+                if !self.tree.leaf(p.leaf).bounding_volume.contains(&new_bv) {
                     // update for real
                     new_bv.loosen(self.margin.clone());
-                    {
-                        let mut bl = leaf.borrow_mut();
-                        bl.bounding_volume = new_bv;
-                    }
-                    self.tree.remove(leaf);
-                    self.to_update.push(leaf.clone());
+                    self.tree.set_bounding_volume(p.leaf, new_bv);
+                    self.to_update.push(object.uid());
                 }
-            }
+            },
+            _ => { }
         }
 
         self.update_updatable();
@@ -250,102 +386,120 @@ InterferencesBroadPhase<B, DV> for DBVTBroadPhase<B, BV, D, DV> {
     #[inline(always)]
     fn for_each_pair(&self, f: |&B, &B, &DV| -> ()) {
         for p in self.pairs.elements().iter() {
-            let bf = p.key.first.borrow_mut();
-            let bs = p.key.second.borrow_mut();
-            f(&bf.object, &bs.object, &p.value)
+            f(&p.key.first, &p.key.second, &p.value)
         }
     }
 
     #[inline(always)]
     fn for_each_pair_mut(&mut self, f: |&B, &B, &mut DV| -> ()) {
         for p in self.pairs.elements_mut().mut_iter() {
-            let bf = p.key.first.borrow();
-            let bs = p.key.second.borrow();
-            f(&bf.object, &bs.object, &mut p.value)
+            f(&p.key.first, &p.key.second, &mut p.value)
         }
     }
 
     #[inline(always)]
     fn activate(&mut self, body: &B, f: |&B, &B, &mut DV| -> ()) {
-        // verify that it is not already active and add it to the active map.
-        let mut leaf =
-            match self.inactive2bv.get_and_remove(&body.uid()) {
-                None    => return, // not found: the object is already active
-                Some(l) => l.value
+        // verify that it is not already active.
+        let uid  = body.uid();
+        let leaf =
+            match self.proxies.find(&uid) {
+                Some(p) if !p.active => p.leaf,
+                _                    => return // not found or already active
             };
 
-        self.active2bv.insert(body.uid(), leaf);
-
         // remove from the inactive tree
-        self.stree.remove(&mut leaf);
+        let leaf = self.stree.remove(leaf);
 
         // Now we find interferences with inactive objects.
-        { // scope to avoid dynamic borrow failure
-            let bleaf = leaf.borrow();
-            self.stree.interferences_with_leaf(bleaf.deref(), &mut self.collector);
-
-            for i in self.collector.iter() {
-                let bi = i.borrow();
-                if self.dispatcher.is_valid(&bleaf.object, &bi.object) {
-                    // the intereference should be registered on the spairs already
-                    match self.spairs.get_and_remove(&Pair::new(leaf, *i)) {
-                        Some(dv) => {
-                            let key   = dv.key;
-                            let value = dv.value;
-                            let bdvf  = key.first.borrow();
-                            let bdvs  = key.second.borrow();
-                            let obj1  = &bdvf.object;
-                            let obj2  = &bdvs.object;
-                            let p     = self.pairs.insert_or_replace(key, value, true);
-
-                            f(obj1, obj2, p)
-                        },
-                        None => fail!("Internal error: found a new collision during the activation.")
-                    }
+        self.stree.interferences_with_leaf(&leaf, &mut self.collector);
+
+        for i in self.collector.iter() {
+            let bi = object_of(&self.proxies, self.stree.leaf(*i).object);
+
+            if self.dispatcher.is_valid(body, bi) {
+                // the intereference should be registered on the spairs already
+                match self.spairs.get_and_remove(&Pair::new(body.clone(), bi.clone())) {
+                    Some(dv) => {
+                        let obj1 = dv.key.first.clone();
+                        let obj2 = dv.key.second.clone();
+                        let p    = self.pairs.insert_or_replace(dv.key, dv.value, true);
+
+                        f(&obj1, &obj2, p)
+                    },
+                    None => fail!("Internal error: found a new collision during the activation.")
                 }
             }
         }
 
-        // add to the active tree
-        self.tree.insert(leaf);
         self.collector.clear();
+
+        // add to the active tree
+        let new_leaf = self.tree.insert(uid, leaf.bounding_volume);
+
+        match self.proxies.find_mut(&uid) {
+            Some(p) => {
+                p.leaf   = new_leaf;
+                p.active = true;
+            },
+            None => unreachable!()
+        }
     }
 
     fn deactivate(&mut self, body: &B) {
-        // verify that it is not already inactive and add it to the inactive map.
-        let mut leaf =
-            match self.active2bv.get_and_remove(&body.uid()) {
-                None    => return, // not found: the object is already inactive
-                Some(l) => l.value
+        // verify that it is not already inactive.
+        let uid  = body.uid();
+        let leaf =
+            match self.proxies.find(&uid) {
+                Some(p) if p.active => p.leaf,
+                _                   => return // not found or already inactive
             };
 
-        self.inactive2bv.insert(body.uid(), leaf);
+        // remove from the active tree
+        let leaf = self.tree.remove(leaf);
 
         // Now transfer all collisions involving `leaf` and deactivated objects from `pairs` to
         // `spairs`.
+        self.stree.interferences_with_leaf(&leaf, &mut self.collector);
 
-        // remove from the active tree
-        self.tree.remove(&mut leaf);
-
-        { // scope to avoid dynamic borrow failure of leaf
-            let bleaf = leaf.borrow();
-            self.stree.interferences_with_leaf(bleaf.deref(), &mut self.collector);
-
-            for i in self.collector.iter() {
-                let fi = i.borrow();
-                if self.dispatcher.is_valid(&bleaf.object, &fi.object) {
-                    // the intereference should be registered on the pairs already
-                    match self.pairs.get_and_remove(&Pair::new(leaf, *i)) {
-                        Some(dv) => { self.spairs.insert(dv.key, dv.value); },
-                        None     => fail!("Internal error: found a new collision during the deactivation.")
-                    }
+        for i in self.collector.iter() {
+            let bi = object_of(&self.proxies, self.stree.leaf(*i).object);
+
+            if self.dispatcher.is_valid(body, bi) {
+                // the intereference should be registered on the pairs already
+                match self.pairs.get_and_remove(&Pair::new(body.clone(), bi.clone())) {
+                    Some(dv) => { self.spairs.insert(dv.key, dv.value); },
+                    None     => fail!("Internal error: found a new collision during the deactivation.")
                 }
             }
         }
 
-        // add to the inactive tree
-        self.stree.insert(leaf);
         self.collector.clear();
+
+        // add to the inactive tree
+        let new_leaf = self.stree.insert(uid, leaf.bounding_volume);
+
+        match self.proxies.find_mut(&uid) {
+            Some(p) => {
+                p.leaf   = new_leaf;
+                p.active = false;
+            },
+            None => unreachable!()
+        }
+    }
+}
+
+impl<B:  'static + HasBoundingVolume<BV> + HasUid + Clone,
+     BV: 'static + LooseBoundingVolume + Translation<Vect> + Clone,
+     D:  Dispatcher<B, B, DV>,
+     DV>
+PairEventsBroadPhase<B, DV> for DBVTBroadPhase<B, BV, D, DV> {
+    #[inline]
+    fn set_pair_events_enabled(&mut self, enabled: bool) {
+        self.events.set_enabled(enabled)
+    }
+
+    fn drain_pair_events(&mut self, added: |&B, &B, &mut DV| -> (), removed: |&B, &B, DV| -> ()) {
+        self.events.drain(&mut self.pairs, &mut self.spairs, added, removed)
     }
 }
 
@@ -359,14 +513,17 @@ BoundingVolumeBroadPhase<B, BV> for DBVTBroadPhase<B, BV, D, DV> {
             let mut visitor = BoundingVolumeInterferencesCollector::new(bv, &mut self.collector);
 
             self.tree.visit(&mut visitor);
-            self.stree.visit(&mut visitor);
         }
 
-        for l in self.collector.iter() {
-            out.push(l.borrow().object.clone())
+        self.output_collected(true, out);
+
+        {
+            let mut visitor = BoundingVolumeInterferencesCollector::new(bv, &mut self.collector);
+
+            self.stree.visit(&mut visitor);
         }
 
-        self.collector.clear()
+        self.output_collected(false, out)
     }
 }
 
@@ -380,14 +537,98 @@ RayCastBroadPhase<B> for DBVTBroadPhase<B, BV, D, DV> {
             let mut visitor = RayInterferencesCollector::new(ray, &mut self.collector);
 
             self.tree.visit(&mut visitor);
+        }
+
+        self.output_collected(true, out);
+
+        {
+            let mut visitor = RayInterferencesCollector::new(ray, &mut self.collector);
+
             self.stree.visit(&mut visitor);
         }
 
-        for l in self.collector.iter() {
-            out.push(l.borrow().object.clone())
+        self.output_collected(false, out)
+    }
+}
+
+impl<B:  'static + HasBoundingVolume<BV> + HasUid + Clone,
+     BV: 'static + LooseBoundingVolume + PointQuery + Translation<Vect> + Clone,
+     D:  Dispatcher<B, B, DV>,
+     DV>
+PointQueryBroadPhase<B> for DBVTBroadPhase<B, BV, D, DV> {
+    fn interferences_with_point(&mut self, pt: &Vect, dist_fn: |&B, &Vect| -> Scalar, out: &mut Vec<B>) {
+        {
+            let proxies      = &self.proxies;
+            let mut uid_dist = |uid: &uint, pt: &Vect| dist_fn(object_of(proxies, *uid), pt);
+
+            self.tree.interferences_with_point(pt, &mut uid_dist, &mut self.collector);
         }
 
-        self.collector.clear()
+        self.output_collected(true, out);
+
+        {
+            let proxies      = &self.proxies;
+            let mut uid_dist = |uid: &uint, pt: &Vect| dist_fn(object_of(proxies, *uid), pt);
+
+            self.stree.interferences_with_point(pt, &mut uid_dist, &mut self.collector);
+        }
+
+        self.output_collected(false, out)
+    }
+
+    fn k_nearest_to_point(&mut self,
+                          pt:      &Vect,
+                          k:       uint,
+                          dist_fn: |&B, &Vect| -> Scalar,
+                          out:     &mut Vec<(Scalar, B)>) {
+        let proxies      = &self.proxies;
+        let mut uid_dist = |uid: &uint, pt: &Vect| dist_fn(object_of(proxies, *uid), pt);
+        let mut leaves   = Vec::new();
+        let mut nearest  = Vec::new();
+
+        self.tree.k_nearest_to_point(pt, k, &mut uid_dist, &mut leaves);
+
+        for &(dist, l) in leaves.iter() {
+            nearest.push((dist, self.tree.leaf(l).object))
+        }
+
+        leaves.clear();
+        self.stree.k_nearest_to_point(pt, k, &mut uid_dist, &mut leaves);
+
+        for &(dist, l) in leaves.iter() {
+            nearest.push((dist, self.stree.leaf(l).object))
+        }
+
+        // Merge the results of both trees.
+        nearest.sort_by(|a, b| a.ref0().partial_cmp(b.ref0()).unwrap_or(Equal));
+        nearest.truncate(k);
+
+        for &(dist, uid) in nearest.iter() {
+            out.push((dist, object_of(proxies, uid).clone()))
+        }
+    }
+
+    fn nearest_to_point_within_distance(&mut self,
+                                        pt:       &Vect,
+                                        max_dist: Scalar,
+                                        dist_fn:  |&B, &Vect| -> Scalar)
+                                        -> Option<(Scalar, B)> {
+        let proxies      = &self.proxies;
+        let mut uid_dist = |uid: &uint, pt: &Vect| dist_fn(object_of(proxies, *uid), pt);
+        let active       = self.tree.nearest_to_point_within_distance(pt, max_dist, &mut uid_dist)
+                               .map(|(dist, l)| (dist, self.tree.leaf(l).object));
+
+        // The inactive objects must be closer than the best active one.
+        let max_dist = match active {
+            Some((dist, _)) => dist,
+            None            => max_dist
+        };
+
+        let inactive = self.stree.nearest_to_point_within_distance(pt, max_dist, &mut uid_dist)
+                           .map(|(dist, l)| (dist, self.stree.leaf(l).object));
+        let nearest  = if inactive.is_some() { inactive } else { active };
+
+        nearest.map(|(dist, uid)| (dist, object_of(proxies, uid).clone()))
     }
 }
 
@@ -401,7 +642,8 @@ mod test {
     use nalgebra::na;
     use geom::Ball;
     use bounding_volume::WithAABB;
-    use broad::{NoIdDispatcher, BroadPhase, InterferencesBroadPhase};
+    use point::PointQuery;
+    use broad::{NoIdDispatcher, BroadPhase, InterferencesBroadPhase, PointQueryBroadPhase};
 
     // #[test]
     // fn test_dbvt_empty() {
@@ -507,6 +749,46 @@ mod test {
         )
     }
 
+    #[test]
+    fn test_dbvt_point_queries() {
+        type Shape = Rc<RefCell<WithAABB<Ball>>>;
+        let dispatcher: NoIdDispatcher<Shape> = NoIdDispatcher;
+        let mut bf     = DBVTBroadPhase::new(dispatcher, 0.2);
+        let ball       = Ball::new(0.3);
+
+        // create a grid
+        for i in range(-10, 10) {
+            for j in range(-10, 10) {
+                let t = Vec3::new(i as f64 * 0.9, j as f64 * 0.9, 0.0);
+                bf.add(Rc::new(RefCell::new(WithAABB(Iso3::new(t, na::zero()), ball))));
+            }
+        }
+
+        bf.update();
+
+        let dist = |s: &Shape, pt: &Vec3<f64>| {
+            let bs = s.borrow();
+            bs.g().distance_to_point_with_transform(bs.m(), pt)
+        };
+
+        let mut inside = Vec::new();
+        bf.interferences_with_point(&Vec3::new(0.1, 0.0, 0.0), |s, pt| dist(s, pt), &mut inside);
+        assert_eq!(inside.len(), 1);
+
+        let mut nearest = Vec::new();
+        bf.k_nearest_to_point(&na::zero(), 5, |s, pt| dist(s, pt), &mut nearest);
+        assert_eq!(nearest.len(), 5);
+        assert!(na::approx_eq(nearest.get(0).ref0(), &0.0));
+
+        for n in nearest.slice_from(1).iter() {
+            assert!(na::approx_eq(n.ref0(), &0.6));
+        }
+
+        let far = Vec3::new(100.0, 0.0, 0.0);
+        assert!(bf.nearest_to_point_within_distance(&far, 1.0, |s, pt| dist(s, pt)).is_none());
+        assert!(bf.nearest_to_point_within_distance(&far, 100.0, |s, pt| dist(s, pt)).is_some());
+    }
+
     // #[test]
     // fn test_dbvt_quadratic_collide() {
     //     type Shape = Rc<WithAABB<Ball>>;
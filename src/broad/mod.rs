@@ -3,16 +3,26 @@
 // types and traits
 pub use broad::broad_phase::{BroadPhase,
                              InterferencesBroadPhase,
+                             PairEventsBroadPhase,
                              BoundingVolumeBroadPhase,
-                             RayCastBroadPhase};
+                             RayCastBroadPhase,
+                             PointQueryBroadPhase};
 pub use broad::dispatcher::{Dispatcher, NoIdDispatcher};
+pub use broad::collision_groups::{CollisionGroups, HasCollisionGroups, CollisionGroupsDispatcher,
+                                  MAX_GROUPS};
 pub use broad::brute_force_broad_phase::BruteForceBroadPhase;
 pub use broad::brute_force_bounding_volume_broad_phase::BruteForceBoundingVolumeBroadPhase;
 pub use broad::dbvt_broad_phase::DBVTBroadPhase;
+pub use broad::sap_broad_phase::SAPBroadPhase;
+pub use broad::spatial_hash_broad_phase::SpatialHashBroadPhase;
 
 // modules
 mod broad_phase;
+mod pair_events;
 mod dispatcher;
+mod collision_groups;
 mod brute_force_broad_phase;
 mod brute_force_bounding_volume_broad_phase;
 mod dbvt_broad_phase;
+mod sap_broad_phase;
+mod spatial_hash_broad_phase;
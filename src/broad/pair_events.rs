@@ -0,0 +1,86 @@
+use std::mem;
+use data::hash_map::HashMap;
+use data::pair::{Pair, PairTWHash};
+use data::has_uid::HasUid;
+
+/// Buffer of the pairs added to and removed from the pair manager of a broad phase.
+///
+/// A pair added then removed before the events are drained is forgotten altogether.
+pub struct PairEvents<P, DV> {
+    enabled: bool,
+    added:   HashMap<Pair<P>, (), PairTWHash>,
+    removed: Vec<(Pair<P>, DV)>
+}
+
+impl<P: HasUid + Clone, DV> PairEvents<P, DV> {
+    /// Creates a new, disabled, event buffer.
+    pub fn new() -> PairEvents<P, DV> {
+        PairEvents {
+            enabled: false,
+            added:   HashMap::new(PairTWHash::new()),
+            removed: Vec::new()
+        }
+    }
+
+    /// Whether events are recorded.
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enables or disables the recording of events. Pending events are discarded when disabled.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+
+        if !enabled {
+            self.added.clear();
+            self.removed.clear();
+        }
+    }
+
+    /// Records the insertion of a pair.
+    #[inline]
+    pub fn record_added(&mut self, key: Pair<P>) {
+        if self.enabled {
+            self.added.insert(key, ());
+        }
+    }
+
+    /// Records the removal of a pair.
+    #[inline]
+    pub fn record_removed(&mut self, key: Pair<P>, value: DV) {
+        if self.enabled && !self.added.remove(&key) {
+            self.removed.push((key, value))
+        }
+    }
+
+    /// Executes `removed` on each removed pair, then `added` on each added pair still existing on
+    /// `pairs` or `spairs`, and clears the events.
+    pub fn drain(&mut self,
+                 pairs:   &mut HashMap<Pair<P>, DV, PairTWHash>,
+                 spairs:  &mut HashMap<Pair<P>, DV, PairTWHash>,
+                 added:   |&P, &P, &mut DV| -> (),
+                 removed: |&P, &P, DV| -> ()) {
+        let removed_pairs = mem::replace(&mut self.removed, Vec::new());
+
+        for (key, value) in removed_pairs.move_iter() {
+            removed(&key.first, &key.second, value)
+        }
+
+        for elt in self.added.elements().iter() {
+            let key = &elt.key;
+
+            match pairs.find_mut(key) {
+                Some(value) => added(&key.first, &key.second, value),
+                None        => {
+                    match spairs.find_mut(key) {
+                        Some(value) => added(&key.first, &key.second, value),
+                        None        => { }
+                    }
+                }
+            }
+        }
+
+        self.added.clear();
+    }
+}
@@ -0,0 +1,517 @@
+use std::gc::{GC, Gc};
+use std::cell::RefCell;
+use nalgebra::na::Indexable;
+use nalgebra::na;
+use broad::{BroadPhase, InterferencesBroadPhase, BoundingVolumeBroadPhase, RayCastBroadPhase,
+            PairEventsBroadPhase};
+use broad::pair_events::PairEvents;
+use data::hash::UintTWHash;
+use data::hash_map::HashMap;
+use data::pair::{Pair, PairTWHash};
+use data::has_uid::HasUid;
+use broad::Dispatcher;
+use bounding_volume::{BoundingVolume, LooseBoundingVolume, HasBoundingVolume, AABB};
+use ray::{Ray, RayCast};
+use math::{Scalar, Vect};
+
+/// An object registered on the sweep and prune broad phase, with its loose AABB.
+struct SAPProxy<B> {
+    aabb:   AABB,
+    object: B,
+    active: bool
+}
+
+/// The lower or upper bound of a proxy AABB along one axis.
+#[deriving(Clone)]
+struct Endpoint<B> {
+    value:  Scalar,
+    is_max: bool,
+    proxy:  Gc<RefCell<SAPProxy<B>>>
+}
+
+impl<B: 'static> Endpoint<B> {
+    /// Tests if this endpoint must be sorted before `other`.
+    ///
+    /// A lower bound precedes an upper bound with the same value so that touching AABBs overlap.
+    #[inline]
+    fn precedes(&self, other: &Endpoint<B>) -> bool {
+        self.value < other.value || (self.value == other.value && !self.is_max && other.is_max)
+    }
+}
+
+/// Broad phase based on the incremental Sweep and Prune algorithm.
+///
+/// The bounds of the objects loose AABBs are kept sorted along each axis. Since objects move by
+/// small amounts between two updates, the lists are sorted back with an insertion sort and the
+/// pairs of overlapping objects are updated whenever two bounds are swapped. This is well suited
+/// for scenes where most objects do not move.
+pub struct SAPBroadPhase<B, D, DV> {
+    axes:       Vec<Vec<Endpoint<B>>>,
+    proxies:    HashMap<uint, Gc<RefCell<SAPProxy<B>>>, UintTWHash>,
+    pairs:      HashMap<Pair<Gc<RefCell<SAPProxy<B>>>>, DV, PairTWHash>, // pair manager
+    spairs:     HashMap<Pair<Gc<RefCell<SAPProxy<B>>>>, DV, PairTWHash>,
+    events:     PairEvents<Gc<RefCell<SAPProxy<B>>>, DV>,
+    dispatcher: D,
+    margin:     Scalar
+}
+
+impl<B:  'static + HasBoundingVolume<AABB> + HasUid + Clone,
+     D:  Dispatcher<B, B, DV>,
+     DV>
+SAPBroadPhase<B, D, DV> {
+    /// Creates a new broad phase based on the Sweep and Prune algorithm.
+    ///
+    /// # Arguments:
+    /// * `dispatcher` - collision dispatcher.
+    /// * `margin`     - loosening margin of the objects AABBs.
+    pub fn new(dispatcher: D, margin: Scalar) -> SAPBroadPhase<B, D, DV> {
+        SAPBroadPhase {
+            axes:       Vec::from_fn(na::dim::<Vect>(), |_| Vec::new()),
+            proxies:    HashMap::new(UintTWHash::new()),
+            pairs:      HashMap::new(PairTWHash::new()),
+            spairs:     HashMap::new(PairTWHash::new()),
+            events:     PairEvents::new(),
+            dispatcher: dispatcher,
+            margin:     margin
+        }
+    }
+
+    /// Number of interferences detected by this broad phase.
+    #[inline]
+    pub fn num_interferences(&self) -> uint {
+        self.pairs.len()
+    }
+
+    /// Sorts back every axis after some AABBs have been modified.
+    fn sort_axes(&mut self) {
+        for (i, axis) in self.axes.mut_iter().enumerate() {
+            for e in axis.mut_iter() {
+                let bproxy = e.proxy.borrow();
+
+                e.value = if e.is_max { bproxy.aabb.maxs().at(i) } else { bproxy.aabb.mins().at(i) };
+            }
+
+            sort_axis(axis, &mut self.pairs, &mut self.events, &self.dispatcher);
+        }
+    }
+}
+
+/// Updates the loose AABB of a proxy.
+///
+/// Returns `true` if the stored AABB has been changed.
+fn update_proxy<B: HasBoundingVolume<AABB>>(proxy: &Gc<RefCell<SAPProxy<B>>>, margin: &Scalar) -> bool {
+    let mut bproxy   = proxy.borrow_mut();
+    let mut new_aabb = bproxy.object.bounding_volume();
+
+    if !bproxy.aabb.contains(&new_aabb) {
+        new_aabb.loosen(margin.clone());
+        bproxy.aabb = new_aabb;
+
+        true
+    }
+    else {
+        false
+    }
+}
+
+/// Removes the pairs involving `proxy` from `pairs` and returns them.
+///
+/// Only the pairs with objects satisfying `filter` are removed.
+fn remove_pairs_with<B: 'static, DV>(pairs:  &mut HashMap<Pair<Gc<RefCell<SAPProxy<B>>>>, DV, PairTWHash>,
+                                     proxy:  &Gc<RefCell<SAPProxy<B>>>,
+                                     filter: |&SAPProxy<B>| -> bool)
+                                     -> Vec<(Pair<Gc<RefCell<SAPProxy<B>>>>, DV)> {
+    let mut keys_to_remove = Vec::new();
+
+    for elt in pairs.elements().iter() {
+        let other =
+            if elt.key.first.uid() == proxy.uid() {
+                &elt.key.second
+            }
+            else if elt.key.second.uid() == proxy.uid() {
+                &elt.key.first
+            }
+            else {
+                continue
+            };
+
+        if filter(other.borrow().deref()) {
+            keys_to_remove.push(elt.key.clone());
+        }
+    }
+
+    let mut res = Vec::with_capacity(keys_to_remove.len());
+
+    for k in keys_to_remove.iter() {
+        match pairs.get_and_remove(k) {
+            Some(entry) => res.push((entry.key, entry.value)),
+            None        => { }
+        }
+    }
+
+    res
+}
+
+/// Sorts an axis with an insertion sort, and updates the pairs each time two bounds are swapped.
+fn sort_axis<B:  'static + HasUid,
+             D:  Dispatcher<B, B, DV>,
+             DV>(
+             endpoints:  &mut Vec<Endpoint<B>>,
+             pairs:      &mut HashMap<Pair<Gc<RefCell<SAPProxy<B>>>>, DV, PairTWHash>,
+             events:     &mut PairEvents<Gc<RefCell<SAPProxy<B>>>, DV>,
+             dispatcher: &D) {
+    for i in range(1, endpoints.len()) {
+        let mut j = i;
+
+        while j > 0 && endpoints.get(j).precedes(endpoints.get(j - 1)) {
+            {
+                let left  = endpoints.get(j - 1);
+                let right = endpoints.get(j);
+
+                if !right.is_max && left.is_max {
+                    // The lower bound moves before an upper bound: the AABBs may start overlapping.
+                    let bright = right.proxy.borrow();
+                    let bleft  = left.proxy.borrow();
+
+                    if dispatcher.is_valid(&bright.object, &bleft.object) &&
+                       bright.aabb.intersects(&bleft.aabb) {
+                        let num_pairs = pairs.len();
+                        let _ = pairs.find_or_insert_lazy(
+                            Pair::new(right.proxy, left.proxy),
+                            || dispatcher.dispatch(&bright.object, &bleft.object));
+
+                        if pairs.len() != num_pairs {
+                            events.record_added(Pair::new(right.proxy, left.proxy));
+                        }
+                    }
+                }
+                else if right.is_max && !left.is_max {
+                    // The upper bound moves before a lower bound: the AABBs stop overlapping.
+                    match pairs.get_and_remove(&Pair::new(right.proxy, left.proxy)) {
+                        Some(entry) => events.record_removed(entry.key, entry.value),
+                        None        => { }
+                    }
+                }
+            }
+
+            endpoints.as_mut_slice().swap(j - 1, j);
+            j = j - 1;
+        }
+    }
+}
+
+impl<B:  'static + HasBoundingVolume<AABB> + HasUid + Clone,
+     D:  Dispatcher<B, B, DV>,
+     DV>
+BroadPhase<B> for SAPBroadPhase<B, D, DV> {
+    fn add(&mut self, b: B) {
+        let id    = b.uid();
+        let aabb  = b.bounding_volume().loosened(self.margin.clone());
+        let proxy = box(GC) RefCell::new(SAPProxy { aabb: aabb, object: b, active: true });
+
+        // The new bounds are appended at the end of each axis and sorted back by `sort_axes`.
+        for axis in self.axes.mut_iter() {
+            axis.push(Endpoint { value: na::zero(), is_max: false, proxy: proxy });
+            axis.push(Endpoint { value: na::zero(), is_max: true,  proxy: proxy });
+        }
+
+        self.proxies.insert(id, proxy);
+        self.sort_axes();
+    }
+
+    fn remove(&mut self, b: &B) {
+        let proxy =
+            match self.proxies.get_and_remove(&b.uid()) {
+                None    => return,
+                Some(p) => p.value
+            };
+
+        for axis in self.axes.mut_iter() {
+            axis.retain(|e| e.proxy.uid() != proxy.uid());
+        }
+
+        let removed  = remove_pairs_with(&mut self.pairs, &proxy, |_| true);
+        let sremoved = remove_pairs_with(&mut self.spairs, &proxy, |_| true);
+
+        for (key, value) in removed.move_iter().chain(sremoved.move_iter()) {
+            self.events.record_removed(key, value);
+        }
+    }
+
+    fn update(&mut self) {
+        let mut need_sort = false;
+
+        for p in self.proxies.elements().iter() {
+            let active = p.value.borrow().active;
+
+            if active && update_proxy(&p.value, &self.margin) {
+                need_sort = true;
+            }
+        }
+
+        if need_sort {
+            self.sort_axes();
+        }
+    }
+
+    fn update_object(&mut self, object: &B) {
+        let need_sort =
+            match self.proxies.find(&object.uid()) {
+                None        => false,
+                Some(proxy) => {
+                    let active = proxy.borrow().active;
+
+                    active && update_proxy(proxy, &self.margin)
+                }
+            };
+
+        if need_sort {
+            self.sort_axes();
+        }
+    }
+}
+
+impl<B:  'static + HasBoundingVolume<AABB> + HasUid + Clone,
+     D:  Dispatcher<B, B, DV>,
+     DV>
+InterferencesBroadPhase<B, DV> for SAPBroadPhase<B, D, DV> {
+    #[inline(always)]
+    fn for_each_pair(&self, f: |&B, &B, &DV| -> ()) {
+        for p in self.pairs.elements().iter() {
+            let bf = p.key.first.borrow();
+            let bs = p.key.second.borrow();
+            f(&bf.object, &bs.object, &p.value)
+        }
+    }
+
+    #[inline(always)]
+    fn for_each_pair_mut(&mut self, f: |&B, &B, &mut DV| -> ()) {
+        for p in self.pairs.elements_mut().mut_iter() {
+            let bf = p.key.first.borrow();
+            let bs = p.key.second.borrow();
+            f(&bf.object, &bs.object, &mut p.value)
+        }
+    }
+
+    fn activate(&mut self, body: &B, f: |&B, &B, &mut DV| -> ()) {
+        let proxy =
+            match self.proxies.find(&body.uid()) {
+                Some(p) if !p.borrow().active => p.clone(),
+                _                             => return // unknown or already active
+            };
+
+        proxy.borrow_mut().active = true;
+
+        // The interferences with inactive objects are registered on `spairs` already.
+        let woken_up = remove_pairs_with(&mut self.spairs, &proxy, |_| true);
+
+        for (key, value) in woken_up.move_iter() {
+            let bf = key.first.borrow();
+            let bs = key.second.borrow();
+            let p  = self.pairs.insert_or_replace(key.clone(), value, true);
+
+            f(&bf.object, &bs.object, p)
+        }
+
+        // The bounding volume has not been updated while the object was inactive.
+        if update_proxy(&proxy, &self.margin) {
+            self.sort_axes();
+        }
+    }
+
+    fn deactivate(&mut self, body: &B) {
+        let proxy =
+            match self.proxies.find(&body.uid()) {
+                Some(p) if p.borrow().active => p.clone(),
+                _                            => return // unknown or already inactive
+            };
+
+        proxy.borrow_mut().active = false;
+
+        // Transfer all the interferences involving `proxy` and inactive objects from `pairs` to
+        // `spairs`.
+        let asleep = remove_pairs_with(&mut self.pairs, &proxy, |p| !p.active);
+
+        for (key, value) in asleep.move_iter() {
+            self.spairs.insert(key, value);
+        }
+    }
+}
+
+impl<B:  'static + HasBoundingVolume<AABB> + HasUid + Clone,
+     D:  Dispatcher<B, B, DV>,
+     DV>
+PairEventsBroadPhase<B, DV> for SAPBroadPhase<B, D, DV> {
+    #[inline]
+    fn set_pair_events_enabled(&mut self, enabled: bool) {
+        self.events.set_enabled(enabled)
+    }
+
+    fn drain_pair_events(&mut self, added: |&B, &B, &mut DV| -> (), removed: |&B, &B, DV| -> ()) {
+        self.events.drain(
+            &mut self.pairs,
+            &mut self.spairs,
+            |p1, p2, dv| added(&p1.borrow().object, &p2.borrow().object, dv),
+            |p1, p2, dv| removed(&p1.borrow().object, &p2.borrow().object, dv))
+    }
+}
+
+impl<B:  'static + HasBoundingVolume<AABB> + HasUid + Clone,
+     D:  Dispatcher<B, B, DV>,
+     DV>
+BoundingVolumeBroadPhase<B, AABB> for SAPBroadPhase<B, D, DV> {
+    fn interferences_with_bounding_volume(&mut self, bv: &AABB, out: &mut Vec<B>) {
+        // Only the objects starting before the end of `bv` along the first axis are tested.
+        let max = bv.maxs().at(0);
+
+        for e in self.axes.get(0).iter() {
+            if e.value > max {
+                break;
+            }
+
+            if !e.is_max {
+                let bproxy = e.proxy.borrow();
+
+                if bproxy.aabb.intersects(bv) {
+                    out.push(bproxy.object.clone())
+                }
+            }
+        }
+    }
+}
+
+impl<B:  'static + HasBoundingVolume<AABB> + HasUid + Clone,
+     D:  Dispatcher<B, B, DV>,
+     DV>
+RayCastBroadPhase<B> for SAPBroadPhase<B, D, DV> {
+    fn interferences_with_ray(&mut self, ray: &Ray, out: &mut Vec<B>) {
+        for p in self.proxies.elements().iter() {
+            let bproxy = p.value.borrow();
+
+            if bproxy.aabb.toi_with_ray(ray, true).is_some() {
+                out.push(bproxy.object.clone())
+            }
+        }
+    }
+}
+
+#[cfg(test, dim3, f64)]
+mod test {
+    use super::SAPBroadPhase;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use std::vec::Vec;
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::Ball;
+    use bounding_volume::WithAABB;
+    use broad::{NoIdDispatcher, BroadPhase, InterferencesBroadPhase, PairEventsBroadPhase};
+
+    #[test]
+    fn test_sap_nbh_move_collide() {
+        type Shape = Rc<RefCell<WithAABB<Ball>>>;
+        let dispatcher: NoIdDispatcher<Shape> = NoIdDispatcher;
+        let mut bf     = SAPBroadPhase::new(dispatcher, 0.2);
+        let ball       = Ball::new(0.3);
+
+        let mut to_move = Vec::new();
+
+        // create a grid
+        for i in range(-10, 10) {
+            for j in range(-10, 10) {
+                let t = Vec3::new(i as f64 * 0.9, j as f64 * 0.9, 0.0);
+                let to_add = Rc::new(RefCell::new(WithAABB(Iso3::new(t, na::zero()), ball)));
+                bf.add(to_add.clone());
+                to_move.push(to_add);
+            }
+        }
+
+        bf.update();
+
+        let expected =
+            (18 * 18 * 8 + // internal rectangles have 8 neighbors
+             18 * 4 * 5  + // border (excluding corners) rectangles have 5 neighbors
+             4 * 3)        // corners have 3 neighbors
+             / 2;          // remove all duplicates
+
+        assert_eq!(bf.num_interferences(), expected);
+
+        // deactivating every object moves every interference to `spairs`…
+        for e in to_move.mut_iter() {
+            bf.deactivate(e);
+        }
+
+        assert_eq!(bf.num_interferences(), 0);
+
+        // … and activating them moves them back.
+        for e in to_move.mut_iter() {
+            bf.activate(e, |_, _, _| { });
+        }
+
+        assert_eq!(bf.num_interferences(), expected);
+
+        for e in to_move.mut_iter() {
+            let mut wa = e.borrow_mut();
+            let m      = wa.m().clone();
+            let g      = wa.g().clone();
+            *wa        = WithAABB(na::append_translation(&m, &Vec3::new(10.0, 10.0, 10.0)), g)
+        }
+
+        bf.update();
+
+        assert_eq!(bf.num_interferences(), expected);
+
+        // removing an object in a corner removes its 3 interferences.
+        bf.remove(to_move.get(0));
+
+        assert_eq!(bf.num_interferences(), expected - 3);
+    }
+
+    #[test]
+    fn test_sap_pair_events() {
+        type Shape = Rc<RefCell<WithAABB<Ball>>>;
+        let dispatcher: NoIdDispatcher<Shape> = NoIdDispatcher;
+        let mut bf     = SAPBroadPhase::new(dispatcher, 0.2);
+        let ball       = Ball::new(0.3);
+
+        bf.set_pair_events_enabled(true);
+
+        let a = Rc::new(RefCell::new(WithAABB(Iso3::new(na::zero(), na::zero()), ball)));
+        let b = Rc::new(RefCell::new(WithAABB(Iso3::new(Vec3::new(0.5, 0.0, 0.0), na::zero()), ball)));
+
+        bf.add(a.clone());
+        bf.add(b.clone());
+
+        let mut num_added   = 0u;
+        let mut num_removed = 0u;
+
+        bf.drain_pair_events(|_, _, _| num_added = num_added + 1, |_, _, _| num_removed = num_removed + 1);
+        assert_eq!((num_added, num_removed), (1, 0));
+
+        let translate = |shape: &Shape, t: Vec3<f64>| {
+            let mut wa = shape.borrow_mut();
+            let m      = wa.m().clone();
+            let g      = wa.g().clone();
+            *wa        = WithAABB(na::append_translation(&m, &t), g)
+        };
+
+        translate(&b, Vec3::new(10.0, 0.0, 0.0));
+        bf.update();
+
+        num_added   = 0;
+        num_removed = 0;
+        bf.drain_pair_events(|_, _, _| num_added = num_added + 1, |_, _, _| num_removed = num_removed + 1);
+        assert_eq!((num_added, num_removed), (0, 1));
+
+        // an interference starting and stopping between two drains is not reported.
+        translate(&b, Vec3::new(-10.0, 0.0, 0.0));
+        bf.update();
+        translate(&b, Vec3::new(10.0, 0.0, 0.0));
+        bf.update();
+
+        num_added   = 0;
+        num_removed = 0;
+        bf.drain_pair_events(|_, _, _| num_added = num_added + 1, |_, _, _| num_removed = num_removed + 1);
+        assert_eq!((num_added, num_removed), (0, 0));
+    }
+}
@@ -0,0 +1,768 @@
+use std::gc::{GC, Gc};
+use std::cell::RefCell;
+use nalgebra::na::Indexable;
+use nalgebra::na;
+use broad::{BroadPhase, InterferencesBroadPhase, BoundingVolumeBroadPhase, RayCastBroadPhase,
+            PairEventsBroadPhase};
+use broad::pair_events::PairEvents;
+use data::hash;
+use data::hash::{HashFun, UintTWHash};
+use data::hash_map::HashMap;
+use data::pair::{Pair, PairTWHash};
+use data::has_uid::HasUid;
+use broad::Dispatcher;
+use bounding_volume::{BoundingVolume, LooseBoundingVolume, HasBoundingVolume, AABB};
+use ray::{Ray, RayCast};
+use math::{Scalar, Vect};
+
+/// Largest absolute value of a cell coordinate. Cells farther from the origin are clamped.
+static MAX_CELL_COORD: int = 1 << 20;
+
+/// Maximum number of cells an object can be registered on. Larger objects are kept apart and
+/// tested against every other object.
+static MAX_CELLS_PER_OBJECT: uint = 1024;
+
+/// Integer coordinates of a cell of the grid.
+///
+/// Coordinates along axis not existing in the current dimension are zero.
+#[deriving(Clone, PartialEq)]
+struct Cell {
+    coords: [int, ..4]
+}
+
+impl Cell {
+    /// The cell containing the point `pt`.
+    ///
+    /// The coordinates are clamped to `[-MAX_CELL_COORD, MAX_CELL_COORD]` so that unbounded
+    /// objects, e.g. planes, do not overflow them.
+    fn containing(pt: &Vect, cell_size: &Scalar) -> Cell {
+        let _max: Scalar = na::cast(MAX_CELL_COORD as f64);
+        let mut coords   = [0i, ..4];
+
+        for i in range(0u, na::dim::<Vect>()) {
+            let coord = (pt.at(i) / *cell_size).floor();
+
+            coords[i] = na::clamp(coord, -_max, _max) as int
+        }
+
+        Cell { coords: coords }
+    }
+}
+
+/// Whether the range of cells between `mins` and `maxs` inclusive contains more than
+/// `MAX_CELLS_PER_OBJECT` cells.
+fn is_oversized(mins: &Cell, maxs: &Cell) -> bool {
+    let mut num_cells = 1u;
+
+    for i in range(0u, na::dim::<Vect>()) {
+        num_cells = num_cells * ((maxs.coords[i] - mins.coords[i]) as uint + 1);
+
+        if num_cells > MAX_CELLS_PER_OBJECT {
+            return true
+        }
+    }
+
+    false
+}
+
+/// Tomas Wang based hash function for a `Cell`.
+struct CellTWHash { unused: uint } // FIXME: ICE with zero-sized structs
+
+impl CellTWHash {
+    fn new() -> CellTWHash {
+        CellTWHash { unused: 0 }
+    }
+}
+
+impl HashFun<Cell> for CellTWHash {
+    #[inline]
+    fn hash(&self, cell: &Cell) -> uint {
+        let mut res = 0u;
+
+        for c in cell.coords.iter() {
+            res = hash::tomas_wang_hash(hash::key_from_pair(*c as uint, res))
+        }
+
+        res
+    }
+}
+
+/// An object registered on the spatial hash broad phase, with its loose AABB and the range of
+/// cells it intersects.
+struct SpatialHashProxy<B> {
+    aabb:      AABB,
+    mins:      Cell,
+    maxs:      Cell,
+    object:    B,
+    active:    bool,
+    oversized: bool
+}
+
+/// Executes `f` on each cell between `mins` and `maxs` inclusive.
+fn for_each_cell(mins: &Cell, maxs: &Cell, f: |&Cell| -> ()) {
+    let dim      = na::dim::<Vect>();
+    let mut curr = mins.clone();
+
+    loop {
+        f(&curr);
+
+        // Move to the next cell, the first axis varying the fastest.
+        let mut i = 0u;
+
+        loop {
+            if i == dim {
+                return
+            }
+
+            if curr.coords[i] < maxs.coords[i] {
+                curr.coords[i] = curr.coords[i] + 1;
+                break;
+            }
+
+            curr.coords[i] = mins.coords[i];
+            i = i + 1;
+        }
+    }
+}
+
+/// Broad phase based on a uniform grid indexed by a spatial hash.
+///
+/// Each object is registered on every cell its loose AABB intersects. Only objects sharing a cell
+/// are tested for interferences. This is well suited for many objects of similar sizes, provided
+/// the cell size is of the order of magnitude of those objects size. Objects spanning too many
+/// cells, e.g. planes, are not registered on the grid but tested against every other object.
+pub struct SpatialHashBroadPhase<B, D, DV> {
+    cells:      HashMap<Cell, Vec<Gc<RefCell<SpatialHashProxy<B>>>>, CellTWHash>,
+    oversized:  Vec<Gc<RefCell<SpatialHashProxy<B>>>>,
+    proxies:    HashMap<uint, Gc<RefCell<SpatialHashProxy<B>>>, UintTWHash>,
+    pairs:      HashMap<Pair<Gc<RefCell<SpatialHashProxy<B>>>>, DV, PairTWHash>, // pair manager
+    spairs:     HashMap<Pair<Gc<RefCell<SpatialHashProxy<B>>>>, DV, PairTWHash>,
+    events:     PairEvents<Gc<RefCell<SpatialHashProxy<B>>>, DV>,
+    dispatcher: D,
+    cell_size:  Scalar,
+    margin:     Scalar,
+    collector:  Vec<Gc<RefCell<SpatialHashProxy<B>>>>,
+    to_update:  Vec<Gc<RefCell<SpatialHashProxy<B>>>>
+}
+
+impl<B:  'static + HasBoundingVolume<AABB> + HasUid + Clone,
+     D:  Dispatcher<B, B, DV>,
+     DV>
+SpatialHashBroadPhase<B, D, DV> {
+    /// Creates a new broad phase based on a spatial hash.
+    ///
+    /// # Arguments:
+    /// * `dispatcher` - collision dispatcher.
+    /// * `cell_size`  - the width of the grid cells along each axis.
+    /// * `margin`     - loosening margin of the objects AABBs.
+    pub fn new(dispatcher: D, cell_size: Scalar, margin: Scalar) -> SpatialHashBroadPhase<B, D, DV> {
+        assert!(cell_size > na::zero(), "The cell size must be strictly positive.");
+
+        SpatialHashBroadPhase {
+            cells:      HashMap::new(CellTWHash::new()),
+            oversized:  Vec::new(),
+            proxies:    HashMap::new(UintTWHash::new()),
+            pairs:      HashMap::new(PairTWHash::new()),
+            spairs:     HashMap::new(PairTWHash::new()),
+            events:     PairEvents::new(),
+            dispatcher: dispatcher,
+            cell_size:  cell_size,
+            margin:     margin,
+            collector:  Vec::new(),
+            to_update:  Vec::new()
+        }
+    }
+
+    /// Number of interferences detected by this broad phase.
+    #[inline]
+    pub fn num_interferences(&self) -> uint {
+        self.pairs.len()
+    }
+
+    /// The width of the grid cells along each axis.
+    #[inline]
+    pub fn cell_size(&self) -> Scalar {
+        self.cell_size.clone()
+    }
+
+    /// Changes the width of the grid cells along each axis.
+    ///
+    /// Every object is registered again on the new grid. The interferences are not affected.
+    pub fn set_cell_size(&mut self, cell_size: Scalar) {
+        assert!(cell_size > na::zero(), "The cell size must be strictly positive.");
+
+        self.cell_size = cell_size;
+        self.cells     = HashMap::new(CellTWHash::new());
+        self.oversized.clear();
+
+        let proxies: Vec<Gc<RefCell<SpatialHashProxy<B>>>> =
+            self.proxies.elements().iter().map(|p| p.value).collect();
+
+        for p in proxies.move_iter() {
+            self.insert_in_cells(p);
+        }
+    }
+
+    fn insert_in_cells(&mut self, proxy: Gc<RefCell<SpatialHashProxy<B>>>) {
+        let (mins, maxs, oversized) = {
+            let mut bproxy = proxy.borrow_mut();
+
+            bproxy.mins      = Cell::containing(bproxy.aabb.mins(), &self.cell_size);
+            bproxy.maxs      = Cell::containing(bproxy.aabb.maxs(), &self.cell_size);
+            bproxy.oversized = is_oversized(&bproxy.mins, &bproxy.maxs);
+
+            (bproxy.mins.clone(), bproxy.maxs.clone(), bproxy.oversized)
+        };
+
+        if oversized {
+            self.oversized.push(proxy);
+
+            return
+        }
+
+        let cells = &mut self.cells;
+
+        for_each_cell(&mins, &maxs, |cell| {
+            let objects = cells.find_or_insert_lazy(cell.clone(), || Some(Vec::new()));
+
+            objects.unwrap().push(proxy)
+        });
+    }
+
+    fn remove_from_cells(&mut self, proxy: Gc<RefCell<SpatialHashProxy<B>>>) {
+        let (mins, maxs, oversized) = {
+            let bproxy = proxy.borrow();
+
+            (bproxy.mins.clone(), bproxy.maxs.clone(), bproxy.oversized)
+        };
+
+        if oversized {
+            self.oversized.retain(|o| o.uid() != proxy.uid());
+
+            return
+        }
+
+        let cells = &mut self.cells;
+
+        for_each_cell(&mins, &maxs, |cell| {
+            let empty =
+                match cells.find_mut(cell) {
+                    Some(objects) => {
+                        objects.retain(|o| o.uid() != proxy.uid());
+                        objects.is_empty()
+                    },
+                    None => false
+                };
+
+            if empty {
+                cells.remove(cell);
+            }
+        });
+    }
+
+    /// Collects the proxies sharing at least one cell with `proxy`, including itself.
+    ///
+    /// Oversized proxies share a cell with every other proxy.
+    fn collect_neighbors(&mut self, proxy: Gc<RefCell<SpatialHashProxy<B>>>) {
+        let (mins, maxs, oversized) = {
+            let bproxy = proxy.borrow();
+
+            (bproxy.mins.clone(), bproxy.maxs.clone(), bproxy.oversized)
+        };
+
+        if oversized {
+            for p in self.proxies.elements().iter() {
+                self.collector.push(p.value)
+            }
+
+            return
+        }
+
+        let cells     = &self.cells;
+        let collector = &mut self.collector;
+
+        collector.push_all(self.oversized.as_slice());
+
+        for_each_cell(&mins, &maxs, |cell| {
+            match cells.find(cell) {
+                Some(objects) => collector.push_all(objects.as_slice()),
+                None          => { }
+            }
+        });
+    }
+
+    fn update_updatable(&mut self) {
+        if self.to_update.is_empty() {
+            return
+        }
+
+        /*
+         * Re-insert outdated proxies and collect interferences at the same time.
+         */
+        let to_update = self.to_update.clone();
+
+        for u in to_update.iter() {
+            // Every partner of `u` shares a cell with it: remove the outdated interferences
+            // before leaving the old cells.
+            self.collect_neighbors(*u);
+
+            {
+                let bu = u.borrow();
+
+                for i in self.collector.iter() {
+                    if i.uid() == u.uid() || bu.aabb.intersects(&i.borrow().aabb) {
+                        continue
+                    }
+
+                    match self.pairs.get_and_remove(&Pair::new(u.clone(), i.clone())) {
+                        Some(entry) => self.events.record_removed(entry.key, entry.value),
+                        None        => { }
+                    }
+                }
+            }
+
+            self.collector.clear();
+
+            self.remove_from_cells(*u);
+            self.insert_in_cells(*u);
+            self.collect_neighbors(*u);
+
+            {
+                let bu = u.borrow();
+
+                for i in self.collector.iter() {
+                    if i.uid() == u.uid() {
+                        continue
+                    }
+
+                    let bi = i.borrow();
+
+                    if self.dispatcher.is_valid(&bu.object, &bi.object) &&
+                       bu.aabb.intersects(&bi.aabb) {
+                        let dispatcher = &mut self.dispatcher;
+                        let num_pairs  = self.pairs.len();
+                        let _ = self.pairs.find_or_insert_lazy(
+                            Pair::new(u.clone(), i.clone()),
+                            || dispatcher.dispatch(&bu.object, &bi.object)
+                            );
+
+                        if self.pairs.len() != num_pairs {
+                            self.events.record_added(Pair::new(u.clone(), i.clone()));
+                        }
+                    }
+                }
+            }
+
+            self.collector.clear();
+        }
+
+        self.to_update.clear();
+    }
+
+    /// Updates the loose AABB of a proxy and marks it as outdated if it changed.
+    fn update_proxy(&mut self, proxy: Gc<RefCell<SpatialHashProxy<B>>>) {
+        let mut bproxy   = proxy.borrow_mut();
+        let mut new_aabb = bproxy.object.bounding_volume();
+
+        if !bproxy.aabb.contains(&new_aabb) {
+            new_aabb.loosen(self.margin.clone());
+            bproxy.aabb = new_aabb;
+
+            self.to_update.push(proxy);
+        }
+    }
+}
+
+impl<B:  'static + HasBoundingVolume<AABB> + HasUid + Clone,
+     D:  Dispatcher<B, B, DV>,
+     DV>
+BroadPhase<B> for SpatialHashBroadPhase<B, D, DV> {
+    fn add(&mut self, b: B) {
+        let id     = b.uid();
+        let aabb   = b.bounding_volume().loosened(self.margin.clone());
+        let origin = Cell { coords: [0i, ..4] };
+        let proxy  = box(GC) RefCell::new(SpatialHashProxy {
+            aabb:      aabb,
+            mins:      origin.clone(),
+            maxs:      origin,
+            object:    b,
+            active:    true,
+            oversized: false
+        });
+
+        // Registers the proxy on the cells and finds its interferences.
+        self.to_update.push(proxy);
+        self.update_updatable();
+
+        self.proxies.insert(id, proxy);
+    }
+
+    fn remove(&mut self, b: &B) {
+        let proxy =
+            match self.proxies.get_and_remove(&b.uid()) {
+                None    => return,
+                Some(p) => p.value
+            };
+
+        self.remove_from_cells(proxy);
+
+        let mut keys_to_remove = Vec::new();
+
+        // remove every pair involving b
+        for elt in self.pairs.elements().iter() {
+            if elt.key.first.uid() == proxy.uid() || elt.key.second.uid() == proxy.uid() {
+                keys_to_remove.push(elt.key.clone());
+            }
+        }
+
+        for k in keys_to_remove.iter() {
+            match self.pairs.get_and_remove(k) {
+                Some(entry) => self.events.record_removed(entry.key, entry.value),
+                None        => { }
+            }
+        }
+
+        keys_to_remove.clear();
+
+        // remove every "sleeping" pair involving b
+        for elt in self.spairs.elements().iter() {
+            if elt.key.first.uid() == proxy.uid() || elt.key.second.uid() == proxy.uid() {
+                keys_to_remove.push(elt.key.clone());
+            }
+        }
+
+        for k in keys_to_remove.iter() {
+            match self.spairs.get_and_remove(k) {
+                Some(entry) => self.events.record_removed(entry.key, entry.value),
+                None        => { }
+            }
+        }
+    }
+
+    fn update(&mut self) {
+        let proxies: Vec<Gc<RefCell<SpatialHashProxy<B>>>> =
+            self.proxies.elements().iter().map(|p| p.value).collect();
+
+        for p in proxies.move_iter() {
+            let active = p.borrow().active;
+
+            if active {
+                self.update_proxy(p);
+            }
+        }
+
+        self.update_updatable();
+    }
+
+    fn update_object(&mut self, object: &B) {
+        let proxy =
+            match self.proxies.find(&object.uid()) {
+                Some(p) if p.borrow().active => p.clone(),
+                _                            => return
+            };
+
+        self.update_proxy(proxy);
+        self.update_updatable();
+    }
+}
+
+impl<B:  'static + HasBoundingVolume<AABB> + HasUid + Clone,
+     D:  Dispatcher<B, B, DV>,
+     DV>
+InterferencesBroadPhase<B, DV> for SpatialHashBroadPhase<B, D, DV> {
+    #[inline(always)]
+    fn for_each_pair(&self, f: |&B, &B, &DV| -> ()) {
+        for p in self.pairs.elements().iter() {
+            let bf = p.key.first.borrow();
+            let bs = p.key.second.borrow();
+            f(&bf.object, &bs.object, &p.value)
+        }
+    }
+
+    #[inline(always)]
+    fn for_each_pair_mut(&mut self, f: |&B, &B, &mut DV| -> ()) {
+        for p in self.pairs.elements_mut().mut_iter() {
+            let bf = p.key.first.borrow();
+            let bs = p.key.second.borrow();
+            f(&bf.object, &bs.object, &mut p.value)
+        }
+    }
+
+    fn activate(&mut self, body: &B, f: |&B, &B, &mut DV| -> ()) {
+        let proxy =
+            match self.proxies.find(&body.uid()) {
+                Some(p) if !p.borrow().active => p.clone(),
+                _                             => return // unknown or already active
+            };
+
+        proxy.borrow_mut().active = true;
+
+        // Now we find interferences with inactive objects.
+        self.collect_neighbors(proxy);
+
+        let bproxy = proxy.borrow();
+
+        for i in self.collector.iter() {
+            if i.uid() == proxy.uid() {
+                continue
+            }
+
+            let bi = i.borrow();
+
+            if !bi.active && self.dispatcher.is_valid(&bproxy.object, &bi.object) {
+                // the intereference should be registered on the spairs already
+                match self.spairs.get_and_remove(&Pair::new(proxy, *i)) {
+                    Some(dv) => {
+                        let key   = dv.key;
+                        let value = dv.value;
+                        let bdvf  = key.first.borrow();
+                        let bdvs  = key.second.borrow();
+                        let obj1  = &bdvf.object;
+                        let obj2  = &bdvs.object;
+                        let p     = self.pairs.insert_or_replace(key, value, true);
+
+                        f(obj1, obj2, p)
+                    },
+                    None => { } // the proxies share a cell without interfering.
+                }
+            }
+        }
+
+        self.collector.clear();
+    }
+
+    fn deactivate(&mut self, body: &B) {
+        let proxy =
+            match self.proxies.find(&body.uid()) {
+                Some(p) if p.borrow().active => p.clone(),
+                _                            => return // unknown or already inactive
+            };
+
+        proxy.borrow_mut().active = false;
+
+        // Now transfer all collisions involving `proxy` and deactivated objects from `pairs` to
+        // `spairs`.
+        self.collect_neighbors(proxy);
+
+        for i in self.collector.iter() {
+            if i.uid() == proxy.uid() || i.borrow().active {
+                continue
+            }
+
+            match self.pairs.get_and_remove(&Pair::new(proxy, *i)) {
+                Some(dv) => { self.spairs.insert(dv.key, dv.value); },
+                None     => { }
+            }
+        }
+
+        self.collector.clear();
+    }
+}
+
+impl<B:  'static + HasBoundingVolume<AABB> + HasUid + Clone,
+     D:  Dispatcher<B, B, DV>,
+     DV>
+PairEventsBroadPhase<B, DV> for SpatialHashBroadPhase<B, D, DV> {
+    #[inline]
+    fn set_pair_events_enabled(&mut self, enabled: bool) {
+        self.events.set_enabled(enabled)
+    }
+
+    fn drain_pair_events(&mut self, added: |&B, &B, &mut DV| -> (), removed: |&B, &B, DV| -> ()) {
+        self.events.drain(
+            &mut self.pairs,
+            &mut self.spairs,
+            |p1, p2, dv| added(&p1.borrow().object, &p2.borrow().object, dv),
+            |p1, p2, dv| removed(&p1.borrow().object, &p2.borrow().object, dv))
+    }
+}
+
+impl<B:  'static + HasBoundingVolume<AABB> + HasUid + Clone,
+     D:  Dispatcher<B, B, DV>,
+     DV>
+BoundingVolumeBroadPhase<B, AABB> for SpatialHashBroadPhase<B, D, DV> {
+    fn interferences_with_bounding_volume(&mut self, bv: &AABB, out: &mut Vec<B>) {
+        let mins = Cell::containing(bv.mins(), &self.cell_size);
+        let maxs = Cell::containing(bv.maxs(), &self.cell_size);
+
+        if is_oversized(&mins, &maxs) {
+            // Enumerating the cells would be slower than testing every object.
+            for p in self.proxies.elements().iter() {
+                let bproxy = p.value.borrow();
+
+                if bproxy.aabb.intersects(bv) {
+                    out.push(bproxy.object.clone())
+                }
+            }
+
+            return
+        }
+
+        {
+            let cells     = &self.cells;
+            let collector = &mut self.collector;
+
+            for o in self.oversized.iter() {
+                if o.borrow().aabb.intersects(bv) {
+                    collector.push(*o)
+                }
+            }
+
+            for_each_cell(&mins, &maxs, |cell| {
+                match cells.find(cell) {
+                    Some(objects) => {
+                        for o in objects.iter() {
+                            let bo = o.borrow();
+
+                            // Objects spanning several cells must be reported only once.
+                            if bo.aabb.intersects(bv) && !collector.iter().any(|c| c.uid() == o.uid()) {
+                                collector.push(*o)
+                            }
+                        }
+                    },
+                    None => { }
+                }
+            });
+        }
+
+        for l in self.collector.iter() {
+            out.push(l.borrow().object.clone())
+        }
+
+        self.collector.clear()
+    }
+}
+
+impl<B:  'static + HasBoundingVolume<AABB> + HasUid + Clone,
+     D:  Dispatcher<B, B, DV>,
+     DV>
+RayCastBroadPhase<B> for SpatialHashBroadPhase<B, D, DV> {
+    fn interferences_with_ray(&mut self, ray: &Ray, out: &mut Vec<B>) {
+        // The ray may be infinite: the cells it traverses cannot be enumerated.
+        for p in self.proxies.elements().iter() {
+            let bproxy = p.value.borrow();
+
+            if bproxy.aabb.toi_with_ray(ray, true).is_some() {
+                out.push(bproxy.object.clone())
+            }
+        }
+    }
+}
+
+#[cfg(test, dim3, f64)]
+mod test {
+    use super::SpatialHashBroadPhase;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use std::vec::Vec;
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::Ball;
+    use bounding_volume::{WithAABB, AABB};
+    use broad::{NoIdDispatcher, BroadPhase, InterferencesBroadPhase, BoundingVolumeBroadPhase};
+
+    #[test]
+    fn test_spatial_hash_nbh_move_collide() {
+        type Shape = Rc<RefCell<WithAABB<Ball>>>;
+        let dispatcher: NoIdDispatcher<Shape> = NoIdDispatcher;
+        let mut bf     = SpatialHashBroadPhase::new(dispatcher, 1.0, 0.2);
+        let ball       = Ball::new(0.3);
+
+        let mut to_move = Vec::new();
+
+        // create a grid
+        for i in range(-10, 10) {
+            for j in range(-10, 10) {
+                let t = Vec3::new(i as f64 * 0.9, j as f64 * 0.9, 0.0);
+                let to_add = Rc::new(RefCell::new(WithAABB(Iso3::new(t, na::zero()), ball)));
+                bf.add(to_add.clone());
+                to_move.push(to_add);
+            }
+        }
+
+        bf.update();
+
+        let expected =
+            (18 * 18 * 8 + // internal rectangles have 8 neighbors
+             18 * 4 * 5  + // border (excluding corners) rectangles have 5 neighbors
+             4 * 3)        // corners have 3 neighbors
+             / 2;          // remove all duplicates
+
+        assert_eq!(bf.num_interferences(), expected);
+
+        for e in to_move.mut_iter() {
+            bf.deactivate(e);
+        }
+
+        assert_eq!(bf.num_interferences(), 0);
+
+        for e in to_move.mut_iter() {
+            bf.activate(e, |_, _, _| { });
+        }
+
+        assert_eq!(bf.num_interferences(), expected);
+
+        for e in to_move.mut_iter() {
+            let mut wa = e.borrow_mut();
+            let m      = wa.m().clone();
+            let g      = wa.g().clone();
+            *wa        = WithAABB(na::append_translation(&m, &Vec3::new(10.0, 10.0, 10.0)), g)
+        }
+
+        bf.update();
+
+        assert_eq!(bf.num_interferences(), expected);
+
+        // a coarser grid does not change the interferences.
+        bf.set_cell_size(3.0);
+
+        for e in to_move.mut_iter() {
+            let mut wa = e.borrow_mut();
+            let m      = wa.m().clone();
+            let g      = wa.g().clone();
+            *wa        = WithAABB(na::append_translation(&m, &Vec3::new(-10.0, -10.0, -10.0)), g)
+        }
+
+        bf.update();
+
+        assert_eq!(bf.num_interferences(), expected);
+    }
+
+    #[test]
+    fn test_spatial_hash_oversized() {
+        type Shape = Rc<RefCell<WithAABB<Ball>>>;
+        let dispatcher: NoIdDispatcher<Shape> = NoIdDispatcher;
+        let mut bf     = SpatialHashBroadPhase::new(dispatcher, 1.0, 0.2);
+        let ball       = Ball::new(0.3);
+
+        let mut small = Vec::new();
+
+        for i in range(0i, 10) {
+            let t = Vec3::new(i as f64 * 5.0, 0.0, 0.0);
+            let to_add = Rc::new(RefCell::new(WithAABB(Iso3::new(t, na::zero()), ball)));
+            bf.add(to_add.clone());
+            small.push(to_add);
+        }
+
+        // This one spans billions of cells.
+        bf.add(Rc::new(RefCell::new(WithAABB(Iso3::new(na::zero(), na::zero()), Ball::new(1000.0)))));
+        bf.update();
+
+        assert_eq!(bf.num_interferences(), 10);
+
+        // Move one small ball away from the big one.
+        {
+            let mut wa = small.get_mut(3).borrow_mut();
+            let g      = wa.g().clone();
+            *wa        = WithAABB(Iso3::new(Vec3::new(1.0e4, 0.0, 0.0), na::zero()), g)
+        }
+
+        bf.update();
+
+        assert_eq!(bf.num_interferences(), 9);
+
+        let mut inter = Vec::new();
+        bf.interferences_with_bounding_volume(&AABB::new(Vec3::new(-1.0e5, -1.0e5, -1.0e5), Vec3::new(1.0e5, 1.0e5, 1.0e5)), &mut inter);
+        assert_eq!(inter.len(), 11);
+    }
+}
@@ -7,7 +7,7 @@ use nalgebra::na;
 use bounding_volume::{LooseBoundingVolume, AABB, HasAABB};
 use ray::Ray;
 use volumetric::Volumetric;
-use partitioning::BVT;
+use partitioning::{BVT, BinaryPartition, kdtree_partitioner};
 use partitioning::{BoundingVolumeInterferencesCollector, RayInterferencesCollector};
 use geom::{Geom, ConcaveGeom};
 use math::{Scalar, Vect, Matrix, AngularInertia};
@@ -91,6 +91,14 @@ pub struct Compound {
 impl Compound {
     /// Builds a new compound geometry.
     pub fn new(data: CompoundData) -> Compound {
+        Compound::new_with_partitioner(data, kdtree_partitioner)
+    }
+
+    /// Builds a new compound geometry with a custom construction function for its `BVT`, e.g.,
+    /// `partitioning::sah_partitioner`.
+    pub fn new_with_partitioner(data:        CompoundData,
+                                partitioner: |uint, Vec<(uint, AABB)>| -> (AABB, BinaryPartition<uint, AABB>))
+                                -> Compound {
         let mut bvs    = Vec::new();
         let mut leaves = Vec::new();
 
@@ -101,7 +109,7 @@ impl Compound {
             leaves.push((i, bv));
         }
 
-        let bvt = BVT::new_kdtree(leaves);
+        let bvt = BVT::new_with_partitioner(leaves, partitioner);
 
         let (mass, com, inertia) = data.mass_properties(&na::one());
         let surface = data.surface();
@@ -191,7 +199,7 @@ impl ConcaveGeom for Compound {
     }
 
     #[inline]
-    fn aabb_at<'a>(&'a self, i: uint) -> &'a AABB {
-        &self.bvs[i]
+    fn aabb_at(&self, i: uint) -> AABB {
+        self.bvs[i].clone()
     }
 }
@@ -3,6 +3,8 @@
 //!
 use nalgebra::na::Vec3;
 use nalgebra::na;
+use bounding_volume::BoundingSphere;
+use bounding_volume;
 use math::{Scalar, Vect};
 use procedural::{Polyline, TriMesh, UnifiedIndexBuffer, SplitIndexBuffer};
 use procedural;
@@ -10,22 +12,25 @@ use procedural;
 /// Set of point assumed to form a convex polytope.
 #[not_dim2]
 pub struct Convex {
-    mesh:   TriMesh<Scalar, Vect>,
-    margin: Scalar
+    mesh:    TriMesh<Scalar, Vect>,
+    bsphere: BoundingSphere,
+    margin:  Scalar
 }
 
 /// Set of point assumed to form a convex polyline.
 #[dim2]
 pub struct Convex {
-    mesh:   Polyline<Scalar, Vect>,
-    margin: Scalar
+    mesh:    Polyline<Scalar, Vect>,
+    bsphere: BoundingSphere,
+    margin:  Scalar
 }
 
 impl Clone for Convex {
     fn clone(&self) -> Convex {
         Convex {
-            mesh:   self.mesh.clone(),
-            margin: self.margin.clone()
+            mesh:    self.mesh.clone(),
+            bsphere: self.bsphere.clone(),
+            margin:  self.margin.clone()
         }
     }
 }
@@ -86,8 +91,9 @@ impl Convex {
         assert!(mesh.coords.len() > 0, "A convex geometry must have at least one vertex.");
 
         Convex {
-            mesh:   mesh,
-            margin: margin
+            bsphere: minimal_bounding_sphere(mesh.coords.as_slice(), margin.clone()),
+            mesh:    mesh,
+            margin:  margin
         }
     }
 
@@ -98,6 +104,9 @@ impl Convex {
     }
 
     /// The mutable convex mesh of this geometry.
+    ///
+    /// The bounding sphere computed at construction is not updated. Rebuild the geometry from its
+    /// modified mesh instead if its vertices move.
     #[inline]
     pub fn mesh_mut<'a>(&'a mut self) -> &'a mut TriMesh<Scalar, Vect> {
         &mut self.mesh
@@ -141,8 +150,9 @@ impl Convex {
         mesh.normals = None;
 
         Convex {
-            mesh:   mesh,
-            margin: margin
+            bsphere: minimal_bounding_sphere(mesh.coords.as_slice(), margin.clone()),
+            mesh:    mesh,
+            margin:  margin
         }
     }
 
@@ -153,6 +163,9 @@ impl Convex {
     }
 
     /// The mutable convex polyline of this geometry.
+    ///
+    /// The bounding sphere computed at construction is not updated. Rebuild the geometry from its
+    /// modified polyline instead if its vertices move.
     #[inline]
     pub fn mesh_mut<'a>(&'a mut self) -> &'a mut Polyline<Scalar, Vect> {
         &mut self.mesh
@@ -177,4 +190,19 @@ impl Convex {
     pub fn margin(&self) -> Scalar {
         self.margin.clone()
     }
+
+    /// The minimal bounding sphere of this convex polytope, in its local space.
+    ///
+    /// It is computed once at construction and includes the margin.
+    #[inline]
+    pub fn local_bounding_sphere<'a>(&'a self) -> &'a BoundingSphere {
+        &self.bsphere
+    }
+}
+
+// Welzl's algorithm is too slow to be run at each bounding sphere query.
+fn minimal_bounding_sphere(pts: &[Vect], margin: Scalar) -> BoundingSphere {
+    let (center, radius) = bounding_volume::point_cloud_minimal_bounding_sphere(pts);
+
+    BoundingSphere::new(center, radius + margin)
 }
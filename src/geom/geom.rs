@@ -35,7 +35,7 @@ pub trait ConcaveGeom : Geom {
     fn approx_interferences_with_ray(&self, &Ray, &mut Vec<uint>);
     // FIXME: kind of ad-hoc…
     /// Gets the AABB of the geometry identified by the index `i`.
-    fn aabb_at<'a>(&'a self, i: uint) -> &'a AABB;
+    fn aabb_at(&self, i: uint) -> AABB;
 }
 
 impl<T: 'static + Send + Clone + HasAABB + HasBoundingSphere + RayCast + AnyPrivate + Any>
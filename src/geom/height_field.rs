@@ -0,0 +1,549 @@
+//!
+//! Regular grid of heights.
+//!
+
+use std::num::{Zero, Bounded, Signed};
+use sync::Arc;
+use nalgebra::na::Indexable;
+use nalgebra::na;
+use ray::{Ray, RayCast};
+use bounding_volume::{BoundingVolume, LooseBoundingVolume, HasAABB, AABB};
+use geom::{Geom, ConcaveGeom, MeshPrimitive};
+use math::{Scalar, Vect, Matrix};
+
+// #[dim2]
+use geom::Segment;
+// #[dim3]
+use geom::Triangle;
+
+/// A geometry defined by heights sampled on a regular grid.
+///
+/// The heights are along the `y` axis. In 2d, the samples are evenly spaced along the `x` axis and
+/// the height field is a line strip. In 3d, the samples are evenly spaced on the `xz` plane and
+/// each cell of the grid is split into two triangles. The height field is centered at the origin:
+/// along the `x` (resp. `z`) axis, it spans `[-scale.x / 2, scale.x / 2]` (resp.
+/// `[-scale.z / 2, scale.z / 2]`). The heights are multiplied by `scale.y`.
+pub struct HeightField {
+    heights: Arc<Vec<Scalar>>,
+    nrows:   uint,
+    ncols:   uint,
+    scale:   Vect,
+    margin:  Scalar,
+    aabb:    AABB
+}
+
+impl Clone for HeightField {
+    fn clone(&self) -> HeightField {
+        HeightField {
+            heights: self.heights.clone(),
+            nrows:   self.nrows.clone(),
+            ncols:   self.ncols.clone(),
+            scale:   self.scale.clone(),
+            margin:  self.margin.clone(),
+            aabb:    self.aabb.clone()
+        }
+    }
+}
+
+#[dim2]
+impl HeightField {
+    /// Builds a new 2d height field with a default margin of 0.04.
+    ///
+    /// The `heights` are evenly spaced along the `x` axis.
+    pub fn new(heights: Arc<Vec<Scalar>>, scale: Vect) -> HeightField {
+        HeightField::new_with_margin(heights, scale, na::cast(0.04f64))
+    }
+
+    /// Builds a new 2d height field with a custom margin.
+    pub fn new_with_margin(heights: Arc<Vec<Scalar>>, scale: Vect, margin: Scalar) -> HeightField {
+        let ncols = heights.len();
+
+        HeightField::new_with_grid(heights, 1, ncols, scale, margin)
+    }
+}
+
+#[dim3]
+impl HeightField {
+    /// Builds a new 3d height field with a default margin of 0.04.
+    ///
+    /// The `heights` are given row by row. There are `nrows` rows along the `z` axis and `ncols`
+    /// columns along the `x` axis.
+    pub fn new(heights: Arc<Vec<Scalar>>, nrows: uint, ncols: uint, scale: Vect) -> HeightField {
+        HeightField::new_with_margin(heights, nrows, ncols, scale, na::cast(0.04f64))
+    }
+
+    /// Builds a new 3d height field with a custom margin.
+    pub fn new_with_margin(heights: Arc<Vec<Scalar>>,
+                           nrows:   uint,
+                           ncols:   uint,
+                           scale:   Vect,
+                           margin:  Scalar)
+                           -> HeightField {
+        assert!(nrows > 1, "A height field must have at least two rows.");
+
+        HeightField::new_with_grid(heights, nrows, ncols, scale, margin)
+    }
+}
+
+#[dim4]
+impl HeightField {
+    /// Height fields are not yet implemented in 4d.
+    pub fn new(_: Arc<Vec<Scalar>>, _: uint, _: uint, _: Vect) -> HeightField {
+        fail!("`HeightField` is not yet implemented in 4d.")
+    }
+}
+
+impl HeightField {
+    fn new_with_grid(heights: Arc<Vec<Scalar>>,
+                     nrows:   uint,
+                     ncols:   uint,
+                     scale:   Vect,
+                     margin:  Scalar)
+                     -> HeightField {
+        assert!(ncols > 1, "A height field must have at least two columns.");
+        assert!(heights.len() == nrows * ncols, "The number of heights must be `nrows * ncols`.");
+
+        let mut res = HeightField {
+            heights: heights,
+            nrows:   nrows,
+            ncols:   ncols,
+            scale:   scale,
+            margin:  margin,
+            aabb:    AABB::new_invalid()
+        };
+
+        let mut aabb = res.part_aabb(0);
+
+        for i in range(1u, res.nparts()) {
+            aabb.merge(&res.part_aabb(i));
+        }
+
+        res.aabb = aabb;
+
+        res
+    }
+
+    /// The heights of this height field, row by row.
+    #[inline]
+    pub fn heights<'a>(&'a self) -> &'a Arc<Vec<Scalar>> {
+        &self.heights
+    }
+
+    /// The number of rows of this height field (along the `z` axis). It is 1 in 2d.
+    #[inline]
+    pub fn nrows(&self) -> uint {
+        self.nrows
+    }
+
+    /// The number of columns of this height field (along the `x` axis).
+    #[inline]
+    pub fn ncols(&self) -> uint {
+        self.ncols
+    }
+
+    /// The scale factors of this height field.
+    #[inline]
+    pub fn scale<'a>(&'a self) -> &'a Vect {
+        &self.scale
+    }
+
+    /// The collision margin used by this height field.
+    #[inline]
+    pub fn margin(&self) -> Scalar {
+        self.margin.clone()
+    }
+
+    /// The local-space AABB of this height field.
+    #[inline]
+    pub fn local_aabb<'a>(&'a self) -> &'a AABB {
+        &self.aabb
+    }
+
+    /// The height of the sample at the row `i` and the column `j`.
+    #[inline]
+    pub fn height_at(&self, i: uint, j: uint) -> Scalar {
+        self.heights.deref()[i * self.ncols + j]
+    }
+
+    /// The number of cells of this height field.
+    #[inline]
+    pub fn ncells(&self) -> uint {
+        if self.nrows == 1 {
+            self.ncols - 1
+        }
+        else {
+            (self.nrows - 1) * (self.ncols - 1)
+        }
+    }
+
+    /// The number of parts of this height field.
+    ///
+    /// A cell is made of one segment in 2d and two triangles in 3d.
+    #[inline]
+    pub fn nparts(&self) -> uint {
+        self.ncells() * (na::dim::<Vect>() - 1)
+    }
+
+    /// The local-space AABB of the i-th part of this height field.
+    ///
+    /// It is loosened by the margin for better persistancy.
+    #[inline]
+    pub fn part_aabb(&self, i: uint) -> AABB {
+        let id: Matrix = na::one();
+
+        self.part_at(i).aabb(&id).loosened(self.margin.clone())
+    }
+
+    /// The local `x` (or `z`) coordinate of the `i`-th sample along an axis of length `len`.
+    #[inline]
+    fn sample_coord(i: uint, n: uint, len: Scalar) -> Scalar {
+        let _0_5: Scalar = na::cast(0.5f64);
+
+        (na::cast::<f64, Scalar>(i as f64) / na::cast((n - 1) as f64) - _0_5) * len
+    }
+
+    /// The index of the cell containing `x` along an axis with `n` samples and length `len`,
+    /// clamped to the valid range.
+    #[inline]
+    fn cell_coord(x: Scalar, n: uint, len: Scalar) -> uint {
+        let _0_5: Scalar = na::cast(0.5f64);
+        let t = (x / len + _0_5) * na::cast((n - 1) as f64);
+
+        if t <= na::zero() {
+            0
+        }
+        else {
+            let t: f64 = na::cast(t);
+
+            (t as uint).min(n - 2)
+        }
+    }
+}
+
+#[dim2]
+impl HeightField {
+    /// The local-space position of the sample at the row `i` (always 0 in 2d) and the column `j`.
+    #[inline]
+    pub fn point_at(&self, _: uint, j: uint) -> Vect {
+        let mut res: Vect = na::zero();
+
+        res.set(0, HeightField::sample_coord(j, self.ncols, self.scale.at(0)));
+        res.set(1, self.height_at(0, j) * self.scale.at(1));
+
+        res
+    }
+
+    /// Gets the i-th part of this height field.
+    #[inline]
+    pub fn part_at(&self, i: uint) -> MeshPrimitive {
+        Segment::new_with_margin(self.point_at(0, i), self.point_at(0, i + 1), self.margin.clone())
+    }
+
+    /// Calls `f` with the index of every cell intersecting a ray, in the order they are traversed.
+    ///
+    /// The traversal stops when `f` returns `false`.
+    pub fn traverse_cells_with_ray(&self, ray: &Ray, f: |uint| -> bool) {
+        let tmin = match self.aabb.toi_with_ray(ray, true) {
+            Some(t) => t,
+            None    => return
+        };
+
+        let x     = ray.orig.at(0) + ray.dir.at(0) * tmin;
+        let mut j = HeightField::cell_coord(x, self.ncols, self.scale.at(0));
+
+        loop {
+            if !f(j) {
+                return
+            }
+
+            if ray.dir.at(0) > na::zero() && j + 2 < self.ncols {
+                j = j + 1
+            }
+            else if ray.dir.at(0) < na::zero() && j > 0 {
+                j = j - 1
+            }
+            else {
+                return
+            }
+        }
+    }
+
+    /// Computes the indices of the cells whose columns intersect the given local-space AABB.
+    fn cells_in_aabb(&self, aabb: &AABB, out: &mut Vec<uint>) {
+        if aabb.maxs().at(0) < self.aabb.mins().at(0) || aabb.mins().at(0) > self.aabb.maxs().at(0) {
+            return
+        }
+
+        let jmin = HeightField::cell_coord(aabb.mins().at(0), self.ncols, self.scale.at(0));
+        let jmax = HeightField::cell_coord(aabb.maxs().at(0), self.ncols, self.scale.at(0));
+
+        for j in range(jmin, jmax + 1) {
+            out.push(j)
+        }
+    }
+}
+
+#[dim3]
+impl HeightField {
+    /// The local-space position of the sample at the row `i` and the column `j`.
+    #[inline]
+    pub fn point_at(&self, i: uint, j: uint) -> Vect {
+        let mut res: Vect = na::zero();
+
+        res.set(0, HeightField::sample_coord(j, self.ncols, self.scale.at(0)));
+        res.set(1, self.height_at(i, j) * self.scale.at(1));
+        res.set(2, HeightField::sample_coord(i, self.nrows, self.scale.at(2)));
+
+        res
+    }
+
+    /// Gets the i-th part of this height field.
+    ///
+    /// The parts `2 * k` and `2 * k + 1` are the two triangles of the `k`-th cell.
+    #[inline]
+    pub fn part_at(&self, i: uint) -> MeshPrimitive {
+        let cell = i / 2;
+        let row  = cell / (self.ncols - 1);
+        let col  = cell % (self.ncols - 1);
+
+        let p00 = self.point_at(row, col);
+        let p01 = self.point_at(row, col + 1);
+        let p10 = self.point_at(row + 1, col);
+        let p11 = self.point_at(row + 1, col + 1);
+
+        if i % 2 == 0 {
+            Triangle::new_with_margin(p00, p10, p11, self.margin.clone())
+        }
+        else {
+            Triangle::new_with_margin(p00, p11, p01, self.margin.clone())
+        }
+    }
+
+    /// Calls `f` with the index of every cell intersecting a ray, in the order they are traversed.
+    ///
+    /// The traversal stops when `f` returns `false`.
+    pub fn traverse_cells_with_ray(&self, ray: &Ray, f: |uint| -> bool) {
+        let tmin = match self.aabb.toi_with_ray(ray, true) {
+            Some(t) => t,
+            None    => return
+        };
+
+        // Grid traversal on the `xz` plane (Amanatides & Woo).
+        let sizes = [ self.scale.at(0) / na::cast((self.ncols - 1) as f64),
+                      self.scale.at(2) / na::cast((self.nrows - 1) as f64) ];
+        let axes  = [ 0u, 2 ];
+        let ns    = [ self.ncols, self.nrows ];
+
+        let start    = ray.orig + ray.dir * tmin;
+        let mut cell = [ HeightField::cell_coord(start.at(0), self.ncols, self.scale.at(0)),
+                         HeightField::cell_coord(start.at(2), self.nrows, self.scale.at(2)) ];
+        let mut tmax: [Scalar, ..2] = [ Bounded::max_value(), Bounded::max_value() ];
+        let mut tdelta: [Scalar, ..2] = [ Bounded::max_value(), Bounded::max_value() ];
+
+        // A ray which does not move along an axis and lies on a cell boundary along that axis
+        // touches the cells on both sides of the boundary for its whole length.
+        let mut neighbors: [Option<uint>, ..2] = [ None, None ];
+
+        let _eps: Scalar = Float::epsilon();
+        let _eps_rel     = _eps.sqrt();
+
+        for k in range(0u, 2) {
+            let dir   = ray.dir.at(axes[k]);
+            let lower = HeightField::sample_coord(cell[k], ns[k], self.scale.at(axes[k]));
+
+            if !dir.is_zero() {
+                let bound = if dir > na::zero() { lower + sizes[k] } else { lower };
+
+                tmax[k]   = (bound - ray.orig.at(axes[k])) / dir;
+                tdelta[k] = sizes[k] / dir.abs();
+            }
+            else {
+                let tol = sizes[k] * _eps_rel;
+
+                if cell[k] > 0 && (start.at(axes[k]) - lower).abs() <= tol {
+                    neighbors[k] = Some(cell[k] - 1)
+                }
+                else if cell[k] + 2 < ns[k] && (lower + sizes[k] - start.at(axes[k])).abs() <= tol {
+                    neighbors[k] = Some(cell[k] + 1)
+                }
+            }
+        }
+
+        loop {
+            for i in Some(cell[1]).iter().chain(neighbors[1].iter()) {
+                for j in Some(cell[0]).iter().chain(neighbors[0].iter()) {
+                    if !f(*i * (self.ncols - 1) + *j) {
+                        return
+                    }
+                }
+            }
+
+            let k   = if tmax[0] < tmax[1] { 0 } else { 1 };
+            let dir = ray.dir.at(axes[k]);
+
+            if dir.is_zero() {
+                return
+            }
+
+            if dir > na::zero() {
+                if cell[k] + 2 >= ns[k] {
+                    return
+                }
+
+                cell[k] = cell[k] + 1;
+            }
+            else {
+                if cell[k] == 0 {
+                    return
+                }
+
+                cell[k] = cell[k] - 1;
+            }
+
+            tmax[k] = tmax[k] + tdelta[k];
+        }
+    }
+
+    /// Computes the indices of the cells whose columns intersect the given local-space AABB.
+    fn cells_in_aabb(&self, aabb: &AABB, out: &mut Vec<uint>) {
+        for k in [ 0u, 2 ].iter() {
+            if aabb.maxs().at(*k) < self.aabb.mins().at(*k) || aabb.mins().at(*k) > self.aabb.maxs().at(*k) {
+                return
+            }
+        }
+
+        let jmin = HeightField::cell_coord(aabb.mins().at(0), self.ncols, self.scale.at(0));
+        let jmax = HeightField::cell_coord(aabb.maxs().at(0), self.ncols, self.scale.at(0));
+        let imin = HeightField::cell_coord(aabb.mins().at(2), self.nrows, self.scale.at(2));
+        let imax = HeightField::cell_coord(aabb.maxs().at(2), self.nrows, self.scale.at(2));
+
+        for i in range(imin, imax + 1) {
+            for j in range(jmin, jmax + 1) {
+                out.push(i * (self.ncols - 1) + j)
+            }
+        }
+    }
+}
+
+#[dim4]
+impl HeightField {
+    /// Height fields are not yet implemented in 4d.
+    pub fn point_at(&self, _: uint, _: uint) -> Vect {
+        fail!("`HeightField` is not yet implemented in 4d.")
+    }
+
+    /// Height fields are not yet implemented in 4d.
+    pub fn part_at(&self, _: uint) -> MeshPrimitive {
+        fail!("`HeightField` is not yet implemented in 4d.")
+    }
+
+    /// Height fields are not yet implemented in 4d.
+    pub fn traverse_cells_with_ray(&self, _: &Ray, _: |uint| -> bool) {
+        fail!("`HeightField` is not yet implemented in 4d.")
+    }
+
+    fn cells_in_aabb(&self, _: &AABB, _: &mut Vec<uint>) {
+        fail!("`HeightField` is not yet implemented in 4d.")
+    }
+}
+
+impl ConcaveGeom for HeightField {
+    #[inline(always)]
+    fn map_part_at<T>(&self, i: uint, f: |&Matrix, &Geom| -> T) -> T {
+        let one: Matrix = na::one();
+
+        self.map_transformed_part_at(&one, i, f)
+    }
+
+    #[inline(always)]
+    fn map_transformed_part_at<T>(&self, m: &Matrix, i: uint, f: |&Matrix, &Geom| -> T) -> T {
+        let part = self.part_at(i);
+
+        f(m, &part as &Geom)
+    }
+
+    fn approx_interferences_with_aabb(&self, aabb: &AABB, out: &mut Vec<uint>) {
+        let nparts_per_cell = na::dim::<Vect>() - 1;
+        let mut cells       = Vec::new();
+
+        self.cells_in_aabb(aabb, &mut cells);
+
+        for cell in cells.iter() {
+            for k in range(0u, nparts_per_cell) {
+                let part = *cell * nparts_per_cell + k;
+
+                if self.part_aabb(part).intersects(aabb) {
+                    out.push(part)
+                }
+            }
+        }
+    }
+
+    fn approx_interferences_with_ray(&self, ray: &Ray, out: &mut Vec<uint>) {
+        let nparts_per_cell = na::dim::<Vect>() - 1;
+
+        self.traverse_cells_with_ray(ray, |cell| {
+            for k in range(0u, nparts_per_cell) {
+                out.push(cell * nparts_per_cell + k)
+            }
+
+            true
+        })
+    }
+
+    #[inline]
+    fn aabb_at(&self, i: uint) -> AABB {
+        self.part_aabb(i)
+    }
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use sync::Arc;
+    use nalgebra::na::Vec3;
+    use nalgebra::na;
+    use ray::{Ray, RayCast};
+    use geom::HeightField;
+
+    #[test]
+    fn test_ray_cast_on_height_field() {
+        let heights = vec!(0.0f64, 0.0, 0.0,
+                           0.0,    1.0, 0.0,
+                           0.0,    0.0, 0.0);
+        let field   = HeightField::new_with_margin(Arc::new(heights), 3, 3, Vec3::new(2.0f64, 1.0, 2.0), 0.0);
+        let ray     = Ray::new(Vec3::new(0.0f64, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let missing = Ray::new(Vec3::new(5.0f64, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+
+        let off     = Ray::new(Vec3::new(0.1f64, 10.0, 0.05), Vec3::new(0.0, -1.0, 0.0));
+
+        assert!(na::approx_eq(&field.toi_with_ray(&ray, true).unwrap(), &9.0));
+        assert!(na::approx_eq(&field.toi_with_ray(&off, true).unwrap(), &9.1));
+        assert!(field.toi_with_ray(&missing, true).is_none());
+    }
+
+    #[test]
+    fn test_traverse_cells_on_cell_boundaries() {
+        let heights = vec!(0.0f64, 0.0, 0.0,
+                           0.0,    1.0, 0.0,
+                           0.0,    0.0, 0.0);
+        let field   = HeightField::new_with_margin(Arc::new(heights), 3, 3, Vec3::new(2.0f64, 1.0, 2.0), 0.0);
+
+        // A vertical ray on a vertex touches the four cells around it.
+        let mut cells = Vec::new();
+        field.traverse_cells_with_ray(&Ray::new(Vec3::new(0.0f64, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0)), |c| {
+            cells.push(c);
+            true
+        });
+        cells.sort();
+        assert!(cells == vec!(0u, 1, 2, 3));
+
+        // A vertical ray on an edge touches the two cells sharing it.
+        let mut cells = Vec::new();
+        field.traverse_cells_with_ray(&Ray::new(Vec3::new(0.5f64, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0)), |c| {
+            cells.push(c);
+            true
+        });
+        cells.sort();
+        assert!(cells == vec!(1u, 3));
+    }
+}
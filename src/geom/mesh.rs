@@ -6,8 +6,9 @@ use sync::Arc;
 use nalgebra::na;
 use nalgebra::na::Vec2;
 use ray::Ray;
-use partitioning::BVT;
-use bounding_volume::{HasAABB, AABB, LooseBoundingVolume};
+use partitioning::{BVT, BinaryPartition, kdtree_partitioner};
+use bounding_volume::{HasAABB, AABB, BoundingVolume, LooseBoundingVolume, KDOP, KDOPKind, BoundingSphere};
+use bounding_volume;
 use partitioning::{BoundingVolumeInterferencesCollector, RayInterferencesCollector};
 use implicit::HasMargin;
 use geom::{Geom, ConcaveGeom, Segment, Triangle};
@@ -42,6 +43,7 @@ pub type MeshPrimitive = Triangle; // XXX: this is wrong
 pub struct Mesh {
     bvt:      BVT<uint, AABB>,
     bvs:      Vec<AABB>,
+    bsphere:  BoundingSphere,
     margin:   Scalar,
     vertices: Arc<Vec<Vect>>,
     indices:  Arc<Vec<uint>>,
@@ -54,6 +56,7 @@ impl Clone for Mesh {
         Mesh {
             bvt:      self.bvt.clone(),
             bvs:      self.bvs.clone(),
+            bsphere:  self.bsphere.clone(),
             margin:   self.margin.clone(),
             vertices: self.vertices.clone(),
             indices:  self.indices.clone(),
@@ -80,6 +83,18 @@ impl Mesh {
                            normals:  Option<Arc<Vec<Vect>>>,
                            margin:   Scalar)
                            -> Mesh {
+        Mesh::new_with_partitioner(vertices, indices, uvs, normals, margin, kdtree_partitioner)
+    }
+
+    /// Builds a new mesh with a custom margin and a custom construction function for its
+    /// `BVT`, e.g., `partitioning::sah_partitioner`.
+    pub fn new_with_partitioner(vertices:    Arc<Vec<Vect>>,
+                                indices:     Arc<Vec<uint>>,
+                                uvs:         Option<Arc<Vec<Vec2<Scalar>>>>,
+                                normals:     Option<Arc<Vec<Vect>>>,
+                                margin:      Scalar,
+                                partitioner: |uint, Vec<(uint, AABB)>| -> (AABB, BinaryPartition<uint, AABB>))
+                                -> Mesh {
         assert!(indices.len() % MeshElement::nvertices(None::<MeshPrimitive>) == 0);
 
         for uvs in uvs.iter() {
@@ -104,11 +119,21 @@ impl Mesh {
             }
         }
 
-        let bvt = BVT::new_kdtree(leaves);
+        let bvt = BVT::new_with_partitioner(leaves, partitioner);
+
+        // Computed once here: the bounding sphere queries only transform it.
+        let (center, radius) =
+            if vertices.is_empty() {
+                (na::zero(), na::zero())
+            }
+            else {
+                bounding_volume::point_cloud_minimal_bounding_sphere(vertices.as_slice())
+            };
 
         Mesh {
             bvt:      bvt,
             bvs:      bvs,
+            bsphere:  BoundingSphere::new(center, radius + margin),
             margin:   margin,
             vertices: vertices,
             indices:  indices,
@@ -144,6 +169,14 @@ impl Mesh {
 }
 
 impl Mesh {
+    /// The minimal bounding sphere of this mesh, in its local space.
+    ///
+    /// It is computed once at construction and includes the margin.
+    #[inline]
+    pub fn local_bounding_sphere<'a>(&'a self) -> &'a BoundingSphere {
+        &self.bsphere
+    }
+
     /// The vertices of this mesh.
     #[inline]
     pub fn vertices<'a>(&'a self) -> &'a Arc<Vec<Vect>> {
@@ -188,6 +221,58 @@ impl Mesh {
 }
 
 impl Mesh {
+    /// Modifies the vertices of this mesh in-place, then refits its `BVT`.
+    ///
+    /// The indices are not modified so each element keeps its identifier, and the collision
+    /// detectors involving this mesh use the new geometry on their next update. `f` is given the
+    /// normals of this mesh too, if any, in order to keep them consistent with the new vertices.
+    /// Both buffers are copied first if they are shared.
+    pub fn update_vertices(&mut self, f: |&mut [Vect], Option<&mut [Vect]>| -> ()) {
+        let normals = self.normals.as_mut().map(|ns| ns.make_unique().as_mut_slice());
+
+        f(self.vertices.make_unique().as_mut_slice(), normals);
+
+        self.refit_bvt();
+    }
+
+    /// Recomputes the bounding volumes of the mesh elements and refits the `BVT` bottom-up.
+    ///
+    /// The loosened bounding volume of an element is only recomputed if the element moved out of
+    /// it. The topology of the tree is kept: use `rebuild_bvt` if it degraded too much.
+    pub fn refit_bvt(&mut self) {
+        let id: Matrix = na::one();
+
+        for i in range(0, self.bvs.len()) {
+            let aabb = self.element_at(i).aabb(&id);
+
+            if !self.bvs[i].contains(&aabb) {
+                *self.bvs.get_mut(i) = aabb.loosened(self.margin.clone());
+            }
+        }
+
+        let bvs = &self.bvs;
+
+        self.bvt.refit(&mut |i, _| bvs[*i].clone());
+    }
+
+    /// Builds a new `BVT` for the current geometry, using a custom construction function.
+    pub fn rebuild_bvt(&mut self,
+                       partitioner: |uint, Vec<(uint, AABB)>| -> (AABB, BinaryPartition<uint, AABB>)) {
+        let leaves = self.bvs.iter().enumerate().map(|(i, bv)| (i, bv.clone())).collect();
+
+        self.bvt = BVT::new_with_partitioner(leaves, partitioner);
+    }
+
+    /// Computes the k-DOP of each element of this mesh, in its local frame.
+    ///
+    /// The result can be used to build a `BVT<uint, KDOP>` fitting tighter than the AABB-based
+    /// `BVT` of this mesh when its elements are not aligned with the coordinate axes.
+    pub fn element_kdops(&self, kind: KDOPKind) -> Vec<(uint, KDOP)> {
+        let id: Matrix = na::one();
+
+        Vec::from_fn(self.bvs.len(), |i| (i, bounding_volume::implicit_shape_kdop(kind, &id, &self.element_at(i))))
+    }
+
     /// Gets the i-th mesh element.
     #[inline(always)]
     pub fn element_at(&self, i: uint) -> MeshPrimitive {
@@ -227,7 +312,74 @@ impl ConcaveGeom for Mesh {
     }
 
     #[inline]
-    fn aabb_at<'a>(&'a self, i: uint) -> &'a AABB {
-        &self.bvs[i]
+    fn aabb_at(&self, i: uint) -> AABB {
+        self.bvs[i].clone()
+    }
+}
+
+#[cfg(test, dim3, f64)]
+mod test {
+    use sync::Arc;
+    use nalgebra::na::Vec3;
+    use ray::{Ray, RayCast};
+    use bounding_volume::BoundingVolume;
+    use partitioning::kdtree_partitioner;
+    use super::Mesh;
+
+    #[test]
+    fn test_mesh_update_vertices() {
+        let vertices = vec!(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0),
+                            Vec3::new(5.0, 0.0, 0.0), Vec3::new(6.0, 0.0, 0.0), Vec3::new(5.0, 1.0, 0.0));
+        let indices  = vec!(0u, 1, 2, 3, 4, 5);
+        let vertices = Arc::new(vertices);
+        let mut mesh = Mesh::new(vertices.clone(), Arc::new(indices), None, None);
+
+        let ray = Ray::new(Vec3::new(0.2, 0.2, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(mesh.toi_with_ray(&ray, true).is_some());
+
+        // Move the first triangle upward.
+        mesh.update_vertices(|vs, ns| {
+            assert!(ns.is_none());
+
+            for v in vs.mut_slice_to(3).mut_iter() {
+                v.z = v.z + 10.0;
+            }
+        });
+
+        // The vertices shared with the caller are left untouched.
+        assert!(vertices.get(0).z == 0.0);
+
+        let ray = Ray::new(Vec3::new(0.2, 0.2, 5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(mesh.toi_with_ray(&ray, true).is_some());
+
+        for (i, bv) in mesh.bounding_volumes().iter().enumerate() {
+            assert!(mesh.bvt().root_bounding_volume().unwrap().contains(bv), "Element {} not refitted.", i);
+        }
+
+        mesh.rebuild_bvt(kdtree_partitioner);
+        assert!(mesh.toi_with_ray(&ray, true).is_some());
+    }
+
+    #[test]
+    fn test_mesh_update_vertices_and_normals() {
+        let vertices = Arc::new(vec!(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)));
+        let normals  = Arc::new(vec!(Vec3::z(), Vec3::z(), Vec3::z()));
+        let mut mesh = Mesh::new(vertices, Arc::new(vec!(0u, 1, 2)), None, Some(normals.clone()));
+
+        // Turn the triangle upside down.
+        mesh.update_vertices(|vs, ns| {
+            vs.swap(1, 2);
+
+            for n in ns.unwrap().mut_iter() {
+                *n = -*n;
+            }
+        });
+
+        // The normals shared with the caller are left untouched.
+        assert!(*normals.get(0) == Vec3::z());
+
+        for n in mesh.normals().as_ref().unwrap().iter() {
+            assert!(*n == -Vec3::z());
+        }
     }
 }
@@ -13,13 +13,14 @@ pub use geom::reflection::Reflection;
 pub use geom::compound::{Compound, CompoundData};
 pub use geom::geom_with_margin::GeomWithMargin;
 pub use geom::mesh::{Mesh, MeshElement, MeshPrimitive};
+pub use geom::height_field::HeightField;
 pub use geom::segment::Segment;
 pub use geom::triangle::Triangle;
 #[doc(inline)]
 pub use geom::geom::{Geom, ConcaveGeom};
 pub use geom::bezier_surface::{BezierSurface, BezierSurfaceEvaluationCache};
 pub use geom::bezier_curve::{BezierCurve, BezierCurveEvaluationCache};
-pub use geom::torus::Torus;
+pub use geom::torus::{Torus, DEFAULT_TORUS_NSUBDIVS};
 // pub use geom::reparametrized_surface::ReparametrizedSurface;
 
 // modules
@@ -39,6 +40,7 @@ mod segment;
 mod triangle;
 mod geom_with_margin;
 mod mesh;
+mod height_field;
 mod bezier_curve;
 mod bezier_surface;
 mod torus;
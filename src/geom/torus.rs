@@ -1,4 +1,11 @@
-use math::Scalar;
+use std::num::Float;
+use nalgebra::na::Indexable;
+use nalgebra::na;
+use geom::Segment;
+use math::{Scalar, Vect};
+
+/// The number of segments approximating the core circle of a torus when none is specified.
+pub static DEFAULT_TORUS_NSUBDIVS: uint = 32;
 
 /// A torus.
 #[deriving(PartialEq, Show, Clone, Encodable, Decodable)]
@@ -30,4 +37,28 @@ impl Torus {
     pub fn major_radius(&self) -> Scalar {
         self.major_radius.clone()
     }
+
+    /// The `i`-th of the `nsubdivs` rounded segments approximating this torus, in its local space.
+    ///
+    /// The segments are inscribed in the core circle of the torus, which lies on the plane
+    /// orthogonal to the second axis, and are inflated by its minor radius. Tori being defined in
+    /// 3d only, this must not be used in lower dimensions.
+    pub fn chord(&self, nsubdivs: uint, i: uint) -> Segment {
+        let two_pi: Scalar = Float::two_pi();
+        let step           = two_pi / na::cast(nsubdivs as f64);
+        let angle1         = step * na::cast(i as f64);
+        let angle2         = step * na::cast(((i + 1) % nsubdivs) as f64);
+        let (s1, c1)       = angle1.sin_cos();
+        let (s2, c2)       = angle2.sin_cos();
+
+        let mut a: Vect = na::zero();
+        let mut b: Vect = na::zero();
+
+        a.set(0, self.major_radius * c1);
+        a.set(2, self.major_radius * s1);
+        b.set(0, self.major_radius * c2);
+        b.set(2, self.major_radius * s2);
+
+        Segment::new_with_margin(a, b, self.minor_radius.clone())
+    }
 }
@@ -2,6 +2,7 @@
 
 use nalgebra::na::FloatVec;
 use nalgebra::na;
+use narrow::FeatureId;
 use math::Scalar;
 
 // Sadly, we cannot put this on the `Implicit` trait because the caller of `margin` might get
@@ -55,6 +56,21 @@ pub trait Implicit<Vect: FloatVec<Scalar>, Matrix>: HasMargin {
      *            be normalized.
      */
     fn support_point_without_margin(&self, transform: &Matrix, dir: &Vect) -> Vect;
+
+    /**
+     * Identifies the feature of the object supporting the direction `dir`, i.e., the set of
+     * points maximizing their dot product with `dir`. This is the vertex returned by
+     * `support_point_without_margin`, or the edge or face containing it if `dir` is orthogonal to
+     * them. The feature is unknown if the support point lies on a smooth part of the object.
+     *
+     * # Arguments:
+     *  * `dir` - the input of the support function. It is not required for it to
+     *            be normalized.
+     */
+    #[inline]
+    fn support_feature_id(&self, _: &Matrix, _: &Vect) -> FeatureId {
+        FeatureId::unknown()
+    }
 }
 
 impl<'a, Vect: FloatVec<Scalar>, Matrix> HasMargin for &'a Implicit<Vect, Matrix> + 'a {
@@ -74,6 +90,11 @@ impl<'a, Vect: FloatVec<Scalar>, Matrix> Implicit<Vect, Matrix> for &'a Implicit
     fn support_point_without_margin(&self, transform: &Matrix, dir: &Vect) -> Vect {
         self.support_point_without_margin(transform, dir)
     }
+
+    #[inline]
+    fn support_feature_id(&self, transform: &Matrix, dir: &Vect) -> FeatureId {
+        self.support_feature_id(transform, dir)
+    }
 }
 
 /// Trait of geometries having prefered sampling directions for the Minkowski sampling algorithm.
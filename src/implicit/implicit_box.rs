@@ -3,6 +3,7 @@ use std::num::Signed;
 use nalgebra::na::{Indexable, Transform, Rotate};
 use nalgebra::na;
 use geom::Cuboid;
+use narrow::{FeatureId, Vertex, Edge, Face};
 use implicit::{HasMargin, Implicit, PreferedSamplingDirections};
 use math::{Scalar, Vect};
 
@@ -33,6 +34,47 @@ Implicit<Vect, _M> for Cuboid {
 
         m.transform(&vres)
     }
+
+    fn support_feature_id(&self, m: &_M, dir: &Vect) -> FeatureId {
+        let _eps: Scalar = Float::epsilon();
+        let _eps_rel     = _eps.sqrt();
+        let local_dir    = na::normalize(&m.inv_rotate(dir));
+
+        // Each axis is either fixed to one of its extremities, or free if the feature spans it.
+        let mut id    = 0u;
+        let mut pow   = 1u;
+        let mut nfree = 0u;
+
+        for i in range(0u, na::dim::<Vect>()) {
+            let state =
+                if local_dir.at(i).abs() <= _eps_rel {
+                    nfree = nfree + 1;
+                    2
+                }
+                else if local_dir.at(i).is_negative() {
+                    1
+                }
+                else {
+                    0
+                };
+
+            id  = id + state * pow;
+            pow = pow * 3;
+        }
+
+        if nfree == 0 {
+            FeatureId::new(Vertex(id))
+        }
+        else if nfree == na::dim::<Vect>() {
+            FeatureId::unknown()
+        }
+        else if nfree == na::dim::<Vect>() - 1 {
+            FeatureId::new(Face(id))
+        }
+        else {
+            FeatureId::new(Edge(id))
+        }
+    }
 }
 
 impl<_M: Rotate<Vect>>
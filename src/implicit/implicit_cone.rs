@@ -3,6 +3,7 @@ use nalgebra::na::{Indexable, Rotate, Transform, Norm};
 use nalgebra::na;
 use implicit::{Implicit, HasMargin, PreferedSamplingDirections};
 use geom::Cone;
+use narrow::{FeatureId, Vertex, Face};
 use math::{Scalar, Vect};
 
 impl HasMargin for Cone {
@@ -44,6 +45,27 @@ Implicit<Vect, _M> for Cone {
 
         m.transform(&vres)
     }
+
+    fn support_feature_id(&self, m: &_M, dir: &Vect) -> FeatureId {
+        let _eps: Scalar = Float::epsilon();
+        let _eps_rel     = _eps.sqrt();
+        let local_dir    = na::normalize(&m.inv_rotate(dir));
+        let local_pt     = m.inv_transform(&self.support_point_without_margin(m, dir));
+
+        let mut radial = local_dir.clone();
+        radial.set(1, na::zero());
+
+        // The apex is the only vertex, the base is the only flat face. The base circle is smooth.
+        if radial.norm() <= _eps_rel && local_dir.at(1).is_negative() {
+            FeatureId::new(Face(0))
+        }
+        else if local_pt.at(1) > na::zero() {
+            FeatureId::new(Vertex(0))
+        }
+        else {
+            FeatureId::unknown()
+        }
+    }
 }
 
 impl<_M: Rotate<Vect>>
@@ -2,6 +2,7 @@ use nalgebra::na::{Transform, Rotate};
 use implicit::{Implicit, HasMargin, PreferedSamplingDirections};
 use implicit;
 use geom::Convex;
+use narrow::FeatureId;
 use math::{Scalar, Vect};
 
 impl HasMargin for Convex {
@@ -20,6 +21,11 @@ impl<_M: Transform<Vect> + Rotate<Vect>> Implicit<Vect, _M> for Convex {
 
         m.transform(&best_pt)
     }
+
+    #[inline]
+    fn support_feature_id(&self, m: &_M, dir: &Vect) -> FeatureId {
+        implicit::point_cloud_support_feature_id(&m.inv_rotate(dir), self.pts())
+    }
 }
 
 impl<_M>
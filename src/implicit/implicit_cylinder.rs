@@ -3,6 +3,7 @@ use nalgebra::na::{Indexable, Rotate, Transform, Norm};
 use nalgebra::na;
 use implicit::{Implicit, HasMargin, PreferedSamplingDirections};
 use geom::Cylinder;
+use narrow::{FeatureId, Face};
 use math::{Scalar, Vect};
 
 
@@ -40,6 +41,26 @@ Implicit<Vect, _M> for Cylinder {
 
         m.transform(&vres)
     }
+
+    fn support_feature_id(&self, m: &_M, dir: &Vect) -> FeatureId {
+        let _eps: Scalar = Float::epsilon();
+        let _eps_rel     = _eps.sqrt();
+        let local_dir    = na::normalize(&m.inv_rotate(dir));
+
+        let mut radial = local_dir.clone();
+        radial.set(1, na::zero());
+
+        // Both caps are flat faces. The rest of the cylinder is smooth.
+        if radial.norm() > _eps_rel {
+            FeatureId::unknown()
+        }
+        else if local_dir.at(1).is_negative() {
+            FeatureId::new(Face(1))
+        }
+        else {
+            FeatureId::new(Face(0))
+        }
+    }
 }
 
 impl<_M: Rotate<Vect>>
@@ -1,5 +1,6 @@
 use implicit::{Implicit, HasMargin};
 use geom::Reflection;
+use narrow::FeatureId;
 use math::{Scalar, Vect};
 
 impl<'a, G: HasMargin> HasMargin for Reflection<'a, G> {
@@ -19,4 +20,9 @@ impl<'a, _M, G: Implicit<Vect, _M>> Implicit<Vect, _M> for Reflection<'a, G> {
     fn support_point_without_margin(&self, m: &_M, dir: &Vect) -> Vect {
         -self.geom().support_point_without_margin(m, &-dir)
     }
+
+    #[inline]
+    fn support_feature_id(&self, m: &_M, dir: &Vect) -> FeatureId {
+        self.geom().support_feature_id(m, &-dir)
+    }
 }
@@ -1,7 +1,9 @@
 use nalgebra::na::{Transform, Rotate};
 use nalgebra::na;
 use implicit::{Implicit, HasMargin, PreferedSamplingDirections};
+use implicit;
 use geom::Segment;
+use narrow::FeatureId;
 use math::{Scalar, Vect};
 
 impl HasMargin for Segment {
@@ -24,6 +26,11 @@ Implicit<Vect, _M> for Segment {
             m.transform(self.b())
         }
     }
+
+    #[inline]
+    fn support_feature_id(&self, m: &_M, dir: &Vect) -> FeatureId {
+        implicit::point_cloud_support_feature_id(&m.inv_rotate(dir), &[ self.a().clone(), self.b().clone() ])
+    }
 }
 
 impl<_V, _M> PreferedSamplingDirections<_V, _M> for Segment {
@@ -1,7 +1,9 @@
 use nalgebra::na::{Transform, Rotate};
 use nalgebra::na;
 use implicit::{Implicit, HasMargin, PreferedSamplingDirections};
+use implicit;
 use geom::Triangle;
+use narrow::FeatureId;
 use math::{Scalar, Vect};
 
 impl HasMargin for Triangle {
@@ -41,6 +43,13 @@ Implicit<Vect, _M> for Triangle {
 
         m.transform(res)
     }
+
+    #[inline]
+    fn support_feature_id(&self, m: &_M, dir: &Vect) -> FeatureId {
+        let pts = [ self.a().clone(), self.b().clone(), self.c().clone() ];
+
+        implicit::point_cloud_support_feature_id(&m.inv_rotate(dir), pts.as_slice())
+    }
 }
 
 impl<_V, _M> PreferedSamplingDirections<_V, _M> for Triangle {
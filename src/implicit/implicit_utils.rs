@@ -1,6 +1,9 @@
 use std::num::Bounded;
 use nalgebra::na::FloatVec;
 use nalgebra::na;
+use data::hash;
+use narrow::{FeatureId, Vertex, Edge, Face};
+use math::{Scalar, Vect};
 
 // FIXME: move this to `utils::`?
 /// Computes the support point of a cloud of points.
@@ -21,3 +24,38 @@ pub fn point_cloud_support_point<V: FloatVec<N> + Clone, N: Float>(dir: &V, poin
 
     best_pt.clone()
 }
+
+/// Identifies the feature of the convex hull of a cloud of points which supports a direction.
+///
+/// The points with a dot product with `dir` close to the maximal one span the feature: a vertex if
+/// there is only one, an edge if there are two, and a face otherwise. A vertex is identified by
+/// its index on `points`, an edge or a face by the indices of all the points spanning it.
+pub fn point_cloud_support_feature_id(dir: &Vect, points: &[Vect]) -> FeatureId {
+    let _eps: Scalar = Float::epsilon();
+    let _eps_rel     = _eps.sqrt();
+    let _max: Scalar = Bounded::max_value();
+    let ndir         = na::normalize(dir);
+    let mut best_dot = -_max;
+
+    for p in points.iter() {
+        best_dot = best_dot.max(na::dot(p, &ndir));
+    }
+
+    let tolerance = _eps_rel * (na::one::<Scalar>() + best_dot.abs());
+    let mut id    = 0u;
+    let mut npts  = 0u;
+
+    for (i, p) in points.iter().enumerate() {
+        if na::dot(p, &ndir) >= best_dot - tolerance {
+            id   = if npts == 0 { i } else { hash::key_from_pair(id, i) };
+            npts = npts + 1;
+        }
+    }
+
+    match npts {
+        0 => FeatureId::unknown(),
+        1 => FeatureId::new(Vertex(id)),
+        2 => FeatureId::new(Edge(id)),
+        _ => FeatureId::new(Face(id))
+    }
+}
@@ -3,7 +3,7 @@
 #[doc(inline)]
 pub use implicit::implicit::{Implicit, HasMargin, PreferedSamplingDirections};
 pub use implicit::implicit_minkowski_sum::{cso_support_point, cso_support_point_without_margin};
-pub use implicit::implicit_utils::{point_cloud_support_point};
+pub use implicit::implicit_utils::{point_cloud_support_point, point_cloud_support_feature_id};
 
 #[doc(hidden)]
 pub mod implicit;
@@ -125,6 +125,11 @@ Simplex<_V> for BruteForceSimplex<_V> {
         self.points.len() - 1
     }
 
+    #[inline]
+    fn point<'a>(&'a self, i: uint) -> &'a _V {
+        &self.points[i]
+    }
+
     #[inline]
     fn max_sq_len(&self) -> Scalar {
         let mut max_sq_len = na::zero();
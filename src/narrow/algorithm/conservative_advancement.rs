@@ -0,0 +1,94 @@
+//! The Conservative Advancement algorithm.
+
+use nalgebra::na::{Translation, Norm};
+use nalgebra::na;
+use narrow::ClosestPoints;
+use math::{Scalar, Vect, Orientation, Matrix};
+
+/// Computes the linear and angular velocities which move `start` to `end` during one unit of time.
+pub fn velocities(start: &Matrix, end: &Matrix) -> (Vect, Orientation) {
+    let linvel = end.translation() - start.translation();
+    let inv    = na::inv(&start.rotation).expect("A rotation matrix must be inversible.");
+    let angvel = na::rotation(&(end.rotation * inv));
+
+    (linvel, angvel)
+}
+
+/// Computes the transformation reached at time `t` by a body starting at `start` with constant
+/// velocities.
+///
+/// The body rotates around its own origin.
+pub fn interpolate(start: &Matrix, linvel: &Vect, angvel: &Orientation, t: &Scalar) -> Matrix {
+    let rotation = na::append_rotation(&start.rotation, &(*angvel * *t));
+
+    Matrix::new_with_rotmat(start.translation() + *linvel * *t, rotation)
+}
+
+/// Computes the time of impact of two bodies moving from `start1` to `end1`, and from `start2` to
+/// `end2`, using the Conservative Advancement algorithm.
+///
+/// The time of impact is expressed in the interval `[0, 1]`. Both bodies are assumed to move with
+/// constant linear and angular velocities. The returned time of impact is never greater than the
+/// actual one: if the algorithm does not converge after a fixed number of iterations, the last
+/// (safe) time is returned.
+///
+/// # Arguments:
+/// * `radius1`  - the distance from the origin of the first body to its farthest point.
+/// * `radius2`  - the distance from the origin of the second body to its farthest point.
+/// * `distance` - computes the signed distance and closest points of the bodies at the given
+///                transformations. Returning `None` means that the penetration depth could not be
+///                computed, so the bodies are assumed to be penetrating.
+pub fn toi(start1:   &Matrix,
+           end1:     &Matrix,
+           radius1:  &Scalar,
+           start2:   &Matrix,
+           end2:     &Matrix,
+           radius2:  &Scalar,
+           distance: |&Matrix, &Matrix| -> Option<ClosestPoints>)
+           -> Option<Scalar> {
+    let _eps: Scalar = Float::epsilon();
+    let _eps_rel     = _eps.sqrt();
+    let _max_iter    = 100u;
+
+    let (linvel1, angvel1) = velocities(start1, end1);
+    let (linvel2, angvel2) = velocities(start2, end2);
+
+    // Upper bound of the relative speed of any two points due to the rotations.
+    let rot_bound = na::norm(&angvel1) * *radius1 + na::norm(&angvel2) * *radius2;
+    let linvel    = linvel1 - linvel2;
+
+    let mut t: Scalar = na::zero();
+    let mut niter     = 0u;
+
+    loop {
+        let m1 = interpolate(start1, &linvel1, &angvel1, &t);
+        let m2 = interpolate(start2, &linvel2, &angvel2, &t);
+
+        let cp = match distance(&m1, &m2) {
+            Some(cp) => cp,
+            None     => return Some(t) // reporting no impact here would let the bodies tunnel.
+        };
+
+        if cp.distance <= _eps_rel {
+            return Some(t)
+        }
+
+        let normal    = (cp.world2 - cp.world1) / cp.distance;
+        let max_speed = na::dot(&linvel, &normal) + rot_bound;
+
+        if max_speed <= _eps_rel {
+            return None // the bodies are moving apart.
+        }
+
+        t     = t + cp.distance / max_speed;
+        niter = niter + 1;
+
+        if t > na::one() {
+            return None
+        }
+
+        if niter == _max_iter {
+            return Some(t)
+        }
+    }
+}
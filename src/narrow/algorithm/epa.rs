@@ -0,0 +1,334 @@
+//! The Expanding Polytope Algorithm.
+
+use std::num::Bounded;
+use nalgebra::na::{Identity, Norm};
+use nalgebra::na;
+use geom::{Reflection, AnnotatedPoint, AnnotatedMinkowskiSum};
+use implicit::Implicit;
+use narrow::algorithm::simplex::Simplex;
+use narrow::algorithm::johnson_simplex::JohnsonSimplex;
+use narrow::Contact;
+use math::{Scalar, Vect, Matrix};
+
+/// A facet of the polytope expanded by the EPA.
+struct Facet {
+    /// Indices of the facet vertices. There are exactly `dim` of them.
+    pts:    Vec<uint>,
+    /// The outward normal of the facet.
+    normal: Vect,
+    /// The distance from the origin to the facet hyperplane.
+    dist:   Scalar,
+    /// `false` if this facet has been removed from the polytope.
+    valid:  bool
+}
+
+impl Facet {
+    fn new(pts: Vec<uint>, vertices: &[AnnotatedPoint], interior: &Vect, eps: &Scalar) -> Option<Facet> {
+        facet_normal(pts.as_slice(), vertices, interior, eps).map(|(normal, dist)| {
+            Facet {
+                pts:    pts,
+                normal: normal,
+                dist:   dist,
+                valid:  true
+            }
+        })
+    }
+}
+
+/// Computes the penetration depth, normal, and witness points of two intersecting implicit
+/// geometries using the Expanding Polytope Algorithm.
+///
+/// The polytope is initialized with the points of the simplex the GJK algorithm terminated with.
+/// If this simplex is degenerate, it is completed with support points along the canonical axis.
+/// Returns `None` if the origin is not inside of the (margin-free) CSO or if the polytope
+/// degenerates.
+///
+/// The returned contact includes the geometries margins.
+///
+/// # Arguments:
+/// * `g1`      - the first geometry.
+/// * `g2`      - the second geometry.
+/// * `simplex` - the simplex on which the GJK algorithm (without margin) terminated.
+pub fn closest_points<S:  Simplex<AnnotatedPoint>,
+                      G1: Implicit<Vect, Matrix>,
+                      G2: Implicit<Vect, Matrix>>(
+                      m1:      &Matrix,
+                      g1:      &G1,
+                      m2:      &Matrix,
+                      g2:      &G2,
+                      simplex: &S)
+                      -> Option<Contact> {
+    let reflect2 = Reflection::new(g2);
+    let cso      = AnnotatedMinkowskiSum::new(m1, g1, m2, &reflect2);
+    let margin1  = g1.margin();
+    let margin2  = g2.margin();
+
+    let _eps: Scalar = Float::epsilon();
+    let _eps_tol     = _eps * na::cast(100.0f64);
+    let _eps_rel     = _eps.sqrt();
+    let _dim         = na::dim::<Vect>();
+    let _max_iter    = 100u;
+
+    /*
+     * Initialize the polytope with the GJK simplex.
+     */
+    let mut vertices = Vec::with_capacity(_dim + 1);
+    let mut basis    = Vec::with_capacity(_dim);
+
+    for i in range(0u, simplex.dimension() + 1) {
+        push_if_affinely_independent(&mut vertices, &mut basis, simplex.point(i).clone(), &_eps_tol);
+    }
+
+    if vertices.len() != _dim + 1 {
+        na::canonical_basis(|b: Vect| {
+            let dirs = [ b.clone(), -b ];
+
+            for dir in dirs.iter() {
+                if vertices.len() != _dim + 1 {
+                    let dir = AnnotatedPoint::new_invalid(dir.clone());
+                    let pt  = cso.support_point_without_margin(&Identity::new(), &dir);
+
+                    push_if_affinely_independent(&mut vertices, &mut basis, pt, &_eps_tol);
+                }
+            }
+
+            vertices.len() != _dim + 1
+        });
+
+        if vertices.len() != _dim + 1 {
+            return None
+        }
+    }
+
+    // The centroid of the initial simplex stays inside of the polytope while it expands. It is
+    // used to orient the facets normals.
+    let mut interior: Vect = na::zero();
+
+    for v in vertices.iter() {
+        interior = interior + *v.point();
+    }
+
+    interior = interior / na::cast(vertices.len());
+
+    let mut facets = Vec::new();
+
+    for i in range(0u, _dim + 1) {
+        let pts = range(0u, _dim + 1).filter(|j| *j != i).collect();
+
+        match Facet::new(pts, vertices.as_slice(), &interior, &_eps_tol) {
+            Some(f) => {
+                if f.dist < -_eps_tol {
+                    return None // the origin is outside of the CSO.
+                }
+
+                facets.push(f)
+            },
+            None => return None
+        }
+    }
+
+    /*
+     * Expand the polytope until the closest facet lies on the CSO boundary.
+     */
+    let mut niter = 0u;
+    let mut horizon: Vec<Vec<uint>> = Vec::new();
+
+    loop {
+        let mut best      = facets.len();
+        let mut best_dist = Bounded::max_value();
+
+        for (i, f) in facets.iter().enumerate() {
+            if f.valid && f.dist < best_dist {
+                best      = i;
+                best_dist = f.dist;
+            }
+        }
+
+        if best == facets.len() {
+            return None
+        }
+
+        let dir     = AnnotatedPoint::new_invalid(facets[best].normal.clone());
+        let support = cso.support_point_without_margin(&Identity::new(), &dir);
+        let gap     = na::dot(support.point(), &facets[best].normal) - best_dist;
+
+        niter = niter + 1;
+
+        if gap <= _eps_tol || gap <= _eps_rel * best_dist || niter == _max_iter {
+            return Some(facet_contact(m1, g1, m2, g2, &facets[best], vertices.as_slice(), margin1, margin2))
+        }
+
+        let new_id = vertices.len();
+        vertices.push(support);
+
+        // Remove every facet visible from the support point and keep track of the horizon, i.e.,
+        // the ridges shared by exactly one removed facet.
+        for f in facets.mut_iter() {
+            if f.valid && na::sub_dot(vertices[new_id].point(), vertices[f.pts[0]].point(), &f.normal) > _eps_tol {
+                f.valid = false;
+
+                for i in range(0u, f.pts.len()) {
+                    let mut ridge: Vec<uint> = f.pts.iter().enumerate().filter(|&(j, _)| j != i).map(|(_, p)| *p).collect();
+                    ridge.sort();
+
+                    match horizon.iter().position(|r| *r == ridge) {
+                        Some(pos) => { let _ = horizon.swap_remove(pos); },
+                        None      => horizon.push(ridge)
+                    }
+                }
+            }
+        }
+
+        for ridge in horizon.mut_iter() {
+            let mut pts = Vec::with_capacity(_dim);
+
+            pts.push_all(ridge.as_slice());
+            pts.push(new_id);
+
+            match Facet::new(pts, vertices.as_slice(), &interior, &_eps_tol) {
+                Some(f) => facets.push(f),
+                None    => {
+                    // The polytope degenerated: the best facet is the best we can do.
+                    return Some(facet_contact(m1, g1, m2, g2, &facets[best], vertices.as_slice(), margin1, margin2))
+                }
+            }
+        }
+
+        horizon.clear();
+    }
+}
+
+/// Computes the contact corresponding to the projection of the origin on a facet.
+///
+/// The features of the contact are the ones of each geometry supporting the facet normal.
+fn facet_contact<G1: Implicit<Vect, Matrix>, G2: Implicit<Vect, Matrix>>(
+                 m1:       &Matrix,
+                 g1:       &G1,
+                 m2:       &Matrix,
+                 g2:       &G2,
+                 facet:    &Facet,
+                 vertices: &[AnnotatedPoint],
+                 margin1:  Scalar,
+                 margin2:  Scalar)
+                 -> Contact {
+    let mut splx: JohnsonSimplex<AnnotatedPoint> = JohnsonSimplex::new_w_tls();
+
+    splx.reset(vertices[facet.pts[0]].clone());
+
+    for i in facet.pts.slice_from(1).iter() {
+        splx.add_point(vertices[*i].clone());
+    }
+
+    let proj = splx.project_origin();
+    let p1   = proj.orig1().clone();
+    let p2   = -*proj.orig2();
+
+    Contact::new_with_features(
+        p1 + facet.normal * margin1,
+        p2 - facet.normal * margin2,
+        facet.normal.clone(),
+        facet.dist + margin1 + margin2,
+        g1.support_feature_id(m1, &facet.normal),
+        g2.support_feature_id(m2, &-facet.normal))
+}
+
+/// Computes the outward normal of a facet and its distance to the origin.
+///
+/// Returns `None` if the facet is degenerate.
+fn facet_normal(pts: &[uint], vertices: &[AnnotatedPoint], interior: &Vect, eps: &Scalar) -> Option<(Vect, Scalar)> {
+    let orig      = vertices[pts[0]].point();
+    let mut basis = Vec::with_capacity(pts.len() - 1);
+
+    for i in pts.slice_from(1).iter() {
+        if !push_orthonormal(&mut basis, *vertices[*i].point() - *orig, eps) {
+            return None
+        }
+    }
+
+    // The part of `orig - interior` orthogonal to the facet points outward.
+    let mut normal = *orig - *interior;
+
+    for b in basis.iter() {
+        normal = normal - *b * na::dot(&normal, b);
+    }
+
+    if normal.normalize() <= *eps {
+        None
+    }
+    else {
+        Some((normal.clone(), na::dot(&normal, orig)))
+    }
+}
+
+/// Adds a point to `vertices` if it is affinely independent from the points already there.
+///
+/// `basis` is an orthonormal basis of the space spanned by the `vertices[i] - vertices[0]`.
+fn push_if_affinely_independent(vertices: &mut Vec<AnnotatedPoint>,
+                                basis:    &mut Vec<Vect>,
+                                pt:       AnnotatedPoint,
+                                eps:      &Scalar) {
+    if vertices.is_empty() {
+        vertices.push(pt)
+    }
+    else {
+        let edge = *pt.point() - *vertices[0].point();
+
+        if push_orthonormal(basis, edge, eps) {
+            vertices.push(pt)
+        }
+    }
+}
+
+/// Orthogonalizes `v` wrt. an orthonormal basis and adds the result to the basis if it is not
+/// zero.
+fn push_orthonormal(basis: &mut Vec<Vect>, v: Vect, eps: &Scalar) -> bool {
+    let mut v = v;
+
+    for b in basis.iter() {
+        v = v - *b * na::dot(&v, b);
+    }
+
+    if v.normalize() <= *eps {
+        false
+    }
+    else {
+        basis.push(v);
+
+        true
+    }
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use super::closest_points;
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::{Cuboid, AnnotatedPoint};
+    use implicit;
+    use narrow::algorithm::johnson_simplex::JohnsonSimplex;
+    use narrow::algorithm::simplex::Simplex;
+    use narrow::algorithm::gjk;
+    use narrow::algorithm::gjk::Intersection;
+
+    #[test]
+    fn test_cuboid_cuboid_penetration() {
+        let a  = Cuboid::new(Vec3::new(1.0f64, 1.0, 1.0));
+        let b  = Cuboid::new(Vec3::new(1.0f64, 1.0, 1.0));
+        let ta = Iso3::new(Vec3::new(0.0f64, 0.0, 0.0), na::zero());
+        let tb = Iso3::new(Vec3::new(1.5f64, 0.1, 0.0), na::zero());
+        let mut splx: JohnsonSimplex<AnnotatedPoint> = JohnsonSimplex::new_w_tls();
+
+        splx.reset(implicit::cso_support_point_without_margin(&ta, &a, &tb, &b, Vec3::new(1.0f64, 0.0, 0.0)));
+
+        match gjk::closest_points_without_margin_with_max_dist(&ta, &a, &tb, &b, &0.0, &mut splx) {
+            Intersection => { },
+            _            => fail!("The cuboids should intersect.")
+        }
+
+        let c = closest_points(&ta, &a, &tb, &b, &splx).expect("EPA failed.");
+
+        assert!(na::approx_eq(&c.depth, &0.5));
+        assert!(na::approx_eq(&c.normal, &Vec3::new(1.0f64, 0.0, 0.0)));
+        assert!(na::approx_eq(&(c.world1.x - c.world2.x), &0.5));
+    }
+}
@@ -31,6 +31,9 @@ pub trait Simplex<_V> {
     /// Dimension of the simplex. A simplex with `n` must be a `n - 1`-dimensional simplex.
     fn dimension(&self) -> uint;
 
+    /// The i-th point of the simplex. `i` must be smaller than `self.dimension() + 1`.
+    fn point<'a>(&'a self, i: uint) -> &'a _V;
+
     /// The maximum among the simplex point squared lengths.
     fn max_sq_len(&self) -> Scalar;
 }
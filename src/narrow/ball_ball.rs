@@ -2,7 +2,7 @@ use std::num::Zero;
 use nalgebra::na::Translation;
 use nalgebra::na;
 use geom::Ball;
-use narrow::{CollisionDetector, Contact};
+use narrow::{CollisionDetector, Contact, FeatureId, Face};
 use ray::{Ray, ball_toi_with_ray};
 use math::{Scalar, Vect, Matrix};
 
@@ -89,11 +89,14 @@ pub fn collide(center1: &Vect, b1: &Ball, center2: &Vect, b2: &Ball, prediction:
             })
         }
 
-        Some(Contact::new(
+        // A ball has only one smooth face.
+        Some(Contact::new_with_features(
                 center1 + normal * r1,
                 center2 - normal * r2,
                 normal,
-                (sum_radius - sqdist.sqrt())))
+                (sum_radius - sqdist.sqrt()),
+                FeatureId::new(Face(0)),
+                FeatureId::new(Face(0))))
     }
     else {
         None
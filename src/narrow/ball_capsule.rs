@@ -0,0 +1,139 @@
+use nalgebra::na::{Translation, Transform, Indexable};
+use nalgebra::na;
+use geom::{Ball, Capsule};
+use narrow::{CollisionDetector, Contact};
+use narrow::ball_segment;
+use ray::{Ray, RayCast};
+use math::{Scalar, Vect, Matrix};
+
+/// Collision detector between a ball and a capsule.
+#[deriving(Encodable, Decodable)]
+pub struct BallCapsule {
+    prediction: Scalar,
+    contact:    Option<Contact>
+}
+
+impl Clone for BallCapsule {
+    fn clone(&self) -> BallCapsule {
+        BallCapsule {
+            prediction: self.prediction.clone(),
+            contact:    self.contact.clone()
+        }
+    }
+}
+
+impl BallCapsule {
+    /// Creates a new persistent collision detector between a ball and a capsule.
+    #[inline]
+    pub fn new(prediction: Scalar) -> BallCapsule {
+        BallCapsule {
+            prediction: prediction,
+            contact:    None
+        }
+    }
+}
+
+impl CollisionDetector<Ball, Capsule> for BallCapsule {
+    fn update(&mut self, ma: &Matrix, a: &Ball, mb: &Matrix, b: &Capsule) {
+        self.contact = collide(&ma.translation(), a, mb, b, &self.prediction);
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        match self.contact {
+            None    => 0,
+            Some(_) => 1
+        }
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        match self.contact {
+            Some(ref c) => out_colls.push(c.clone()),
+            None        => ()
+        }
+    }
+
+    #[inline]
+    fn toi(_: Option<BallCapsule>, ma: &Matrix, dir: &Vect, _: &Scalar, a: &Ball, mb: &Matrix, b: &Capsule) -> Option<Scalar> {
+        toi(ma, dir, a, mb, b)
+    }
+}
+
+/// Collision detector between a capsule and a ball.
+#[deriving(Encodable, Decodable, Clone)]
+pub struct CapsuleBall {
+    sub_detector: BallCapsule
+}
+
+impl CapsuleBall {
+    /// Creates a new persistent collision detector between a capsule and a ball.
+    #[inline]
+    pub fn new(prediction: Scalar) -> CapsuleBall {
+        CapsuleBall {
+            sub_detector: BallCapsule::new(prediction)
+        }
+    }
+}
+
+impl CollisionDetector<Capsule, Ball> for CapsuleBall {
+    fn update(&mut self, ma: &Matrix, a: &Capsule, mb: &Matrix, b: &Ball) {
+        self.sub_detector.update(mb, b, ma, a);
+
+        match self.sub_detector.contact {
+            Some(ref mut c) => c.flip(),
+            None            => { }
+        }
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.sub_detector.num_colls()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        self.sub_detector.colls(out_colls)
+    }
+
+    #[inline]
+    fn toi(_: Option<CapsuleBall>, ma: &Matrix, dir: &Vect, _: &Scalar, a: &Capsule, mb: &Matrix, b: &Ball) -> Option<Scalar> {
+        toi(mb, &-*dir, b, ma, a)
+    }
+}
+
+/// The extremities of the segment at the core of a transformed capsule.
+pub fn capsule_segment(m: &Matrix, capsule: &Capsule) -> (Vect, Vect) {
+    let mut a: Vect = na::zero();
+    let mut b: Vect = na::zero();
+
+    a.set(1, -capsule.half_height());
+    b.set(1, capsule.half_height());
+
+    (m.transform(&a), m.transform(&b))
+}
+
+/// Computes the contact point between a ball and a capsule.
+#[inline]
+pub fn collide(center: &Vect, ball: &Ball, mcapsule: &Matrix, capsule: &Capsule, prediction: &Scalar) -> Option<Contact> {
+    let (a, b) = capsule_segment(mcapsule, capsule);
+
+    ball_segment::collide_with_rounded_segment(center, &ball.radius(), &a, &b, &capsule.radius(), prediction)
+}
+
+/// Computes the Time Of Impact of a ball and a capsule.
+///
+/// This casts a ray from the ball center on the capsule inflated by the ball radius.
+///
+/// Arguments:
+/// * `mball`    - the ball transform.
+/// * `dir`      - the direction of the ball movement.
+/// * `ball`     - the ball.
+/// * `mcapsule` - the capsule transform.
+/// * `capsule`  - the capsule.
+#[inline]
+pub fn toi(mball: &Matrix, dir: &Vect, ball: &Ball, mcapsule: &Matrix, capsule: &Capsule) -> Option<Scalar> {
+    let inflated = Capsule::new(capsule.half_height(), capsule.radius() + ball.radius());
+
+    inflated.toi_with_transform_and_ray(mcapsule, &Ray::new(mball.translation(), dir.clone()), true)
+}
@@ -0,0 +1,55 @@
+use nalgebra::na::Translation;
+use geom::Ball;
+use point::PointQuery;
+use narrow::{ProximityDetector, Proximity, Disjoint};
+use math::{Scalar, Vect, Matrix};
+
+/// Proximity detector between a ball and any geometry supporting point queries.
+#[deriving(Encodable, Decodable)]
+pub struct BallGeomProximity<G> {
+    margin:    Scalar,
+    proximity: Proximity
+}
+
+impl<G> Clone for BallGeomProximity<G> {
+    fn clone(&self) -> BallGeomProximity<G> {
+        BallGeomProximity {
+            margin:    self.margin.clone(),
+            proximity: self.proximity.clone()
+        }
+    }
+}
+
+impl<G> BallGeomProximity<G> {
+    /// Creates a new persistent proximity detector between a ball and a geometry.
+    #[inline]
+    pub fn new(margin: Scalar) -> BallGeomProximity<G> {
+        BallGeomProximity {
+            margin:    margin,
+            proximity: Disjoint
+        }
+    }
+}
+
+impl<G: PointQuery> ProximityDetector<Ball, G> for BallGeomProximity<G> {
+    #[inline]
+    fn update(&mut self, ma: &Matrix, a: &Ball, mb: &Matrix, b: &G) {
+        self.proximity = proximity(&ma.translation(), a, mb, b, &self.margin)
+    }
+
+    #[inline]
+    fn proximity(&self) -> Proximity {
+        self.proximity
+    }
+}
+
+/// Computes the proximity status of a ball and a geometry.
+///
+/// The distance between the ball and the geometry is the distance between the ball center and the
+/// geometry minus the ball radius.
+#[inline]
+pub fn proximity<G: PointQuery>(center: &Vect, ball: &Ball, mgeom: &Matrix, geom: &G, margin: &Scalar) -> Proximity {
+    let dist = geom.distance_to_point_with_transform(mgeom, center) - ball.radius();
+
+    Proximity::from_distance(&dist, margin)
+}
@@ -0,0 +1,307 @@
+use std::num::Zero;
+use nalgebra::na::{Translation, Transform};
+use nalgebra::na;
+use geom::{Ball, Segment};
+use narrow::{CollisionDetector, Contact, FeatureId, Vertex, Edge, Face};
+use utils;
+use math::{Scalar, Vect, Matrix};
+
+/// Collision detector between a ball and a segment.
+#[deriving(Encodable, Decodable)]
+pub struct BallSegment {
+    prediction: Scalar,
+    contact:    Option<Contact>
+}
+
+impl Clone for BallSegment {
+    fn clone(&self) -> BallSegment {
+        BallSegment {
+            prediction: self.prediction.clone(),
+            contact:    self.contact.clone()
+        }
+    }
+}
+
+impl BallSegment {
+    /// Creates a new persistent collision detector between a ball and a segment.
+    #[inline]
+    pub fn new(prediction: Scalar) -> BallSegment {
+        BallSegment {
+            prediction: prediction,
+            contact:    None
+        }
+    }
+}
+
+impl CollisionDetector<Ball, Segment> for BallSegment {
+    fn update(&mut self, ma: &Matrix, a: &Ball, mb: &Matrix, b: &Segment) {
+        self.contact = collide(&ma.translation(), a, mb, b, &self.prediction);
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        match self.contact {
+            None    => 0,
+            Some(_) => 1
+        }
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        match self.contact {
+            Some(ref c) => out_colls.push(c.clone()),
+            None        => ()
+        }
+    }
+
+    #[inline]
+    fn toi(_: Option<BallSegment>, ma: &Matrix, dir: &Vect, _: &Scalar, a: &Ball, mb: &Matrix, b: &Segment) -> Option<Scalar> {
+        toi(ma, dir, a, mb, b)
+    }
+}
+
+/// Collision detector between a segment and a ball.
+#[deriving(Encodable, Decodable, Clone)]
+pub struct SegmentBall {
+    sub_detector: BallSegment
+}
+
+impl SegmentBall {
+    /// Creates a new persistent collision detector between a segment and a ball.
+    #[inline]
+    pub fn new(prediction: Scalar) -> SegmentBall {
+        SegmentBall {
+            sub_detector: BallSegment::new(prediction)
+        }
+    }
+}
+
+impl CollisionDetector<Segment, Ball> for SegmentBall {
+    fn update(&mut self, ma: &Matrix, a: &Segment, mb: &Matrix, b: &Ball) {
+        self.sub_detector.update(mb, b, ma, a);
+
+        match self.sub_detector.contact {
+            Some(ref mut c) => c.flip(),
+            None            => { }
+        }
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.sub_detector.num_colls()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        self.sub_detector.colls(out_colls)
+    }
+
+    #[inline]
+    fn toi(_: Option<SegmentBall>, ma: &Matrix, dir: &Vect, _: &Scalar, a: &Segment, mb: &Matrix, b: &Ball) -> Option<Scalar> {
+        toi(mb, &-*dir, b, ma, a)
+    }
+}
+
+/// Computes the contact point between a ball and a segment.
+///
+/// The segment margin is taken into account.
+#[inline]
+pub fn collide(center: &Vect, ball: &Ball, msegment: &Matrix, segment: &Segment, prediction: &Scalar) -> Option<Contact> {
+    let a = msegment.transform(segment.a());
+    let b = msegment.transform(segment.b());
+
+    collide_with_rounded_segment(center, &ball.radius(), &a, &b, &segment.margin(), prediction)
+}
+
+/// Computes the contact point between a ball and the set of points at a distance smaller than
+/// `seg_radius` from the segment `[a, b]`.
+///
+/// The ball feature is its only face and the segment feature is given by `segment_feature`.
+pub fn collide_with_rounded_segment(center:     &Vect,
+                                    radius:     &Scalar,
+                                    a:          &Vect,
+                                    b:          &Vect,
+                                    seg_radius: &Scalar,
+                                    prediction: &Scalar)
+                                    -> Option<Contact> {
+    let (proj, t)  = utils::closest_point_on_segment(center, a, b);
+    let delta_pos  = proj - *center;
+    let sqdist     = na::sqnorm(&delta_pos);
+    let sum_radius = *radius + *seg_radius;
+    let sum_radius_with_error = sum_radius + *prediction;
+
+    if sqdist < sum_radius_with_error * sum_radius_with_error {
+        let mut normal = na::normalize(&delta_pos);
+
+        if sqdist.is_zero() {
+            na::canonical_basis(|b| {
+                normal = b;
+
+                false
+            })
+        }
+
+        Some(Contact::new_with_features(
+                *center + normal * *radius,
+                proj - normal * *seg_radius,
+                normal,
+                (sum_radius - sqdist.sqrt()),
+                FeatureId::new(Face(0)),
+                segment_feature(&t)))
+    }
+    else {
+        None
+    }
+}
+
+/// Computes the Time Of Impact of a ball and a segment.
+///
+/// The segment margin is taken into account.
+///
+/// Arguments:
+/// * `mball`    - the ball transform.
+/// * `dir`      - the direction of the ball movement.
+/// * `ball`     - the ball.
+/// * `msegment` - the segment transform.
+/// * `segment`  - the segment.
+#[inline]
+pub fn toi(mball: &Matrix, dir: &Vect, ball: &Ball, msegment: &Matrix, segment: &Segment) -> Option<Scalar> {
+    let a = msegment.transform(segment.a());
+    let b = msegment.transform(segment.b());
+
+    toi_with_rounded_segment(&mball.translation(), &ball.radius(), dir, &a, &b, &segment.margin())
+}
+
+/// Computes the Time Of Impact of a ball moving along `dir` and the set of points at a distance
+/// smaller than `seg_radius` from the segment `[a, b]`.
+///
+/// Returns `Some(0)` if they already intersect.
+pub fn toi_with_rounded_segment(center:     &Vect,
+                                radius:     &Scalar,
+                                dir:        &Vect,
+                                a:          &Vect,
+                                b:          &Vect,
+                                seg_radius: &Scalar)
+                                -> Option<Scalar> {
+    let sum_radius = *radius + *seg_radius;
+    let (proj, _)  = utils::closest_point_on_segment(center, a, b);
+
+    if na::sqnorm(&(proj - *center)) <= sum_radius * sum_radius {
+        return Some(na::zero())
+    }
+
+    // The center hits the cylindrical part of the rounded segment...
+    let ab      = *b - *a;
+    let ac      = *center - *a;
+    let sqlen   = na::sqnorm(&ab);
+    let mut res = None;
+
+    if sqlen > Float::epsilon() {
+        let perp_ac  = ac - ab * (na::dot(&ac, &ab) / sqlen);
+        let perp_dir = *dir - ab * (na::dot(dir, &ab) / sqlen);
+
+        match approach_time(&perp_ac, &perp_dir, &sum_radius) {
+            Some(t) => {
+                let s = na::dot(&(ac + *dir * t), &ab) / sqlen;
+
+                if s >= na::zero() && s <= na::one() {
+                    res = Some(t)
+                }
+            },
+            None => { }
+        }
+    }
+
+    // ... or one of its spherical ends.
+    res = min_toi(res, approach_time(&ac, dir, &sum_radius));
+    min_toi(res, approach_time(&(*center - *b), dir, &sum_radius))
+}
+
+/// Computes the smallest `t >= 0` such that the norm of `v0 + v1 * t` is `dist`.
+///
+/// Returns `None` if the norm of `v0` is already smaller than `dist`, or if it never decreases
+/// down to `dist`.
+pub fn approach_time(v0: &Vect, v1: &Vect, dist: &Scalar) -> Option<Scalar> {
+    let a = na::sqnorm(v1);
+    let b = na::dot(v0, v1);
+    let c = na::sqnorm(v0) - *dist * *dist;
+
+    if c <= na::zero() || b >= na::zero() {
+        return None
+    }
+
+    let delta = b * b - a * c;
+
+    if delta < na::zero() {
+        None
+    }
+    else {
+        Some((-b - delta.sqrt()) / a)
+    }
+}
+
+/// The smallest of two optional times of impact.
+#[inline]
+pub fn min_toi(toi1: Option<Scalar>, toi2: Option<Scalar>) -> Option<Scalar> {
+    match (toi1, toi2) {
+        (Some(t1), Some(t2)) => Some(t1.min(t2)),
+        (Some(t1), None)     => Some(t1),
+        (None, t2)           => t2
+    }
+}
+
+/// The feature of the segment `[a, b]` at the point `a + (b - a) * t`.
+///
+/// This is the vertex `a` (resp. `b`) if `t` is `0` (resp. `1`), and the segment edge otherwise.
+pub fn segment_feature(t: &Scalar) -> FeatureId {
+    let _eps: Scalar = Float::epsilon();
+    let _eps_rel     = _eps.sqrt();
+
+    if *t <= _eps_rel {
+        FeatureId::new(Vertex(0))
+    }
+    else if *t >= na::one::<Scalar>() - _eps_rel {
+        FeatureId::new(Vertex(1))
+    }
+    else {
+        FeatureId::new(Edge(0))
+    }
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use super::toi;
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::{Ball, Segment};
+    use narrow::implicit_implicit;
+
+    #[test]
+    fn test_ball_segment_toi() {
+        let ball = Ball::new(0.5f64);
+        let seg  = Segment::new_with_margin(Vec3::new(-1.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.1);
+        let mb   = Iso3::new(Vec3::new(0.0f64, 3.0, 0.0), na::zero());
+        let ms   = Iso3::new(na::zero(), Vec3::new(0.0f64, 0.0, 0.3));
+
+        // Hits the edge, one of the ends, and misses the segment.
+        let dirs = [
+            Vec3::new(0.0f64, -1.0, 0.0),
+            Vec3::new(0.45f64, -1.0, 0.0),
+            Vec3::new(1.0f64, -0.2, 0.0)
+        ];
+
+        for dir in dirs.iter() {
+            let exact = toi(&mb, dir, &ball, &ms, &seg);
+            let gjk   = implicit_implicit::toi(&mb, dir, &ball, &ms, &seg);
+
+            match (exact, gjk) {
+                (Some(t1), Some(t2)) => assert!(na::approx_eq_eps(&t1, &t2, &1.0e-3)),
+                (None, None)         => { },
+                _                    => fail!("Inconsistent ball-segment time of impact.")
+            }
+        }
+
+        let edge = toi(&mb, &Vec3::new(0.0f64, -1.0, 0.0), &ball, &Iso3::new(na::zero(), na::zero()), &seg);
+        assert!(na::approx_eq(&edge.unwrap(), &2.4));
+    }
+}
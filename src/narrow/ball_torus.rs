@@ -0,0 +1,215 @@
+use std::num::Zero;
+use nalgebra::na::{Vec3, Translation, Transform};
+use nalgebra::na;
+use geom::{Ball, Torus};
+use narrow::{CollisionDetector, Contact, ClosestPoints};
+use narrow::ball_ball;
+use ray::{Ray, RayCast};
+use math::{Scalar, Vect, Matrix};
+
+/// Collision detector between a ball and a torus.
+#[deriving(Encodable, Decodable)]
+pub struct BallTorus {
+    prediction: Scalar,
+    contact:    Option<Contact>
+}
+
+impl Clone for BallTorus {
+    fn clone(&self) -> BallTorus {
+        BallTorus {
+            prediction: self.prediction.clone(),
+            contact:    self.contact.clone()
+        }
+    }
+}
+
+impl BallTorus {
+    /// Creates a new persistent collision detector between a ball and a torus.
+    #[inline]
+    pub fn new(prediction: Scalar) -> BallTorus {
+        BallTorus {
+            prediction: prediction,
+            contact:    None
+        }
+    }
+}
+
+impl CollisionDetector<Ball, Torus> for BallTorus {
+    fn update(&mut self, ma: &Matrix, a: &Ball, mb: &Matrix, b: &Torus) {
+        self.contact = collide(&ma.translation(), a, mb, b, &self.prediction);
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        match self.contact {
+            None    => 0,
+            Some(_) => 1
+        }
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        match self.contact {
+            Some(ref c) => out_colls.push(c.clone()),
+            None        => ()
+        }
+    }
+
+    #[inline]
+    fn toi(_: Option<BallTorus>, ma: &Matrix, dir: &Vect, _: &Scalar, a: &Ball, mb: &Matrix, b: &Torus) -> Option<Scalar> {
+        toi(ma, dir, a, mb, b)
+    }
+}
+
+/// Collision detector between a torus and a ball.
+#[deriving(Encodable, Decodable, Clone)]
+pub struct TorusBall {
+    sub_detector: BallTorus
+}
+
+impl TorusBall {
+    /// Creates a new persistent collision detector between a torus and a ball.
+    #[inline]
+    pub fn new(prediction: Scalar) -> TorusBall {
+        TorusBall {
+            sub_detector: BallTorus::new(prediction)
+        }
+    }
+}
+
+impl CollisionDetector<Torus, Ball> for TorusBall {
+    fn update(&mut self, ma: &Matrix, a: &Torus, mb: &Matrix, b: &Ball) {
+        self.sub_detector.update(mb, b, ma, a);
+
+        match self.sub_detector.contact {
+            Some(ref mut c) => c.flip(),
+            None            => { }
+        }
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.sub_detector.num_colls()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        self.sub_detector.colls(out_colls)
+    }
+
+    #[inline]
+    fn toi(_: Option<TorusBall>, ma: &Matrix, dir: &Vect, _: &Scalar, a: &Torus, mb: &Matrix, b: &Ball) -> Option<Scalar> {
+        toi(mb, &-*dir, b, ma, a)
+    }
+}
+
+/// Computes the contact point between a ball and a torus.
+///
+/// The torus is the set of points at a distance smaller than its minor radius from its core
+/// circle. Thus, this is a contact between the ball and a ball centered at the point of the core
+/// circle closest to the ball center.
+pub fn collide(center: &Vect, ball: &Ball, mtorus: &Matrix, torus: &Torus, prediction: &Scalar) -> Option<Contact> {
+    let core = closest_core_point(center, mtorus, torus);
+
+    ball_ball::collide(center, ball, &core, &Ball::new(torus.minor_radius()), prediction)
+}
+
+/// Computes the closest points between a ball and a torus.
+///
+/// If they are intersecting, the points corresponding to the penetration depth are returned.
+pub fn closest_points(center: &Vect, ball: &Ball, mtorus: &Matrix, torus: &Torus) -> ClosestPoints {
+    let core     = closest_core_point(center, mtorus, torus);
+    let (p1, p2) = ball_ball::closest_points(center, ball, &core, &Ball::new(torus.minor_radius()));
+    let dist     = na::norm(&(core - *center)) - ball.radius() - torus.minor_radius();
+
+    ClosestPoints::new(p1, p2, dist)
+}
+
+/// The point of the core circle of a transformed torus closest to a given point.
+fn closest_core_point(pt: &Vect, mtorus: &Matrix, torus: &Torus) -> Vect {
+    let local  = mtorus.inv_transform(pt);
+    let sqlen  = local.x * local.x + local.z * local.z;
+    let radial =
+        if sqlen.is_zero() {
+            // Every point of the core circle is at the same distance from the point.
+            Vec3::new(na::one(), na::zero(), na::zero())
+        }
+        else {
+            Vec3::new(local.x, na::zero(), local.z) / sqlen.sqrt()
+        };
+
+    mtorus.transform(&(radial * torus.major_radius()))
+}
+
+/// Computes the Time Of Impact of a ball and a torus.
+///
+/// This casts a ray from the ball center on the torus inflated by the ball radius.
+///
+/// Arguments:
+/// * `mball`  - the ball transform.
+/// * `dir`    - the direction of the ball movement.
+/// * `ball`   - the ball.
+/// * `mtorus` - the torus transform.
+/// * `torus`  - the torus.
+#[inline]
+pub fn toi(mball: &Matrix, dir: &Vect, ball: &Ball, mtorus: &Matrix, torus: &Torus) -> Option<Scalar> {
+    let inflated = Torus::new(torus.major_radius(), torus.minor_radius() + ball.radius());
+
+    inflated.toi_with_transform_and_ray(mtorus, &Ray::new(mball.translation(), dir.clone()), true)
+}
+
+#[cfg(test, f64)]
+mod test {
+    use std::num::Float;
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::{Ball, Torus};
+    use narrow::CollisionDetector;
+    use super::BallTorus;
+
+    #[test]
+    fn test_ball_torus() {
+        let torus  = Torus::new(2.0f64, 0.5);
+        let mtorus = Iso3::new(na::zero(), na::zero());
+
+        let mut detector = BallTorus::new(0.0);
+        let mut colls    = Vec::new();
+
+        // A ball on the top of the tube.
+        detector.update(&Iso3::new(Vec3::new(2.0f64, 1.0, 0.0), na::zero()), &Ball::new(0.6), &mtorus, &torus);
+        detector.colls(&mut colls);
+
+        assert!(colls.len() == 1);
+        assert!(na::approx_eq(&colls.get(0).depth, &0.1));
+        assert!(na::approx_eq(&colls.get(0).normal, &Vec3::new(0.0f64, -1.0, 0.0)));
+        assert!(na::approx_eq(&colls.get(0).world1, &Vec3::new(2.0f64, 0.4, 0.0)));
+        assert!(na::approx_eq(&colls.get(0).world2, &Vec3::new(2.0f64, 0.5, 0.0)));
+
+        // A ball stuck in the hole: it touches the torus everywhere.
+        colls.clear();
+        detector.update(&mtorus, &Ball::new(1.6), &mtorus, &torus);
+        detector.colls(&mut colls);
+
+        assert!(colls.len() == 1);
+        assert!(na::approx_eq(&colls.get(0).depth, &0.1));
+
+        // A ball next to a standing torus.
+        let _pi: f64 = Float::pi();
+        let standing = Iso3::new(na::zero(), Vec3::new(0.0f64, 0.0, _pi / 2.0));
+
+        colls.clear();
+        detector.update(&Iso3::new(Vec3::new(1.0f64, 2.0, 0.0), na::zero()), &Ball::new(0.6), &standing, &torus);
+        detector.colls(&mut colls);
+
+        assert!(colls.len() == 1);
+        assert!(na::approx_eq(&colls.get(0).depth, &0.1));
+        assert!(na::approx_eq(&colls.get(0).normal, &Vec3::new(-1.0f64, 0.0, 0.0)));
+
+        // A ball above the tube.
+        colls.clear();
+        detector.update(&Iso3::new(Vec3::new(2.0f64, 1.0, 0.0), na::zero()), &Ball::new(0.4), &mtorus, &torus);
+        detector.colls(&mut colls);
+
+        assert!(colls.is_empty());
+    }
+}
@@ -1,3 +1,5 @@
+//! Collision detection between a ball and a bézier surface.
+
 use std::rand;
 use sync::{Arc, RWLock};
 use nalgebra::na;
@@ -0,0 +1,250 @@
+use std::num::Signed;
+use nalgebra::na::Norm;
+use nalgebra::na;
+use geom::Capsule;
+use narrow::{CollisionDetector, Contact};
+use narrow::{ball_capsule, ball_segment};
+use utils;
+use math::{Scalar, Vect, Matrix};
+
+/// Collision detector between two capsules.
+///
+/// Two contacts are generated if the capsules are parallel. Otherwise, only one contact is
+/// generated.
+#[deriving(Encodable, Decodable)]
+pub struct CapsuleCapsule {
+    prediction: Scalar,
+    contacts:   Vec<Contact>
+}
+
+impl Clone for CapsuleCapsule {
+    fn clone(&self) -> CapsuleCapsule {
+        CapsuleCapsule {
+            prediction: self.prediction.clone(),
+            contacts:   self.contacts.clone()
+        }
+    }
+}
+
+impl CapsuleCapsule {
+    /// Creates a new persistent collision detector between two capsules.
+    #[inline]
+    pub fn new(prediction: Scalar) -> CapsuleCapsule {
+        CapsuleCapsule {
+            prediction: prediction,
+            contacts:   Vec::with_capacity(2)
+        }
+    }
+}
+
+impl CollisionDetector<Capsule, Capsule> for CapsuleCapsule {
+    fn update(&mut self, ma: &Matrix, a: &Capsule, mb: &Matrix, b: &Capsule) {
+        self.contacts.clear();
+
+        collide(ma, a, mb, b, &self.prediction, &mut self.contacts)
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.contacts.len()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        out_colls.push_all(self.contacts.as_slice())
+    }
+
+    #[inline]
+    fn toi(_: Option<CapsuleCapsule>, ma: &Matrix, dir: &Vect, _: &Scalar, a: &Capsule, mb: &Matrix, b: &Capsule) -> Option<Scalar> {
+        toi(ma, dir, a, mb, b)
+    }
+}
+
+/// Computes the contact points between two capsules.
+///
+/// If the capsules are parallel, up to two contacts are pushed on `out`, at both ends of the
+/// overlap of their segments. Otherwise, at most one contact is pushed.
+pub fn collide(m1:         &Matrix,
+               c1:         &Capsule,
+               m2:         &Matrix,
+               c2:         &Capsule,
+               prediction: &Scalar,
+               out:        &mut Vec<Contact>) {
+    let _parallel_tol: Scalar = na::cast(1.0e-3f64);
+
+    let (a1, b1) = ball_capsule::capsule_segment(m1, c1);
+    let (a2, b2) = ball_capsule::capsule_segment(m2, c2);
+    let r1       = c1.radius();
+    let r2       = c2.radius();
+
+    let mut dir1 = b1 - a1;
+    let mut dir2 = b2 - a2;
+    let len1     = dir1.normalize();
+    let _        = dir2.normalize();
+
+    if na::dot(&dir1, &dir2).abs() >= na::one::<Scalar>() - _parallel_tol {
+        // Project the second segment on the first one to find their overlap.
+        let ta2 = na::dot(&(a2 - a1), &dir1);
+        let tb2 = na::dot(&(b2 - a1), &dir1);
+        let lo  = ta2.min(tb2).max(na::zero());
+        let hi  = ta2.max(tb2).min(len1);
+
+        if hi > lo {
+            for t in [ lo, hi ].iter() {
+                let p1 = a1 + dir1 * *t;
+
+                match ball_segment::collide_with_rounded_segment(&p1, &r1, &a2, &b2, &r2, prediction) {
+                    Some(mut c) => {
+                        c.feature1 = ball_segment::segment_feature(&(*t / len1));
+                        out.push(c)
+                    },
+                    None => { }
+                }
+            }
+
+            return
+        }
+    }
+
+    let (p1, _) = utils::segment_segment_closest_points(&a1, &b1, &a2, &b2);
+
+    match ball_segment::collide_with_rounded_segment(&p1, &r1, &a2, &b2, &r2, prediction) {
+        Some(mut c) => {
+            let (_, t1) = utils::closest_point_on_segment(&p1, &a1, &b1);
+
+            c.feature1 = ball_segment::segment_feature(&t1);
+            out.push(c)
+        },
+        None => { }
+    }
+}
+
+/// Computes the Time Of Impact of two capsules.
+///
+/// Arguments:
+/// * `m1`  - the first capsule transform.
+/// * `dir` - the direction of the first capsule movement.
+/// * `c1`  - the first capsule.
+/// * `m2`  - the second capsule transform.
+/// * `c2`  - the second capsule.
+pub fn toi(m1: &Matrix, dir: &Vect, c1: &Capsule, m2: &Matrix, c2: &Capsule) -> Option<Scalar> {
+    let (a1, b1) = ball_capsule::capsule_segment(m1, c1);
+    let (a2, b2) = ball_capsule::capsule_segment(m2, c2);
+    let radius   = c1.radius() + c2.radius();
+    let zero     = na::zero();
+
+    let (p1, p2) = utils::segment_segment_closest_points(&a1, &b1, &a2, &b2);
+
+    if na::sqnorm(&(p2 - p1)) <= radius * radius {
+        return Some(na::zero())
+    }
+
+    // One extremity of a segment hits the other segment...
+    let mut res = ball_segment::toi_with_rounded_segment(&a1, &zero, dir, &a2, &b2, &radius);
+    res = ball_segment::min_toi(res, ball_segment::toi_with_rounded_segment(&b1, &zero, dir, &a2, &b2, &radius));
+    res = ball_segment::min_toi(res, ball_segment::toi_with_rounded_segment(&a2, &zero, &-*dir, &a1, &b1, &radius));
+    res = ball_segment::min_toi(res, ball_segment::toi_with_rounded_segment(&b2, &zero, &-*dir, &a1, &b1, &radius));
+
+    // ... or their closest points are inside of both segments.
+    let d1    = b1 - a1;
+    let d2    = b2 - a2;
+    let sq1   = na::sqnorm(&d1);
+    let sq2   = na::sqnorm(&d2);
+    let d12   = na::dot(&d1, &d2);
+    let denom = sq1 * sq2 - d12 * d12;
+    let _eps: Scalar = Float::epsilon();
+
+    if denom > _eps * sq1 * sq2 {
+        // The vector joining the closest points of the lines supporting the segments, and their
+        // parameters on each line. This is linear wrt. `w`, the vector joining their origins.
+        let lines_offset = |w: &Vect| -> (Vect, Scalar, Scalar) {
+            let w1 = na::dot(&d1, w);
+            let w2 = na::dot(&d2, w);
+            let s1 = (d12 * w2 - sq2 * w1) / denom;
+            let s2 = (sq1 * w2 - d12 * w1) / denom;
+
+            (*w + d1 * s1 - d2 * s2, s1, s2)
+        };
+
+        let (v0, s10, s20) = lines_offset(&(a1 - a2));
+        let (v1, s11, s21) = lines_offset(dir);
+
+        match ball_segment::approach_time(&v0, &v1, &radius) {
+            Some(t) => {
+                let s1 = s10 + s11 * t;
+                let s2 = s20 + s21 * t;
+
+                if s1 >= na::zero() && s1 <= na::one() && s2 >= na::zero() && s2 <= na::one() {
+                    res = ball_segment::min_toi(res, Some(t))
+                }
+            },
+            None => { }
+        }
+    }
+
+    res
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use super::{collide, toi};
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::Capsule;
+    use narrow::implicit_implicit;
+
+    #[test]
+    fn test_parallel_capsules_manifold() {
+        let a  = Capsule::new(1.0f64, 0.5);
+        let b  = Capsule::new(1.0f64, 0.5);
+        let ta = Iso3::new(Vec3::new(0.0f64, 0.0, 0.0), na::zero());
+        let tb = Iso3::new(Vec3::new(0.9f64, 0.5, 0.0), na::zero());
+        let mut contacts = Vec::new();
+
+        collide(&ta, &a, &tb, &b, &0.0, &mut contacts);
+
+        assert!(contacts.len() == 2);
+
+        for c in contacts.iter() {
+            assert!(na::approx_eq(&c.depth, &0.1));
+            assert!(na::approx_eq(&c.normal, &Vec3::new(1.0f64, 0.0, 0.0)));
+        }
+    }
+
+    #[test]
+    fn test_capsule_capsule_toi() {
+        let a  = Capsule::new(1.0f64, 0.5);
+        let b  = Capsule::new(1.0f64, 0.5);
+        let ta = Iso3::new(Vec3::new(0.0f64, 0.0, 0.0), na::zero());
+
+        // Crossed capsules: the contact is between the interiors of both segments.
+        let crossed = Iso3::new(Vec3::new(0.0f64, 0.0, 5.0), Vec3::new(0.0f64, 0.0, 1.57079632679));
+        let t = toi(&ta, &Vec3::new(0.0f64, 0.0, 1.0), &a, &crossed, &b);
+        assert!(na::approx_eq(&t.unwrap(), &4.0));
+
+        // Aligned capsules: the contact is between two extremities.
+        let aligned = Iso3::new(Vec3::new(0.0f64, 4.0, 0.0), na::zero());
+        let t = toi(&ta, &Vec3::new(0.0f64, 1.0, 0.0), &a, &aligned, &b);
+        assert!(na::approx_eq(&t.unwrap(), &1.0));
+
+        // Any configuration must match the general algorithm.
+        let tb   = Iso3::new(Vec3::new(3.0f64, 1.0, -2.0), Vec3::new(0.3f64, -0.8, 1.1));
+        let dirs = [
+            Vec3::new(-1.0f64, 0.0, 0.0),
+            Vec3::new(-1.0f64, -0.3, 0.7),
+            Vec3::new(0.0f64, 1.0, -0.5),
+            Vec3::new(1.0f64, 0.0, 0.0)
+        ];
+
+        for dir in dirs.iter() {
+            let exact = toi(&ta, dir, &a, &tb, &b);
+            let gjk   = implicit_implicit::toi(&ta, dir, &a, &tb, &b);
+
+            match (exact, gjk) {
+                (Some(t1), Some(t2)) => assert!(na::approx_eq_eps(&t1, &t2, &1.0e-3)),
+                (None, None)         => { },
+                _                    => fail!("Inconsistent capsule-capsule time of impact.")
+            }
+        }
+    }
+}
@@ -1,44 +1,170 @@
 use std::mem;
+use data::hash;
 use math::{Scalar, Vect, Matrix};
 
+/// A geometric feature of a geometry.
+///
+/// The meaning of the index depends on the geometry. It only has to be the same from one update
+/// to the next for contacts to be matched.
+#[deriving(Show, PartialEq, Eq, Clone, Encodable, Decodable)]
+pub enum Feature {
+    /// A vertex.
+    Vertex(uint),
+    /// An edge.
+    Edge(uint),
+    /// A face.
+    Face(uint),
+    /// The feature is not known.
+    UnknownFeature
+}
+
+/// Identifier of the feature of a geometry involved in a contact.
+#[deriving(Show, PartialEq, Eq, Clone, Encodable, Decodable)]
+pub struct FeatureId {
+    /// Index of the part of the geometry containing the feature.
+    ///
+    /// This is the triangle index for a `Mesh` and the part index for a `Compound`. This is
+    /// `None` if the geometry is not composite.
+    pub part:    Option<uint>,
+
+    /// The feature itself.
+    pub feature: Feature
+}
+
+impl FeatureId {
+    /// Creates a new feature identifier on a non-composite geometry.
+    #[inline]
+    pub fn new(feature: Feature) -> FeatureId {
+        FeatureId {
+            part:    None,
+            feature: feature
+        }
+    }
+
+    /// Creates the identifier of an unknown feature.
+    #[inline]
+    pub fn unknown() -> FeatureId {
+        FeatureId::new(UnknownFeature)
+    }
+
+    /// Whether the feature is known.
+    #[inline]
+    pub fn is_known(&self) -> bool {
+        self.feature != UnknownFeature
+    }
+
+    /// Records that this feature belongs to the `i`-th part of a composite geometry.
+    ///
+    /// If the feature already belongs to a part (i.e. composite geometries are nested), both part
+    /// indices are combined.
+    #[inline]
+    pub fn push_part(&mut self, i: uint) {
+        self.part = match self.part {
+            Some(p) => Some(hash::key_from_pair(p, i)),
+            None    => Some(i)
+        }
+    }
+}
+
 /// Geometric description of a contact.
 #[deriving(Show, PartialEq, Clone, Encodable, Decodable)]
 pub struct Contact {
     /// Position of the contact on the first object. The position is expressed in world space.
-    pub world1: Vect,
+    pub world1:   Vect,
 
     /// Position of the contact on the second object. The position is expressed in world space.
-    pub world2: Vect,
+    pub world2:   Vect,
 
     /// Contact normal
-    pub normal: Vect,
+    pub normal:   Vect,
 
     /// Penetration depth
-    pub depth:  Scalar
+    pub depth:    Scalar,
+
+    /// Feature of the first object involved in the contact.
+    pub feature1: FeatureId,
+
+    /// Feature of the second object involved in the contact.
+    pub feature2: FeatureId
 }
 
 impl Contact {
-    /// Creates a new contact.
+    /// Creates a new contact with unknown features.
     #[inline]
     pub fn new(world1: Vect, world2: Vect, normal: Vect, depth: Scalar) -> Contact {
+        Contact::new_with_features(world1, world2, normal, depth, FeatureId::unknown(), FeatureId::unknown())
+    }
+
+    /// Creates a new contact between the features `feature1` and `feature2`.
+    #[inline]
+    pub fn new_with_features(world1:   Vect,
+                             world2:   Vect,
+                             normal:   Vect,
+                             depth:    Scalar,
+                             feature1: FeatureId,
+                             feature2: FeatureId)
+                             -> Contact {
         Contact {
-            world1: world1,
-            world2: world2,
-            normal: normal,
-            depth:  depth
+            world1:   world1,
+            world2:   world2,
+            normal:   normal,
+            depth:    depth,
+            feature1: feature1,
+            feature2: feature2
         }
     }
+
+    /// Whether this contact and `other` are between the same (known) features.
+    #[inline]
+    pub fn has_same_features(&self, other: &Contact) -> bool {
+        self.feature1.is_known() && self.feature2.is_known() &&
+        self.feature1 == other.feature1 && self.feature2 == other.feature2
+    }
 }
 
 impl Contact {
-    /// Reverts the contact normal and swaps `world1` and `world2`.
+    /// Reverts the contact normal and swaps `world1` and `world2`, as well as the features.
     #[inline]
     pub fn flip(&mut self) {
         mem::swap(&mut self.world1, &mut self.world2);
+        mem::swap(&mut self.feature1, &mut self.feature2);
         self.normal = -self.normal;
     }
 }
 
+/// Signed distance and closest points between two objects.
+#[deriving(Show, PartialEq, Clone, Encodable, Decodable)]
+pub struct ClosestPoints {
+    /// Point of the first object closest to the second one. It is expressed in world space.
+    pub world1:   Vect,
+
+    /// Point of the second object closest to the first one. It is expressed in world space.
+    pub world2:   Vect,
+
+    /// Distance between the two objects. It is negative if they are penetrating.
+    pub distance: Scalar
+}
+
+impl ClosestPoints {
+    /// Creates a new `ClosestPoints`.
+    #[inline]
+    pub fn new(world1: Vect, world2: Vect, distance: Scalar) -> ClosestPoints {
+        ClosestPoints {
+            world1:   world1,
+            world2:   world2,
+            distance: distance
+        }
+    }
+}
+
+impl ClosestPoints {
+    /// Swaps `world1` and `world2`.
+    #[inline]
+    pub fn flip(&mut self) {
+        mem::swap(&mut self.world1, &mut self.world2);
+    }
+}
+
 /// Trait of the algorithms executed during the so-called Narrow Phase.
 ///
 /// The goal of the narrow phase is to determine exactly if two objects collide. If there is
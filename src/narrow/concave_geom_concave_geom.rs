@@ -0,0 +1,279 @@
+use std::any::AnyRefExt;
+use nalgebra::na::{Translation, AbsoluteRotate, Transform};
+use nalgebra::na;
+use data::hash_map::HashMap;
+use data::hash::UintPairTWHash;
+use bounding_volume::{BoundingVolume, HasAABB, AABB};
+use narrow::{GeomGeomDispatcher, GeomGeomCollisionDetector, DynamicCollisionDetector,
+             CollisionDetectorFactory, Contact};
+use geom::{Geom, ConcaveGeom};
+use math::{Scalar, Matrix};
+
+/// Collision detector between two concave geometries.
+///
+/// The sub-geometries of the first geometry which might intersect the second one are found first.
+/// Then, the sub-geometries of the second geometry which might intersect each of them are found.
+/// The contacts of every pair are then merged into a single contact manifold.
+pub struct ConcaveGeomConcaveGeom<G1, G2> {
+    sub_detectors:  HashMap<(uint, uint), Box<GeomGeomCollisionDetector + Send>, UintPairTWHash>,
+    to_delete:      Vec<(uint, uint)>,
+    interferences1: Vec<uint>,
+    interferences2: Vec<uint>,
+    contacts:       Vec<Contact>
+}
+
+impl<G1, G2> ConcaveGeomConcaveGeom<G1, G2> {
+    /// Creates a new collision detector between two concave geometries.
+    pub fn new() -> ConcaveGeomConcaveGeom<G1, G2> {
+        ConcaveGeomConcaveGeom {
+            sub_detectors:  HashMap::new_with_capacity(5, UintPairTWHash::new()),
+            to_delete:      Vec::new(),
+            interferences1: Vec::new(),
+            interferences2: Vec::new(),
+            contacts:       Vec::new()
+        }
+    }
+}
+
+impl<G1: ConcaveGeom, G2: ConcaveGeom> ConcaveGeomConcaveGeom<G1, G2> {
+    fn do_update(&mut self,
+                 dispatcher: &GeomGeomDispatcher,
+                 m1:         &Matrix,
+                 g1:         &G1,
+                 m2:         &Matrix,
+                 g2:         &G2) {
+        // Find new collisions
+        let ls_m2 = na::inv(m1).expect("The transformation `m1` must be inversible.") * *m2;
+        let ls_m1 = na::inv(&ls_m2).expect("The transformation `m2` must be inversible.");
+
+        g1.approx_interferences_with_aabb(&g2.aabb(&ls_m2), &mut self.interferences1);
+
+        for &i in self.interferences1.iter() {
+            let aabb1 = g1.aabb_at(i);
+
+            g2.approx_interferences_with_aabb(&transform_aabb(&ls_m1, &aabb1), &mut self.interferences2);
+
+            for &j in self.interferences2.iter() {
+                if !aabb1.intersects(&transform_aabb(&ls_m2, &g2.aabb_at(j))) {
+                    continue
+                }
+
+                let detector = g1.map_part_at(i, |_, g1| {
+                    g2.map_part_at(j, |_, g2| dispatcher.dispatch(g1, g2))
+                });
+
+                match detector {
+                    Some(detector) => {
+                        let _ = self.sub_detectors.insert_or_replace((i, j), detector, false);
+                    },
+                    None => { }
+                }
+            }
+
+            self.interferences2.clear();
+        }
+
+        self.interferences1.clear();
+
+        // Update all collisions
+        for detector in self.sub_detectors.elements_mut().mut_iter() {
+            let (i, j) = detector.key;
+
+            if g1.aabb_at(i).intersects(&transform_aabb(&ls_m2, &g2.aabb_at(j))) {
+                g1.map_transformed_part_at(m1, i, |m1, g1| {
+                    g2.map_transformed_part_at(m2, j, |m2, g2| {
+                        detector.value.update(dispatcher, m1, g1, m2, g2)
+                    })
+                });
+            }
+            else {
+                // FIXME: ask the detector if it wants to be removed or not
+                self.to_delete.push(detector.key);
+            }
+        }
+
+        // Remove outdated sub detectors
+        for key in self.to_delete.iter() {
+            self.sub_detectors.remove(key);
+        }
+
+        self.to_delete.clear();
+
+        // Merge the contacts of every sub detectors
+        self.contacts.clear();
+
+        let mut sub_contacts = Vec::new();
+
+        for detector in self.sub_detectors.elements().iter() {
+            let (i, j) = detector.key;
+            let start  = sub_contacts.len();
+
+            detector.value.colls(&mut sub_contacts);
+
+            for c in sub_contacts.mut_slice_from(start).mut_iter() {
+                c.feature1.push_part(i);
+                c.feature2.push_part(j);
+            }
+        }
+
+        merge_contacts(sub_contacts, &mut self.contacts);
+    }
+}
+
+impl<G1: 'static + ConcaveGeom, G2: 'static + ConcaveGeom>
+GeomGeomCollisionDetector for ConcaveGeomConcaveGeom<G1, G2> {
+    fn update(&mut self,
+              dispatcher: &GeomGeomDispatcher,
+              m1:         &Matrix,
+              g1:         &Geom,
+              m2:         &Matrix,
+              g2:         &Geom) {
+        self.do_update(dispatcher,
+                       m1,
+                       g1.downcast_ref::<G1>().expect("Invalid geometry."),
+                       m2,
+                       g2.downcast_ref::<G2>().expect("Invalid geometry."));
+    }
+
+    fn num_colls(&self) -> uint {
+        self.contacts.len()
+    }
+
+    fn colls(&self, out: &mut Vec<Contact>) {
+        out.push_all(self.contacts.as_slice())
+    }
+}
+
+impl<G1: ConcaveGeom, G2: ConcaveGeom>
+DynamicCollisionDetector<G1, G2> for ConcaveGeomConcaveGeom<G1, G2> { }
+
+/// Merges the contacts generated by pairs of adjacent sub-geometries.
+///
+/// Sub-geometries sharing a vertex or an edge generate the same contact several times. Only the
+/// deepest of those duplicates is kept.
+fn merge_contacts(contacts: Vec<Contact>, out: &mut Vec<Contact>) {
+    let tolerance: Scalar = na::cast(1.0e-3f64);
+    let sqtolerance       = tolerance * tolerance;
+
+    for c in contacts.move_iter() {
+        let mut duplicate = false;
+
+        for other in out.mut_iter() {
+            if na::sqnorm(&(c.world1 - other.world1)) <= sqtolerance &&
+               na::sqnorm(&(c.world2 - other.world2)) <= sqtolerance {
+                duplicate = true;
+
+                if c.depth > other.depth {
+                    *other = c.clone();
+                }
+
+                break;
+            }
+        }
+
+        if !duplicate {
+            out.push(c)
+        }
+    }
+}
+
+/// Computes the AABB of `aabb` transformed by `m`.
+fn transform_aabb(m: &Matrix, aabb: &AABB) -> AABB {
+    let center          = m.transform(&aabb.translation());
+    let half_extents    = (*aabb.maxs() - *aabb.mins()) / na::cast::<f32, Scalar>(2.0);
+    let ws_half_extents = m.absolute_rotate(&half_extents);
+
+    AABB::new(center - ws_half_extents, center + ws_half_extents)
+}
+
+/*
+ *
+ * Custom factories
+ *
+ */
+/// Structure implementing `CollisionDetectorFactory` in order to create a new
+/// `ConcaveGeomConcaveGeom` collision detector.
+pub struct ConcaveGeomConcaveGeomFactory<G1, G2>;
+
+impl<G1: 'static + ConcaveGeom, G2: 'static + ConcaveGeom>
+CollisionDetectorFactory for ConcaveGeomConcaveGeomFactory<G1, G2> {
+    fn build(&self) -> Box<GeomGeomCollisionDetector + Send> {
+        let res: ConcaveGeomConcaveGeom<G1, G2> = ConcaveGeomConcaveGeom::new();
+        box res as Box<GeomGeomCollisionDetector + Send>
+    }
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use std::sync::Arc;
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use narrow::{GeomGeomDispatcher, GeomGeomCollisionDetector};
+    use geom::{Geom, Mesh, Compound, CompoundData, Ball, Cuboid};
+
+    #[test]
+    fn test_mesh_mesh_contacts() {
+        // A flat square.
+        let vertices = vec!(Vec3::new(-1.0f64, 0.0, -1.0), Vec3::new(1.0, 0.0, -1.0),
+                            Vec3::new(1.0, 0.0, 1.0), Vec3::new(-1.0, 0.0, 1.0));
+        let indices  = vec!(0u, 2, 1, 0, 3, 2);
+        let square   = Mesh::new(Arc::new(vertices), Arc::new(indices), None, None);
+
+        // An upside-down pyramid with its apex slightly above the first triangle of the square.
+        let vertices = vec!(Vec3::new(0.0f64, 0.0, 0.0),
+                            Vec3::new(-0.5, 1.0, -0.5), Vec3::new(0.5, 1.0, -0.5),
+                            Vec3::new(0.5, 1.0, 0.5), Vec3::new(-0.5, 1.0, 0.5));
+        let indices  = vec!(0u, 1, 2, 0, 2, 3, 0, 3, 4, 0, 4, 1);
+        let pyramid  = Mesh::new(Arc::new(vertices), Arc::new(indices), None, None);
+
+        let dispatcher = GeomGeomDispatcher::new();
+        let m1 = Iso3::new(na::zero(), na::zero());
+        let m2 = Iso3::new(Vec3::new(0.3f64, 0.05, -0.4), na::zero());
+        let g1           = &square as &Geom;
+        let g2           = &pyramid as &Geom;
+        let mut detector = dispatcher.dispatch(g1, g2).expect("Mesh vs. Mesh is not supported.");
+        let mut contacts = Vec::new();
+
+        detector.update(&dispatcher, &m1, g1, &m2, g2);
+        detector.colls(&mut contacts);
+
+        // Every face of the pyramid touches the square at its apex: the duplicate contacts are
+        // merged. The margins of both meshes (0.04 each) overlap by 0.03.
+        assert!(contacts.len() == 1);
+
+        let c = &contacts[0];
+        assert!(na::approx_eq_eps(&c.depth, &0.03, &1.0e-4));
+        assert!(na::approx_eq_eps(&c.normal, &Vec3::new(0.0f64, 1.0, 0.0), &1.0e-4));
+        assert!(na::approx_eq_eps(&c.world2, &Vec3::new(0.3f64, 0.01, -0.4), &1.0e-4));
+    }
+
+    #[test]
+    fn test_compound_compound_toi() {
+        let mut balls = CompoundData::new();
+        balls.push_geom(Iso3::new(Vec3::new(-1.0f64, 0.0, 0.0), na::zero()), Ball::new(0.5), 1.0);
+        balls.push_geom(Iso3::new(Vec3::new(1.0f64, 0.0, 0.0), na::zero()), Ball::new(0.5), 1.0);
+
+        let mut cuboids = CompoundData::new();
+        cuboids.push_geom(Iso3::new(Vec3::new(0.0f64, 0.0, 0.0), na::zero()), Cuboid::new(Vec3::new(0.5, 0.5, 0.5)), 1.0);
+        cuboids.push_geom(Iso3::new(Vec3::new(0.0f64, 5.0, 0.0), na::zero()), Cuboid::new(Vec3::new(0.5, 0.5, 0.5)), 1.0);
+
+        let balls   = Compound::new(balls);
+        let cuboids = Compound::new(cuboids);
+
+        let dispatcher = GeomGeomDispatcher::new();
+        let m1 = Iso3::new(na::zero(), na::zero());
+        let m2 = Iso3::new(Vec3::new(10.0f64, 0.0, 0.0), na::zero());
+        let g1 = &balls as &Geom;
+        let g2 = &cuboids as &Geom;
+
+        // The right ball hits the first cuboid after traveling about 10 - 1 - 0.5 - 0.5 = 8.
+        let toi = dispatcher.toi(&m1, &Vec3::x(), &20.0, g1, &m2, g2).expect("Compound vs. Compound TOI not found.");
+        assert!(toi > 7.9 && toi <= 8.0);
+
+        // Same thing when the moving compound is the second one.
+        let toi = dispatcher.toi(&m2, &-Vec3::x(), &20.0, g2, &m1, g1).expect("Compound vs. Compound TOI not found.");
+        assert!(toi > 7.9 && toi <= 8.0);
+
+        assert!(dispatcher.toi(&m1, &Vec3::y(), &20.0, g1, &m2, g2).is_none());
+    }
+}
@@ -1,13 +1,14 @@
 use std::any::AnyRefExt;
+use nalgebra::na::Rotate;
 use nalgebra::na;
 use data::hash_map::HashMap;
 use data::hash::UintTWHash;
-use bounding_volume::{BoundingVolume, HasAABB};
+use bounding_volume::{BoundingVolume, LooseBoundingVolume, HasAABB};
 use broad::Dispatcher;
 use narrow::{CollisionDetector, GeomGeomDispatcher, GeomGeomCollisionDetector,
-             DynamicCollisionDetector, CollisionDetectorFactory, Contact};
+             DynamicCollisionDetector, CollisionDetectorFactory, Contact, ClosestPoints};
 use geom::{Geom, ConcaveGeom};
-use math::Matrix;
+use math::{Scalar, Vect, Matrix};
 
 /// Collision detector between a concave geometry and another geometry.
 pub struct ConcaveGeomGeom<G1, G2> {
@@ -65,7 +66,7 @@ impl<G1: ConcaveGeom, G2: Geom> ConcaveGeomGeom<G1, G2> {
         // Update all collisions
         for detector in self.sub_detectors.elements_mut().mut_iter() {
             let key = detector.key;
-            if ls_aabb2.intersects(g1.aabb_at(key)) {
+            if ls_aabb2.intersects(&g1.aabb_at(key)) {
                 g1.map_transformed_part_at(m1, key, |m1, g1| {
                     if swap {
                         detector.value.update(dispatcher, m2, g2, m1, g1);
@@ -88,6 +89,24 @@ impl<G1: ConcaveGeom, G2: Geom> ConcaveGeomGeom<G1, G2> {
 
         self.to_delete.clear();
     }
+
+    fn do_colls(&self, out: &mut Vec<Contact>, swap: bool) {
+        for detector in self.sub_detectors.elements().iter() {
+            let start = out.len();
+
+            detector.value.colls(out);
+
+            // Record the part of the concave geometry each contact lies on.
+            for c in out.mut_slice_from(start).mut_iter() {
+                if swap {
+                    c.feature2.push_part(detector.key)
+                }
+                else {
+                    c.feature1.push_part(detector.key)
+                }
+            }
+        }
+    }
 }
 
 impl<G1: 'static + ConcaveGeom, G2: 'static + Geom>
@@ -117,9 +136,7 @@ GeomGeomCollisionDetector for ConcaveGeomGeom<G1, G2> {
     }
 
     fn colls(&self, out: &mut Vec<Contact>) {
-        for detector in self.sub_detectors.elements().iter() {
-            detector.value.colls(out);
-        }
+        self.do_colls(out, false)
     }
 }
 
@@ -161,13 +178,137 @@ GeomGeomCollisionDetector for GeomConcaveGeom<G1, G2> {
     }
 
     fn colls(&self, out: &mut Vec<Contact>) {
-        self.sub_detector.colls(out)
+        self.sub_detector.do_colls(out, true)
     }
 }
 
 impl<G1: Geom, G2: ConcaveGeom>
 DynamicCollisionDetector<G1, G2> for GeomConcaveGeom<G1, G2> { }
 
+/// Computes the signed distance and the closest points between a concave geometry and another
+/// geometry.
+///
+/// The closest points between each relevant sub-geometry of `g1` and `g2` are computed by the
+/// dispatcher. Returns `None` if the dispatcher does not support any of those pairs.
+pub fn closest_points<G1: ConcaveGeom>(dispatcher: &GeomGeomDispatcher,
+                                       m1:         &Matrix,
+                                       g1:         &G1,
+                                       m2:         &Matrix,
+                                       g2:         &Geom)
+                                       -> Option<ClosestPoints> {
+    let ls_m2    = na::inv(m1).expect("The transformation `m1` must be inversible.") * *m2;
+    let ls_aabb2 = g2.aabb(&ls_m2);
+    let aabb1    = g1.aabb(&na::one());
+    let all      = aabb1.merged(&ls_aabb2);
+
+    // The distance between the two AABBs is a lower bound of the distance between the geometries.
+    let gap = na::sup(&(*aabb1.mins() - *ls_aabb2.maxs()), &(*ls_aabb2.mins() - *aabb1.maxs()));
+    let max_loosening: Scalar = na::norm(&(*all.maxs() - *all.mins()));
+    let mut loosening         = na::norm(&na::sup(&gap, &na::zero()));
+    let mut interferences     = Vec::new();
+
+    // Enlarge the AABB of `g2` until it hits some sub-geometries.
+    loop {
+        g1.approx_interferences_with_aabb(&ls_aabb2.loosened(loosening.clone()), &mut interferences);
+
+        if !interferences.is_empty() || loosening >= max_loosening {
+            break
+        }
+
+        loosening = loosening * na::cast(2.0f64) + max_loosening * na::cast(0.01f64);
+    }
+
+    let mut res = None;
+
+    update_closest_points(dispatcher, m1, g1, m2, g2, interferences.as_slice(), &mut res);
+
+    // Any sub-geometry closer than the current best has an AABB closer than the best distance.
+    let bound = match res {
+        Some(ref cp) if cp.distance > loosening => cp.distance.clone(),
+        _                                       => return res
+    };
+
+    let mut others = Vec::new();
+
+    g1.approx_interferences_with_aabb(&ls_aabb2.loosened(bound), &mut others);
+    others.retain(|i| !interferences.contains(i));
+
+    update_closest_points(dispatcher, m1, g1, m2, g2, others.as_slice(), &mut res);
+
+    res
+}
+
+/// Computes the time of impact of a concave geometry and another geometry.
+///
+/// The time of impact of each sub-geometry of `g1` which might be hit during the motion is computed
+/// by the dispatcher.
+///
+/// # Arguments
+/// * `m1`   - the concave geometry transform.
+/// * `dir`  - the concave geometry displacement direction.
+/// * `dist` - the concave geometry displacement distance.
+/// * `g1`   - the concave geometry.
+/// * `m2`   - the other geometry transform.
+/// * `g2`   - the other geometry.
+pub fn toi<G1: ConcaveGeom>(dispatcher: &GeomGeomDispatcher,
+                            m1:         &Matrix,
+                            dir:        &Vect,
+                            dist:       &Scalar,
+                            g1:         &G1,
+                            m2:         &Matrix,
+                            g2:         &Geom)
+                            -> Option<Scalar> {
+    let ls_m2    = na::inv(m1).expect("The transformation `m1` must be inversible.") * *m2;
+    let ls_aabb2 = g2.aabb(&ls_m2);
+
+    // Relative to `g1`, `g2` moves along `-dir`.
+    let ls_motion         = m1.inv_rotate(&(-*dir * *dist));
+    let swept_aabb2       = ls_aabb2.merged(&na::append_translation(&ls_aabb2, &ls_motion));
+    let mut interferences = Vec::new();
+
+    g1.approx_interferences_with_aabb(&swept_aabb2, &mut interferences);
+
+    let mut res = None;
+
+    for i in interferences.iter() {
+        let toi = g1.map_transformed_part_at(m1, *i, |m1, g1| dispatcher.toi(m1, dir, dist, g1, m2, g2));
+
+        res = match (res, toi) {
+            (Some(a), Some(b)) => Some(if a < b { a } else { b }),
+            (None, b)          => b,
+            (a, None)          => a
+        }
+    }
+
+    res
+}
+
+fn update_closest_points<G1: ConcaveGeom>(dispatcher: &GeomGeomDispatcher,
+                                          m1:         &Matrix,
+                                          g1:         &G1,
+                                          m2:         &Matrix,
+                                          g2:         &Geom,
+                                          parts:      &[uint],
+                                          res:        &mut Option<ClosestPoints>) {
+    for i in parts.iter() {
+        let cp = g1.map_transformed_part_at(m1, *i, |m1, g1| dispatcher.closest_points(m1, g1, m2, g2));
+
+        match cp {
+            Some(cp) => {
+                let better = match *res {
+                    Some(ref best) => cp.distance < best.distance,
+                    None           => true
+                };
+
+                if better {
+                    *res = Some(cp)
+                }
+            },
+            None => { }
+        }
+    }
+}
+
 /*
  *
  * Custom factories
@@ -0,0 +1,138 @@
+use std::any::AnyRefExt;
+use nalgebra::na;
+use data::hash_map::HashMap;
+use data::hash::UintTWHash;
+use bounding_volume::{BoundingVolume, LooseBoundingVolume, HasAABB};
+use narrow::{GeomGeomProximityDispatcher, GeomGeomProximityDetector, DynamicProximityDetector,
+             ProximityDetectorFactory, Proximity, Intersecting, Disjoint};
+use geom::{Geom, ConcaveGeom};
+use math::{Scalar, Matrix};
+
+/// Proximity detector between a concave geometry and another geometry.
+///
+/// The proximity status is the closest of the proximity status of every sub-geometry of the
+/// concave geometry. If the other geometry is concave too, its own sub-geometries are handled by
+/// the proximity detectors the dispatcher creates for each pair.
+pub struct ConcaveGeomGeomProximity<G1, G2> {
+    margin:        Scalar,
+    sub_detectors: HashMap<uint, Box<GeomGeomProximityDetector + Send>, UintTWHash>,
+    to_delete:     Vec<uint>,
+    interferences: Vec<uint>,
+    proximity:     Proximity
+}
+
+impl<G1, G2> ConcaveGeomGeomProximity<G1, G2> {
+    /// Creates a new proximity detector between a concave geometry and another geometry.
+    pub fn new(margin: Scalar) -> ConcaveGeomGeomProximity<G1, G2> {
+        ConcaveGeomGeomProximity {
+            margin:        margin,
+            sub_detectors: HashMap::new_with_capacity(5, UintTWHash::new()),
+            to_delete:     Vec::new(),
+            interferences: Vec::new(),
+            proximity:     Disjoint
+        }
+    }
+}
+
+impl<G1: ConcaveGeom, G2: Geom> ConcaveGeomGeomProximity<G1, G2> {
+    fn do_update(&mut self,
+                 dispatcher: &GeomGeomProximityDispatcher,
+                 m1:         &Matrix,
+                 g1:         &G1,
+                 m2:         &Matrix,
+                 g2:         &G2) {
+        // Find the sub-geometries closer than the margin.
+        let ls_m2    = na::inv(m1).expect("The transformation `m1` must be inversible.") * *m2;
+        let ls_aabb2 = g2.aabb(&ls_m2).loosened(self.margin.clone());
+        let g2       = g2 as &Geom;
+
+        g1.approx_interferences_with_aabb(&ls_aabb2, &mut self.interferences);
+
+        for i in self.interferences.iter() {
+            let _ = self.sub_detectors.find_or_insert_lazy(
+                        *i,
+                        || g1.map_part_at(*i, |_, g1| dispatcher.dispatch(g1, g2)));
+        }
+
+        self.interferences.clear();
+
+        // Update all the sub-detectors.
+        self.proximity = Disjoint;
+
+        for detector in self.sub_detectors.elements_mut().mut_iter() {
+            let key = detector.key;
+
+            if ls_aabb2.intersects(&g1.aabb_at(key)) {
+                if self.proximity != Intersecting {
+                    g1.map_transformed_part_at(m1, key, |m1, g1| {
+                        detector.value.update(dispatcher, m1, g1, m2, g2)
+                    });
+
+                    self.proximity = self.proximity.closest(detector.value.proximity());
+                }
+            }
+            else {
+                self.to_delete.push(key);
+            }
+        }
+
+        // Remove outdated sub detectors
+        for i in self.to_delete.iter() {
+            self.sub_detectors.remove(i);
+        }
+
+        self.to_delete.clear();
+    }
+}
+
+impl<G1: 'static + ConcaveGeom, G2: 'static + Geom>
+GeomGeomProximityDetector for ConcaveGeomGeomProximity<G1, G2> {
+    fn update(&mut self,
+              dispatcher: &GeomGeomProximityDispatcher,
+              m1:         &Matrix,
+              g1:         &Geom,
+              m2:         &Matrix,
+              g2:         &Geom) {
+        self.do_update(dispatcher,
+                       m1,
+                       g1.downcast_ref::<G1>().expect("Invalid geometry."),
+                       m2,
+                       g2.downcast_ref::<G2>().expect("Invalid geometry."));
+    }
+
+    #[inline]
+    fn proximity(&self) -> Proximity {
+        self.proximity
+    }
+}
+
+impl<G1: ConcaveGeom, G2: Geom>
+DynamicProximityDetector<G1, G2> for ConcaveGeomGeomProximity<G1, G2> { }
+
+/*
+ *
+ * Custom factories
+ *
+ */
+/// Structure implementing `ProximityDetectorFactory` in order to create a new
+/// `ConcaveGeomGeomProximity` proximity detector.
+pub struct ConcaveGeomGeomProximityFactory<G1, G2> {
+    margin: Scalar
+}
+
+impl<G1, G2> ConcaveGeomGeomProximityFactory<G1, G2> {
+    /// Creates a new factory of proximity detectors with the given margin.
+    pub fn new(margin: Scalar) -> ConcaveGeomGeomProximityFactory<G1, G2> {
+        ConcaveGeomGeomProximityFactory {
+            margin: margin
+        }
+    }
+}
+
+impl<G1: 'static + ConcaveGeom, G2: 'static + Geom>
+ProximityDetectorFactory for ConcaveGeomGeomProximityFactory<G1, G2> {
+    fn build(&self) -> Box<GeomGeomProximityDetector + Send> {
+        let res: ConcaveGeomGeomProximity<G1, G2> = ConcaveGeomGeomProximity::new(self.margin.clone());
+        box res as Box<GeomGeomProximityDetector + Send>
+    }
+}
@@ -0,0 +1,563 @@
+use std::num::{Signed, Bounded};
+use nalgebra::na::{Translation, Rotate, Indexable, Norm};
+use nalgebra::na;
+use geom::Cuboid;
+use data::hash;
+use narrow::{CollisionDetector, Contact, FeatureId, Feature, Vertex, Edge, Face};
+use narrow::implicit_implicit;
+use math::{Scalar, Vect, Matrix};
+
+// #[dim4]
+use geom::AnnotatedPoint;
+// #[dim4]
+use narrow::algorithm::johnson_simplex::JohnsonSimplex;
+// #[dim4]
+use narrow::algorithm::gjk::Projection;
+
+/// Collision detector between two boxes.
+///
+/// It is based on the separating axis theorem. When the axis of minimum penetration is a face
+/// normal, the face of the other box most anti-parallel to it is clipped against the side planes
+/// of this reference face. This generates up to 4 contacts in 3D and up to 2 contacts in 2D.
+#[deriving(Encodable, Decodable)]
+pub struct CuboidCuboid {
+    prediction: Scalar,
+    contacts:   Vec<Contact>
+}
+
+impl Clone for CuboidCuboid {
+    fn clone(&self) -> CuboidCuboid {
+        CuboidCuboid {
+            prediction: self.prediction.clone(),
+            contacts:   self.contacts.clone()
+        }
+    }
+}
+
+impl CuboidCuboid {
+    /// Creates a new persistent collision detector between two boxes.
+    #[inline]
+    pub fn new(prediction: Scalar) -> CuboidCuboid {
+        CuboidCuboid {
+            prediction: prediction,
+            contacts:   Vec::new()
+        }
+    }
+}
+
+impl CollisionDetector<Cuboid, Cuboid> for CuboidCuboid {
+    fn update(&mut self, ma: &Matrix, a: &Cuboid, mb: &Matrix, b: &Cuboid) {
+        self.contacts.clear();
+
+        collide(ma, a, mb, b, &self.prediction, &mut self.contacts)
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.contacts.len()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        out_colls.push_all(self.contacts.as_slice())
+    }
+
+    #[inline]
+    fn toi(_: Option<CuboidCuboid>, ma: &Matrix, dir: &Vect, _: &Scalar, a: &Cuboid, mb: &Matrix, b: &Cuboid) -> Option<Scalar> {
+        implicit_implicit::toi(ma, dir, a, mb, b)
+    }
+}
+
+/// The feature of the boxes the separating axis comes from.
+enum SeparatingFeature {
+    FaceOf1(uint),
+    FaceOf2(uint),
+    EdgeEdge(uint, uint)
+}
+
+/// Computes the contact manifold between two boxes.
+///
+/// The contacts are pushed on `out`. Contacts with a separation greater than `prediction` are
+/// ignored.
+#[not_dim4]
+pub fn collide(m1:         &Matrix,
+               c1:         &Cuboid,
+               m2:         &Matrix,
+               c2:         &Cuboid,
+               prediction: &Scalar,
+               out:        &mut Vec<Contact>) {
+    // Edge axes are selected only if they are significantly better than face axes. This avoids
+    // flip-flops of the manifold between frames.
+    let _rel_tol: Scalar = na::cast(0.95f64);
+    let _abs_tol: Scalar = na::cast(0.005f64);
+
+    let he1     = c1.half_extents() + c1.margin();
+    let he2     = c2.half_extents() + c2.margin();
+    let axes1   = box_axes(m1);
+    let axes2   = box_axes(m2);
+    let center1 = m1.translation();
+    let center2 = m2.translation();
+    let delta   = center2 - center1;
+
+    /*
+     * Find the axis of minimum penetration.
+     */
+    let mut best_sep: Scalar = Bounded::min_value();
+    let mut best_normal: Vect = na::zero();
+    let mut best_feature      = FaceOf1(0);
+
+    for i in range(0u, axes1.len()) {
+        let sep = separation(&axes1[i], &delta, axes1.as_slice(), &he1, axes2.as_slice(), &he2);
+
+        if sep > *prediction {
+            return
+        }
+
+        if sep > best_sep {
+            best_sep     = sep;
+            best_normal  = axes1[i] * sign(na::dot(&delta, &axes1[i]));
+            best_feature = FaceOf1(i);
+        }
+    }
+
+    for i in range(0u, axes2.len()) {
+        let sep = separation(&axes2[i], &delta, axes1.as_slice(), &he1, axes2.as_slice(), &he2);
+
+        if sep > *prediction {
+            return
+        }
+
+        if sep > _rel_tol * best_sep + _abs_tol {
+            best_sep     = sep;
+            best_normal  = axes2[i] * sign(na::dot(&delta, &axes2[i]));
+            best_feature = FaceOf2(i);
+        }
+    }
+
+    let mut separated = false;
+
+    edge_axes(axes1.as_slice(), axes2.as_slice(), |i, j, axis| {
+        let sep = separation(&axis, &delta, axes1.as_slice(), &he1, axes2.as_slice(), &he2);
+
+        if sep > *prediction {
+            separated = true;
+        }
+        else if sep > _rel_tol * best_sep + _abs_tol {
+            best_sep     = sep;
+            best_normal  = axis * sign(na::dot(&delta, &axis));
+            best_feature = EdgeEdge(i, j);
+        }
+    });
+
+    if separated {
+        return
+    }
+
+    /*
+     * Generate the contacts.
+     */
+    match best_feature {
+        FaceOf1(i) => {
+            face_contacts(&center1, axes1.as_slice(), &he1, i, &best_normal,
+                          &center2, axes2.as_slice(), &he2,
+                          prediction, false, out)
+        },
+        FaceOf2(i) => {
+            face_contacts(&center2, axes2.as_slice(), &he2, i, &-best_normal,
+                          &center1, axes1.as_slice(), &he1,
+                          prediction, true, out)
+        },
+        EdgeEdge(i, j) => {
+            out.push(edge_edge_contact(&center1, axes1.as_slice(), &he1, i,
+                                       &center2, axes2.as_slice(), &he2, j,
+                                       &best_normal, &best_sep))
+        }
+    }
+}
+
+/// Computes the contact manifold between two boxes.
+///
+/// The face clipping is not implemented in 4d: only the single contact found by the
+/// `ImplicitImplicit` algorithm is pushed on `out`.
+#[dim4]
+pub fn collide(m1:         &Matrix,
+               c1:         &Cuboid,
+               m2:         &Matrix,
+               c2:         &Cuboid,
+               prediction: &Scalar,
+               out:        &mut Vec<Contact>) {
+    let mut simplex: JohnsonSimplex<AnnotatedPoint> = JohnsonSimplex::new_w_tls();
+
+    match implicit_implicit::collide(m1, c1, m2, c2, prediction, &mut simplex, None) {
+        Projection(c) => out.push(c),
+        _             => { }
+    }
+}
+
+/// The world-space axis of a box.
+fn box_axes(m: &Matrix) -> Vec<Vect> {
+    let mut res = Vec::with_capacity(na::dim::<Vect>());
+
+    na::canonical_basis(|b: Vect| {
+        res.push(m.rotate(&b));
+
+        true
+    });
+
+    res
+}
+
+/// The separation of two boxes along an axis. It is negative if they are overlapping.
+fn separation(axis: &Vect, delta: &Vect, axes1: &[Vect], he1: &Vect, axes2: &[Vect], he2: &Vect) -> Scalar {
+    let mut radius: Scalar = na::zero();
+
+    for i in range(0u, axes1.len()) {
+        radius = radius + he1.at(i) * na::dot(&axes1[i], axis).abs() +
+                          he2.at(i) * na::dot(&axes2[i], axis).abs();
+    }
+
+    na::dot(delta, axis).abs() - radius
+}
+
+#[inline]
+fn sign(x: Scalar) -> Scalar {
+    if x < na::zero() { -na::one::<Scalar>() } else { na::one() }
+}
+
+/// Computes the contacts between the face `ref_face` (with normal `normal`) of the reference box
+/// and the incident box by clipping.
+///
+/// If `flip` is `true`, the reference box is the second box of the pair.
+fn face_contacts(ref_center: &Vect,
+                 ref_axes:   &[Vect],
+                 ref_he:     &Vect,
+                 ref_face:   uint,
+                 normal:     &Vect,
+                 inc_center: &Vect,
+                 inc_axes:   &[Vect],
+                 inc_he:     &Vect,
+                 prediction: &Scalar,
+                 flip:       bool,
+                 out:        &mut Vec<Contact>) {
+    // The incident face is the face of the incident box most anti-parallel to the normal.
+    let mut inc_face = 0;
+    let mut best_dot = na::zero();
+
+    for i in range(0u, inc_axes.len()) {
+        let dot = na::dot(&inc_axes[i], normal).abs();
+
+        if dot > best_dot {
+            inc_face = i;
+            best_dot = dot;
+        }
+    }
+
+    let inc_sign        = -sign(na::dot(&inc_axes[inc_face], normal));
+    let inc_face_center = *inc_center + inc_axes[inc_face] * (inc_he.at(inc_face) * inc_sign);
+    let inc_face_id     = face_id(inc_face, inc_sign);
+    let ref_face_id     = face_id(ref_face, na::dot(&ref_axes[ref_face], normal));
+
+    // Each vertex of the clipped polygon remembers the feature of the incident box it lies on.
+    let mut poly: Vec<(Vect, Feature)> =
+        face_vertices(&inc_face_center, inc_axes, inc_he, inc_face).move_iter().enumerate().map(
+            |(k, pt)| (pt, Vertex(inc_face_id * 4 + k))).collect();
+
+    // Clip the incident face against the side planes of the reference face.
+    for i in range(0u, ref_axes.len()) {
+        if i != ref_face {
+            let offset = na::dot(&ref_axes[i], ref_center);
+
+            poly = clip(poly.as_slice(), &ref_axes[i], &(offset + ref_he.at(i)), 2 * i);
+            poly = clip(poly.as_slice(), &-ref_axes[i], &(ref_he.at(i) - offset), 2 * i + 1);
+
+            if poly.is_empty() {
+                return
+            }
+        }
+    }
+
+    let ref_offset     = na::dot(normal, ref_center) + ref_he.at(ref_face);
+    let ref_feature    = FeatureId::new(Face(ref_face_id));
+    let mut candidates = Vec::with_capacity(poly.len());
+
+    for &(ref pt, ref feature) in poly.iter() {
+        let sep = na::dot(normal, pt) - ref_offset;
+
+        if sep <= *prediction {
+            let on_ref      = *pt - *normal * sep;
+            let inc_feature = FeatureId::new(feature.clone());
+
+            if flip {
+                candidates.push(Contact::new_with_features(pt.clone(), on_ref, -*normal, -sep,
+                                                           inc_feature, ref_feature.clone()))
+            }
+            else {
+                candidates.push(Contact::new_with_features(on_ref, pt.clone(), normal.clone(), -sep,
+                                                           ref_feature.clone(), inc_feature))
+            }
+        }
+    }
+
+    reduce(candidates, 1 << (ref_axes.len() - 1), out)
+}
+
+/// Keeps at most `max` contacts: the deepest one, and the ones spreading the most the manifold.
+fn reduce(candidates: Vec<Contact>, max: uint, out: &mut Vec<Contact>) {
+    if candidates.len() <= max {
+        out.push_all_move(candidates);
+
+        return
+    }
+
+    let mut selected = Vec::with_capacity(max);
+    let mut deepest  = 0;
+
+    for i in range(1u, candidates.len()) {
+        if candidates[i].depth > candidates[deepest].depth {
+            deepest = i
+        }
+    }
+
+    selected.push(deepest);
+
+    while selected.len() != max {
+        let mut best      = 0;
+        let mut best_dist = -na::one::<Scalar>();
+
+        for i in range(0u, candidates.len()) {
+            if !selected.contains(&i) {
+                let mut dist: Scalar = Bounded::max_value();
+
+                for j in selected.iter() {
+                    let sqdist = na::sqnorm(&(candidates[i].world1 - candidates[*j].world1));
+
+                    if sqdist < dist {
+                        dist = sqdist
+                    }
+                }
+
+                if dist > best_dist {
+                    best      = i;
+                    best_dist = dist;
+                }
+            }
+        }
+
+        selected.push(best);
+    }
+
+    for i in selected.iter() {
+        out.push(candidates[*i].clone())
+    }
+}
+
+/// The identifier of the face of a box orthogonal to its `axis`-th axis, on the side of `side`.
+#[inline]
+fn face_id(axis: uint, side: Scalar) -> uint {
+    if side < na::zero() { 2 * axis + 1 } else { 2 * axis }
+}
+
+/// The feature of the point where the polygon edge from `a` to `b` crosses the clipping plane
+/// `plane`.
+fn clipped_feature(a: &Feature, b: &Feature, plane: uint) -> Feature {
+    fn key(f: &Feature) -> uint {
+        match *f {
+            Vertex(i) | Edge(i) | Face(i) => i,
+            _                             => 0
+        }
+    }
+
+    Edge(hash::tomas_wang_hash(hash::key_from_pair(hash::key_from_pair(key(a), key(b)), plane)))
+}
+
+/// Clips a polygon (or a segment) against the half-space `dot(normal, p) <= offset`.
+///
+/// The new vertices are identified by the edge they lie on and the index of the clipping plane.
+fn clip(poly: &[(Vect, Feature)], normal: &Vect, offset: &Scalar, plane: uint) -> Vec<(Vect, Feature)> {
+    let mut res = Vec::with_capacity(poly.len() + 1);
+
+    // A segment has only one edge.
+    let nedges = if poly.len() == 2 { 1 } else { poly.len() };
+
+    for i in range(0u, poly.len()) {
+        let (ref a, ref fa) = poly[i];
+        let da = na::dot(normal, a) - *offset;
+
+        if da <= na::zero() {
+            res.push((a.clone(), fa.clone()))
+        }
+
+        if i < nedges {
+            let (ref b, ref fb) = poly[(i + 1) % poly.len()];
+            let db = na::dot(normal, b) - *offset;
+
+            if (da < na::zero() && db > na::zero()) || (da > na::zero() && db < na::zero()) {
+                res.push((*a + (*b - *a) * (da / (da - db)), clipped_feature(fa, fb, plane)))
+            }
+        }
+    }
+
+    res
+}
+
+/// The vertices of the face orthogonal to `axes[face]` centered at `center`.
+#[dim2]
+fn face_vertices(center: &Vect, axes: &[Vect], he: &Vect, face: uint) -> Vec<Vect> {
+    let other = 1 - face;
+    let u     = axes[other] * he.at(other);
+
+    vec!(*center + u, *center - u)
+}
+
+/// The vertices of the face orthogonal to `axes[face]` centered at `center`, in cyclic order.
+#[dim3]
+fn face_vertices(center: &Vect, axes: &[Vect], he: &Vect, face: uint) -> Vec<Vect> {
+    let i = (face + 1) % 3;
+    let j = (face + 2) % 3;
+    let u = axes[i] * he.at(i);
+    let v = axes[j] * he.at(j);
+
+    vec!(*center + u + v, *center - u + v, *center - u - v, *center + u - v)
+}
+
+#[dim4]
+fn face_vertices(_: &Vect, _: &[Vect], _: &Vect, _: uint) -> Vec<Vect> {
+    unreachable!()
+}
+
+/// Calls `f` with every axis orthogonal to one edge of each box.
+#[dim3]
+fn edge_axes(axes1: &[Vect], axes2: &[Vect], f: |uint, uint, Vect| -> ()) {
+    let _eps: Scalar = Float::epsilon();
+    let _eps_tol     = _eps * na::cast(100.0f64);
+
+    for i in range(0u, 3) {
+        for j in range(0u, 3) {
+            let mut axis = na::cross(&axes1[i], &axes2[j]);
+
+            // Parallel edges are already covered by the face axes.
+            if axis.normalize() > _eps_tol {
+                f(i, j, axis)
+            }
+        }
+    }
+}
+
+#[not_dim3]
+fn edge_axes(_: &[Vect], _: &[Vect], _: |uint, uint, Vect| -> ()) {
+}
+
+/// Computes the contact between the edge `axes1[i]` of the first box and the edge `axes2[j]` of
+/// the second one.
+#[dim3]
+fn edge_edge_contact(center1: &Vect, axes1: &[Vect], he1: &Vect, i: uint,
+                     center2: &Vect, axes2: &[Vect], he2: &Vect, j: uint,
+                     normal:  &Vect, sep: &Scalar) -> Contact {
+    // The edges of each box the farthest along the normal (resp. its opposite). An edge is
+    // identified by its direction and the side of the box it lies on along the two other axes.
+    let mut p1    = center1.clone();
+    let mut p2    = center2.clone();
+    let mut edge1 = i;
+    let mut edge2 = j;
+
+    for k in range(0u, 3) {
+        if k != i {
+            let s1 = sign(na::dot(&axes1[k], normal));
+
+            p1    = p1 + axes1[k] * (he1.at(k) * s1);
+            edge1 = (edge1 << 1) | (if s1 < na::zero() { 1 } else { 0 });
+        }
+
+        if k != j {
+            let s2 = -sign(na::dot(&axes2[k], normal));
+
+            p2    = p2 + axes2[k] * (he2.at(k) * s2);
+            edge2 = (edge2 << 1) | (if s2 < na::zero() { 1 } else { 0 });
+        }
+    }
+
+    // Closest points between the two (unit-directed) lines.
+    let d1    = &axes1[i];
+    let d2    = &axes2[j];
+    let r     = p1 - p2;
+    let b     = na::dot(d1, d2);
+    let c     = na::dot(d1, &r);
+    let f     = na::dot(d2, &r);
+    let denom = na::one::<Scalar>() - b * b;
+
+    let s = ((b * f - c) / denom).max(-he1.at(i)).min(he1.at(i));
+    let t = (b * s + f).max(-he2.at(j)).min(he2.at(j));
+
+    Contact::new_with_features(p1 + *d1 * s, p2 + *d2 * t, normal.clone(), -*sep,
+                               FeatureId::new(Edge(edge1)), FeatureId::new(Edge(edge2)))
+}
+
+#[not_dim3]
+fn edge_edge_contact(_: &Vect, _: &[Vect], _: &Vect, _: uint,
+                     _: &Vect, _: &[Vect], _: &Vect, _: uint,
+                     _: &Vect, _: &Scalar) -> Contact {
+    unreachable!()
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use super::collide;
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::Cuboid;
+
+    #[test]
+    fn test_stacked_cuboids_manifold() {
+        let a  = Cuboid::new(Vec3::new(1.0f64, 1.0, 1.0));
+        let b  = Cuboid::new(Vec3::new(0.5f64, 0.5, 0.5));
+        let ta = Iso3::new(Vec3::new(0.0f64, 0.0, 0.0), na::zero());
+        let tb = Iso3::new(Vec3::new(0.0f64, 1.4, 0.0), na::zero());
+        let mut contacts = Vec::new();
+
+        collide(&ta, &a, &tb, &b, &0.0, &mut contacts);
+
+        assert!(contacts.len() == 4);
+
+        for c in contacts.iter() {
+            assert!(na::approx_eq(&c.depth, &0.1));
+            assert!(na::approx_eq(&c.normal, &Vec3::new(0.0f64, 1.0, 0.0)));
+        }
+    }
+}
+
+#[cfg(dim2, f64, test)]
+mod test_2d {
+    use super::collide;
+    use nalgebra::na::{Vec2, Iso2};
+    use nalgebra::na;
+    use geom::Cuboid;
+    use narrow::Contact;
+
+    #[test]
+    fn test_stacked_cuboids_manifold_2d() {
+        // The bottom edge of `b` lies on the top edge of `a` and overhangs it on the right.
+        let a  = Cuboid::new(Vec2::new(1.0f64, 1.0));
+        let b  = Cuboid::new(Vec2::new(1.0f64, 0.5));
+        let ta = Iso2::new(Vec2::new(0.0f64, 0.0), na::zero());
+        let tb = Iso2::new(Vec2::new(1.5f64, 1.4), na::zero());
+        let mut contacts: Vec<Contact> = Vec::new();
+
+        collide(&ta, &a, &tb, &b, &0.0, &mut contacts);
+
+        assert!(contacts.len() == 2);
+
+        for c in contacts.iter() {
+            assert!(na::approx_eq(&c.depth, &0.1));
+            assert!(na::approx_eq(&c.normal, &Vec2::new(0.0f64, 1.0)));
+            assert!(na::approx_eq(&c.world1.y, &1.0));
+        }
+
+        // The contacts span the overlap of both edges, i.e. `[0.5, 1.0]`.
+        let x0 = contacts.get(0).world1.x;
+        let x1 = contacts.get(1).world1.x;
+        let (xmin, xmax) = if x0 < x1 { (x0, x1) } else { (x1, x0) };
+
+        assert!(na::approx_eq(&xmin, &0.5));
+        assert!(na::approx_eq(&xmax, &1.0));
+    }
+}
@@ -1,23 +1,29 @@
 //! Collision detector between two `Box<Geom>`.
 
-use std::num::Bounded;
+use std::num::{Bounded, Zero};
 use std::intrinsics::TypeId;
 use std::any::{Any, AnyRefExt};
 use std::collections::HashMap;
 use sync::{Arc, RWLock};
+use nalgebra::na::{Translation, Indexable};
 use nalgebra::na;
 use geom::{AnnotatedPoint, Geom, ConcaveGeom, Cone, Cuboid, Ball, Capsule, Convex, Cylinder,
-           Compound, Mesh, Triangle, Segment, Plane};
+           Compound, Mesh, HeightField, Triangle, Segment, Plane, Torus, DEFAULT_TORUS_NSUBDIVS};
 use implicit::{Implicit, PreferedSamplingDirections};
+use implicit;
 use narrow::algorithm::simplex::Simplex;
 use narrow::algorithm::johnson_simplex::{JohnsonSimplex, RecursionTemplate};
-use narrow::{CollisionDetector, ImplicitImplicit, BallBall,
-             ImplicitPlane, PlaneImplicit, ConcaveGeomGeomFactory, GeomConcaveGeomFactory,
+use narrow::algorithm::conservative_advancement;
+use narrow::{CollisionDetector, ImplicitImplicit, BallBall, CuboidCuboid, BallCapsule, CapsuleBall,
+             CapsuleCapsule, BallSegment, SegmentBall, ImplicitPlane, PlaneImplicit, ConcaveGeomGeomFactory, GeomConcaveGeomFactory,
+             ConcaveGeomConcaveGeomFactory,
              BezierSurfaceBall, BallBezierSurface, BezierSurfaceBezierSurface,
-             Contact};
+             Contact, ClosestPoints};
+use narrow::{ball_ball, plane_implicit, implicit_implicit, concave_geom_geom};
 use narrow::surface_selector::HyperPlaneSurfaceSelector;
 use narrow::surface_subdivision_tree::SurfaceSubdivisionTreeCache;
 use narrow::OneShotContactManifoldGenerator as OSCMG;
+use bounding_volume::{HasAABB, HasBoundingSphere};
 use math::{Scalar, Vect, Matrix};
 
 /// Same as the `CollisionDetector` trait but using dynamic dispatch on the geometries.
@@ -97,9 +103,22 @@ GeomGeomCollisionDetector for DetectorWithoutRedispatch<D> {
     }
 }
 
+/// Function computing the signed distance and the closest points between two geometries.
+///
+/// The geometries are given in the order of the types the function has been registered with.
+pub type ClosestPointsFn = fn(&GeomGeomDispatcher, &Matrix, &Geom, &Matrix, &Geom) -> Option<ClosestPoints>;
+
+/// Function computing the time of impact of two geometries.
+///
+/// The geometries are given in the order of the types the function has been registered with. The
+/// first geometry moves along the given direction while the second one is static.
+pub type TOIFn = fn(&GeomGeomDispatcher, &Matrix, &Vect, &Scalar, &Geom, &Matrix, &Geom) -> Option<Scalar>;
+
 /// Collision dispatcher between two `~Geom`.
 pub struct GeomGeomDispatcher {
-    constructors: HashMap<(TypeId, TypeId), Box<CollisionDetectorFactory>>
+    constructors:       HashMap<(TypeId, TypeId), Box<CollisionDetectorFactory>>,
+    closest_points_fns: HashMap<(TypeId, TypeId), ClosestPointsFn>,
+    toi_fns:            HashMap<(TypeId, TypeId), TOIFn>
 }
 
 impl GeomGeomDispatcher {
@@ -107,7 +126,9 @@ impl GeomGeomDispatcher {
     /// factories.
     pub fn new_without_default() -> GeomGeomDispatcher {
         GeomGeomDispatcher {
-            constructors: HashMap::new()
+            constructors:       HashMap::new(),
+            closest_points_fns: HashMap::new(),
+            toi_fns:            HashMap::new()
         }
     }
 
@@ -137,24 +158,147 @@ impl GeomGeomDispatcher {
     }
 
     /// Registers a new collision detector for two geometries.
+    ///
+    /// The time of impact function of the detector is registered as well.
     pub fn register_detector<G1: 'static + Any,
                              G2: 'static + Any,
                              D:  'static + Send + CollisionDetector<G1, G2> + Clone>(
                              &mut self,
                              d:   D) {
+        self.register_toi::<G1, G2>(detector_toi::<G1, G2, D>);
         self.register_dynamic_detector(DetectorWithoutRedispatch::new(d));
     }
 
-    /// Unregister the collision detector for a givem pair of geometries.
+    /// Unregister the collision detector and the time of impact function for a givem pair of
+    /// geometries.
     pub fn unregister_detector<G1: 'static + Any, G2: 'static + Any>(&mut self) {
         let key = (TypeId::of::<G1>(), TypeId::of::<G2>());
         self.constructors.remove(&key);
+        self.toi_fns.remove(&key);
+    }
+
+    /// Registers a function computing the time of impact of two geometries.
+    ///
+    /// The function is used for the pairs `(G1, G2)` and, if no function is registered for it,
+    /// `(G2, G1)`.
+    pub fn register_toi<G1: 'static + Any, G2: 'static + Any>(&mut self, f: TOIFn) {
+        let key = (TypeId::of::<G1>(), TypeId::of::<G2>());
+        self.toi_fns.insert(key, f);
+    }
+
+    /// Unregister the time of impact function for a given pair of geometries.
+    pub fn unregister_toi<G1: 'static + Any, G2: 'static + Any>(&mut self) {
+        let key = (TypeId::of::<G1>(), TypeId::of::<G2>());
+        self.toi_fns.remove(&key);
+    }
+
+    /// If supported, computes the time of impact of two geometries.
+    ///
+    /// # Arguments
+    /// * `m1`   - the first geometry transform.
+    /// * `dir`  - the first geometry displacement direction.
+    /// * `dist` - the first geometry displacement distance.
+    /// * `a`    - the first geometry.
+    /// * `m2`   - the second geometry transform.
+    /// * `b`    - the second geometry.
+    pub fn toi(&self, m1: &Matrix, dir: &Vect, dist: &Scalar, a: &Geom, m2: &Matrix, b: &Geom) -> Option<Scalar> {
+        let ta = a.get_dyn_type_id();
+        let tb = b.get_dyn_type_id();
+
+        match self.toi_fns.find(&(ta, tb)) {
+            Some(f) => return (*f)(self, m1, dir, dist, a, m2, b),
+            None    => { }
+        }
+
+        // The relative motion of `a` wrt. `b` is the opposite of the relative motion of `b` wrt. `a`.
+        match self.toi_fns.find(&(tb, ta)) {
+            Some(f) => (*f)(self, m2, &-*dir, dist, b, m1, a),
+            None    => None
+        }
+    }
+
+    /// If supported, computes the time of impact of two geometries undergoing both a translational
+    /// and a rotational motion.
+    ///
+    /// The time of impact is computed by conservative advancement using the closest points
+    /// functions of this dispatcher. It is expressed in the interval `[0, 1]`, `0` being the time
+    /// the geometries are at their start transformations and `1` the time they are at their end
+    /// transformations. Returns `None` if no closest points function is registered for those
+    /// geometries.
+    pub fn nonlinear_toi(&self,
+                         start1: &Matrix,
+                         end1:   &Matrix,
+                         a:      &Geom,
+                         start2: &Matrix,
+                         end2:   &Matrix,
+                         b:      &Geom)
+                         -> Option<Scalar> {
+        if !self.supports_closest_points(a, b) {
+            return None
+        }
+
+        let bs1 = a.bounding_sphere(&na::one());
+        let bs2 = b.bounding_sphere(&na::one());
+        let r1  = na::norm(bs1.center()) + bs1.radius();
+        let r2  = na::norm(bs2.center()) + bs2.radius();
+
+        conservative_advancement::toi(start1, end1, &r1, start2, end2, &r2, |m1, m2| {
+            self.closest_points(m1, a, m2, b)
+        })
     }
 
     /// If registered, creates a new collision detector adapted for the two given geometries.
     pub fn dispatch(&self, a: &Geom, b: &Geom) -> Option<Box<GeomGeomCollisionDetector + Send>> {
         self.constructors.find(&(a.get_dyn_type_id(), b.get_dyn_type_id())).map(|f| f.build())
     }
+
+    /// Registers a function computing the closest points between two geometries.
+    ///
+    /// The function is used for the pairs `(G1, G2)` and, if no function is registered for it,
+    /// `(G2, G1)`.
+    pub fn register_closest_points<G1: 'static + Any, G2: 'static + Any>(&mut self, f: ClosestPointsFn) {
+        let key = (TypeId::of::<G1>(), TypeId::of::<G2>());
+        self.closest_points_fns.insert(key, f);
+    }
+
+    /// Unregister the closest points function for a given pair of geometries.
+    pub fn unregister_closest_points<G1: 'static + Any, G2: 'static + Any>(&mut self) {
+        let key = (TypeId::of::<G1>(), TypeId::of::<G2>());
+        self.closest_points_fns.remove(&key);
+    }
+
+    /// If supported, computes the signed distance and the closest points between two geometries.
+    ///
+    /// The distance is negative if the geometries are penetrating. In this case, the points
+    /// corresponding to the penetration depth are returned.
+    pub fn closest_points(&self, m1: &Matrix, a: &Geom, m2: &Matrix, b: &Geom) -> Option<ClosestPoints> {
+        let ta = a.get_dyn_type_id();
+        let tb = b.get_dyn_type_id();
+
+        match self.closest_points_fns.find(&(ta, tb)) {
+            Some(f) => return (*f)(self, m1, a, m2, b),
+            None    => { }
+        }
+
+        match self.closest_points_fns.find(&(tb, ta)) {
+            Some(f) => (*f)(self, m2, b, m1, a).map(|mut cp| { cp.flip(); cp }),
+            None    => None
+        }
+    }
+
+    fn supports_closest_points(&self, a: &Geom, b: &Geom) -> bool {
+        let ta = a.get_dyn_type_id();
+        let tb = b.get_dyn_type_id();
+
+        self.closest_points_fns.contains_key(&(ta, tb)) || self.closest_points_fns.contains_key(&(tb, ta))
+    }
+
+    /// If supported, computes the signed distance between two geometries.
+    ///
+    /// The distance is negative if the geometries are penetrating.
+    pub fn distance(&self, m1: &Matrix, a: &Geom, m2: &Matrix, b: &Geom) -> Option<Scalar> {
+        self.closest_points(m1, a, m2, b).map(|cp| cp.distance)
+    }
 }
 
 impl GeomGeomDispatcher {
@@ -209,6 +353,23 @@ impl GeomGeomDispatcher {
         res.register_default_implicit_detectors::<Triangle>(true, prediction);
         res.register_default_implicit_detectors::<Segment>(true, prediction);
 
+        // Cuboid vs. Cuboid
+        res.register_default_cuboid_cuboid_detector(prediction);
+
+        // Ball vs. Capsule
+        res.register_detector(BallCapsule::new(prediction.clone()));
+        res.register_detector(CapsuleBall::new(prediction.clone()));
+
+        // Capsule vs. Capsule
+        res.register_detector(CapsuleCapsule::new(prediction.clone()));
+
+        // Ball vs. Segment
+        res.register_detector(BallSegment::new(prediction.clone()));
+        res.register_detector(SegmentBall::new(prediction.clone()));
+
+        // Torus vs. Other
+        res.register_default_torus_detectors(prediction);
+
         // FIXME: refactor the three following blocks?
         // Compound vs. Other
         res.register_default_concave_geom_geom_detector::<Compound, Plane>();
@@ -232,13 +393,125 @@ impl GeomGeomDispatcher {
         res.register_default_concave_geom_geom_detector::<Mesh, Triangle>();
         res.register_default_concave_geom_geom_detector::<Mesh, Segment>();
 
-        // FIXME: implement a ConcaveGeomConcaveGeom detector?
-        res.register_default_concave_geom_geom_detector::<Compound, Compound>();
-        res.register_default_concave_geom_geom_detector::<Mesh, Compound>();
+        // HeightField vs. Other
+        res.register_default_concave_geom_geom_detector::<HeightField, Ball>();
+        res.register_default_concave_geom_geom_detector::<HeightField, Cuboid>();
+        res.register_default_concave_geom_geom_detector::<HeightField, Cone>();
+        res.register_default_concave_geom_geom_detector::<HeightField, Cylinder>();
+        res.register_default_concave_geom_geom_detector::<HeightField, Capsule>();
+        res.register_default_concave_geom_geom_detector::<HeightField, Convex>();
+        res.register_default_concave_geom_geom_detector::<HeightField, Triangle>();
+        res.register_default_concave_geom_geom_detector::<HeightField, Segment>();
+
+        // Concave vs. Concave
+        res.register_default_concave_geom_concave_geom_detector::<Compound, Compound>();
+        res.register_default_concave_geom_concave_geom_detector::<Mesh, Compound>();
+        res.register_default_concave_geom_concave_geom_detector::<Mesh, Mesh>();
+        res.register_default_concave_geom_concave_geom_detector::<HeightField, Compound>();
+        res.register_default_concave_geom_concave_geom_detector::<HeightField, Mesh>();
+
+        /*
+         * Closest points
+         */
+        res.register_closest_points::<Ball, Ball>(ball_ball_closest_points);
+
+        // Plane vs. Implicit
+        res.register_default_plane_implicit_closest_points::<Ball>();
+        res.register_default_plane_implicit_closest_points::<Cuboid>();
+        res.register_default_plane_implicit_closest_points::<Cone>();
+        res.register_default_plane_implicit_closest_points::<Cylinder>();
+        res.register_default_plane_implicit_closest_points::<Capsule>();
+        res.register_default_plane_implicit_closest_points::<Convex>();
+        res.register_default_plane_implicit_closest_points::<Triangle>();
+        res.register_default_plane_implicit_closest_points::<Segment>();
+
+        // Implicit vs. Implicit
+        res.register_default_implicit_closest_points::<Cuboid>();
+        res.register_default_implicit_closest_points::<Cone>();
+        res.register_default_implicit_closest_points::<Cylinder>();
+        res.register_default_implicit_closest_points::<Capsule>();
+        res.register_default_implicit_closest_points::<Convex>();
+        res.register_default_implicit_closest_points::<Triangle>();
+        res.register_default_implicit_closest_points::<Segment>();
+
+        // Torus vs. Other
+        res.register_default_torus_closest_points();
+
+        // Concave vs. Other
+        res.register_default_concave_geom_closest_points::<Compound>();
+        res.register_default_concave_geom_closest_points::<Mesh>();
+        res.register_default_concave_geom_closest_points::<HeightField>();
 
         res
     }
 
+    /// Registers the closest points function between a plane and a given implicit geometry.
+    pub fn register_default_plane_implicit_closest_points<G: 'static + Implicit<Vect, Matrix>>(&mut self) {
+        self.register_closest_points::<Plane, G>(plane_implicit_closest_points::<G>);
+    }
+
+    /// Registers the closest points functions between a given implicit geometry and every implicit
+    /// geometry supported by `ncollide`.
+    pub fn register_default_implicit_closest_points<G: 'static + Implicit<Vect, Matrix> +
+                                                       PreferedSamplingDirections<Vect, Matrix>>(
+                                                    &mut self) {
+        self.register_closest_points::<Ball, G>(implicit_implicit_closest_points::<Ball, G>);
+        self.register_closest_points::<Cuboid, G>(implicit_implicit_closest_points::<Cuboid, G>);
+        self.register_closest_points::<Cone, G>(implicit_implicit_closest_points::<Cone, G>);
+        self.register_closest_points::<Cylinder, G>(implicit_implicit_closest_points::<Cylinder, G>);
+        self.register_closest_points::<Capsule, G>(implicit_implicit_closest_points::<Capsule, G>);
+        self.register_closest_points::<Convex, G>(implicit_implicit_closest_points::<Convex, G>);
+        self.register_closest_points::<Triangle, G>(implicit_implicit_closest_points::<Triangle, G>);
+        self.register_closest_points::<Segment, G>(implicit_implicit_closest_points::<Segment, G>);
+    }
+
+    /// Registers the closest points functions between a torus and every geometry it is supported
+    /// with.
+    #[dim3]
+    pub fn register_default_torus_closest_points(&mut self) {
+        self.register_closest_points::<Ball, Torus>(ball_torus_closest_points);
+        self.register_closest_points::<Plane, Torus>(plane_torus_closest_points);
+
+        self.register_closest_points::<Torus, Cuboid>(torus_implicit_closest_points::<Cuboid>);
+        self.register_closest_points::<Torus, Cone>(torus_implicit_closest_points::<Cone>);
+        self.register_closest_points::<Torus, Cylinder>(torus_implicit_closest_points::<Cylinder>);
+        self.register_closest_points::<Torus, Capsule>(torus_implicit_closest_points::<Capsule>);
+        self.register_closest_points::<Torus, Convex>(torus_implicit_closest_points::<Convex>);
+        self.register_closest_points::<Torus, Triangle>(torus_implicit_closest_points::<Triangle>);
+        self.register_closest_points::<Torus, Segment>(torus_implicit_closest_points::<Segment>);
+
+        self.register_closest_points::<Compound, Torus>(concave_geom_geom_closest_points::<Compound>);
+        self.register_closest_points::<Mesh, Torus>(concave_geom_geom_closest_points::<Mesh>);
+        self.register_closest_points::<HeightField, Torus>(concave_geom_geom_closest_points::<HeightField>);
+    }
+
+    /// Registers the closest points functions between a torus and every geometry it is supported
+    /// with.
+    ///
+    /// This does nothing in 2d and 4d: tori are defined in 3d only.
+    #[not_dim3]
+    pub fn register_default_torus_closest_points(&mut self) {
+    }
+
+    /// Registers the closest points functions between a given concave geometry and every geometry
+    /// supported by `ncollide`.
+    pub fn register_default_concave_geom_closest_points<G: 'static + ConcaveGeom>(&mut self) {
+        let f = concave_geom_geom_closest_points::<G>;
+
+        self.register_closest_points::<G, Plane>(f);
+        self.register_closest_points::<G, Ball>(f);
+        self.register_closest_points::<G, Cuboid>(f);
+        self.register_closest_points::<G, Cone>(f);
+        self.register_closest_points::<G, Cylinder>(f);
+        self.register_closest_points::<G, Capsule>(f);
+        self.register_closest_points::<G, Convex>(f);
+        self.register_closest_points::<G, Triangle>(f);
+        self.register_closest_points::<G, Segment>(f);
+        self.register_closest_points::<G, Compound>(f);
+        self.register_closest_points::<G, Mesh>(f);
+        self.register_closest_points::<G, HeightField>(f);
+    }
+
     /// Registers a `PlaneImplicit` collision detector between a given implicit geometry and a plane.
     pub fn register_default_plane_implicit_detector<I: 'static + Implicit<Vect, Matrix>>(
                                                     &mut self,
@@ -286,8 +559,77 @@ impl GeomGeomDispatcher {
         }
     }
 
+    /// Registers a `CuboidCuboid` collision detector.
+    ///
+    /// This replaces the `ImplicitImplicit` collision detector between two boxes.
+    #[not_dim4]
+    pub fn register_default_cuboid_cuboid_detector(&mut self, prediction: &Scalar) {
+        self.register_detector(CuboidCuboid::new(prediction.clone()));
+    }
+
+    /// Registers a `CuboidCuboid` collision detector.
+    ///
+    /// This does nothing in 4d: boxes are handled by the `ImplicitImplicit` collision detector.
+    #[dim4]
+    pub fn register_default_cuboid_cuboid_detector(&mut self, _: &Scalar) {
+    }
+
+    /// Registers the collision detectors between a torus and every geometry it is supported with.
+    ///
+    /// This includes balls, planes, every implicit geometry supported by `ncollide`, and the
+    /// concave geometries containing them.
+    #[dim3]
+    pub fn register_default_torus_detectors(&mut self, prediction: &Scalar) {
+        use narrow::torus::{BallTorus, TorusBall, PlaneTorus, TorusPlane};
+
+        self.register_detector(BallTorus::new(prediction.clone()));
+        self.register_detector(TorusBall::new(prediction.clone()));
+
+        self.register_detector_with_contact_manifold_generator(PlaneTorus::new(na::zero()), prediction);
+        self.register_detector_with_contact_manifold_generator(TorusPlane::new(na::zero()), prediction);
+
+        self.register_default_torus_implicit_detector::<Cuboid>(prediction);
+        self.register_default_torus_implicit_detector::<Cone>(prediction);
+        self.register_default_torus_implicit_detector::<Cylinder>(prediction);
+        self.register_default_torus_implicit_detector::<Capsule>(prediction);
+        self.register_default_torus_implicit_detector::<Convex>(prediction);
+        self.register_default_torus_implicit_detector::<Triangle>(prediction);
+        self.register_default_torus_implicit_detector::<Segment>(prediction);
+
+        self.register_default_concave_geom_geom_detector::<Compound, Torus>();
+        self.register_default_concave_geom_geom_detector::<Mesh, Torus>();
+        self.register_default_concave_geom_geom_detector::<HeightField, Torus>();
+    }
+
+    /// Registers the collision detectors between a torus and every geometry it is supported with.
+    ///
+    /// This does nothing in 2d and 4d: tori are defined in 3d only.
+    #[not_dim3]
+    pub fn register_default_torus_detectors(&mut self, _: &Scalar) {
+    }
+
+    /// Registers a `TorusImplicit` collision detector between a torus and a given implicit
+    /// geometry.
+    #[dim3]
+    pub fn register_default_torus_implicit_detector<G: 'static + HasAABB + Implicit<Vect, Matrix> +
+                                                       PreferedSamplingDirections<Vect, Matrix>>(
+                                                    &mut self,
+                                                    prediction: &Scalar) {
+        use narrow::torus::{TorusImplicit, ImplicitTorus};
+
+        type Simplex = JohnsonSimplex<AnnotatedPoint>;
+
+        let js = JohnsonSimplex::new(RecursionTemplate::new(na::dim::<Vect>()));
+        let n  = DEFAULT_TORUS_NSUBDIVS;
+
+        self.register_detector(TorusImplicit::<Simplex, G>::new(prediction.clone(), n, js.clone()));
+        self.register_detector(ImplicitTorus::<Simplex, G>::new(prediction.clone(), n, js));
+    }
+
     /// Register an `ConcaveGeomGeom` collision detector between a given concave geometry and a
     /// given geometry.
+    ///
+    /// The time of impact function between those geometries is registered as well.
     pub fn register_default_concave_geom_geom_detector<G1: 'static + ConcaveGeom,
                                                        G2: 'static + Geom>(&mut self) {
         let  f1 = ConcaveGeomGeomFactory::<G1, G2>;
@@ -296,6 +638,24 @@ impl GeomGeomDispatcher {
         // FIXME: find a way to factorize that?
         unsafe { self.register_factory::<G1, G2, ConcaveGeomGeomFactory<G1, G2>>(f1) }
         unsafe { self.register_factory::<G2, G1, GeomConcaveGeomFactory<G2, G1>>(f2) }
+
+        self.register_toi::<G1, G2>(concave_geom_geom_toi::<G1>);
+    }
+
+    /// Register a `ConcaveGeomConcaveGeom` collision detector between two given concave
+    /// geometries.
+    ///
+    /// The time of impact function between those geometries is registered as well. It tests each
+    /// part of `G1` against the whole `G2`.
+    pub fn register_default_concave_geom_concave_geom_detector<G1: 'static + ConcaveGeom,
+                                                               G2: 'static + ConcaveGeom>(&mut self) {
+        let  f1 = ConcaveGeomConcaveGeomFactory::<G1, G2>;
+        let  f2 = ConcaveGeomConcaveGeomFactory::<G2, G1>;
+
+        unsafe { self.register_factory::<G1, G2, ConcaveGeomConcaveGeomFactory<G1, G2>>(f1) }
+        unsafe { self.register_factory::<G2, G1, ConcaveGeomConcaveGeomFactory<G2, G1>>(f2) }
+
+        self.register_toi::<G1, G2>(concave_geom_geom_toi::<G1>);
     }
 
     /// Register a given collision detector and adds it a contact manifold generator (a
@@ -341,6 +701,174 @@ impl GeomGeomDispatcher {
     }
 }
 
+/*
+ *
+ * Closest points functions
+ *
+ */
+fn ball_ball_closest_points(_:  &GeomGeomDispatcher,
+                            m1: &Matrix,
+                            g1: &Geom,
+                            m2: &Matrix,
+                            g2: &Geom)
+                            -> Option<ClosestPoints> {
+    let b1 = g1.downcast_ref::<Ball>().expect("Invalid geometry.");
+    let b2 = g2.downcast_ref::<Ball>().expect("Invalid geometry.");
+    let c1 = m1.translation();
+    let c2 = m2.translation();
+
+    let (p1, p2) = ball_ball::closest_points(&c1, b1, &c2, b2);
+    let dist     = na::norm(&(c2 - c1)) - b1.radius() - b2.radius();
+
+    Some(ClosestPoints::new(p1, p2, dist))
+}
+
+fn plane_implicit_closest_points<G: 'static + Implicit<Vect, Matrix>>(
+                                 _:  &GeomGeomDispatcher,
+                                 m1: &Matrix,
+                                 g1: &Geom,
+                                 m2: &Matrix,
+                                 g2: &Geom)
+                                 -> Option<ClosestPoints> {
+    Some(plane_implicit::closest_points(
+            m1,
+            g1.downcast_ref::<Plane>().expect("Invalid geometry."),
+            m2,
+            g2.downcast_ref::<G>().expect("Invalid geometry.")))
+}
+
+fn implicit_implicit_closest_points<G1: 'static + Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>,
+                                    G2: 'static + Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>>(
+                                    _:  &GeomGeomDispatcher,
+                                    m1: &Matrix,
+                                    g1: &Geom,
+                                    m2: &Matrix,
+                                    g2: &Geom)
+                                    -> Option<ClosestPoints> {
+    let g1 = g1.downcast_ref::<G1>().expect("Invalid geometry.");
+    let g2 = g2.downcast_ref::<G2>().expect("Invalid geometry.");
+
+    let mut simplex: JohnsonSimplex<AnnotatedPoint> = JohnsonSimplex::new_w_tls();
+    let mut dir = m1.translation() - m2.translation();
+
+    if dir.is_zero() {
+        dir.set(0, na::one());
+    }
+
+    simplex.reset(implicit::cso_support_point_without_margin(m1, g1, m2, g2, dir));
+
+    implicit_implicit::closest_points(m1, g1, m2, g2, &mut simplex)
+}
+
+#[dim3]
+fn ball_torus_closest_points(_:  &GeomGeomDispatcher,
+                             m1: &Matrix,
+                             g1: &Geom,
+                             m2: &Matrix,
+                             g2: &Geom)
+                             -> Option<ClosestPoints> {
+    use narrow::ball_torus;
+
+    Some(ball_torus::closest_points(
+            &m1.translation(),
+            g1.downcast_ref::<Ball>().expect("Invalid geometry."),
+            m2,
+            g2.downcast_ref::<Torus>().expect("Invalid geometry.")))
+}
+
+#[dim3]
+fn plane_torus_closest_points(_:  &GeomGeomDispatcher,
+                              m1: &Matrix,
+                              g1: &Geom,
+                              m2: &Matrix,
+                              g2: &Geom)
+                              -> Option<ClosestPoints> {
+    use narrow::plane_torus;
+
+    Some(plane_torus::closest_points(
+            m1,
+            g1.downcast_ref::<Plane>().expect("Invalid geometry."),
+            m2,
+            g2.downcast_ref::<Torus>().expect("Invalid geometry.")))
+}
+
+#[dim3]
+fn torus_implicit_closest_points<G: 'static + Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>>(
+                                 _:  &GeomGeomDispatcher,
+                                 m1: &Matrix,
+                                 g1: &Geom,
+                                 m2: &Matrix,
+                                 g2: &Geom)
+                                 -> Option<ClosestPoints> {
+    use narrow::implicit_torus;
+
+    let mut simplex: JohnsonSimplex<AnnotatedPoint> = JohnsonSimplex::new_w_tls();
+
+    implicit_torus::closest_points(
+        m1,
+        g1.downcast_ref::<Torus>().expect("Invalid geometry."),
+        m2,
+        g2.downcast_ref::<G>().expect("Invalid geometry."),
+        DEFAULT_TORUS_NSUBDIVS,
+        &mut simplex)
+}
+
+fn concave_geom_geom_closest_points<G: 'static + ConcaveGeom>(dispatcher: &GeomGeomDispatcher,
+                                                              m1:         &Matrix,
+                                                              g1:         &Geom,
+                                                              m2:         &Matrix,
+                                                              g2:         &Geom)
+                                                              -> Option<ClosestPoints> {
+    concave_geom_geom::closest_points(
+        dispatcher,
+        m1,
+        g1.downcast_ref::<G>().expect("Invalid geometry."),
+        m2,
+        g2)
+}
+
+/*
+ *
+ * Time of impact functions
+ *
+ */
+fn detector_toi<G1: 'static, G2: 'static, D: CollisionDetector<G1, G2>>(
+                _:    &GeomGeomDispatcher,
+                m1:   &Matrix,
+                dir:  &Vect,
+                dist: &Scalar,
+                g1:   &Geom,
+                m2:   &Matrix,
+                g2:   &Geom)
+                -> Option<Scalar> {
+    CollisionDetector::toi(
+        None::<D>,
+        m1,
+        dir,
+        dist,
+        g1.downcast_ref::<G1>().expect("Invalid geometry."),
+        m2,
+        g2.downcast_ref::<G2>().expect("Invalid geometry."))
+}
+
+fn concave_geom_geom_toi<G: 'static + ConcaveGeom>(dispatcher: &GeomGeomDispatcher,
+                                                   m1:         &Matrix,
+                                                   dir:        &Vect,
+                                                   dist:       &Scalar,
+                                                   g1:         &Geom,
+                                                   m2:         &Matrix,
+                                                   g2:         &Geom)
+                                                   -> Option<Scalar> {
+    concave_geom_geom::toi(
+        dispatcher,
+        m1,
+        dir,
+        dist,
+        g1.downcast_ref::<G>().expect("Invalid geometry."),
+        m2,
+        g2)
+}
+
 // FIXME: rename that GeomGeomCollisionDetectorFactory ?
 /// Trait of structures able do build a new collision detector.
 pub trait CollisionDetectorFactory : Send {
@@ -370,3 +898,155 @@ CollisionDetectorFactory for CollisionDetectorCloner<CD> {
         box self.template.clone() as Box<GeomGeomCollisionDetector + Send>
     }
 }
+
+#[cfg(test, dim3, f64)]
+mod test {
+    use std::num::Float;
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::{Geom, Ball, Cuboid, Triangle, Plane, Torus, Compound, CompoundData};
+    use super::GeomGeomDispatcher;
+
+    #[test]
+    fn test_dispatcher_distance() {
+        let dispatcher = GeomGeomDispatcher::new();
+        let ball       = Ball::new(1.0f64);
+        let cuboid     = Cuboid::new_with_margin(Vec3::new(1.0f64, 1.0, 1.0), 0.0);
+        let m1         = Iso3::new(na::zero(), na::zero());
+        let m2         = Iso3::new(Vec3::new(5.0f64, 0.0, 0.0), na::zero());
+
+        let dist = dispatcher.distance(&m1, &ball as &Geom, &m2, &ball as &Geom).unwrap();
+        assert!(na::approx_eq(&dist, &3.0));
+
+        // The arguments order does not matter.
+        let cp = dispatcher.closest_points(&m1, &ball as &Geom, &m2, &cuboid as &Geom).unwrap();
+        assert!(na::approx_eq(&cp.distance, &3.0));
+        assert!(na::approx_eq(&cp.world1, &Vec3::new(1.0, 0.0, 0.0)));
+        assert!(na::approx_eq(&cp.world2, &Vec3::new(4.0, 0.0, 0.0)));
+
+        let cp = dispatcher.closest_points(&m2, &cuboid as &Geom, &m1, &ball as &Geom).unwrap();
+        assert!(na::approx_eq(&cp.distance, &3.0));
+        assert!(na::approx_eq(&cp.world1, &Vec3::new(4.0, 0.0, 0.0)));
+        assert!(na::approx_eq(&cp.world2, &Vec3::new(1.0, 0.0, 0.0)));
+
+        // Penetration.
+        let m2   = Iso3::new(Vec3::new(1.5f64, 0.0, 0.0), na::zero());
+        let dist = dispatcher.distance(&m1, &ball as &Geom, &m2, &cuboid as &Geom).unwrap();
+        assert!(na::approx_eq(&dist, &-0.5));
+    }
+
+    #[test]
+    fn test_dispatcher_distance_degenerate_penetration() {
+        // Two overlapping coplanar triangles: their CSO is flat so the EPA cannot handle it.
+        let dispatcher = GeomGeomDispatcher::new();
+        let t1 = Triangle::new(Vec3::new(0.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        let t2 = Triangle::new(Vec3::new(0.2f64, 0.0, 0.2), Vec3::new(1.2, 0.0, 0.2), Vec3::new(0.2, 0.0, 1.2));
+        let m  = Iso3::new(na::zero(), na::zero());
+
+        let dist = dispatcher.distance(&m, &t1 as &Geom, &m, &t2 as &Geom).expect("Penetration not handled.");
+        assert!(dist < 0.0);
+    }
+
+    #[test]
+    fn test_dispatcher_distance_compound() {
+        let mut data = CompoundData::new();
+        data.push_geom(Iso3::new(Vec3::new(-3.0f64, 0.0, 0.0), na::zero()), Ball::new(1.0), 1.0);
+        data.push_geom(Iso3::new(Vec3::new(3.0f64, 0.0, 0.0), na::zero()), Ball::new(1.0), 1.0);
+
+        let dispatcher = GeomGeomDispatcher::new();
+        let compound   = Compound::new(data);
+        let ball       = Ball::new(1.0f64);
+        let m1         = Iso3::new(na::zero(), na::zero());
+        let m2         = Iso3::new(Vec3::new(6.0f64, 0.0, 0.0), na::zero());
+
+        let cp = dispatcher.closest_points(&m1, &compound as &Geom, &m2, &ball as &Geom).unwrap();
+        assert!(na::approx_eq(&cp.distance, &1.0));
+        assert!(na::approx_eq(&cp.world1, &Vec3::new(4.0, 0.0, 0.0)));
+
+        let dist = dispatcher.distance(&m2, &ball as &Geom, &m1, &compound as &Geom).unwrap();
+        assert!(na::approx_eq(&dist, &1.0));
+    }
+
+    #[test]
+    fn test_dispatcher_distance_torus() {
+        let dispatcher = GeomGeomDispatcher::new();
+        let torus      = Torus::new(2.0f64, 0.5);
+        let ball       = Ball::new(1.0f64);
+        let plane      = Plane::new(Vec3::y());
+        let cuboid     = Cuboid::new_with_margin(Vec3::new(1.0f64, 1.0, 1.0), 0.0);
+        let mtorus     = Iso3::new(na::zero(), na::zero());
+
+        let mball = Iso3::new(Vec3::new(5.0f64, 0.0, 0.0), na::zero());
+        let cp    = dispatcher.closest_points(&mtorus, &torus as &Geom, &mball, &ball as &Geom).unwrap();
+        assert!(na::approx_eq(&cp.distance, &1.5));
+        assert!(na::approx_eq(&cp.world1, &Vec3::new(2.5, 0.0, 0.0)));
+        assert!(na::approx_eq(&cp.world2, &Vec3::new(4.0, 0.0, 0.0)));
+
+        let mplane = Iso3::new(Vec3::new(0.0f64, -2.0, 0.0), na::zero());
+        let dist   = dispatcher.distance(&mtorus, &torus as &Geom, &mplane, &plane as &Geom).unwrap();
+        assert!(na::approx_eq(&dist, &1.5));
+
+        let mcuboid = Iso3::new(Vec3::new(0.0f64, 5.0, 0.0), na::zero());
+        let dist    = dispatcher.distance(&mcuboid, &cuboid as &Geom, &mtorus, &torus as &Geom).unwrap();
+        assert!(na::approx_eq_eps(&dist, &3.5, &1.0e-3));
+
+        // Through a compound.
+        let mut data = CompoundData::new();
+        data.push_geom(mcuboid, cuboid, 1.0);
+
+        let compound = Compound::new(data);
+        let dist     = dispatcher.distance(&mtorus, &torus as &Geom, &mtorus, &compound as &Geom).unwrap();
+        assert!(na::approx_eq_eps(&dist, &3.5, &1.0e-3));
+
+        // The ball moves toward the torus: it touches it when its center reaches `x = 3.5`.
+        let start = Iso3::new(Vec3::new(5.0f64, 0.0, 0.0), na::zero());
+        let end   = Iso3::new(Vec3::new(1.0f64, 0.0, 0.0), na::zero());
+        let toi   = dispatcher.nonlinear_toi(&start, &end, &ball as &Geom, &mtorus, &mtorus, &torus as &Geom).unwrap();
+        assert!(toi > 0.365 && toi <= 0.375);
+    }
+
+    #[test]
+    fn test_dispatcher_nonlinear_toi_spinning() {
+        // A long thin bar spinning around `z` hits a ball without translating.
+        let dispatcher = GeomGeomDispatcher::new();
+        let bar        = Cuboid::new(Vec3::new(5.0f64, 0.1, 0.1));
+        let ball       = Ball::new(0.5f64);
+        let start1     = Iso3::new(na::zero(), na::zero());
+        let end1       = Iso3::new(na::zero(), Vec3::new(0.0f64, 0.0, Float::frac_pi_2()));
+        let m2         = Iso3::new(Vec3::new(0.0f64, 3.0, 0.0), na::zero());
+
+        // The bar touches the ball when `3 * cos(angle) = 0.6`.
+        let expected = (0.2f64).acos() / Float::frac_pi_2();
+
+        let toi = dispatcher.nonlinear_toi(&start1, &end1, &bar as &Geom, &m2, &m2, &ball as &Geom).unwrap();
+        assert!(toi > expected - 0.01 && toi <= expected);
+
+        let toi = dispatcher.nonlinear_toi(&m2, &m2, &ball as &Geom, &start1, &end1, &bar as &Geom).unwrap();
+        assert!(toi > expected - 0.01 && toi <= expected);
+
+        // Only a quarter of the motion: the bar does not reach the ball.
+        let end1 = Iso3::new(na::zero(), Vec3::new(0.0f64, 0.0, Float::frac_pi_4()));
+        assert!(dispatcher.nonlinear_toi(&start1, &end1, &bar as &Geom, &m2, &m2, &ball as &Geom).is_none());
+    }
+
+    #[test]
+    fn test_dispatcher_nonlinear_toi_spinning_compound() {
+        let mut data = CompoundData::new();
+        data.push_geom(Iso3::new(Vec3::new(2.5f64, 0.0, 0.0), na::zero()), Cuboid::new(Vec3::new(2.5f64, 0.1, 0.1)), 1.0);
+        data.push_geom(Iso3::new(Vec3::new(-2.5f64, 0.0, 0.0), na::zero()), Cuboid::new(Vec3::new(2.5f64, 0.1, 0.1)), 1.0);
+
+        let dispatcher = GeomGeomDispatcher::new();
+        let bar        = Compound::new(data);
+        let ball       = Ball::new(0.5f64);
+        let start1     = Iso3::new(na::zero(), na::zero());
+        let end1       = Iso3::new(na::zero(), Vec3::new(0.0f64, 0.0, Float::frac_pi_2()));
+        let m2         = Iso3::new(Vec3::new(0.0f64, 3.0, 0.0), na::zero());
+        let expected   = (0.2f64).acos() / Float::frac_pi_2();
+
+        let toi = dispatcher.nonlinear_toi(&start1, &end1, &bar as &Geom, &m2, &m2, &ball as &Geom).unwrap();
+        assert!(toi > expected - 0.01 && toi <= expected);
+
+        // The same displacement without the rotation misses the ball.
+        assert!(dispatcher.toi(&start1, &Vec3::z(), &1.0, &bar as &Geom, &m2, &ball as &Geom).is_none());
+    }
+}
@@ -0,0 +1,425 @@
+//! Proximity detector between two `Box<Geom>`.
+
+use std::intrinsics::TypeId;
+use std::any::{Any, AnyRefExt};
+use std::collections::HashMap;
+use nalgebra::na;
+use geom::{AnnotatedPoint, Geom, ConcaveGeom, Cone, Cuboid, Ball, Capsule, Convex, Cylinder,
+           Compound, Mesh, HeightField, Triangle, Segment, Plane, Torus, BezierSurface,
+           DEFAULT_TORUS_NSUBDIVS};
+use implicit::Implicit;
+use narrow::algorithm::johnson_simplex::{JohnsonSimplex, RecursionTemplate};
+use narrow::{ProximityDetector, Proximity, ProximityTransition, BallGeomProximity,
+             PlaneImplicitProximity, ImplicitImplicitProximity, ConcaveGeomGeomProximityFactory};
+use bounding_volume::HasAABB;
+use point::PointQuery;
+use math::{Scalar, Vect, Matrix};
+
+/// Same as the `ProximityDetector` trait but using dynamic dispatch on the geometries.
+pub trait GeomGeomProximityDetector {
+    /// Updates the proximity status of two objects. It is assumed that the same proximity detector
+    /// (the same structure) is always used with the same pair of object.
+    fn update(&mut self,
+              &GeomGeomProximityDispatcher,
+              &Matrix,
+              &Geom,
+              &Matrix,
+              &Geom);
+
+    /// The proximity status computed during the last update.
+    fn proximity(&self) -> Proximity;
+
+    /// Updates the proximity status of two objects and returns its change, if any.
+    ///
+    /// A newly created detector starts `Disjoint` so that the first update reports the geometries
+    /// getting close to each other.
+    fn update_with_transition(&mut self,
+                              dispatcher: &GeomGeomProximityDispatcher,
+                              m1:         &Matrix,
+                              g1:         &Geom,
+                              m2:         &Matrix,
+                              g2:         &Geom)
+                              -> Option<ProximityTransition> {
+        let prev = self.proximity();
+
+        self.update(dispatcher, m1, g1, m2, g2);
+
+        ProximityTransition::between(prev, self.proximity())
+    }
+}
+
+/// Trait to be implemented by proximity detector using dynamic dispatch.
+///
+/// This is used to know the exact type of the geometries.
+pub trait DynamicProximityDetector<G1, G2>: GeomGeomProximityDetector { }
+
+#[deriving(Clone)]
+struct ProximityDetectorWithoutRedispatch<D> {
+    detector: D
+}
+
+impl<D> ProximityDetectorWithoutRedispatch<D> {
+    pub fn new(d: D) -> ProximityDetectorWithoutRedispatch<D> {
+        ProximityDetectorWithoutRedispatch {
+            detector: d
+        }
+    }
+}
+
+impl<D: ProximityDetector<G1, G2>, G1, G2>
+DynamicProximityDetector<G1, G2> for ProximityDetectorWithoutRedispatch<D> { }
+
+impl<D: ProximityDetector<G1, G2>, G1: 'static, G2: 'static>
+GeomGeomProximityDetector for ProximityDetectorWithoutRedispatch<D> {
+    #[inline]
+    fn update(&mut self,
+              _:  &GeomGeomProximityDispatcher,
+              m1: &Matrix,
+              g1: &Geom,
+              m2: &Matrix,
+              g2: &Geom) {
+        self.detector.update(
+            m1,
+            g1.downcast_ref::<G1>().expect("Invalid geometry."),
+            m2,
+            g2.downcast_ref::<G2>().expect("Invalid geometry."))
+    }
+
+    #[inline]
+    fn proximity(&self) -> Proximity {
+        self.detector.proximity()
+    }
+}
+
+/// Proximity detector registered for the geometries in the reverse order.
+///
+/// The proximity status does not depend on the order of the geometries so this simply swaps them.
+struct FlippedProximityDetector {
+    detector: Box<GeomGeomProximityDetector + Send>
+}
+
+impl GeomGeomProximityDetector for FlippedProximityDetector {
+    #[inline]
+    fn update(&mut self,
+              dispatcher: &GeomGeomProximityDispatcher,
+              m1:         &Matrix,
+              g1:         &Geom,
+              m2:         &Matrix,
+              g2:         &Geom) {
+        self.detector.update(dispatcher, m2, g2, m1, g1)
+    }
+
+    #[inline]
+    fn proximity(&self) -> Proximity {
+        self.detector.proximity()
+    }
+}
+
+/// Proximity dispatcher between two `~Geom`.
+pub struct GeomGeomProximityDispatcher {
+    constructors: HashMap<(TypeId, TypeId), Box<ProximityDetectorFactory>>
+}
+
+impl GeomGeomProximityDispatcher {
+    /// Creates a new `GeomGeomProximityDispatcher` without the default set of proximity detectors
+    /// factories.
+    pub fn new_without_default() -> GeomGeomProximityDispatcher {
+        GeomGeomProximityDispatcher {
+            constructors: HashMap::new()
+        }
+    }
+
+    /// Registers a new proximity detection algorithm factory for a pair of geometries.
+    ///
+    /// This is unsafe because there is no way to check that the factory will really generate
+    /// proximity detectors suited for `G1` and `G2`. Whenever possible, use `register_detector` or
+    /// `register_dynamic_detector` instead.
+    pub unsafe fn register_factory<G1: 'static + Any,
+                                   G2: 'static + Any,
+                                   F:  ProximityDetectorFactory>(
+                                   &mut self,
+                                   factory: F) {
+        let key = (TypeId::of::<G1>(), TypeId::of::<G2>());
+        self.constructors.insert(key, box factory as Box<ProximityDetectorFactory>);
+    }
+
+    /// Registers a new dynamic proximity detector for two geometries.
+    pub fn register_dynamic_detector<G1: 'static + Any,
+                                     G2: 'static + Any,
+                                     D:  'static + Send + Clone +
+                                         DynamicProximityDetector<G1, G2>>(
+                                     &mut self,
+                                     d:   D) {
+        let factory = ProximityDetectorCloner::new(d);
+        unsafe { self.register_factory::<G1, G2, ProximityDetectorCloner<D>>(factory) }
+    }
+
+    /// Registers a new proximity detector for two geometries.
+    ///
+    /// The detector is used for the pairs `(G1, G2)` and, if no detector is registered for it,
+    /// `(G2, G1)`.
+    pub fn register_detector<G1: 'static + Any,
+                             G2: 'static + Any,
+                             D:  'static + Send + ProximityDetector<G1, G2> + Clone>(
+                             &mut self,
+                             d:   D) {
+        self.register_dynamic_detector(ProximityDetectorWithoutRedispatch::new(d));
+    }
+
+    /// Unregister the proximity detector for a given pair of geometries.
+    pub fn unregister_detector<G1: 'static + Any, G2: 'static + Any>(&mut self) {
+        let key = (TypeId::of::<G1>(), TypeId::of::<G2>());
+        self.constructors.remove(&key);
+    }
+
+    /// If registered, creates a new proximity detector adapted for the two given geometries.
+    pub fn dispatch(&self, a: &Geom, b: &Geom) -> Option<Box<GeomGeomProximityDetector + Send>> {
+        let ta = a.get_dyn_type_id();
+        let tb = b.get_dyn_type_id();
+
+        match self.constructors.find(&(ta, tb)) {
+            Some(f) => return Some(f.build()),
+            None    => { }
+        }
+
+        self.constructors.find(&(tb, ta)).map(|f| {
+            box FlippedProximityDetector { detector: f.build() } as Box<GeomGeomProximityDetector + Send>
+        })
+    }
+}
+
+impl GeomGeomProximityDispatcher {
+    /// Creates a new `GeomGeomProximityDispatcher` able do build proximity detectors for any valid
+    /// pair of geometries supported by `ncollide`.
+    ///
+    /// Geometries closer than `0.1` are reported as `WithinMargin`.
+    pub fn new() -> GeomGeomProximityDispatcher {
+        GeomGeomProximityDispatcher::new_with_margin(&na::cast(0.1f64))
+    }
+
+    /// Creates a new `GeomGeomProximityDispatcher` able do build proximity detectors for any valid
+    /// pair of geometries supported by `ncollide`.
+    ///
+    /// Geometries closer than `margin` are reported as `WithinMargin`. Bézier surfaces are
+    /// supported against balls only.
+    pub fn new_with_margin(margin: &Scalar) -> GeomGeomProximityDispatcher {
+        let mut res = GeomGeomProximityDispatcher::new_without_default();
+
+        // Ball vs. Ball
+        res.register_default_ball_detector::<Ball>(margin);
+
+        // Ball vs. Bézier
+        res.register_default_ball_detector::<BezierSurface>(margin);
+
+        // Plane vs. Implicit
+        res.register_default_plane_implicit_detector::<Cuboid>(margin);
+        res.register_default_plane_implicit_detector::<Ball>(margin);
+        res.register_default_plane_implicit_detector::<Cone>(margin);
+        res.register_default_plane_implicit_detector::<Cylinder>(margin);
+        res.register_default_plane_implicit_detector::<Capsule>(margin);
+        res.register_default_plane_implicit_detector::<Convex>(margin);
+        res.register_default_plane_implicit_detector::<Triangle>(margin);
+        res.register_default_plane_implicit_detector::<Segment>(margin);
+
+        // Implicit vs. Implicit
+        // NOTE: some pair will be registered twice…
+        res.register_default_implicit_detectors::<Cuboid>(margin);
+        res.register_default_implicit_detectors::<Cone>(margin);
+        res.register_default_implicit_detectors::<Cylinder>(margin);
+        res.register_default_implicit_detectors::<Capsule>(margin);
+        res.register_default_implicit_detectors::<Convex>(margin);
+        res.register_default_implicit_detectors::<Triangle>(margin);
+        res.register_default_implicit_detectors::<Segment>(margin);
+
+        // Torus vs. Other
+        res.register_default_torus_detectors(margin);
+
+        // Concave vs. Other
+        res.register_default_concave_geom_detectors::<Compound>(margin);
+        res.register_default_concave_geom_detectors::<Mesh>(margin);
+        res.register_default_concave_geom_detectors::<HeightField>(margin);
+
+        res
+    }
+
+    /// Registers a `BallGeomProximity` proximity detector between a ball and a given geometry.
+    pub fn register_default_ball_detector<G: 'static + PointQuery>(&mut self, margin: &Scalar) {
+        self.register_detector(BallGeomProximity::<G>::new(margin.clone()));
+    }
+
+    /// Registers a `PlaneImplicitProximity` proximity detector between a plane and a given
+    /// implicit geometry.
+    pub fn register_default_plane_implicit_detector<G: 'static + Implicit<Vect, Matrix>>(
+                                                    &mut self,
+                                                    margin: &Scalar) {
+        self.register_detector(PlaneImplicitProximity::<G>::new(margin.clone()));
+    }
+
+    /// Registers an `ImplicitImplicitProximity` proximity detector between two implicit
+    /// geometries.
+    pub fn register_default_implicit_implicit_detector<G1: 'static + Implicit<Vect, Matrix>,
+                                                       G2: 'static + Implicit<Vect, Matrix>>(
+                                                       &mut self,
+                                                       margin: &Scalar) {
+        use narrow::torus::TorusImplicitProximity;
+
+        type Simplex = JohnsonSimplex<AnnotatedPoint>;
+
+        let js = JohnsonSimplex::new(RecursionTemplate::new(na::dim::<Vect>()));
+
+        self.register_detector(ImplicitImplicitProximity::<Simplex, G1, G2>::new(margin.clone(), js));
+    }
+
+    /// Registers `ImplicitImplicitProximity` proximity detectors between a given geometry and
+    /// every implicit geometry supported by `ncollide`.
+    pub fn register_default_implicit_detectors<G: 'static + Implicit<Vect, Matrix>>(
+                                               &mut self,
+                                               margin: &Scalar) {
+        self.register_default_implicit_implicit_detector::<Ball, G>(margin);
+        self.register_default_implicit_implicit_detector::<Cuboid, G>(margin);
+        self.register_default_implicit_implicit_detector::<Cone, G>(margin);
+        self.register_default_implicit_implicit_detector::<Cylinder, G>(margin);
+        self.register_default_implicit_implicit_detector::<Capsule, G>(margin);
+        self.register_default_implicit_implicit_detector::<Convex, G>(margin);
+        self.register_default_implicit_implicit_detector::<Triangle, G>(margin);
+        self.register_default_implicit_implicit_detector::<Segment, G>(margin);
+    }
+
+    /// Registers the proximity detectors between a torus and every geometry it is supported with.
+    ///
+    /// This includes balls, planes, every implicit geometry supported by `ncollide`, and the
+    /// concave geometries containing them.
+    #[dim3]
+    pub fn register_default_torus_detectors(&mut self, margin: &Scalar) {
+        use narrow::torus::PlaneTorusProximity;
+
+        self.register_default_ball_detector::<Torus>(margin);
+        self.register_detector(PlaneTorusProximity::new(margin.clone()));
+
+        self.register_default_torus_implicit_detector::<Cuboid>(margin);
+        self.register_default_torus_implicit_detector::<Cone>(margin);
+        self.register_default_torus_implicit_detector::<Cylinder>(margin);
+        self.register_default_torus_implicit_detector::<Capsule>(margin);
+        self.register_default_torus_implicit_detector::<Convex>(margin);
+        self.register_default_torus_implicit_detector::<Triangle>(margin);
+        self.register_default_torus_implicit_detector::<Segment>(margin);
+
+        self.register_default_concave_geom_geom_detector::<Compound, Torus>(margin);
+        self.register_default_concave_geom_geom_detector::<Mesh, Torus>(margin);
+        self.register_default_concave_geom_geom_detector::<HeightField, Torus>(margin);
+    }
+
+    /// Registers the proximity detectors between a torus and every geometry it is supported with.
+    ///
+    /// This does nothing in 2d and 4d: tori are defined in 3d only.
+    #[not_dim3]
+    pub fn register_default_torus_detectors(&mut self, _: &Scalar) {
+    }
+
+    /// Registers a `TorusImplicitProximity` proximity detector between a torus and a given
+    /// implicit geometry.
+    #[dim3]
+    pub fn register_default_torus_implicit_detector<G: 'static + HasAABB + Implicit<Vect, Matrix>>(
+                                                    &mut self,
+                                                    margin: &Scalar) {
+        type Simplex = JohnsonSimplex<AnnotatedPoint>;
+
+        let js = JohnsonSimplex::new(RecursionTemplate::new(na::dim::<Vect>()));
+        let n  = DEFAULT_TORUS_NSUBDIVS;
+
+        self.register_detector(TorusImplicitProximity::<Simplex, G>::new(margin.clone(), n, js));
+    }
+
+    /// Registers a `ConcaveGeomGeomProximity` proximity detector between a given concave geometry
+    /// and a given geometry.
+    pub fn register_default_concave_geom_geom_detector<G1: 'static + ConcaveGeom,
+                                                       G2: 'static + Geom>(
+                                                       &mut self,
+                                                       margin: &Scalar) {
+        let f = ConcaveGeomGeomProximityFactory::<G1, G2>::new(margin.clone());
+
+        unsafe { self.register_factory::<G1, G2, ConcaveGeomGeomProximityFactory<G1, G2>>(f) }
+    }
+
+    /// Registers `ConcaveGeomGeomProximity` proximity detectors between a given concave geometry
+    /// and every geometry supported by `ncollide`, including the concave ones.
+    pub fn register_default_concave_geom_detectors<G: 'static + ConcaveGeom>(&mut self, margin: &Scalar) {
+        self.register_default_concave_geom_geom_detector::<G, Plane>(margin);
+        self.register_default_concave_geom_geom_detector::<G, Ball>(margin);
+        self.register_default_concave_geom_geom_detector::<G, Cuboid>(margin);
+        self.register_default_concave_geom_geom_detector::<G, Cone>(margin);
+        self.register_default_concave_geom_geom_detector::<G, Cylinder>(margin);
+        self.register_default_concave_geom_geom_detector::<G, Capsule>(margin);
+        self.register_default_concave_geom_geom_detector::<G, Convex>(margin);
+        self.register_default_concave_geom_geom_detector::<G, Triangle>(margin);
+        self.register_default_concave_geom_geom_detector::<G, Segment>(margin);
+        self.register_default_concave_geom_geom_detector::<G, Compound>(margin);
+        self.register_default_concave_geom_geom_detector::<G, Mesh>(margin);
+        self.register_default_concave_geom_geom_detector::<G, HeightField>(margin);
+    }
+}
+
+/// Trait of structures able do build a new proximity detector.
+pub trait ProximityDetectorFactory : Send {
+    /// Builds a new proximity detector.
+    fn build(&self) -> Box<GeomGeomProximityDetector + Send>;
+}
+
+/// Cloning-based proximity detector factory.
+struct ProximityDetectorCloner<PD> {
+    template: PD
+}
+
+impl<PD: GeomGeomProximityDetector + Clone> ProximityDetectorCloner<PD> {
+    /// Creates a new `ProximityDetectorCloner`.
+    ///
+    /// The cloned detector is `PD`.
+    fn new(detector: PD) -> ProximityDetectorCloner<PD> {
+        ProximityDetectorCloner {
+            template: detector
+        }
+    }
+}
+
+impl<PD: 'static + Send + GeomGeomProximityDetector + Clone>
+ProximityDetectorFactory for ProximityDetectorCloner<PD> {
+    fn build(&self) -> Box<GeomGeomProximityDetector + Send> {
+        box self.template.clone() as Box<GeomGeomProximityDetector + Send>
+    }
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use narrow::{GeomGeomProximityDispatcher, GeomGeomProximityDetector, Intersecting,
+                 WithinMargin, Disjoint};
+    use geom::{Geom, Ball, Cuboid};
+
+    #[test]
+    fn test_ball_cuboid_proximity_transitions() {
+        let dispatcher = GeomGeomProximityDispatcher::new_with_margin(&0.5);
+        let ball       = Ball::new(1.0f64);
+        let cuboid     = Cuboid::new(Vec3::new(1.0f64, 1.0, 1.0));
+        let g1         = &ball as &Geom;
+        let g2         = &cuboid as &Geom;
+        let m2         = Iso3::new(na::zero(), na::zero());
+        let mut detector = dispatcher.dispatch(g1, g2).expect("Ball vs. Cuboid is not supported.");
+
+        let far   = Iso3::new(Vec3::new(5.0f64, 0.0, 0.0), na::zero());
+        let close = Iso3::new(Vec3::new(2.3f64, 0.0, 0.0), na::zero());
+        let hit   = Iso3::new(Vec3::new(1.5f64, 0.0, 0.0), na::zero());
+
+        assert!(detector.update_with_transition(&dispatcher, &far, g1, &m2, g2).is_none());
+        assert!(detector.proximity() == Disjoint);
+
+        let t = detector.update_with_transition(&dispatcher, &close, g1, &m2, g2).unwrap();
+        assert!(t.curr == WithinMargin && !t.is_enter() && !t.is_exit());
+
+        let t = detector.update_with_transition(&dispatcher, &hit, g1, &m2, g2).unwrap();
+        assert!(t.curr == Intersecting && t.is_enter());
+
+        let t = detector.update_with_transition(&dispatcher, &far, g1, &m2, g2).unwrap();
+        assert!(t.curr == Disjoint && t.is_exit());
+    }
+}
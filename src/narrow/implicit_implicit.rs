@@ -8,10 +8,23 @@ use narrow::algorithm::simplex::Simplex;
 use narrow::algorithm::gjk;
 use narrow::algorithm::gjk::{GJKResult, NoIntersection, Intersection, Projection};
 use narrow::algorithm::minkowski_sampling;
-use narrow::{CollisionDetector, Contact};
+use narrow::algorithm::epa;
+use narrow::algorithm::johnson_simplex::JohnsonSimplex;
+use narrow::algorithm::conservative_advancement;
+use narrow::{CollisionDetector, Contact, ClosestPoints};
 use ray::{Ray, RayCast};
+use bounding_volume::HasBoundingSphere;
 use math::{Scalar, Vect, Matrix};
 
+/// Algorithm used to compute the penetration depth of two deeply penetrating implicit geometries.
+#[deriving(Show, PartialEq, Clone, Encodable, Decodable)]
+pub enum PenetrationAlgorithm {
+    /// Approximates the penetration depth by sampling the Minkowski sum support function.
+    MinkowskiSampling,
+    /// Computes the exact penetration depth with the Expanding Polytope Algorithm.
+    ExpandingPolytope
+}
+
 /// Persistent collision detector between two shapes having a support mapping function.
 ///
 /// It is based on the GJK algorithm.  This detector generates only one contact point. For a full
@@ -20,15 +33,17 @@ use math::{Scalar, Vect, Matrix};
 pub struct ImplicitImplicit<S, G1, G2> {
     simplex:       S,
     prediction:    Scalar,
+    penetration:   PenetrationAlgorithm,
     contact:       GJKResult<Contact, Vect>
 }
 
 impl<S: Clone, G1, G2> Clone for ImplicitImplicit<S, G1, G2> {
     fn clone(&self) -> ImplicitImplicit<S, G1, G2> {
         ImplicitImplicit {
-            simplex:    self.simplex.clone(),
-            prediction: self.prediction.clone(),
-            contact:    self.contact.clone()
+            simplex:     self.simplex.clone(),
+            prediction:  self.prediction.clone(),
+            penetration: self.penetration.clone(),
+            contact:     self.contact.clone()
         }
     }
 }
@@ -37,15 +52,37 @@ impl<S, G1, G2> ImplicitImplicit<S, G1, G2> {
     /// Creates a new persistent collision detector between two geometries with support mapping
     /// functions.
     ///
-    /// It is initialized with a pre-created simplex.
+    /// It is initialized with a pre-created simplex. Deep penetrations are handled by the
+    /// Minkowski sampling algorithm.
     pub fn new(prediction: Scalar, simplex: S) -> ImplicitImplicit<S, G1, G2> {
+        ImplicitImplicit::new_with_penetration_algorithm(prediction, simplex, MinkowskiSampling)
+    }
+
+    /// Creates a new persistent collision detector between two geometries with support mapping
+    /// functions, using a specific algorithm to handle deep penetrations.
+    pub fn new_with_penetration_algorithm(prediction:  Scalar,
+                                          simplex:     S,
+                                          penetration: PenetrationAlgorithm)
+                                          -> ImplicitImplicit<S, G1, G2> {
         ImplicitImplicit {
-            simplex:    simplex,
-            prediction: prediction,
-            contact:    Intersection
+            simplex:     simplex,
+            prediction:  prediction,
+            penetration: penetration,
+            contact:     Intersection
         }
     }
 
+    /// The algorithm used by this detector to handle deep penetrations.
+    #[inline]
+    pub fn penetration_algorithm(&self) -> PenetrationAlgorithm {
+        self.penetration.clone()
+    }
+
+    /// Sets the algorithm used by this detector to handle deep penetrations.
+    #[inline]
+    pub fn set_penetration_algorithm(&mut self, penetration: PenetrationAlgorithm) {
+        self.penetration = penetration
+    }
 }
 
 impl<S:  Simplex<AnnotatedPoint>,
@@ -60,14 +97,15 @@ impl<S:  Simplex<AnnotatedPoint>,
             Intersection                  => None
         };
 
-        self.contact = collide(
+        self.contact = collide_with_penetration_algorithm(
             ma,
             a,
             mb,
             b,
             &self.prediction,
             &mut self.simplex,
-            initial_direction)
+            initial_direction,
+            self.penetration.clone())
     }
 
     #[inline]
@@ -120,6 +158,26 @@ pub fn collide<S:  Simplex<AnnotatedPoint>,
                simplex:    &mut S,
                init_dir:   Option<Vect>)
                -> GJKResult<Contact, Vect> {
+    collide_with_penetration_algorithm(m1, g1, m2, g2, prediction, simplex, init_dir, MinkowskiSampling)
+}
+
+/// Computes a contact point between two implicit geometries, using a specific algorithm to handle
+/// deep penetrations.
+///
+/// This is the same as `collide` except that the fallback algorithm used when the origin is inside
+/// of the CSO is given by `penetration`.
+pub fn collide_with_penetration_algorithm<S:  Simplex<AnnotatedPoint>,
+                                          G1: Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>,
+                                          G2: Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>>(
+                                          m1:          &Matrix,
+                                          g1:          &G1,
+                                          m2:          &Matrix,
+                                          g2:          &G2,
+                                          prediction:  &Scalar,
+                                          simplex:     &mut S,
+                                          init_dir:    Option<Vect>,
+                                          penetration: PenetrationAlgorithm)
+                                          -> GJKResult<Contact, Vect> {
     let mut dir = 
         match init_dir {
             None      => m1.translation() - m2.translation(), // FIXME: or m2.translation - m1.translation ?
@@ -146,11 +204,13 @@ pub fn collide<S:  Simplex<AnnotatedPoint>,
                 let depth      = normal.normalize();
 
                 return Projection(
-                    Contact::new(
+                    Contact::new_with_features(
                         p1 + normal * margin1,
                         p2 + normal * (-margin2),
                         normal,
-                        margin1 + margin2 - depth)
+                        margin1 + margin2 - depth,
+                        g1.support_feature_id(m1, &normal),
+                        g2.support_feature_id(m2, &-normal))
                     );
             }
         },
@@ -159,6 +219,13 @@ pub fn collide<S:  Simplex<AnnotatedPoint>,
     }
 
     // The point is inside of the CSO: use the fallback algorithm
+    if penetration == ExpandingPolytope {
+        match epa::closest_points(m1, g1, m2, g2, simplex) {
+            Some(contact) => return Projection(contact),
+            None          => { } // fallback to the Minkowski sampling
+        }
+    }
+
     match minkowski_sampling::closest_points(m1, g1, m2, g2, simplex) {
         Some((p1, p2)) => {
             let mut normal = p1 - p2;
@@ -172,7 +239,10 @@ pub fn collide<S:  Simplex<AnnotatedPoint>,
                 NoIntersection(na::zero())
             }
             else {
-                Projection(Contact::new(p1, p2, normal, depth))
+                let f1 = g1.support_feature_id(m1, &normal);
+                let f2 = g2.support_feature_id(m2, &-normal);
+
+                Projection(Contact::new_with_features(p1, p2, normal, depth, f1, f2))
             }
         }
         None => {
@@ -181,6 +251,54 @@ pub fn collide<S:  Simplex<AnnotatedPoint>,
     }
 }
 
+/// Computes the signed distance and the closest points between two implicit geometries.
+///
+/// If the geometries are penetrating, the points corresponding to the penetration depth computed
+/// by the EPA are returned. If the EPA fails, e.g., because the CSO is degenerate, the
+/// penetration depth is approximated by sampling the Minkowski sum support function instead.
+///
+/// # Arguments:
+/// * `g1`      - the first geometry.
+/// * `g2`      - the second geometry.
+/// * `simplex` - the simplex to be used by the GJK algorithm. It must be already initialized
+///               with at least one point on the geometries CSO. See
+///               `minkowski_sum::cso_support_point` to compute such point.
+pub fn closest_points<S:  Simplex<AnnotatedPoint>,
+                      G1: Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>,
+                      G2: Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>>(
+                      m1:      &Matrix,
+                      g1:      &G1,
+                      m2:      &Matrix,
+                      g2:      &G2,
+                      simplex: &mut S)
+                      -> Option<ClosestPoints> {
+    let margin1 = g1.margin();
+    let margin2 = g2.margin();
+
+    match gjk::closest_points_without_margin(m1, g1, m2, g2, simplex) {
+        Some((p1, p2)) => {
+            let mut normal = p2 - p1;
+            let dist       = normal.normalize();
+
+            Some(ClosestPoints::new(
+                    p1 + normal * margin1,
+                    p2 - normal * margin2,
+                    dist - margin1 - margin2))
+        },
+        None => {
+            match epa::closest_points(m1, g1, m2, g2, simplex) {
+                Some(c) => Some(ClosestPoints::new(c.world1, c.world2, -c.depth)),
+                None    => {
+                    // Same fallback as `collide_with_penetration_algorithm`.
+                    minkowski_sampling::closest_points(m1, g1, m2, g2, simplex).map(|(p1, p2)| {
+                        ClosestPoints::new(p1, p2, -na::norm(&(p1 - p2)))
+                    })
+                }
+            }
+        }
+    }
+}
+
 /// Computes the Time Of Impact of two geometries.
 ///
 /// # Arguments:
@@ -224,3 +342,45 @@ pub fn toi_and_normal<G1: Implicit<Vect, Matrix>,
 
     cso.toi_and_normal_with_ray(&Ray::new(na::zero(), -dir), true).map(|i| (i.toi, -i.normal))
 }
+
+/// Computes the time of impact of two geometries undergoing both a translational and a rotational
+/// motion.
+///
+/// The time of impact is expressed in the interval `[0, 1]`, `0` being the time the geometries are
+/// at their start transformations and `1` the time they are at their end transformations. It is
+/// computed by conservative advancement.
+///
+/// # Arguments:
+/// * `start1` - the first geometry transform at the beginning of the motion.
+/// * `end1`   - the first geometry transform at the end of the motion.
+/// * `g1`     - the first geometry.
+/// * `start2` - the second geometry transform at the beginning of the motion.
+/// * `end2`   - the second geometry transform at the end of the motion.
+/// * `g2`     - the second geometry.
+pub fn nonlinear_toi<G1: Implicit<Vect, Matrix> + HasBoundingSphere,
+                     G2: Implicit<Vect, Matrix> + HasBoundingSphere>(
+                     start1: &Matrix,
+                     end1:   &Matrix,
+                     g1:     &G1,
+                     start2: &Matrix,
+                     end2:   &Matrix,
+                     g2:     &G2)
+                     -> Option<Scalar> {
+    let bs1 = g1.bounding_sphere(&na::one());
+    let bs2 = g2.bounding_sphere(&na::one());
+    let r1  = na::norm(bs1.center()) + bs1.radius();
+    let r2  = na::norm(bs2.center()) + bs2.radius();
+
+    conservative_advancement::toi(start1, end1, &r1, start2, end2, &r2, |m1, m2| {
+        let mut simplex: JohnsonSimplex<AnnotatedPoint> = JohnsonSimplex::new_w_tls();
+        let mut dir = m1.translation() - m2.translation();
+
+        if dir.is_zero() {
+            dir.set(0, na::one());
+        }
+
+        simplex.reset(implicit::cso_support_point_without_margin(m1, g1, m2, g2, dir));
+
+        closest_points(m1, g1, m2, g2, &mut simplex)
+    })
+}
@@ -0,0 +1,96 @@
+use std::num::Zero;
+use nalgebra::na::{Translation, Indexable};
+use nalgebra::na;
+use geom::AnnotatedPoint;
+use implicit::Implicit;
+use implicit;
+use narrow::algorithm::simplex::Simplex;
+use narrow::algorithm::gjk;
+use narrow::algorithm::gjk::{NoIntersection, Intersection, Projection};
+use narrow::{ProximityDetector, Proximity, Intersecting, Disjoint};
+use math::{Scalar, Vect, Matrix};
+
+/// Persistent proximity detector between two shapes having a support mapping function.
+///
+/// It is based on the GJK algorithm which stops as soon as the geometries are known to be
+/// separated by more than the margin. No penetration depth is ever computed.
+#[deriving(Encodable, Decodable)]
+pub struct ImplicitImplicitProximity<S, G1, G2> {
+    simplex:   S,
+    margin:    Scalar,
+    proximity: Proximity
+}
+
+impl<S: Clone, G1, G2> Clone for ImplicitImplicitProximity<S, G1, G2> {
+    fn clone(&self) -> ImplicitImplicitProximity<S, G1, G2> {
+        ImplicitImplicitProximity {
+            simplex:   self.simplex.clone(),
+            margin:    self.margin.clone(),
+            proximity: self.proximity.clone()
+        }
+    }
+}
+
+impl<S, G1, G2> ImplicitImplicitProximity<S, G1, G2> {
+    /// Creates a new persistent proximity detector between two geometries with support mapping
+    /// functions.
+    ///
+    /// It is initialized with a pre-created simplex.
+    pub fn new(margin: Scalar, simplex: S) -> ImplicitImplicitProximity<S, G1, G2> {
+        ImplicitImplicitProximity {
+            simplex:   simplex,
+            margin:    margin,
+            proximity: Disjoint
+        }
+    }
+}
+
+impl<S:  Simplex<AnnotatedPoint>,
+     G1: Implicit<Vect, Matrix>,
+     G2: Implicit<Vect, Matrix>>
+ProximityDetector<G1, G2> for ImplicitImplicitProximity<S, G1, G2> {
+    #[inline]
+    fn update(&mut self, ma: &Matrix, a: &G1, mb: &Matrix, b: &G2) {
+        self.proximity = proximity(ma, a, mb, b, &self.margin, &mut self.simplex)
+    }
+
+    #[inline]
+    fn proximity(&self) -> Proximity {
+        self.proximity
+    }
+}
+
+/// Computes the proximity status of two implicit geometries.
+///
+/// # Arguments:
+/// * `g1`      - the first geometry.
+/// * `g2`      - the second geometry.
+/// * `margin`  - the distance under which the geometries are considered close to each other.
+/// * `simplex` - the simplex to be used by the GJK algorithm. It is reset by this function.
+pub fn proximity<S:  Simplex<AnnotatedPoint>,
+                 G1: Implicit<Vect, Matrix>,
+                 G2: Implicit<Vect, Matrix>>(
+                 m1:      &Matrix,
+                 g1:      &G1,
+                 m2:      &Matrix,
+                 g2:      &G2,
+                 margin:  &Scalar,
+                 simplex: &mut S)
+                 -> Proximity {
+    let mut dir = m1.translation() - m2.translation();
+
+    if dir.is_zero() {
+        dir.set(0, na::one());
+    }
+
+    simplex.reset(implicit::cso_support_point_without_margin(m1, g1, m2, g2, dir));
+
+    let margins  = g1.margin() + g2.margin();
+    let max_dist = margins + *margin;
+
+    match gjk::closest_points_without_margin_with_max_dist(m1, g1, m2, g2, &max_dist, simplex) {
+        Projection((p1, p2)) => Proximity::from_distance(&(na::norm(&(p2 - p1)) - margins), margin),
+        Intersection         => Intersecting,
+        NoIntersection(_)    => Disjoint
+    }
+}
@@ -0,0 +1,340 @@
+use std::num::Zero;
+use nalgebra::na::{Translation, Transform, Indexable};
+use nalgebra::na;
+use bounding_volume::{BoundingVolume, LooseBoundingVolume, HasAABB};
+use geom::{AnnotatedPoint, Torus, DEFAULT_TORUS_NSUBDIVS};
+use implicit::{Implicit, PreferedSamplingDirections};
+use implicit;
+use narrow::algorithm::simplex::Simplex;
+use narrow::algorithm::gjk::Projection;
+use narrow::{CollisionDetector, Contact, ClosestPoints};
+use narrow::implicit_implicit;
+use math::{Scalar, Vect, Matrix};
+
+/// Collision detector between a torus and a geometry implementing the `Implicit` trait.
+///
+/// The torus is approximated by a closed ring of `nsubdivs` segments inscribed in its core circle
+/// and inflated by its minor radius. One contact is generated for each run of consecutive
+/// segments touching the other geometry, so that the other geometry may touch the torus at
+/// several places.
+#[deriving(Encodable, Decodable)]
+pub struct TorusImplicit<S, G> {
+    simplex:    S,
+    prediction: Scalar,
+    nsubdivs:   uint,
+    contacts:   Vec<Contact>
+}
+
+impl<S: Clone, G> Clone for TorusImplicit<S, G> {
+    fn clone(&self) -> TorusImplicit<S, G> {
+        TorusImplicit {
+            simplex:    self.simplex.clone(),
+            prediction: self.prediction.clone(),
+            nsubdivs:   self.nsubdivs,
+            contacts:   self.contacts.clone()
+        }
+    }
+}
+
+impl<S, G> TorusImplicit<S, G> {
+    /// Creates a new persistent collision detector between a torus and a geometry with a support
+    /// mapping function.
+    ///
+    /// The core circle of the torus is approximated by `nsubdivs` segments.
+    pub fn new(prediction: Scalar, nsubdivs: uint, simplex: S) -> TorusImplicit<S, G> {
+        assert!(nsubdivs >= 3, "A torus must be approximated by at least 3 segments.");
+
+        TorusImplicit {
+            simplex:    simplex,
+            prediction: prediction,
+            nsubdivs:   nsubdivs,
+            contacts:   Vec::new()
+        }
+    }
+
+    /// The number of segments approximating the core circle of the torus.
+    #[inline]
+    pub fn nsubdivs(&self) -> uint {
+        self.nsubdivs
+    }
+}
+
+impl<S: Simplex<AnnotatedPoint>,
+     G: Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix> + HasAABB>
+CollisionDetector<Torus, G> for TorusImplicit<S, G> {
+    #[inline]
+    fn update(&mut self, ma: &Matrix, a: &Torus, mb: &Matrix, b: &G) {
+        self.contacts.clear();
+
+        collide(ma, a, mb, b, &self.prediction, self.nsubdivs, &mut self.simplex, &mut self.contacts)
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.contacts.len()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        out_colls.push_all(self.contacts.as_slice())
+    }
+
+    #[inline]
+    fn toi(detector: Option<TorusImplicit<S, G>>,
+           ma:       &Matrix,
+           dir:      &Vect,
+           _:        &Scalar,
+           a:        &Torus,
+           mb:       &Matrix,
+           b:        &G) -> Option<Scalar> {
+        let nsubdivs = detector.map_or(DEFAULT_TORUS_NSUBDIVS, |d| d.nsubdivs);
+
+        toi(ma, dir, a, mb, b, nsubdivs)
+    }
+}
+
+/// Collision detector between a geometry implementing the `Implicit` trait and a torus.
+///
+/// See `TorusImplicit` for details about the approximation of the torus.
+#[deriving(Encodable, Decodable, Clone)]
+pub struct ImplicitTorus<S, G> {
+    sub_detector: TorusImplicit<S, G>
+}
+
+impl<S, G> ImplicitTorus<S, G> {
+    /// Creates a new persistent collision detector between a geometry with a support mapping
+    /// function and a torus.
+    ///
+    /// The core circle of the torus is approximated by `nsubdivs` segments.
+    pub fn new(prediction: Scalar, nsubdivs: uint, simplex: S) -> ImplicitTorus<S, G> {
+        ImplicitTorus {
+            sub_detector: TorusImplicit::new(prediction, nsubdivs, simplex)
+        }
+    }
+}
+
+impl<S: Simplex<AnnotatedPoint>,
+     G: Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix> + HasAABB>
+CollisionDetector<G, Torus> for ImplicitTorus<S, G> {
+    #[inline]
+    fn update(&mut self, ma: &Matrix, a: &G, mb: &Matrix, b: &Torus) {
+        self.sub_detector.update(mb, b, ma, a);
+
+        for c in self.sub_detector.contacts.mut_iter() {
+            c.flip()
+        }
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.sub_detector.num_colls()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        self.sub_detector.colls(out_colls)
+    }
+
+    #[inline]
+    fn toi(detector: Option<ImplicitTorus<S, G>>,
+           ma:       &Matrix,
+           dir:      &Vect,
+           _:        &Scalar,
+           a:        &G,
+           mb:       &Matrix,
+           b:        &Torus) -> Option<Scalar> {
+        let nsubdivs = detector.map_or(DEFAULT_TORUS_NSUBDIVS, |d| d.sub_detector.nsubdivs);
+
+        toi(mb, &-*dir, b, ma, a, nsubdivs)
+    }
+}
+
+/// Computes the contact points between a torus and an implicit geometry.
+///
+/// The contacts are pushed on `out`. See `TorusImplicit` for details about the approximation of the
+/// torus.
+pub fn collide<S: Simplex<AnnotatedPoint>,
+               G: Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix> + HasAABB>(
+               mtorus:     &Matrix,
+               torus:      &Torus,
+               mother:     &Matrix,
+               other:      &G,
+               prediction: &Scalar,
+               nsubdivs:   uint,
+               simplex:    &mut S,
+               out:        &mut Vec<Contact>) {
+    let ls_mother  = na::inv(mtorus).expect("The torus transformation must be inversible.") * *mother;
+    let other_aabb = other.aabb(&ls_mother).loosened(prediction.clone());
+    let start      = out.len();
+
+    let mut first_hit = false;
+    let mut prev_hit  = false;
+
+    for i in range(0u, nsubdivs) {
+        let chord = torus.chord(nsubdivs, i);
+        let mut hit = false;
+
+        if chord.aabb(&na::one()).intersects(&other_aabb) {
+            match implicit_implicit::collide(mtorus, &chord, mother, other, prediction, simplex, None) {
+                Projection(c) => {
+                    hit = true;
+
+                    if prev_hit {
+                        // Consecutive chords touch the other geometry at the same place.
+                        let last = out.mut_last().unwrap();
+
+                        if c.depth > last.depth {
+                            *last = c
+                        }
+                    }
+                    else {
+                        out.push(c)
+                    }
+                },
+                _ => { }
+            }
+        }
+
+        if i == 0 {
+            first_hit = hit;
+        }
+
+        prev_hit = hit;
+    }
+
+    // The first and the last chords are adjacent too.
+    if first_hit && prev_hit && out.len() - start > 1 {
+        let last = out.pop().unwrap();
+
+        if last.depth > out.get(start).depth {
+            *out.get_mut(start) = last
+        }
+    }
+}
+
+/// Computes the signed distance and the closest points between a torus and an implicit geometry.
+///
+/// Those are the closest points between the other geometry and the closest of the `nsubdivs`
+/// rounded segments approximating the torus. See `TorusImplicit` for details about this
+/// approximation.
+pub fn closest_points<S: Simplex<AnnotatedPoint>,
+                      G: Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>>(
+                      mtorus:   &Matrix,
+                      torus:    &Torus,
+                      mother:   &Matrix,
+                      other:    &G,
+                      nsubdivs: uint,
+                      simplex:  &mut S)
+                      -> Option<ClosestPoints> {
+    let mut res: Option<ClosestPoints> = None;
+
+    for i in range(0u, nsubdivs) {
+        let chord   = torus.chord(nsubdivs, i);
+        let center  = mtorus.transform(&((*chord.a() + *chord.b()) * na::cast::<f32, Scalar>(0.5)));
+        let mut dir = center - mother.translation();
+
+        if dir.is_zero() {
+            dir.set(0, na::one());
+        }
+
+        simplex.reset(implicit::cso_support_point_without_margin(mtorus, &chord, mother, other, dir));
+
+        match implicit_implicit::closest_points(mtorus, &chord, mother, other, simplex) {
+            Some(cp) => {
+                if res.as_ref().map_or(true, |best| cp.distance < best.distance) {
+                    res = Some(cp)
+                }
+            },
+            None => { }
+        }
+    }
+
+    res
+}
+
+/// Computes the Time Of Impact of a torus and an implicit geometry.
+///
+/// Arguments:
+/// * `mtorus`   - the torus transform.
+/// * `dir`      - the direction of the torus movement.
+/// * `torus`    - the torus.
+/// * `mother`   - the other geometry transform.
+/// * `other`    - the other geometry.
+/// * `nsubdivs` - the number of segments approximating the core circle of the torus.
+pub fn toi<G: Implicit<Vect, Matrix>>(mtorus:   &Matrix,
+                                      dir:      &Vect,
+                                      torus:    &Torus,
+                                      mother:   &Matrix,
+                                      other:    &G,
+                                      nsubdivs: uint)
+                                      -> Option<Scalar> {
+    let mut res: Option<Scalar> = None;
+
+    for i in range(0u, nsubdivs) {
+        let chord = torus.chord(nsubdivs, i);
+
+        match implicit_implicit::toi(mtorus, dir, &chord, mother, other) {
+            Some(t) => {
+                if res.map_or(true, |best| t < best) {
+                    res = Some(t)
+                }
+            },
+            None => { }
+        }
+    }
+
+    res
+}
+
+#[cfg(test, f64)]
+mod test {
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::{AnnotatedPoint, Cuboid, Torus};
+    use narrow::algorithm::johnson_simplex::JohnsonSimplex;
+    use super::collide;
+
+    #[test]
+    fn test_torus_implicit_adjacent_chords() {
+        let torus = Torus::new(2.0f64, 0.25);
+        let cube  = Cuboid::new(Vec3::new(0.2f64, 0.2, 0.2));
+        let ident = Iso3::new(na::zero(), na::zero());
+
+        let mut simplex: JohnsonSimplex<AnnotatedPoint> = JohnsonSimplex::new_w_tls();
+        let mut out = Vec::new();
+
+        // The cube touches the ends shared by the 2nd and the 3rd chords.
+        collide(&ident, &torus, &Iso3::new(Vec3::new(0.0f64, 0.0, 2.4), na::zero()), &cube, &0.0,
+                8, &mut simplex, &mut out);
+
+        assert!(out.len() == 1);
+        assert!(na::approx_eq_eps(&out.get(0).depth, &0.05, &1.0e-3));
+        assert!(na::approx_eq_eps(&out.get(0).normal, &Vec3::new(0.0f64, 0.0, 1.0), &1.0e-3));
+
+        // The cube touches the ends shared by the last and the first chords.
+        out.clear();
+        collide(&ident, &torus, &Iso3::new(Vec3::new(2.4f64, 0.0, 0.0), na::zero()), &cube, &0.0,
+                8, &mut simplex, &mut out);
+
+        assert!(out.len() == 1);
+        assert!(na::approx_eq_eps(&out.get(0).depth, &0.05, &1.0e-3));
+        assert!(na::approx_eq_eps(&out.get(0).normal, &Vec3::new(1.0f64, 0.0, 0.0), &1.0e-3));
+    }
+
+    #[test]
+    fn test_torus_implicit_several_places() {
+        let torus = Torus::new(2.0f64, 0.25);
+        let bar   = Cuboid::new(Vec3::new(3.0f64, 0.2, 0.2));
+        let ident = Iso3::new(na::zero(), na::zero());
+
+        let mut simplex: JohnsonSimplex<AnnotatedPoint> = JohnsonSimplex::new_w_tls();
+        let mut out = Vec::new();
+
+        // The bar crosses the torus at both ends of a diameter, each place being covered by two
+        // chords. The first of those places wraps around the ring of chords.
+        collide(&ident, &torus, &ident, &bar, &0.0, 8, &mut simplex, &mut out);
+
+        assert!(out.len() == 2);
+        assert!(out.get(0).world1.x > 1.0 && out.get(1).world1.x < -1.0);
+    }
+}
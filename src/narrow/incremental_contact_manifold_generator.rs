@@ -1,3 +1,4 @@
+use std::mem;
 use nalgebra::na::Transform;
 use nalgebra::na;
 use narrow::{CollisionDetector, Contact};
@@ -28,6 +29,9 @@ impl ContactWLocals {
 /// maximum number of contact is reached, each time a new contact is created, the new manifold is
 /// computed by maximizing the variance along each canonical axis (of the space in which leaves the
 /// contacts).
+///
+/// A new contact between the same features as an existing one replaces it. Thus, contacts
+/// identified by their features persist from one update to the next.
 #[deriving(Encodable, Decodable, Clone)]
 pub struct IncrementalContactManifoldGenerator<CD> {
     contacts:     Vec<ContactWLocals>,
@@ -49,6 +53,32 @@ impl<CD> IncrementalContactManifoldGenerator<CD> {
             sub_detector: cd
         }
     }
+
+    /// Whether this manifold contains a contact between the same features as `c`.
+    pub fn has_contact_with_same_features(&self, c: &Contact) -> bool {
+        self.contacts.iter().any(|old| c.has_same_features(&old.contact))
+    }
+
+    /// Adds a contact to this manifold.
+    ///
+    /// If the manifold already contains a contact between the same features, it is replaced.
+    pub fn add_contact(&mut self, c: Contact, m1: &Matrix, m2: &Matrix) {
+        let _max_num_contact = (na::dim::<Vect>() - 1) * 2;
+
+        match self.contacts.iter().position(|old| c.has_same_features(&old.contact)) {
+            Some(i) => {
+                *self.contacts.get_mut(i) = ContactWLocals::new_with_contact(c, m1, m2);
+            },
+            None => {
+                if self.contacts.len() == _max_num_contact {
+                    add_reduce_by_variance(self.contacts.as_mut_slice(), c, m1, m2)
+                }
+                else {
+                    self.contacts.push(ContactWLocals::new_with_contact(c, m1, m2))
+                }
+            }
+        }
+    }
 }
 
 impl<CD: CollisionDetector<G1, G2>, G1, G2> IncrementalContactManifoldGenerator<CD> {
@@ -70,26 +100,26 @@ impl<CD: CollisionDetector<G1, G2>, G1, G2> IncrementalContactManifoldGenerator<
         res
     }
 
+    /// Gets all the collisions from the sub-detector used by this manifold generator. This does
+    /// not update the manifold itself.
+    pub fn get_sub_collisions(&mut self, m1: &Matrix, g1: &G1, m2: &Matrix, g2: &G2, out: &mut Vec<Contact>) {
+        self.sub_detector.update(m1, g1, m2, g2);
+        self.sub_detector.colls(out);
+    }
+
     /// Updates the current manifold by adding one point.
     pub fn add_new_contacts(&mut self, m1: &Matrix, g1: &G1, m2: &Matrix, g2: &G2) {
         // add the new ones
-        self.sub_detector.update(m1, g1, m2, g2);
-
-        self.sub_detector.colls(&mut self.collector);
+        let mut collector = mem::replace(&mut self.collector, Vec::new());
 
-        // remove duplicates
-        let _max_num_contact = (na::dim::<Vect>() - 1) * 2;
+        self.get_sub_collisions(m1, g1, m2, g2, &mut collector);
 
-        for c in self.collector.iter() {
-            if self.contacts.len() == _max_num_contact {
-                add_reduce_by_variance(self.contacts.as_mut_slice(), c.clone(), m1, m2)
-            }
-            else {
-                self.contacts.push(ContactWLocals::new_with_contact(c.clone(), m1, m2))
-            }
+        for c in collector.iter() {
+            self.add_contact(c.clone(), m1, m2)
         }
 
-        self.collector.clear();
+        collector.clear();
+        self.collector = collector;
     }
 
     /// Updates the contacts already existing on this manifold.
@@ -203,3 +233,49 @@ fn approx_variance(pts: &[ContactWLocals], to_add: &Contact, to_ignore: uint) ->
 
     sum
 }
+
+#[cfg(test, dim3, f64)]
+mod test {
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::{Cone, Cuboid, AnnotatedPoint};
+    use narrow::{CollisionDetector, ImplicitImplicit, Vertex};
+    use narrow::algorithm::johnson_simplex::JohnsonSimplex;
+    use super::IncrementalContactManifoldGenerator;
+
+    #[test]
+    fn test_contact_persistence() {
+        // A cone standing on its apex on a box.
+        let cone     = Cone::new(0.5f64, 0.5);
+        let cuboid   = Cuboid::new(Vec3::new(2.0f64, 2.0, 2.0));
+        let mcuboid  = Iso3::new(na::zero(), na::zero());
+        let simplex: JohnsonSimplex<AnnotatedPoint> = JohnsonSimplex::new_w_tls();
+        let mut icmg = IncrementalContactManifoldGenerator::new(0.1, ImplicitImplicit::new(0.1, simplex));
+
+        let mcone = Iso3::new(Vec3::new(0.3f64, 2.55, 0.2), Vec3::new(Float::pi(), 0.0, 0.0));
+        let mut first = Vec::new();
+
+        icmg.update(&mcone, &cone, &mcuboid, &cuboid);
+        icmg.colls(&mut first);
+
+        assert!(first.len() == 1);
+        match first[0].feature1.feature {
+            Vertex(_) => { },
+            _         => fail!("The cone apex should be a vertex.")
+        }
+
+        assert!(first[0].feature2.is_known());
+
+        // The cone slides a little: the new contact is between the same features and replaces the
+        // old one instead of being added to the manifold.
+        let mcone = Iso3::new(Vec3::new(0.31f64, 2.55, 0.2), Vec3::new(Float::pi(), 0.0, 0.0));
+        let mut second = Vec::new();
+
+        icmg.update(&mcone, &cone, &mcuboid, &cuboid);
+        icmg.colls(&mut second);
+
+        assert!(second.len() == 1);
+        assert!(second[0].has_same_features(&first[0]));
+        assert!(na::approx_eq(&second[0].world1.x, &0.31));
+    }
+}
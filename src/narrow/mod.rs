@@ -1,53 +1,119 @@
 //! Narrow phases.
 
 // types and traits
-pub use narrow::collision_detector::{CollisionDetector, Contact};
+pub use narrow::collision_detector::{CollisionDetector, Contact, ClosestPoints, FeatureId, Feature,
+                                     Vertex, Edge, Face, UnknownFeature};
 pub use narrow::empty::Empty;
 pub use narrow::ball_ball::BallBall;
+pub use narrow::cuboid_cuboid::CuboidCuboid;
+pub use narrow::ball_capsule::{BallCapsule, CapsuleBall};
+pub use narrow::capsule_capsule::CapsuleCapsule;
+pub use narrow::ball_segment::{BallSegment, SegmentBall};
 pub use narrow::plane_implicit::{PlaneImplicit, ImplicitPlane};
-pub use narrow::implicit_implicit::ImplicitImplicit;
+pub use narrow::implicit_implicit::{ImplicitImplicit, PenetrationAlgorithm, MinkowskiSampling,
+                                    ExpandingPolytope};
 pub use narrow::incremental_contact_manifold_generator::IncrementalContactManifoldGenerator;
 pub use narrow::one_shot_contact_manifold_generator::OneShotContactManifoldGenerator;
 pub use narrow::geom_geom::{DynamicCollisionDetector, GeomGeomCollisionDetector, GeomGeomDispatcher,
-                            CollisionDetectorFactory};
+                            CollisionDetectorFactory, ClosestPointsFn, TOIFn};
 pub use narrow::concave_geom_geom::{ConcaveGeomGeom, GeomConcaveGeom, ConcaveGeomGeomFactory,
                                     GeomConcaveGeomFactory};
+pub use narrow::concave_geom_concave_geom::{ConcaveGeomConcaveGeom, ConcaveGeomConcaveGeomFactory};
 pub use narrow::bezier_surface_ball::{BallBezierSurface, BezierSurfaceBall};
 pub use narrow::bezier_surface_bezier_surface::BezierSurfaceBezierSurface;
+pub use narrow::proximity_detector::{ProximityDetector, Proximity, Intersecting, WithinMargin,
+                                     Disjoint, ProximityTransition};
+pub use narrow::ball_proximity::BallGeomProximity;
+pub use narrow::plane_implicit_proximity::PlaneImplicitProximity;
+pub use narrow::implicit_implicit_proximity::ImplicitImplicitProximity;
+pub use narrow::concave_geom_geom_proximity::{ConcaveGeomGeomProximity,
+                                              ConcaveGeomGeomProximityFactory};
+pub use narrow::geom_geom_proximity::{GeomGeomProximityDetector, DynamicProximityDetector,
+                                      GeomGeomProximityDispatcher, ProximityDetectorFactory};
+
+/// Collision and proximity detectors involving a torus.
+///
+/// Tori being defined in 3d only, those are not available in lower or higher dimensions.
+#[dim3]
+pub mod torus {
+    pub use narrow::ball_torus::{BallTorus, TorusBall};
+    pub use narrow::plane_torus::{PlaneTorus, TorusPlane};
+    pub use narrow::implicit_torus::{TorusImplicit, ImplicitTorus};
+    pub use narrow::torus_proximity::{PlaneTorusProximity, TorusImplicitProximity};
+}
 
 // functions
 /// Functions to compute the time of impact between two geometries.
 pub mod toi {
     pub use narrow::ball_ball::toi                    as ball_ball;
+    pub use narrow::ball_capsule::toi                 as ball_capsule;
     pub use narrow::plane_implicit::toi               as plane_implicit;
     pub use narrow::implicit_implicit::toi            as implicit_implicit;
     pub use narrow::implicit_implicit::toi_and_normal as implicit_implicit_and_normal;
+    pub use narrow::concave_geom_geom::toi            as concave_geom_geom;
+    pub use narrow::implicit_implicit::nonlinear_toi  as implicit_implicit_nonlinear;
 }
 
 /// Functions to compute one contact point between two geometries.
 pub mod collide {
     pub use narrow::ball_ball::collide         as ball_ball;
+    pub use narrow::cuboid_cuboid::collide     as cuboid_cuboid;
+    pub use narrow::ball_capsule::collide      as ball_capsule;
+    pub use narrow::capsule_capsule::collide   as capsule_capsule;
+    pub use narrow::ball_segment::collide      as ball_segment;
     pub use narrow::plane_implicit::collide    as plane_implicit;
     pub use narrow::implicit_implicit::collide as implicit_implicit;
+    pub use narrow::implicit_implicit::collide_with_penetration_algorithm
+            as implicit_implicit_with_penetration_algorithm;
 }
 
 /// Functions to compute the closest points between two geometries.
 pub mod closest_points {
-    pub use narrow::ball_ball::closest_points as ball_ball;
+    pub use narrow::ball_ball::closest_points         as ball_ball;
+    pub use narrow::plane_implicit::closest_points    as plane_implicit;
+    pub use narrow::implicit_implicit::closest_points as implicit_implicit;
+    pub use narrow::concave_geom_geom::closest_points as concave_geom_geom;
+}
+
+/// Functions to compute the proximity status of two geometries.
+pub mod proximity {
+    pub use narrow::ball_proximity::proximity              as ball_geom;
+    pub use narrow::plane_implicit_proximity::proximity    as plane_implicit;
+    pub use narrow::implicit_implicit_proximity::proximity as implicit_implicit;
 }
 
 // modules
 mod collision_detector;
 mod empty;
 mod ball_ball;
+mod cuboid_cuboid;
+mod ball_capsule;
+mod capsule_capsule;
+mod ball_segment;
+#[dim3]
+mod ball_torus;
+#[dim3]
+mod plane_torus;
+#[dim3]
+mod implicit_torus;
 mod plane_implicit;
 mod implicit_implicit;
 mod incremental_contact_manifold_generator;
 mod one_shot_contact_manifold_generator;
 mod concave_geom_geom;
+mod concave_geom_concave_geom;
 mod geom_geom;
-mod bezier_surface_ball;
+#[doc(hidden)]
+pub mod bezier_surface_ball;
 mod bezier_surface_bezier_surface;
+mod proximity_detector;
+mod ball_proximity;
+mod plane_implicit_proximity;
+mod implicit_implicit_proximity;
+#[dim3]
+mod torus_proximity;
+mod concave_geom_geom_proximity;
+mod geom_geom_proximity;
 
 // FIXME: move this module somewhere else!
 /// Algorithms needed for distance and penetration depth computation.
@@ -58,6 +124,8 @@ pub mod algorithm
     pub mod brute_force_simplex;
     pub mod gjk;
     pub mod minkowski_sampling;
+    pub mod epa;
+    pub mod conservative_advancement;
 }
 
 // FIXME: move those modules somewhere else!
@@ -7,21 +7,42 @@ use math::Orientation;
 ///
 /// Whenever a new contact is detected (i.e. when the current manifold is empty) a full manifold is
 /// generated. Then, the manifold is incrementally updated by an
-/// `IncrementalContactManifoldGenerator`.
+/// `IncrementalContactManifoldGenerator`. A contact found by a perturbation is dropped if the
+/// manifold already has a contact between the same features, e.g., the one found without
+/// perturbation. Contacts on smooth parts of the geometries (e.g. a ball) have unknown features and
+/// are never merged.
 #[deriving(Encodable, Decodable, Clone)]
 pub struct OneShotContactManifoldGenerator<CD> {
-    sub_detector: IncrementalContactManifoldGenerator<CD>
+    sub_detector: IncrementalContactManifoldGenerator<CD>,
+    collector:    Vec<Contact>
 }
 
 impl<CD> OneShotContactManifoldGenerator<CD> {
     /// Creates a new one shot contact manifold generator.
     pub fn new(prediction: Scalar, cd: CD) -> OneShotContactManifoldGenerator<CD> {
         OneShotContactManifoldGenerator {
-            sub_detector: IncrementalContactManifoldGenerator::new(prediction, cd)
+            sub_detector: IncrementalContactManifoldGenerator::new(prediction, cd),
+            collector:    Vec::new()
         }
     }
 }
 
+impl<CD: CollisionDetector<G1, G2>, G1, G2> OneShotContactManifoldGenerator<CD> {
+    /// Adds the contacts found with the first geometry at the perturbed position `m1`, except the
+    /// ones between the same features as a contact already on the manifold.
+    fn add_perturbed_contacts(&mut self, m1: &Matrix, g1: &G1, m2: &Matrix, g2: &G2) {
+        self.sub_detector.get_sub_collisions(m1, g1, m2, g2, &mut self.collector);
+
+        for c in self.collector.iter() {
+            if !self.sub_detector.has_contact_with_same_features(c) {
+                self.sub_detector.add_contact(c.clone(), m1, m2)
+            }
+        }
+
+        self.collector.clear();
+    }
+}
+
 #[not_dim4]
 impl<CD: CollisionDetector<G1, G2>, G1, G2>
 CollisionDetector<G1, G2> for OneShotContactManifoldGenerator<CD> {
@@ -30,6 +51,8 @@ CollisionDetector<G1, G2> for OneShotContactManifoldGenerator<CD> {
             // do the one-shot manifold generation
             match self.sub_detector.get_sub_collision(m1, g1, m2, g2) {
                 Some(coll) => {
+                    self.sub_detector.add_contact(coll.clone(), m1, m2);
+
                     na::orthonormal_subspace_basis(&coll.normal, |b| {
                         let mut rot_axis: Orientation = na::cross(&coll.normal, &b);
 
@@ -38,12 +61,12 @@ CollisionDetector<G1, G2> for OneShotContactManifoldGenerator<CD> {
 
                         let rot_mat: Matrix = na::append_rotation_wrt_point(m1, &rot_axis, &coll.world1);
 
-                        self.sub_detector.add_new_contacts(&rot_mat, g1, m2, g2);
+                        self.add_perturbed_contacts(&rot_mat, g1, m2, g2);
 
                         // second perturbation (opposite direction)
                         let rot_mat = na::append_rotation_wrt_point(m1, &-rot_axis, &coll.world1);
 
-                        self.sub_detector.add_new_contacts(&rot_mat, g1, m2, g2);
+                        self.add_perturbed_contacts(&rot_mat, g1, m2, g2);
 
                         true
                     });
@@ -109,3 +132,36 @@ CollisionDetector<G1, G2> for OneShotContactManifoldGenerator<CD> {
         CollisionDetector::toi(None::<CD>, m1, dir, dist, g1, m2, g2)
     }
 }
+
+#[cfg(test, dim3, f64)]
+mod test {
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::{Cone, Cuboid, AnnotatedPoint};
+    use narrow::{CollisionDetector, ImplicitImplicit, FeatureId, Vertex, Face};
+    use narrow::algorithm::johnson_simplex::JohnsonSimplex;
+    use super::OneShotContactManifoldGenerator;
+
+    #[test]
+    fn test_perturbed_contacts_matching() {
+        // A cone standing on its apex on a box: every perturbation finds the same pair of features.
+        let cone     = Cone::new(0.5f64, 0.5);
+        let cuboid   = Cuboid::new(Vec3::new(2.0f64, 2.0, 2.0));
+        let mcone    = Iso3::new(Vec3::new(0.3f64, 2.55, 0.2), Vec3::new(Float::pi(), 0.0, 0.0));
+        let mcuboid  = Iso3::new(na::zero(), na::zero());
+        let simplex: JohnsonSimplex<AnnotatedPoint> = JohnsonSimplex::new_w_tls();
+        let mut oscmg = OneShotContactManifoldGenerator::new(0.1, ImplicitImplicit::new(0.1, simplex));
+        let mut contacts = Vec::new();
+
+        oscmg.update(&mcone, &cone, &mcuboid, &cuboid);
+        oscmg.colls(&mut contacts);
+
+        assert!(contacts.len() == 1);
+        assert!(contacts[0].feature1 == FeatureId::new(Vertex(0)));
+        assert!(na::approx_eq(&contacts[0].normal, &Vec3::new(0.0f64, -1.0, 0.0)));
+        assert!(na::approx_eq(&contacts[0].depth, &0.03));
+
+        // The top face of the box: only the second axis is fixed, to its positive extremity.
+        assert!(contacts[0].feature2 == FeatureId::new(Face(2 + 0 * 3 + 2 * 9)));
+    }
+}
@@ -1,6 +1,6 @@
 use nalgebra::na::{Translation, Rotate};
 use nalgebra::na;
-use narrow::{CollisionDetector, Contact};
+use narrow::{CollisionDetector, Contact, ClosestPoints, FeatureId, Face};
 use geom::Plane;
 use implicit::Implicit;
 use ray::{Ray, RayCast};
@@ -148,6 +148,8 @@ impl<G: Implicit<Vect, Matrix>> CollisionDetector<G, Plane> for ImplicitPlane<G>
 
 /// Same as `update_collide_plane_implicit_shape` but the existing collision or `None`.
 ///
+/// The feature of the other geometry is the one supporting the opposite of the plane normal.
+///
 /// # Arguments:
 /// * `plane` - the plane to test.
 /// * `other` - the object to test against the plane.
@@ -165,15 +167,36 @@ pub fn collide<G: Implicit<Vect, Matrix>>(
     let dist = na::dot(&plane_normal, &(plane_center - deepest));
 
     if dist > -prediction {
-        let c1 = deepest + plane_normal * dist;
+        let c1      = deepest + plane_normal * dist;
+        let feature = other.support_feature_id(mother, &-plane_normal);
 
-        Some(Contact::new(c1, deepest, plane_normal, dist))
+        Some(Contact::new_with_features(c1, deepest, plane_normal, dist, FeatureId::new(Face(0)), feature))
     }
     else {
         None
     }
 }
 
+/// Computes the signed distance and the closest points between a plane and a geometry.
+///
+/// # Arguments:
+/// * `plane` - the plane to test.
+/// * `other` - the object to test against the plane.
+pub fn closest_points<G: Implicit<Vect, Matrix>>(
+                      mplane: &Matrix,
+                      plane:  &Plane,
+                      mother: &Matrix,
+                      other:  &G)
+                      -> ClosestPoints {
+    let plane_normal = mplane.rotate(&plane.normal());
+    let plane_center = mplane.translation();
+    let deepest      = other.support_point(mother, &-plane_normal);
+
+    let dist = na::dot(&plane_normal, &(deepest - plane_center));
+
+    ClosestPoints::new(deepest - plane_normal * dist, deepest, dist)
+}
+
 /// Computes the Time Of Impact of a geometry and a plane.
 ///
 /// Arguments:
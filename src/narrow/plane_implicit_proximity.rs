@@ -0,0 +1,64 @@
+use nalgebra::na::{Translation, Rotate};
+use nalgebra::na;
+use geom::Plane;
+use implicit::Implicit;
+use narrow::{ProximityDetector, Proximity, Disjoint};
+use math::{Scalar, Vect, Matrix};
+
+/// Proximity detector between a plane and a geometry implementing the `Implicit` trait.
+#[deriving(Encodable, Decodable)]
+pub struct PlaneImplicitProximity<G> {
+    margin:    Scalar,
+    proximity: Proximity
+}
+
+impl<G> Clone for PlaneImplicitProximity<G> {
+    fn clone(&self) -> PlaneImplicitProximity<G> {
+        PlaneImplicitProximity {
+            margin:    self.margin.clone(),
+            proximity: self.proximity.clone()
+        }
+    }
+}
+
+impl<G> PlaneImplicitProximity<G> {
+    /// Creates a new persistent proximity detector between a plane and a geometry with a support
+    /// mapping function.
+    #[inline]
+    pub fn new(margin: Scalar) -> PlaneImplicitProximity<G> {
+        PlaneImplicitProximity {
+            margin:    margin,
+            proximity: Disjoint
+        }
+    }
+}
+
+impl<G: Implicit<Vect, Matrix>> ProximityDetector<Plane, G> for PlaneImplicitProximity<G> {
+    #[inline]
+    fn update(&mut self, ma: &Matrix, plane: &Plane, mb: &Matrix, b: &G) {
+        self.proximity = proximity(ma, plane, mb, b, &self.margin)
+    }
+
+    #[inline]
+    fn proximity(&self) -> Proximity {
+        self.proximity
+    }
+}
+
+/// Computes the proximity status of a plane and a geometry.
+///
+/// Only the point of `other` which is the deepest along the plane normal is tested.
+pub fn proximity<G: Implicit<Vect, Matrix>>(mplane: &Matrix,
+                                            plane:  &Plane,
+                                            mother: &Matrix,
+                                            other:  &G,
+                                            margin: &Scalar)
+                                            -> Proximity {
+    let plane_normal = mplane.rotate(&plane.normal());
+    let plane_center = mplane.translation();
+    let deepest      = other.support_point(mother, &-plane_normal);
+
+    let dist = na::dot(&plane_normal, &(deepest - plane_center));
+
+    Proximity::from_distance(&dist, margin)
+}
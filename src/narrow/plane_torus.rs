@@ -0,0 +1,264 @@
+use std::num::Zero;
+use nalgebra::na::{Vec3, Translation, Rotate, Transform};
+use nalgebra::na;
+use geom::{Plane, Torus};
+use narrow::{CollisionDetector, Contact, ClosestPoints, FeatureId, Face};
+use ray::{Ray, RayCast};
+use math::{Scalar, Vect, Matrix};
+
+/// Collision detector between a plane and a torus.
+///
+/// This detector generates only one contact point. For a full manifold generation, see
+/// `IncrementalContactManifoldGenerator`.
+#[deriving(Encodable, Decodable)]
+pub struct PlaneTorus {
+    prediction: Scalar,
+    contact:    Option<Contact>
+}
+
+impl Clone for PlaneTorus {
+    fn clone(&self) -> PlaneTorus {
+        PlaneTorus {
+            prediction: self.prediction.clone(),
+            contact:    self.contact.clone()
+        }
+    }
+}
+
+impl PlaneTorus {
+    /// Creates a new persistent collision detector between a plane and a torus.
+    #[inline]
+    pub fn new(prediction: Scalar) -> PlaneTorus {
+        PlaneTorus {
+            prediction: prediction,
+            contact:    None
+        }
+    }
+}
+
+impl CollisionDetector<Plane, Torus> for PlaneTorus {
+    #[inline]
+    fn update(&mut self, ma: &Matrix, plane: &Plane, mb: &Matrix, b: &Torus) {
+        self.contact = collide(ma, plane, mb, b, &self.prediction)
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        match self.contact {
+            None    => 0,
+            Some(_) => 1
+        }
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        match self.contact {
+            Some(ref c) => out_colls.push(c.clone()),
+            None        => ()
+        }
+    }
+
+    #[inline]
+    fn toi(_:     Option<PlaneTorus>,
+           ma:    &Matrix,
+           dir:   &Vect,
+           _:     &Scalar,
+           plane: &Plane,
+           mb:    &Matrix,
+           b:     &Torus) -> Option<Scalar> {
+        toi(ma, plane, mb, &-dir, b)
+    }
+}
+
+/// Collision detector between a torus and a plane.
+///
+/// This detector generates only one contact point. For a full manifold generation, see
+/// `IncrementalContactManifoldGenerator`.
+#[deriving(Encodable, Decodable, Clone)]
+pub struct TorusPlane {
+    sub_detector: PlaneTorus
+}
+
+impl TorusPlane {
+    /// Creates a new persistent collision detector between a torus and a plane.
+    #[inline]
+    pub fn new(prediction: Scalar) -> TorusPlane {
+        TorusPlane {
+            sub_detector: PlaneTorus::new(prediction)
+        }
+    }
+}
+
+impl CollisionDetector<Torus, Plane> for TorusPlane {
+    #[inline]
+    fn update(&mut self, ma: &Matrix, a: &Torus, mb: &Matrix, plane: &Plane) {
+        self.sub_detector.update(mb, plane, ma, a);
+
+        match self.sub_detector.contact {
+            Some(ref mut c) => c.flip(),
+            None            => { }
+        }
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.sub_detector.num_colls()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        self.sub_detector.colls(out_colls)
+    }
+
+    #[inline]
+    fn toi(_:     Option<TorusPlane>,
+           ma:    &Matrix,
+           dir:   &Vect,
+           _:     &Scalar,
+           a:     &Torus,
+           mb:    &Matrix,
+           plane: &Plane) -> Option<Scalar> {
+        toi(mb, plane, ma, dir, a)
+    }
+}
+
+/// Computes the point of a transformed torus which is the furthest along a given direction.
+///
+/// The torus is not convex, so this is the support point of its convex hull.
+pub fn torus_support_point(m: &Matrix, torus: &Torus, dir: &Vect) -> Vect {
+    let local_dir = m.inv_rotate(dir);
+    let sqlen     = local_dir.x * local_dir.x + local_dir.z * local_dir.z;
+    let radial    =
+        if sqlen.is_zero() {
+            // The whole core circle is extremal: pick any of its points.
+            Vec3::new(torus.major_radius(), na::zero(), na::zero())
+        }
+        else {
+            Vec3::new(local_dir.x, na::zero(), local_dir.z) * (torus.major_radius() / sqlen.sqrt())
+        };
+
+    m.transform(&(radial + na::normalize(&local_dir) * torus.minor_radius()))
+}
+
+/// Computes the contact point between a plane and a torus.
+pub fn collide(mplane:     &Matrix,
+               plane:      &Plane,
+               mtorus:     &Matrix,
+               torus:      &Torus,
+               prediction: &Scalar)
+               -> Option<Contact> {
+    let plane_normal = mplane.rotate(&plane.normal());
+    let plane_center = mplane.translation();
+    let deepest      = torus_support_point(mtorus, torus, &-plane_normal);
+
+    let dist = na::dot(&plane_normal, &(plane_center - deepest));
+
+    if dist > -*prediction {
+        let c1 = deepest + plane_normal * dist;
+
+        // The torus surface is smooth: like a ball, it is considered to have a single face.
+        Some(Contact::new_with_features(c1, deepest, plane_normal, dist, FeatureId::new(Face(0)), FeatureId::new(Face(0))))
+    }
+    else {
+        None
+    }
+}
+
+/// Computes the signed distance and the closest points between a plane and a torus.
+pub fn closest_points(mplane: &Matrix, plane: &Plane, mtorus: &Matrix, torus: &Torus) -> ClosestPoints {
+    let plane_normal = mplane.rotate(&plane.normal());
+    let plane_center = mplane.translation();
+    let deepest      = torus_support_point(mtorus, torus, &-plane_normal);
+
+    let dist = na::dot(&plane_normal, &(deepest - plane_center));
+
+    ClosestPoints::new(deepest - plane_normal * dist, deepest, dist)
+}
+
+/// Computes the Time Of Impact of a torus and a plane.
+///
+/// Arguments:
+/// * `mplane` - the plane transform.
+/// * `plane`  - the plane.
+/// * `mtorus` - the torus transform.
+/// * `dir`    - the direction of the torus movement.
+/// * `torus`  - the torus.
+pub fn toi(mplane: &Matrix, plane: &Plane, mtorus: &Matrix, dir: &Vect, torus: &Torus) -> Option<Scalar> {
+    let plane_normal  = mplane.rotate(&plane.normal());
+    let closest_point = torus_support_point(mtorus, torus, &-plane_normal);
+
+    plane.toi_with_transform_and_ray(mplane, &Ray::new(closest_point, dir.clone()), true)
+}
+
+#[cfg(test, f64)]
+mod test {
+    use std::num::Float;
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::{Plane, Torus};
+    use narrow::CollisionDetector;
+    use super::{PlaneTorus, torus_support_point};
+
+    #[test]
+    fn test_torus_support_point() {
+        let torus = Torus::new(2.0f64, 0.5);
+        let m     = Iso3::new(Vec3::new(1.0f64, 2.0, 3.0), na::zero());
+
+        // Along the axis, the whole core circle is extremal.
+        let top = torus_support_point(&m, &torus, &Vec3::new(0.0f64, 1.0, 0.0));
+        assert!(na::approx_eq(&top.y, &2.5));
+        assert!(na::approx_eq(&na::norm(&Vec3::new(top.x - 1.0, 0.0, top.z - 3.0)), &2.0));
+
+        let side = torus_support_point(&m, &torus, &Vec3::new(-1.0f64, 0.0, 0.0));
+        assert!(na::approx_eq(&side, &Vec3::new(-1.5f64, 2.0, 3.0)));
+
+        let dir  = na::normalize(&Vec3::new(0.0f64, -1.0, 1.0));
+        let diag = torus_support_point(&m, &torus, &dir);
+        assert!(na::approx_eq(&diag, &(Vec3::new(1.0f64, 2.0, 5.0) + dir * 0.5f64)));
+
+        // The support point of a rotated torus.
+        let _pi: f64 = Float::pi();
+        let rot      = Iso3::new(na::zero(), Vec3::new(0.0f64, 0.0, _pi / 2.0));
+        let bottom   = torus_support_point(&rot, &torus, &Vec3::new(0.0f64, -1.0, 0.0));
+        assert!(na::approx_eq(&bottom.y, &-2.5));
+    }
+
+    #[test]
+    fn test_plane_torus() {
+        let plane  = Plane::new(Vec3::new(0.0f64, 1.0, 0.0));
+        let torus  = Torus::new(2.0f64, 1.0);
+        let mplane = Iso3::new(na::zero(), na::zero());
+
+        let mut detector = PlaneTorus::new(0.0);
+        let mut colls    = Vec::new();
+
+        // A torus lying on the plane.
+        detector.update(&mplane, &plane, &Iso3::new(Vec3::new(0.0f64, 0.8, 0.0), na::zero()), &torus);
+        detector.colls(&mut colls);
+
+        assert!(colls.len() == 1);
+        assert!(na::approx_eq(&colls.get(0).depth, &0.2));
+        assert!(na::approx_eq(&colls.get(0).normal, &Vec3::new(0.0f64, 1.0, 0.0)));
+        assert!(na::approx_eq(&colls.get(0).world2.y, &-0.2));
+
+        // A torus standing on the plane.
+        let _pi: f64 = Float::pi();
+        let standing = Iso3::new(Vec3::new(0.0f64, 2.9, 0.0), Vec3::new(0.0f64, 0.0, _pi / 2.0));
+
+        colls.clear();
+        detector.update(&mplane, &plane, &standing, &torus);
+        detector.colls(&mut colls);
+
+        assert!(colls.len() == 1);
+        assert!(na::approx_eq(&colls.get(0).depth, &0.1));
+        assert!(na::approx_eq(&colls.get(0).world1, &Vec3::new(0.0f64, 0.0, 0.0)));
+        assert!(na::approx_eq(&colls.get(0).world2, &Vec3::new(0.0f64, -0.1, 0.0)));
+
+        // A torus above the plane.
+        colls.clear();
+        detector.update(&mplane, &plane, &Iso3::new(Vec3::new(0.0f64, 1.1, 0.0), na::zero()), &torus);
+        detector.colls(&mut colls);
+
+        assert!(colls.is_empty());
+    }
+}
@@ -0,0 +1,95 @@
+use nalgebra::na;
+use math::{Scalar, Matrix};
+
+/// Proximity status of two geometries.
+#[deriving(Show, PartialEq, Eq, Clone, Encodable, Decodable)]
+pub enum Proximity {
+    /// The two geometries are intersecting.
+    Intersecting,
+    /// The two geometries are not intersecting but their distance is smaller than the margin of
+    /// the proximity detector.
+    WithinMargin,
+    /// The two geometries are separated by a distance larger than the margin of the proximity
+    /// detector.
+    Disjoint
+}
+
+impl Proximity {
+    /// The proximity status of two geometries separated by the signed distance `distance`.
+    #[inline]
+    pub fn from_distance(distance: &Scalar, margin: &Scalar) -> Proximity {
+        if *distance <= na::zero() {
+            Intersecting
+        }
+        else if *distance <= *margin {
+            WithinMargin
+        }
+        else {
+            Disjoint
+        }
+    }
+
+    /// Selects the status of the closest geometries between `self` and `other`.
+    #[inline]
+    pub fn closest(self, other: Proximity) -> Proximity {
+        match (self, other) {
+            (Intersecting, _) | (_, Intersecting) => Intersecting,
+            (WithinMargin, _) | (_, WithinMargin) => WithinMargin,
+            _                                     => Disjoint
+        }
+    }
+}
+
+/// A change of the proximity status of two geometries.
+#[deriving(Show, PartialEq, Eq, Clone, Encodable, Decodable)]
+pub struct ProximityTransition {
+    /// The proximity status before the update.
+    pub prev: Proximity,
+    /// The proximity status after the update.
+    pub curr: Proximity
+}
+
+impl ProximityTransition {
+    /// The transition from `prev` to `curr`, or `None` if the proximity status did not change.
+    #[inline]
+    pub fn between(prev: Proximity, curr: Proximity) -> Option<ProximityTransition> {
+        if prev == curr {
+            None
+        }
+        else {
+            Some(ProximityTransition { prev: prev, curr: curr })
+        }
+    }
+
+    /// Whether the geometries started intersecting.
+    #[inline]
+    pub fn is_enter(&self) -> bool {
+        self.curr == Intersecting
+    }
+
+    /// Whether the geometries stopped intersecting.
+    #[inline]
+    pub fn is_exit(&self) -> bool {
+        self.prev == Intersecting
+    }
+}
+
+/// Trait of the algorithms determining whether two geometries are intersecting, close to each
+/// other, or separated.
+///
+/// Unlike a `CollisionDetector`, a proximity detector never computes any contact point. This makes
+/// it suited for sensors and trigger volumes.
+///
+/// # Arguments
+/// * `G1`- the type of the first object involved on the proximity query.
+/// * `G2`- the type of the second object involved on the proximity query.
+pub trait ProximityDetector<G1, G2> {
+    /// Updates the proximity status of two objects. It is assumed that the same proximity detector
+    /// (the same structure) is always used with the same pair of object.
+    fn update(&mut self, &Matrix, &G1, &Matrix, &G2);
+
+    /// The proximity status computed during the last update.
+    ///
+    /// This is `Disjoint` if the detector has never been updated.
+    fn proximity(&self) -> Proximity;
+}
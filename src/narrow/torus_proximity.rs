@@ -0,0 +1,150 @@
+use nalgebra::na::{Translation, Rotate};
+use nalgebra::na;
+use bounding_volume::{BoundingVolume, LooseBoundingVolume, HasAABB};
+use geom::{AnnotatedPoint, Plane, Torus};
+use implicit::Implicit;
+use narrow::algorithm::simplex::Simplex;
+use narrow::{ProximityDetector, Proximity, Intersecting, Disjoint};
+use narrow::{implicit_implicit_proximity, plane_torus};
+use math::{Scalar, Vect, Matrix};
+
+/// Proximity detector between a plane and a torus.
+#[deriving(Encodable, Decodable)]
+pub struct PlaneTorusProximity {
+    margin:    Scalar,
+    proximity: Proximity
+}
+
+impl Clone for PlaneTorusProximity {
+    fn clone(&self) -> PlaneTorusProximity {
+        PlaneTorusProximity {
+            margin:    self.margin.clone(),
+            proximity: self.proximity.clone()
+        }
+    }
+}
+
+impl PlaneTorusProximity {
+    /// Creates a new persistent proximity detector between a plane and a torus.
+    #[inline]
+    pub fn new(margin: Scalar) -> PlaneTorusProximity {
+        PlaneTorusProximity {
+            margin:    margin,
+            proximity: Disjoint
+        }
+    }
+}
+
+impl ProximityDetector<Plane, Torus> for PlaneTorusProximity {
+    #[inline]
+    fn update(&mut self, ma: &Matrix, plane: &Plane, mb: &Matrix, torus: &Torus) {
+        self.proximity = plane_torus_proximity(ma, plane, mb, torus, &self.margin)
+    }
+
+    #[inline]
+    fn proximity(&self) -> Proximity {
+        self.proximity
+    }
+}
+
+/// Proximity detector between a torus and a geometry implementing the `Implicit` trait.
+///
+/// The torus is approximated by the same ring of rounded segments as the `TorusImplicit` collision
+/// detector.
+#[deriving(Encodable, Decodable)]
+pub struct TorusImplicitProximity<S, G> {
+    simplex:   S,
+    margin:    Scalar,
+    nsubdivs:  uint,
+    proximity: Proximity
+}
+
+impl<S: Clone, G> Clone for TorusImplicitProximity<S, G> {
+    fn clone(&self) -> TorusImplicitProximity<S, G> {
+        TorusImplicitProximity {
+            simplex:   self.simplex.clone(),
+            margin:    self.margin.clone(),
+            nsubdivs:  self.nsubdivs,
+            proximity: self.proximity.clone()
+        }
+    }
+}
+
+impl<S, G> TorusImplicitProximity<S, G> {
+    /// Creates a new persistent proximity detector between a torus and a geometry with a support
+    /// mapping function.
+    ///
+    /// The core circle of the torus is approximated by `nsubdivs` segments.
+    pub fn new(margin: Scalar, nsubdivs: uint, simplex: S) -> TorusImplicitProximity<S, G> {
+        assert!(nsubdivs >= 3, "A torus must be approximated by at least 3 segments.");
+
+        TorusImplicitProximity {
+            simplex:   simplex,
+            margin:    margin,
+            nsubdivs:  nsubdivs,
+            proximity: Disjoint
+        }
+    }
+}
+
+impl<S: Simplex<AnnotatedPoint>, G: Implicit<Vect, Matrix> + HasAABB>
+ProximityDetector<Torus, G> for TorusImplicitProximity<S, G> {
+    #[inline]
+    fn update(&mut self, ma: &Matrix, a: &Torus, mb: &Matrix, b: &G) {
+        self.proximity = torus_implicit_proximity(ma, a, mb, b, &self.margin, self.nsubdivs, &mut self.simplex)
+    }
+
+    #[inline]
+    fn proximity(&self) -> Proximity {
+        self.proximity
+    }
+}
+
+/// Computes the proximity status of a plane and a torus.
+pub fn plane_torus_proximity(mplane: &Matrix,
+                             plane:  &Plane,
+                             mtorus: &Matrix,
+                             torus:  &Torus,
+                             margin: &Scalar)
+                             -> Proximity {
+    let plane_normal = mplane.rotate(&plane.normal());
+    let plane_center = mplane.translation();
+    let deepest      = plane_torus::torus_support_point(mtorus, torus, &-plane_normal);
+
+    let dist = na::dot(&plane_normal, &(deepest - plane_center));
+
+    Proximity::from_distance(&dist, margin)
+}
+
+/// Computes the proximity status of a torus and an implicit geometry.
+///
+/// This is the closest of the proximity status of each segment approximating the torus.
+pub fn torus_implicit_proximity<S: Simplex<AnnotatedPoint>, G: Implicit<Vect, Matrix> + HasAABB>(
+                                mtorus:   &Matrix,
+                                torus:    &Torus,
+                                mother:   &Matrix,
+                                other:    &G,
+                                margin:   &Scalar,
+                                nsubdivs: uint,
+                                simplex:  &mut S)
+                                -> Proximity {
+    let ls_mother  = na::inv(mtorus).expect("The torus transformation must be inversible.") * *mother;
+    let other_aabb = other.aabb(&ls_mother).loosened(margin.clone());
+    let mut res    = Disjoint;
+
+    for i in range(0u, nsubdivs) {
+        let chord = torus.chord(nsubdivs, i);
+
+        if chord.aabb(&na::one()).intersects(&other_aabb) {
+            let p = implicit_implicit_proximity::proximity(mtorus, &chord, mother, other, margin, simplex);
+
+            res = res.closest(p);
+
+            if res == Intersecting {
+                break;
+            }
+        }
+    }
+
+    res
+}
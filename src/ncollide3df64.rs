@@ -22,20 +22,21 @@ git = "https://github.com/sebcrozet/ncollide"
 
 ## Features
 - dynamic bounding volume tree based broad phase
+- sweep and prune broad phase
+- spatial hashing broad phase
 - ball vs. ball collision detection,
 - plane vs. any convex object collision detection.
 - collision detection between arbitrary convex objects
 - compound geometries
-- ray-casting
-- time of impact computation  for objects without rotational movement (compound vs. compound is not
-  yet implemented)
+- height fields
+- tori (3d only)
+- ray-casting and implicit geometry casting
+- point projection and inclusion tests
+- proximity queries for sensors and trigger volumes
+- time of impact computation (using conservative advancement for objects with rotational
+  movement)
 
 And various traits for collision detectors and broad phase collision detection.
-
-## What is missing
-Some common features are still missing:
-
-- heightmaps
 */
 
 #![deny(non_camel_case_types)]
@@ -67,6 +68,7 @@ extern crate test;
 pub mod bounding_volume;
 pub mod geom;
 pub mod ray;
+pub mod point;
 pub mod narrow;
 pub mod broad;
 pub mod volumetric;
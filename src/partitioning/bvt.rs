@@ -1,12 +1,16 @@
 //! A read-only Bounding Volume Tree.
 
+use std::cmp;
 use std::num::Bounded;
+use std::collections::PriorityQueue;
 use test::stats::Stats;
 use nalgebra::na::{Translation, Indexable, FloatVecExt};
 use nalgebra::na;
 use ray::{Ray, RayCast};
 use partitioning::{BVTVisitor, BVTTVisitor};
-use bounding_volume::{BoundingVolume, AABB};
+use partitioning::weighted_value::WeightedValue;
+use bounding_volume::{BoundingVolume, SurfaceArea, AABB};
+use point::PointQuery;
 use math::{Scalar, Vect};
 
 /// A Boundig Volume Tree.
@@ -104,6 +108,69 @@ impl<B, BV: Translation<Vect> + BoundingVolume + Clone> BVT<B, BV> {
     }
 }
 
+impl<B, BV: BoundingVolume> BVT<B, BV> {
+    /// Recomputes the bounding volumes of this tree bottom-up.
+    ///
+    /// The bounding volume of each leaf is replaced by the result of `leaf_bv`, and the bounding
+    /// volume of each internal node by the merge of its children's. The topology of the tree is
+    /// kept so its quality decreases if the leaves move a lot.
+    pub fn refit(&mut self, leaf_bv: &mut |&B, &BV| -> BV) {
+        match self.tree {
+            Some(ref mut n) => n.refit(leaf_bv),
+            None            => { }
+        }
+    }
+}
+
+impl<B, BV: BoundingVolume + SurfaceArea> BVT<B, BV> {
+    /// The sum of the surface areas of the bounding volumes of every internal node.
+    ///
+    /// Comparing it with its value right after construction tells how much a refitted tree
+    /// degraded.
+    pub fn total_internal_surface_area(&self) -> Scalar {
+        match self.tree {
+            Some(ref n) => n.total_internal_surface_area(),
+            None        => na::zero()
+        }
+    }
+}
+
+impl<B, BV: BoundingVolume> BVTNode<B, BV> {
+    fn refit(&mut self, leaf_bv: &mut |&B, &BV| -> BV) {
+        match *self {
+            Internal(ref mut bv, ref mut left, ref mut right) => {
+                left.refit(leaf_bv);
+                right.refit(leaf_bv);
+
+                *bv = left.bounding_volume().merged(right.bounding_volume());
+            },
+            Leaf(ref mut bv, ref b) => {
+                let new_bv = (*leaf_bv)(b, &*bv);
+
+                *bv = new_bv
+            }
+        }
+    }
+}
+
+impl<B, BV: BoundingVolume + SurfaceArea> BVTNode<B, BV> {
+    fn total_internal_surface_area(&self) -> Scalar {
+        match *self {
+            Internal(ref bv, ref left, ref right) => {
+                bv.surface_area() + left.total_internal_surface_area() + right.total_internal_surface_area()
+            },
+            Leaf(_, _) => na::zero()
+        }
+    }
+}
+
+impl<B, BV: Translation<Vect> + BoundingVolume + SurfaceArea + Clone> BVT<B, BV> {
+    /// Creates a new tree minimizing the surface area heuristic.
+    pub fn new_sah(leaves: Vec<(B, BV)>) -> BVT<B, BV> {
+        BVT::new_with_partitioner(leaves, sah_partitioner)
+    }
+}
+
 impl<B, BV> BVTNode<B, BV> {
     /// The bounding volume of this node.
     #[inline]
@@ -277,6 +344,146 @@ impl<B, BV: RayCast> BVTNode<B, BV> {
     }
 }
 
+/// Element of the priority queue of the best-first traversals.
+enum Candidate<'a, B: 'a, BV: 'a> {
+    /// A node, weighted by the distance to its bounding volume.
+    NodeCandidate(&'a BVTNode<B, BV>),
+    /// An object, weighted by its exact distance.
+    ObjectCandidate(&'a B)
+}
+
+impl<B, BV: PointQuery> BVT<B, BV> {
+    /// Collects the objects containing a given point.
+    ///
+    /// # Arguments:
+    /// * `pt`      - the point.
+    /// * `dist_fn` - computes the exact distance between an object and a point. The object
+    ///               contains the point if this distance is zero.
+    /// * `out`     - will be filled with the objects containing `pt`.
+    pub fn interferences_with_point<'a>(&'a self,
+                                        pt:      &Vect,
+                                        dist_fn: &mut |&B, &Vect| -> Scalar,
+                                        out:     &mut Vec<&'a B>) {
+        match self.tree {
+            Some(ref n) => n.interferences_with_point(pt, dist_fn, out),
+            None        => { }
+        }
+    }
+
+    /// Collects the `k` objects closest to a given point, sorted by increasing distance.
+    ///
+    /// # Arguments:
+    /// * `pt`      - the point.
+    /// * `k`       - the maximum number of objects to collect.
+    /// * `dist_fn` - computes the exact distance between an object and a point. It must never be
+    ///               smaller than the distance between the point and the object bounding volume.
+    /// * `out`     - will be filled with the distance to and a reference of each object found.
+    pub fn k_nearest_to_point<'a>(&'a self,
+                                  pt:      &Vect,
+                                  k:       uint,
+                                  dist_fn: &mut |&B, &Vect| -> Scalar,
+                                  out:     &mut Vec<(Scalar, &'a B)>) {
+        self.nearest_to_point_with_max_dist(pt, k, Bounded::max_value(), dist_fn, out)
+    }
+
+    /// Finds the object closest to a given point, among those closer than `max_dist`.
+    ///
+    /// `dist_fn` computes the exact distance between an object and a point. It must never be
+    /// smaller than the distance between the point and the object bounding volume.
+    pub fn nearest_to_point_within_distance<'a>(&'a self,
+                                                pt:       &Vect,
+                                                max_dist: Scalar,
+                                                dist_fn:  &mut |&B, &Vect| -> Scalar)
+                                                -> Option<(Scalar, &'a B)> {
+        let mut res = Vec::with_capacity(1);
+
+        self.nearest_to_point_with_max_dist(pt, 1, max_dist, dist_fn, &mut res);
+
+        res.pop()
+    }
+
+    /// Best-first traversal collecting the `k` objects closest to `pt` and closer than `max_dist`.
+    ///
+    /// The nodes are weighted by the distance to their bounding volumes, which is a lower bound of
+    /// the distance to the objects they contain. An object is thus final once it is popped out of
+    /// the queue with its exact distance.
+    fn nearest_to_point_with_max_dist<'a>(&'a self,
+                                          pt:       &Vect,
+                                          k:        uint,
+                                          max_dist: Scalar,
+                                          dist_fn:  &mut |&B, &Vect| -> Scalar,
+                                          out:      &mut Vec<(Scalar, &'a B)>) {
+        let mut queue = PriorityQueue::new();
+
+        match self.tree {
+            Some(ref n) => {
+                let dist = n.bounding_volume().distance_to_point(pt);
+
+                if dist <= max_dist {
+                    queue.push(WeightedValue::new(dist, NodeCandidate(n)))
+                }
+            },
+            None => return
+        }
+
+        let mut num_found = 0u;
+
+        while num_found < k {
+            let curr = match queue.pop() {
+                Some(c) => c,
+                None    => break
+            };
+
+            match curr.value {
+                NodeCandidate(&Internal(_, ref left, ref right)) => {
+                    let left_dist  = left.bounding_volume().distance_to_point(pt);
+                    let right_dist = right.bounding_volume().distance_to_point(pt);
+
+                    if left_dist <= max_dist {
+                        queue.push(WeightedValue::new(left_dist, NodeCandidate(&**left)))
+                    }
+
+                    if right_dist <= max_dist {
+                        queue.push(WeightedValue::new(right_dist, NodeCandidate(&**right)))
+                    }
+                },
+                NodeCandidate(&Leaf(_, ref b)) => {
+                    let dist = (*dist_fn)(b, pt);
+
+                    if dist <= max_dist {
+                        queue.push(WeightedValue::new(dist, ObjectCandidate(b)))
+                    }
+                },
+                ObjectCandidate(b) => {
+                    out.push((curr.cost, b));
+                    num_found = num_found + 1;
+                }
+            }
+        }
+    }
+}
+
+impl<B, BV: PointQuery> BVTNode<B, BV> {
+    fn interferences_with_point<'a>(&'a self,
+                                    pt:      &Vect,
+                                    dist_fn: &mut |&B, &Vect| -> Scalar,
+                                    out:     &mut Vec<&'a B>) {
+        match *self {
+            Internal(ref bv, ref left, ref right) => {
+                if bv.contains_point(pt) {
+                    left.interferences_with_point(pt, dist_fn, out);
+                    right.interferences_with_point(pt, dist_fn, out);
+                }
+            },
+            Leaf(ref bv, ref b) => {
+                if bv.contains_point(pt) && (*dist_fn)(b, pt) <= na::zero() {
+                    out.push(b)
+                }
+            }
+        }
+    }
+}
+
 /// Construction function for a kdree to be used with `BVT::new_with_partitioner`.
 #[allow(unnecessary_typecast)]
 pub fn kdtree_partitioner_with_centers<B, BV: BoundingVolume + Clone, V: FloatVecExt<Scalar>>(
@@ -347,6 +554,165 @@ pub fn kdtree_partitioner<B, BV: Translation<Vect> + BoundingVolume + Clone>(
     kdtree_partitioner_with_centers(depth, leaves, &mut |_, bv| bv.translation())
 }
 
+/// Number of bins tested along each axis by `sah_partitioner`.
+static SAH_NUM_BINS: uint = 16;
+
+/// Construction function for a tree minimizing the surface area heuristic, to be used with
+/// `BVT::new_with_partitioner`.
+///
+/// The leaves are split where the sum of the surface areas of both halves, weighted by their
+/// number of leaves, is the smallest. Only the boundaries of a fixed number of bins along each
+/// axis are tested. This is slower than `kdtree_partitioner` but gives much less overlapping
+/// nodes for objects with uneven sizes or density.
+pub fn sah_partitioner<B, BV: Translation<Vect> + BoundingVolume + SurfaceArea + Clone>(
+                       depth:  uint,
+                       leaves: Vec<(B, BV)>)
+                       -> (BV, BinaryPartition<B, BV>) {
+    if leaves.len() == 0 {
+        fail!("Cannot build a tree without leaves.");
+    }
+    else if leaves.len() == 1 {
+        let (b, bv) = leaves.move_iter().next().unwrap();
+        return (bv, Part(b))
+    }
+
+    // bounds of the bounding volumes centers
+    let mut cmins = leaves[0].ref1().translation();
+    let mut cmaxs = cmins.clone();
+
+    for l in leaves.iter() {
+        let c = l.ref1().translation();
+
+        cmins = na::inf(&cmins, &c);
+        cmaxs = na::sup(&cmaxs, &c);
+    }
+
+    // (cost, axis, first bin of the right part)
+    let mut best: Option<(Scalar, uint, uint)> = None;
+
+    for axis in range(0, na::dim::<Vect>()) {
+        let extent = cmaxs.at(axis) - cmins.at(axis);
+
+        if extent <= na::zero() {
+            continue
+        }
+
+        let mut counts = Vec::from_elem(SAH_NUM_BINS, 0u);
+        let mut bvs    = Vec::from_fn(SAH_NUM_BINS, |_| None::<BV>);
+
+        for l in leaves.iter() {
+            let bin = sah_bin(l.ref1(), axis, cmins.at(axis), extent);
+
+            let count = counts[bin];
+
+            *counts.get_mut(bin) = count + 1;
+            merge_option(bvs.get_mut(bin), l.ref1());
+        }
+
+        // costs of the right parts
+        let mut right_costs = Vec::from_elem(SAH_NUM_BINS, na::zero::<Scalar>());
+        let mut right_bv    = None;
+        let mut num_right   = 0u;
+
+        for k in range(1, SAH_NUM_BINS).rev() {
+            num_right = num_right + counts[k];
+
+            match bvs[k] {
+                Some(ref bv) => merge_option(&mut right_bv, bv),
+                None         => { }
+            }
+
+            *right_costs.get_mut(k) = sah_cost(num_right, &right_bv);
+        }
+
+        let mut left_bv  = None;
+        let mut num_left = 0u;
+
+        for k in range(1, SAH_NUM_BINS) {
+            num_left = num_left + counts[k - 1];
+
+            match bvs[k - 1] {
+                Some(ref bv) => merge_option(&mut left_bv, bv),
+                None         => { }
+            }
+
+            if num_left == 0 || num_left == leaves.len() {
+                continue
+            }
+
+            let cost = sah_cost(num_left, &left_bv) + right_costs[k];
+
+            let is_better = match best {
+                Some((best_cost, _, _)) => cost < best_cost,
+                None                    => true
+            };
+
+            if is_better {
+                best = Some((cost, axis, k))
+            }
+        }
+    }
+
+    match best {
+        None => {
+            // All the centers are at the same place: there is nothing better than an arbitrary
+            // split.
+            kdtree_partitioner(depth, leaves)
+        },
+        Some((_, axis, split)) => {
+            let extent = cmaxs.at(axis) - cmins.at(axis);
+
+            let mut right = Vec::new();
+            let mut left  = Vec::new();
+            let mut bounding_bounding_volume = leaves[0].ref1().clone();
+
+            for (b, bv) in leaves.move_iter() {
+                bounding_bounding_volume.merge(&bv);
+
+                if sah_bin(&bv, axis, cmins.at(axis), extent) < split {
+                    left.push((b, bv))
+                }
+                else {
+                    right.push((b, bv))
+                }
+            }
+
+            (bounding_bounding_volume, Parts(left, right))
+        }
+    }
+}
+
+/// The bin of `bv` along `axis`.
+#[inline]
+fn sah_bin<BV: Translation<Vect>>(bv: &BV, axis: uint, min: Scalar, extent: Scalar) -> uint {
+    let t = (bv.translation().at(axis) - min) / extent;
+
+    cmp::min((t * (SAH_NUM_BINS as Scalar)) as uint, SAH_NUM_BINS - 1)
+}
+
+/// The surface area heuristic cost of a set of `n` leaves bounded by `bv`.
+#[inline]
+fn sah_cost<BV: SurfaceArea>(n: uint, bv: &Option<BV>) -> Scalar {
+    match *bv {
+        Some(ref bv) => (n as Scalar) * bv.surface_area(),
+        None         => na::zero()
+    }
+}
+
+/// Merges `bv` to `acc`, or sets `acc` to `bv` if it is `None`.
+#[inline]
+fn merge_option<BV: BoundingVolume + Clone>(acc: &mut Option<BV>, bv: &BV) {
+    match *acc {
+        Some(ref mut acc) => {
+            acc.merge(bv);
+            return
+        },
+        None => { }
+    }
+
+    *acc = Some(bv.clone())
+}
+
 fn _new_with_partitioner<B, BV>(depth:       uint,
                                 leaves:      Vec<(B, BV)>,
                                 partitioner: |uint, Vec<(B, BV)>| -> (BV, BinaryPartition<B, BV>))
@@ -369,3 +735,183 @@ fn __new_with_partitioner<B, BV>(depth:       uint,
         }
     }
 }
+
+#[cfg(test, dim3, f64)]
+mod test {
+    use nalgebra::na::Vec3;
+    use nalgebra::na;
+    use ray::{Ray, RayCast};
+    use bounding_volume::{BoundingVolume, AABB};
+    use partitioning::BVTVisitor;
+    use super::BVT;
+
+    /// Counts the nodes visited while collecting interferences with an AABB.
+    struct VisitCounter<'a> {
+        aabb:        &'a AABB,
+        num_visited: uint
+    }
+
+    impl<'a> BVTVisitor<uint, AABB> for VisitCounter<'a> {
+        fn visit_internal(&mut self, bv: &AABB) -> bool {
+            self.num_visited = self.num_visited + 1;
+            bv.intersects(self.aabb)
+        }
+
+        fn visit_leaf(&mut self, _: &uint, _: &AABB) {
+            self.num_visited = self.num_visited + 1;
+        }
+    }
+
+    // A dense bundle of long thin beams along `x`, and sparse posts along `y`.
+    fn beams() -> Vec<(uint, AABB)> {
+        let mut res = Vec::new();
+
+        for i in range(0u, 200) {
+            let y  = (i % 20) as f64 * 0.1;
+            let z  = (i / 20) as f64 * 0.1;
+            let id = res.len();
+
+            res.push((id, AABB::new(Vec3::new(-50.0, y, z), Vec3::new(50.0, y + 0.05, z + 0.05))));
+        }
+
+        for i in range(0u, 50) {
+            let x  = i as f64 * 2.0 - 50.0;
+            let id = res.len();
+
+            res.push((id, AABB::new(Vec3::new(x, -20.0, 5.0), Vec3::new(x + 0.05, 20.0, 5.05))));
+        }
+
+        res
+    }
+
+    fn num_visited_with_aabbs(bvt: &BVT<uint, AABB>) -> uint {
+        let mut res = 0;
+
+        for i in range(-10i, 10) {
+            for j in range(-4i, 4) {
+                let center  = Vec3::new(i as f64 * 5.0, j as f64 * 5.0, 2.5);
+                let aabb    = AABB::new(center - Vec3::new(1.0, 1.0, 3.0), center + Vec3::new(1.0, 1.0, 3.0));
+                let mut vis = VisitCounter { aabb: &aabb, num_visited: 0 };
+
+                bvt.visit(&mut vis);
+                res = res + vis.num_visited;
+            }
+        }
+
+        res
+    }
+
+    fn num_visited_with_rays(bvt: &BVT<uint, AABB>, bvs: &[(uint, AABB)]) -> uint {
+        let mut res = 0;
+
+        for i in range(-10i, 10) {
+            for j in range(-4i, 4) {
+                let ray = Ray::new(Vec3::new(i as f64 * 5.0, j as f64 * 5.0, -10.0), Vec3::new(0.0, 0.0, 1.0));
+
+                let _ = bvt.cast_ray(&ray, &mut |b, r| {
+                    res = res + 1;
+                    bvs[*b].ref1().toi_with_ray(r, true).map(|t| (t, t))
+                });
+            }
+        }
+
+        res
+    }
+
+    #[test]
+    fn test_sah_visits_fewer_nodes() {
+        let leaves = beams();
+        let kdtree = BVT::new_kdtree(leaves.clone());
+        let sah    = BVT::new_sah(leaves.clone());
+
+        let (kdtree_aabb, sah_aabb) = (num_visited_with_aabbs(&kdtree), num_visited_with_aabbs(&sah));
+        let (kdtree_ray, sah_ray)   = (num_visited_with_rays(&kdtree, leaves.as_slice()),
+                                       num_visited_with_rays(&sah, leaves.as_slice()));
+
+        assert!(sah_aabb < kdtree_aabb, "AABB queries: {} nodes visited with SAH, {} with kdtree.", sah_aabb, kdtree_aabb);
+        assert!(sah_ray < kdtree_ray, "Ray casts: {} leaves tested with SAH, {} with kdtree.", sah_ray, kdtree_ray);
+    }
+
+    static BALL_RADIUS: f64 = 0.4;
+
+    // Overlapping balls on a slightly bumpy grid.
+    fn balls() -> Vec<Vec3<f64>> {
+        let mut res = Vec::new();
+
+        for i in range(0u, 10) {
+            for j in range(0u, 10) {
+                res.push(Vec3::new(i as f64, j as f64 * 0.7, ((i * j) % 3) as f64 * 0.2))
+            }
+        }
+
+        res
+    }
+
+    fn ball_bvt(centers: &[Vec3<f64>]) -> BVT<uint, AABB> {
+        let radius = Vec3::new(BALL_RADIUS, BALL_RADIUS, BALL_RADIUS);
+        let leaves = centers.iter().enumerate().map(|(i, c)| (i, AABB::new(*c - radius, *c + radius))).collect();
+
+        BVT::new_kdtree(leaves)
+    }
+
+    fn distance_to_ball(center: &Vec3<f64>, pt: &Vec3<f64>) -> f64 {
+        (na::norm(&(*pt - *center)) - BALL_RADIUS).max(0.0)
+    }
+
+    fn query_points() -> Vec<Vec3<f64>> {
+        vec!(Vec3::new(0.0, 0.35, 0.0), Vec3::new(4.0, 2.45, 0.2), Vec3::new(9.3, 6.3, 0.4),
+             Vec3::new(-3.0, 1.0, 0.0), Vec3::new(4.5, 20.0, -1.0))
+    }
+
+    #[test]
+    fn test_bvt_interferences_with_point() {
+        let centers = balls();
+        let bvt     = ball_bvt(centers.as_slice());
+
+        for pt in query_points().iter() {
+            let mut found = Vec::new();
+
+            bvt.interferences_with_point(pt, &mut |b, pt| distance_to_ball(&centers[*b], pt), &mut found);
+
+            let mut found: Vec<uint>    = found.iter().map(|b| **b).collect();
+            let mut expected: Vec<uint> = range(0u, centers.len()).filter(|i| distance_to_ball(&centers[*i], pt) == 0.0).collect();
+
+            found.sort();
+            expected.sort();
+
+            assert!(found == expected, "Point {}: found {}, expected {}.", pt, found, expected);
+        }
+    }
+
+    #[test]
+    fn test_bvt_nearest_to_point() {
+        let centers = balls();
+        let bvt     = ball_bvt(centers.as_slice());
+
+        for pt in query_points().iter() {
+            let mut expected: Vec<f64> = centers.iter().map(|c| distance_to_ball(c, pt)).collect();
+            expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+            let mut nearest = Vec::new();
+
+            bvt.k_nearest_to_point(pt, 5, &mut |b, pt| distance_to_ball(&centers[*b], pt), &mut nearest);
+
+            assert!(nearest.len() == 5);
+
+            for (i, &(dist, b)) in nearest.iter().enumerate() {
+                assert!(na::approx_eq(&dist, &expected[i]), "Point {}: {}-th nearest at {}, expected {}.", pt, i, dist, expected[i]);
+                assert!(na::approx_eq(&dist, &distance_to_ball(&centers[*b], pt)));
+            }
+
+            let best = expected[0];
+
+            assert!(bvt.nearest_to_point_within_distance(pt, best + 0.1, &mut |b, pt| distance_to_ball(&centers[*b], pt))
+                       .map_or(false, |(dist, _)| na::approx_eq(&dist, &best)));
+
+            if best > 0.0 {
+                assert!(bvt.nearest_to_point_within_distance(pt, best * 0.9, &mut |b, pt| distance_to_ball(&centers[*b], pt))
+                           .is_none());
+            }
+        }
+    }
+}
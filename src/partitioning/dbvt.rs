@@ -1,152 +1,76 @@
 //! A Dynamic Bounding Volume Tree.
 
-use std::gc::{Gc, GC};
-use std::cell::RefCell;
-use std::ptr;
-use std::mem;
-use data::owned_allocation_cache::OwnedAllocationCache;
+use std::cmp;
+use std::num::Bounded;
+use std::collections::PriorityQueue;
 use nalgebra::na::Translation;
 use nalgebra::na;
-use bounding_volume::BoundingVolume;
+use bounding_volume::{BoundingVolume, SurfaceArea};
 use partitioning::bvt_visitor::{BVTVisitor, BoundingVolumeInterferencesCollector};
+use partitioning::weighted_value::WeightedValue;
+use point::PointQuery;
 use math::{Scalar, Vect};
 
-#[deriving(Encodable, Decodable)]
+#[deriving(Clone, Encodable, Decodable)]
 enum UpdateState {
     NeedsShrink,
     UpToDate
 }
 
-type Cache<B, BV> = OwnedAllocationCache<DBVTInternal<B, BV>>;
+/// Handle of a leaf of a Dynamic Bounding Volume Tree.
+///
+/// It remains valid until the leaf is removed from its tree. The slots of removed leaves are
+/// recycled, but each handle also records the generation of its slot: using the handle of a
+/// removed leaf fails instead of silently referring to the leaf inserted in its place.
+#[deriving(Clone, PartialEq, Eq, Show, Encodable, Decodable)]
+pub struct DBVTLeafId(uint, uint); // (index, generation)
+
+/// Index of a node on one of the arenas of a DBVT.
+#[deriving(Clone, PartialEq, Encodable, Decodable)]
+enum DBVTNodeId {
+    InternalId(uint),
+    LeafId(uint)
+}
 
 /// A Dynamic Bounding Volume Tree.
+///
+/// Nodes are stored on contiguous arenas and refer to each other by their indices.
+#[deriving(Encodable, Decodable)]
 pub struct DBVT<B, BV> {
-    cache: Cache<B, BV>,
-    tree:  Option<DBVTNode<B, BV>>,
-    len:   uint
-}
-
-impl<B, BV> DBVT<B, BV> {
-    /// Creates a new Dynamic Bounding Volume Tree.
-    pub fn new() -> DBVT<B, BV> {
-        DBVT {
-            cache: OwnedAllocationCache::new(),
-            tree:  None,
-            len:   0
-        }
-    }
-}
-
-impl<BV: 'static + BoundingVolume + Translation<Vect> + Clone,
-     B:  'static + Clone>
-DBVT<B, BV> {
-    /// Removes a leaf from the tree. Fails if the tree is empty.
-    pub fn remove(&mut self, leaf: &mut Gc<RefCell<DBVTLeaf<B, BV>>>) {
-        let self_tree = self.tree.take().unwrap();
-
-        let mut bleaf = leaf.borrow_mut();
-        self.tree = bleaf.unlink(&mut self.cache, self_tree);
-        self.len  = self.len - 1;
-    }
-
-    // FIXME: it feels strange that this method takes (B, BV) in this order while the leaves
-    // constructor takes (BV, B)…
-    /// Creates, inserts, and returns a new leaf with the given content.
-    pub fn insert_new(&mut self, b: B, bv: BV) -> Gc<RefCell<DBVTLeaf<B, BV>>> {
-        let leaf = box(GC) RefCell::new(DBVTLeaf::new(bv, b));
-
-        self.insert(leaf.clone());
-
-        leaf
-    }
-
-    /// Inserts a leaf to the tree.
-    pub fn insert(&mut self, leaf: Gc<RefCell<DBVTLeaf<B, BV>>>) {
-        let mut self_tree = None;
-        mem::swap(&mut self_tree, &mut self.tree);
-
-        self.tree = match self_tree {
-            None    => Some(Leaf(leaf)),
-            Some(t) => Some(Internal(t.insert(&mut self.cache, leaf)))
-        };
-
-        self.len = self.len + 1;
-    }
-
-    /// Visit this tree using… a visitor!
-    pub fn visit<Vis: BVTVisitor<Gc<RefCell<DBVTLeaf<B, BV>>>, BV>>(&self, visitor: &mut Vis) {
-        match self.tree {
-            Some(ref t) => t.visit(visitor),
-            None        => { }
-        }
-    }
-
-//    /// Visit this tree using… a visitor! Visitor arguments are mutable.
-//    pub fn visit_mut<Vis: BVTVisitor<GcMut<DBVTLeaf<B, BV>>, BV>>(&mut self, visitor: &mut Vis) {
-//        match self.tree {
-//            Some(ref mut t) => t.visit_mut(visitor),
-//            None            => { }
-//        }
-//    }
-
-    /// Finds all leaves which have their bounding boxes intersecting a specific leave's bounding
-    /// volume.
-    ///
-    /// # Arguments:
-    /// * `to_test` - the leaf to check interferences with.
-    /// * `out` - will be filled with all leaves intersecting `to_test`. Note that `to_test`
-    ///           is not considered intersecting itself.
-    pub fn interferences_with_leaf(&self,
-                                   leaf: &DBVTLeaf<B, BV>,
-                                   out:  &mut Vec<Gc<RefCell<DBVTLeaf<B, BV>>>>) {
-        match self.tree {
-            Some(ref tree) => tree.interferences_with_leaf(leaf, out),
-            None           => { }
-        }
-    }
-
-    /// Finds all interferences between this tree and another one.
-    pub fn interferences_with_tree(&self,
-                                   leaf: &DBVT<B, BV>,
-                                   out:  &mut Vec<Gc<RefCell<DBVTLeaf<B, BV>>>>) {
-        match (&self.tree, &leaf.tree) {
-            (&Some(ref a), &Some(ref b)) => a.interferences_with_tree(b, out),
-            (&None, _) => { },
-            (_, &None) => { }
-        }
-    }
-}
-
-/// Node of the Dynamic Bounding Volume Tree.
-enum DBVTNode<B, BV> {
-    Internal(Box<DBVTInternal<B, BV>>),
-    Leaf(Gc<RefCell<DBVTLeaf<B, BV>>>),
-    Invalid
+    root:           Option<DBVTNodeId>,
+    leaves:         Vec<Option<DBVTLeaf<B, BV>>>,
+    generations:    Vec<uint>, // incremented each time the corresponding leaf slot is freed
+    internals:      Vec<Option<DBVTInternal<BV>>>,
+    free_leaves:    Vec<uint>,
+    free_internals: Vec<uint>,
+    len:            uint,
+    optimize_off:   uint // incremental optimization index
 }
 
 /// Internal node of a DBVT. An internal node always has two children.
-struct DBVTInternal<B, BV> {
+#[deriving(Encodable, Decodable)]
+struct DBVTInternal<BV> {
     /// The bounding volume of this node. It always encloses both its children bounding volumes.
     bounding_volume: BV,
     /// The center of this node bounding volume.
     center:          Vect,
     /// This node left child.
-    left:            DBVTNode<B, BV>,
+    left:            DBVTNodeId,
     /// This node right child.
-    right:           DBVTNode<B, BV>,
+    right:           DBVTNodeId,
     /// This node parent.
-    parent:          *mut DBVTInternal<B, BV>,
+    parent:          Option<uint>,
 
     state:           UpdateState
 }
 
-impl<BV: Translation<Vect>, B> DBVTInternal<B, BV> {
+impl<BV: Translation<Vect>> DBVTInternal<BV> {
     /// Creates a new internal node.
     fn new(bounding_volume: BV,
-           parent:          *mut DBVTInternal<B, BV>,
-           left:            DBVTNode<B, BV>,
-           right:           DBVTNode<B, BV>)
-           -> DBVTInternal<B, BV> {
+           parent:          Option<uint>,
+           left:            DBVTNodeId,
+           right:           DBVTNodeId)
+           -> DBVTInternal<BV> {
         DBVTInternal {
             center:          bounding_volume.translation(),
             bounding_volume: bounding_volume,
@@ -158,41 +82,8 @@ impl<BV: Translation<Vect>, B> DBVTInternal<B, BV> {
     }
 }
 
-#[allow(raw_pointer_deriving)]
-#[deriving(Clone)]
-/// State of a leaf.
-pub enum DBVTLeafState<B, BV> {
-    /// This leaf is the right child of another node.
-    RightChildOf(*mut DBVTInternal<B, BV>),
-    /// This leaf is the left child of another node.
-    LeftChildOf(*mut DBVTInternal<B, BV>),
-    /// This leaf is detached from any tree.
-    Detached
-}
-
-impl<B, BV> DBVTLeafState<B, BV> {
-    /// Indicates whether this leaf is detached.
-    #[inline]
-    pub fn is_detached(&self) -> bool {
-        match *self {
-            Detached => true,
-            _        => false
-        }
-    }
-
-    /// Returns a pointer to this leaf parent and `true` if it is the left child.
-    #[inline]
-    fn unwrap(self) -> (bool, *mut DBVTInternal<B, BV>) {
-        match self {
-            RightChildOf(p) => (false, p),
-            LeftChildOf(p)  => (true, p),
-            _               => fail!("Attempting to unwrap a detached node.")
-        }
-    }
-}
-
 /// Leaf of a Dynamic Bounding Volume Tree.
-#[deriving(Clone)]
+#[deriving(Clone, Encodable, Decodable)]
 pub struct DBVTLeaf<B, BV> {
     /// The bounding volume of this node.
     pub bounding_volume: BV,
@@ -201,264 +92,357 @@ pub struct DBVTLeaf<B, BV> {
     /// An user-defined object.
     pub object:          B,
     /// This node parent.
-    pub parent:          DBVTLeafState<B, BV>
+    parent:              Option<uint>
 }
 
-impl<B, BV> DBVTNode<B, BV> {
-    fn take_internal(self) -> Box<DBVTInternal<B, BV>> {
-        match self {
-            Internal(i) => i,
-            _ => fail!("DBVT internal error: this is not an internal node.")
+impl<B, BV: Translation<Vect>> DBVTLeaf<B, BV> {
+    /// Creates a new leaf, detached from any tree.
+    pub fn new(bounding_volume: BV, object: B) -> DBVTLeaf<B, BV> {
+        DBVTLeaf {
+            center:          bounding_volume.translation(),
+            bounding_volume: bounding_volume,
+            object:          object,
+            parent:          None
         }
     }
+}
 
-    fn invalidate(&mut self) -> DBVTNode<B, BV> {
-        let mut res = Invalid;
+impl<B, BV> DBVT<B, BV> {
+    /// Creates a new Dynamic Bounding Volume Tree.
+    pub fn new() -> DBVT<B, BV> {
+        DBVT {
+            root:           None,
+            leaves:         Vec::new(),
+            generations:    Vec::new(),
+            internals:      Vec::new(),
+            free_leaves:    Vec::new(),
+            free_internals: Vec::new(),
+            len:            0,
+            optimize_off:   0
+        }
+    }
 
-        mem::swap(&mut res, self);
+    /// The number of leaves of this tree.
+    #[inline]
+    pub fn len(&self) -> uint {
+        self.len
+    }
 
-        res
+    /// The length of the longest path from the root to a leaf, counting both ends.
+    ///
+    /// This is zero for an empty tree and one for a tree with a single leaf.
+    pub fn depth(&self) -> uint {
+        match self.root {
+            Some(r) => self.node_depth(r),
+            None    => 0
+        }
     }
-}
 
-impl<B, BV> DBVTInternal<B, BV> {
-    fn is_right_internal_node(&self, r: &mut DBVTInternal<B, BV>) -> bool
-    {
-        match self.right {
-            Internal(ref i) => &**i as *const DBVTInternal<B, BV> == &*r as *const DBVTInternal<B, BV>,
-            _ => false
+    fn node_depth(&self, node: DBVTNodeId) -> uint {
+        match node {
+            InternalId(i) => {
+                let internal = self.internal(i);
+
+                1 + cmp::max(self.node_depth(internal.left), self.node_depth(internal.right))
+            },
+            LeafId(_) => 1
         }
     }
-}
 
-impl<B: 'static, BV: Translation<Vect> + 'static> DBVTLeaf<B, BV> {
-    /// Creates a new leaf.
-    pub fn new(bounding_volume: BV, object: B) -> DBVTLeaf<B, BV> {
-        DBVTLeaf {
-            center:          bounding_volume.translation(),
-            bounding_volume: bounding_volume,
-            object:          object,
-            parent:          Detached
+    /// Reference to the leaf identified by `id`. Fails if the leaf is not on this tree.
+    #[inline]
+    pub fn leaf<'a>(&'a self, id: DBVTLeafId) -> &'a DBVTLeaf<B, BV> {
+        let i = self.leaf_index(id);
+
+        self.leaf_at(i)
+    }
+
+    /// Mutable reference to the object of the leaf identified by `id`. Fails if the leaf is not on
+    /// this tree.
+    #[inline]
+    pub fn object_mut<'a>(&'a mut self, id: DBVTLeafId) -> &'a mut B {
+        let i = self.leaf_index(id);
+
+        &mut self.leaf_at_mut(i).object
+    }
+
+    /// The index of the leaf identified by `id`. Fails if the leaf is not on this tree, including
+    /// if it has been removed and its slot reused by another leaf.
+    #[inline]
+    fn leaf_index(&self, id: DBVTLeafId) -> uint {
+        let DBVTLeafId(i, generation) = id;
+
+        if i >= self.leaves.len() || *self.generations.get(i) != generation || self.leaves.get(i).is_none() {
+            fail!("Invalid DBVT leaf identifier.")
         }
+
+        i
     }
 
-    /// Removes this leaf from the tree.
-    ///
-    /// Returns the new root of the tree.
-    ///
-    /// # Arguments:
-    /// * `curr_root`: current root of the tree.
-    fn unlink(&mut self,
-              cache:     &mut Cache<B, BV>,
-              curr_root: DBVTNode<B, BV>) -> Option<DBVTNode<B, BV>> {
-        if !self.parent.is_detached() {
-            let (is_left, p) = mem::replace(&mut self.parent, Detached).unwrap();
-
-            let pp           = unsafe { (*p).parent };
-            let parent_left  = unsafe { (*p).left.invalidate() };
-            let parent_right = unsafe { (*p).right.invalidate() };
-
-            let mut other = if is_left { parent_right } else { parent_left };
-
-            if pp.is_not_null() {
-                let is_p_right_to_pp = unsafe { (*pp).is_right_internal_node(&mut *p) };
-                // we are far away from the root
-                unsafe {
-                    match other {
-                        Internal(ref mut i) => i.parent = pp,
-                        Leaf(ref mut l)     => {
-                            // FIXME: if deref_mut is not called, the type inference seems to be
-                            // buggy
-                            l.borrow_mut().deref_mut().parent =
-                                if is_p_right_to_pp { RightChildOf(pp) } else { LeftChildOf(pp) }
-                        },
-                        Invalid             => unreachable!()
-                    }
+    /// The handle of the leaf at the index `i`.
+    #[inline]
+    fn leaf_id(&self, i: uint) -> DBVTLeafId {
+        DBVTLeafId(i, *self.generations.get(i))
+    }
 
-                    if is_p_right_to_pp {
-                        mem::swap(&mut (*pp).right, &mut other);
-                        // NOTE: the children have already been invalidated before
-                        cache.retain(other.take_internal())
-                    }
-                    else {
-                        mem::swap(&mut (*pp).left, &mut other);
-                        // NOTE: the children have already been invalidated before
-                        cache.retain(other.take_internal())
-                    }
+    #[inline]
+    fn leaf_at<'a>(&'a self, i: uint) -> &'a DBVTLeaf<B, BV> {
+        self.leaves.get(i).as_ref().expect("DBVT internal error: invalid leaf index.")
+    }
 
-                    (*pp).state = NeedsShrink;
-                }
+    #[inline]
+    fn leaf_at_mut<'a>(&'a mut self, i: uint) -> &'a mut DBVTLeaf<B, BV> {
+        self.leaves.get_mut(i).as_mut().expect("DBVT internal error: invalid leaf index.")
+    }
 
-                Some(curr_root)
-            }
-            else {
-                // the root changes to the other child
-                match other {
-                    Internal(ref mut i) => i.parent = ptr::mut_null(),
-                                           // FIXME: if deref_mut is not called, the type inference seems to be
-                                           // buggy
-                    Leaf(ref l)         => l.borrow_mut().deref_mut().parent = Detached,
-                    Invalid             => unreachable!()
-                }
+    #[inline]
+    fn internal<'a>(&'a self, i: uint) -> &'a DBVTInternal<BV> {
+        self.internals.get(i).as_ref().expect("DBVT internal error: invalid internal node index.")
+    }
 
-                Some(other)
-            }
+    #[inline]
+    fn internal_mut<'a>(&'a mut self, i: uint) -> &'a mut DBVTInternal<BV> {
+        self.internals.get_mut(i).as_mut().expect("DBVT internal error: invalid internal node index.")
+    }
+
+    #[inline]
+    fn node_bounding_volume<'a>(&'a self, node: DBVTNodeId) -> &'a BV {
+        match node {
+            InternalId(i) => &self.internal(i).bounding_volume,
+            LeafId(i)     => &self.leaf_at(i).bounding_volume
         }
-        else {
-            self.parent = Detached;
+    }
 
-            // the tree becomes empty
-            None
+    #[inline]
+    fn node_center(&self, node: DBVTNodeId) -> Vect {
+        match node {
+            InternalId(i) => self.internal(i).center.clone(),
+            LeafId(i)     => self.leaf_at(i).center.clone()
+        }
+    }
+
+    #[inline]
+    fn node_parent(&self, node: DBVTNodeId) -> Option<uint> {
+        match node {
+            InternalId(i) => self.internal(i).parent,
+            LeafId(i)     => self.leaf_at(i).parent
         }
     }
-}
 
-impl<BV: 'static + BoundingVolume, B: 'static> DBVTNode<B, BV> {
-    fn sqdist_to(&self, to: &Vect) -> Scalar {
-        match *self {
-            Internal(ref i) => na::sqnorm(&(i.center - *to)),
-            Leaf(ref l)     => {
-                let bl = l.borrow();
-                na::sqnorm(&(bl.center - *to))
+    #[inline]
+    fn set_node_parent(&mut self, node: DBVTNodeId, parent: Option<uint>) {
+        match node {
+            InternalId(i) => self.internal_mut(i).parent = parent,
+            LeafId(i)     => self.leaf_at_mut(i).parent = parent
+        }
+    }
+
+    /// Replaces the child `old` of the internal node `parent` by `new`.
+    #[inline]
+    fn replace_child(&mut self, parent: uint, old: DBVTNodeId, new: DBVTNodeId) {
+        let internal = self.internal_mut(parent);
+
+        if internal.left == old {
+            internal.left = new
+        }
+        else {
+            internal.right = new
+        }
+    }
+
+    fn alloc_leaf(&mut self, leaf: DBVTLeaf<B, BV>) -> uint {
+        match self.free_leaves.pop() {
+            Some(i) => {
+                let slot = self.leaves.get_mut(i);
+
+                assert!(slot.is_none(), "DBVT internal error: reusing an occupied leaf slot.");
+
+                *slot = Some(leaf);
+                i
             },
-            Invalid         => unreachable!()
+            None => {
+                self.leaves.push(Some(leaf));
+                self.generations.push(0);
+                self.leaves.len() - 1
+            }
         }
     }
 
-    /*
-    fn enclosing_volume(&self, other: &DBVTNode<B, BV>) -> BV {
-        match (self, other) {
-            (&Internal(ref a), &Internal(ref b)) => a.bounding_volume.merged(&b.bounding_volume),
-            (&Leaf(ref a)    , &Internal(ref b)) => a.bounding_volume.merged(&b.bounding_volume),
-            (&Internal(ref a), &Leaf(ref b))     => a.bounding_volume.merged(&b.bounding_volume),
-            (&Leaf(ref a)    , &Leaf(ref b))     => a.bounding_volume.merged(&b.bounding_volume),
-            _ => unreachable!() // combination including invalide nodes
+    fn alloc_internal(&mut self, internal: DBVTInternal<BV>) -> uint {
+        match self.free_internals.pop() {
+            Some(i) => {
+                *self.internals.get_mut(i) = Some(internal);
+                i
+            },
+            None => {
+                self.internals.push(Some(internal));
+                self.internals.len() - 1
+            }
         }
     }
-    */
 }
 
-impl<BV: 'static + Translation<Vect> + BoundingVolume, B: 'static> DBVTInternal<B, BV> {
-    fn is_closest_to_left(&self, pt: &Vect) -> bool {
-        self.right.sqdist_to(pt) > self.left.sqdist_to(pt)
+impl<B, BV: BoundingVolume + Translation<Vect> + Clone> DBVT<B, BV> {
+    /// Creates and inserts a new leaf with the given content.
+    ///
+    /// Returns the handle of the new leaf.
+    pub fn insert(&mut self, object: B, bounding_volume: BV) -> DBVTLeafId {
+        let i = self.alloc_leaf(DBVTLeaf::new(bounding_volume, object));
+
+        self.attach(i);
+        self.len = self.len + 1;
+
+        self.leaf_id(i)
     }
 
-    /*
-    fn is_closest_to_right(&self, pt: &Vect) -> bool {
-        !self.is_closest_to_left(pt)
+    /// Removes a leaf from the tree and returns it. Fails if the leaf is not on this tree.
+    pub fn remove(&mut self, id: DBVTLeafId) -> DBVTLeaf<B, BV> {
+        let i = self.leaf_index(id);
+
+        self.detach(i);
+
+        let leaf = self.leaves.get_mut(i).take().expect("DBVT internal error: invalid leaf index.");
+
+        // Invalidates every handle of the removed leaf.
+        *self.generations.get_mut(i) += 1;
+        self.free_leaves.push(i);
+        self.len = self.len - 1;
+
+        leaf
+    }
+
+    /// Changes the bounding volume of a leaf and moves it to its new place on the tree.
+    ///
+    /// The leaf handle remains valid. Fails if the leaf is not on this tree.
+    pub fn set_bounding_volume(&mut self, id: DBVTLeafId, bounding_volume: BV) {
+        let i = self.leaf_index(id);
+
+        self.detach(i);
+
+        {
+            let leaf = self.leaf_at_mut(i);
+
+            leaf.center          = bounding_volume.translation();
+            leaf.bounding_volume = bounding_volume;
+        }
+
+        self.attach(i);
     }
 
-    fn partial_optimise(&mut self) {
-        match self.state {
-            NeedsShrink => {
-                self.bounding_volume = self.right.enclosing_volume(&self.left);
-                self.state = UpToDate;
+    /// Inserts the detached leaf `i` on the tree.
+    fn attach(&mut self, i: uint) {
+        let root =
+            match self.root {
+                None    => {
+                    self.leaf_at_mut(i).parent = None;
+                    self.root = Some(LeafId(i));
+
+                    return
+                },
+                Some(r) => r
+            };
+
+        let (bv, center) = {
+            let leaf = self.leaf_at(i);
+
+            (leaf.bounding_volume.clone(), leaf.center.clone())
+        };
+
+        // iteratively go to the leaves
+        let mut curr = root;
+
+        loop {
+            match curr {
+                InternalId(n) => {
+                    // FIXME: we could avoid the systematic merge
+                    self.internal_mut(n).bounding_volume.merge(&bv);
+
+                    let (left, right) = (self.internal(n).left, self.internal(n).right);
+
+                    curr = if self.is_closest_to_left(left, right, &center) { left } else { right };
+                },
+                LeafId(_) => break
             }
-            _ => { }
+        }
+
+        // `curr` is replaced by a new internal node with `curr` and the new leaf as children.
+        let parent   = self.node_parent(curr);
+        let merged   = self.node_bounding_volume(curr).merged(&bv);
+        let internal = self.alloc_internal(DBVTInternal::new(merged, parent, curr, LeafId(i)));
+
+        self.set_node_parent(curr, Some(internal));
+        self.leaf_at_mut(i).parent = Some(internal);
+
+        match parent {
+            None    => self.root = Some(InternalId(internal)),
+            Some(p) => self.replace_child(p, curr, InternalId(internal))
         }
     }
-    */
-}
 
-impl<BV: 'static + BoundingVolume + Translation<Vect> + Clone, B: 'static + Clone> DBVTNode<B, BV> {
-    /// Inserts a new leaf on this tree.
-    fn insert(self,
-              cache:     &mut Cache<B, BV>,
-              to_insert: Gc<RefCell<DBVTLeaf<B, BV>>>)
-              -> Box<DBVTInternal<B, BV>> {
-
-        let mut bto_insert = to_insert.borrow_mut();
-        let pto_insert     = bto_insert.deref_mut();
-
-        match self {
-            Internal(i) => {
-                /*
-                 * NOTE: the insersion is done with unsafe pointers.
-                 * This is so because using &mut references dont seem to be possible since we have
-                 * to take successive references to nodes contents.
-                 */
-
-                let mut mut_internal = i;
-                let mut parent       = &mut *mut_internal as *mut DBVTInternal<B, BV>;
-
-                unsafe {
-                    (*parent).bounding_volume.merge(&pto_insert.bounding_volume);
-
-                    // iteratively go to the leaves
-                    let mut curr;
-                    let mut left;
-
-                    if (*parent).is_closest_to_left(&pto_insert.center) {
-                        curr = &mut (*parent).left as *mut DBVTNode<B, BV>;
-                        left = true;
-                    }
-                    else {
-                        curr = &mut (*parent).right as *mut DBVTNode<B, BV>;
-                        left = false;
-                    }
+    /// Removes the leaf `i` from the tree, keeping it on the leaves arena.
+    fn detach(&mut self, i: uint) {
+        let parent = self.leaf_at(i).parent;
+
+        self.leaf_at_mut(i).parent = None;
+
+        match parent {
+            None    => {
+                // the tree becomes empty
+                self.root = None
+            },
+            Some(p) => {
+                let (left, right, pp) = {
+                    let internal = self.internal(p);
+
+                    (internal.left, internal.right, internal.parent)
+                };
+
+                let other = if left == LeafId(i) { right } else { left };
+
+                self.set_node_parent(other, pp);
 
-                    loop {
-                        match *curr {
-                            Internal(ref mut ci) => {
-                                // FIXME: we could avoid the systematic merge
-                                ci.bounding_volume.merge(&pto_insert.bounding_volume);
-
-                                if ci.is_closest_to_left(&pto_insert.center) { // FIXME
-                                    curr = &mut ci.left as *mut DBVTNode<B, BV>;
-                                    left = true;
-                                }
-                                else {
-                                    curr = &mut ci.right as *mut DBVTNode<B, BV>;
-                                    left = false;
-                                }
-
-                                parent = &mut **ci as *mut DBVTInternal<B, BV>;
-                            },
-                            Leaf(ref l) => {
-                                let mut bl       = l.borrow_mut();
-                                let     pl       = bl.deref_mut();
-                                let mut internal = cache.alloc(DBVTInternal::new(
-                                    pl.bounding_volume.merged(&pto_insert.bounding_volume),
-                                    parent,
-                                    Leaf(l.clone()),
-                                    Leaf(to_insert.clone())));
-
-                                pl.parent         = LeftChildOf(&mut *internal as *mut DBVTInternal<B, BV>);
-                                pto_insert.parent = RightChildOf(&mut *internal as *mut DBVTInternal<B, BV>);
-
-                                if left {
-                                    (*parent).left = Internal(internal)
-                                }
-                                else {
-                                    (*parent).right = Internal(internal)
-                                }
-
-                                break;
-                            },
-                            Invalid => unreachable!()
-                        }
+                match pp {
+                    None     => {
+                        // the root changes to the other child
+                        self.root = Some(other)
+                    },
+                    Some(pp) => {
+                        self.replace_child(pp, InternalId(p), other);
+                        self.internal_mut(pp).state = NeedsShrink;
                     }
                 }
 
-                mut_internal
-            },
-            Leaf(l) => {
-                let mut bl = l.borrow_mut();
-                let     pl = bl.deref_mut();
+                *self.internals.get_mut(p) = None;
+                self.free_internals.push(p);
+            }
+        }
+    }
+
+    fn is_closest_to_left(&self, left: DBVTNodeId, right: DBVTNodeId, pt: &Vect) -> bool {
+        na::sqnorm(&(self.node_center(right) - *pt)) > na::sqnorm(&(self.node_center(left) - *pt))
+    }
 
-                // create the root
-                let mut root = cache.alloc(DBVTInternal::new(
-                    pl.bounding_volume.merged(&pto_insert.bounding_volume),
-                    ptr::mut_null(),
-                    Leaf(l),
-                    Leaf(to_insert.clone())));
+    /// Visit this tree using… a visitor!
+    pub fn visit<Vis: BVTVisitor<DBVTLeafId, BV>>(&self, visitor: &mut Vis) {
+        match self.root {
+            Some(r) => self.visit_node(r, visitor),
+            None    => { }
+        }
+    }
 
-                pl.parent         = LeftChildOf(&mut *root as *mut DBVTInternal<B, BV>);
-                pto_insert.parent = RightChildOf(&mut *root as *mut DBVTInternal<B, BV>);
+    fn visit_node<Vis: BVTVisitor<DBVTLeafId, BV>>(&self, node: DBVTNodeId, visitor: &mut Vis) {
+        match node {
+            InternalId(i) => {
+                let internal = self.internal(i);
 
-                root
+                if visitor.visit_internal(&internal.bounding_volume) {
+                    self.visit_node(internal.left, visitor);
+                    self.visit_node(internal.right, visitor);
+                }
             },
-            Invalid => unreachable!()
+            LeafId(i) => {
+                visitor.visit_leaf(&self.leaf_id(i), &self.leaf_at(i).bounding_volume)
+            }
         }
     }
 
@@ -466,107 +450,477 @@ impl<BV: 'static + BoundingVolume + Translation<Vect> + Clone, B: 'static + Clon
     /// volume.
     ///
     /// # Arguments:
-    /// * `to_test` - the leaf to check interference with.
+    /// * `to_test` - the leaf to check interferences with. It may be a leaf of another tree.
     /// * `out` - will be filled with all leaves intersecting `to_test`. Note that `to_test`
-    /// is not considered intersecting itself.
-    fn interferences_with_leaf(&self,
-                               to_test: &DBVTLeaf<B, BV>,
-                               out:     &mut Vec<Gc<RefCell<DBVTLeaf<B, BV>>>>) {
+    ///           is not considered intersecting itself.
+    pub fn interferences_with_leaf(&self, to_test: &DBVTLeaf<B, BV>, out: &mut Vec<DBVTLeafId>) {
         let mut visitor = BoundingVolumeInterferencesCollector::new(&to_test.bounding_volume, out);
+
         self.visit(&mut visitor)
     }
 
-    fn visit<Vis: BVTVisitor<Gc<RefCell<DBVTLeaf<B, BV>>>, BV>>(&self, visitor: &mut Vis) {
-        match *self {
-            Internal(ref i) => {
-                if visitor.visit_internal(&i.bounding_volume) {
-                    i.left.visit(visitor);
-                    i.right.visit(visitor);
-                }
-            },
-            Leaf(ref l) => {
-                let bl = l.borrow();
-                visitor.visit_leaf(l, &bl.bounding_volume)
-            },
-            Invalid => unreachable!()
-        }
-    }
-
-//    fn visit_mut<Vis: BVTVisitor<GcMut<DBVTLeaf<B, BV>>, BV>>(&mut self, visitor: &mut Vis) {
-//        match *self {
-//            Internal(ref mut i) => {
-//                i.partial_optimise();
-//
-//                if visitor.visit_internal(&mut i.bounding_volume) {
-//                    i.left.visit(visitor);
-//                    i.right.visit(visitor);
-//                }
-//            },
-//            Leaf(ref mut l) => {
-//                l.with_mut_borrow(|l_| visitor.visit_leaf_mut(l, &mut l_.bounding_volume))
-//            },
-//            Invalid => data::unreachable()
-//        }
-//    }
-
-    /// Finds all interferences between this tree and another one.
-    fn interferences_with_tree(&self,
-                               to_test: &DBVTNode<B, BV>,
-                               out:     &mut Vec<Gc<RefCell<DBVTLeaf<B, BV>>>>) {
-        match (self, to_test) {
-            (&Leaf(_), &Leaf(ref lb)) => {
-                let blb = lb.borrow();
-                self.interferences_with_leaf(blb.deref(), out)
-            },
-            (&Leaf(ref la), &Internal(_)) => {
-                let bla = la.borrow();
-                to_test.interferences_with_leaf(bla.deref(), out)
+    /// Finds all interferences between the leaves of this tree and those of another one.
+    ///
+    /// # Arguments:
+    /// * `other` - the other tree. It must not be `self`.
+    /// * `out` - will be filled with the pairs of intersecting leaves. The first leaf of each
+    ///           pair is on `self`, the second one on `other`.
+    pub fn interferences_with_tree(&self, other: &DBVT<B, BV>, out: &mut Vec<(DBVTLeafId, DBVTLeafId)>) {
+        match (self.root, other.root) {
+            (Some(a), Some(b)) => self.interferences_between_nodes(a, other, b, out),
+            _                  => { }
+        }
+    }
+
+    fn interferences_between_nodes(&self,
+                                   a:     DBVTNodeId,
+                                   other: &DBVT<B, BV>,
+                                   b:     DBVTNodeId,
+                                   out:   &mut Vec<(DBVTLeafId, DBVTLeafId)>) {
+        if !self.node_bounding_volume(a).intersects(other.node_bounding_volume(b)) {
+            return
+        }
+
+        match (a, b) {
+            (LeafId(la), LeafId(lb)) => out.push((self.leaf_id(la), other.leaf_id(lb))),
+            (LeafId(_), InternalId(ib)) => {
+                self.interferences_between_nodes(a, other, other.internal(ib).left, out);
+                self.interferences_between_nodes(a, other, other.internal(ib).right, out);
             },
-            (&Internal(_), &Leaf(ref lb)) => {
-                let blb = lb.borrow();
-                self.interferences_with_leaf(blb.deref(), out)
+            (InternalId(ia), LeafId(_)) => {
+                self.interferences_between_nodes(self.internal(ia).left, other, b, out);
+                self.interferences_between_nodes(self.internal(ia).right, other, b, out);
             },
-            (&Internal(ref la), &Internal(ref lb)) => {
-                // FIXME: la.partial_optimise();
-                // FIXME: lb.partial_optimise();
-
-                if (&**la as *const DBVTInternal<B, BV> != &**lb as *const DBVTInternal<B, BV>) &&
-                    la.bounding_volume.intersects(&lb.bounding_volume)
-                {
-                    la.right.interferences_with_tree(&lb.right, out);
-                    la.left.interferences_with_tree(&lb.left, out);
-                    la.right.interferences_with_tree(&lb.left, out);
-                    la.left.interferences_with_tree(&lb.right, out);
+            (InternalId(ia), InternalId(ib)) => {
+                let (la, ra) = (self.internal(ia).left, self.internal(ia).right);
+                let (lb, rb) = (other.internal(ib).left, other.internal(ib).right);
+
+                self.interferences_between_nodes(ra, other, rb, out);
+                self.interferences_between_nodes(la, other, lb, out);
+                self.interferences_between_nodes(ra, other, lb, out);
+                self.interferences_between_nodes(la, other, rb, out);
+            }
+        }
+    }
+}
+
+/// Element of the priority queue of the best-first traversals.
+enum Candidate {
+    /// A node, weighted by the distance to its bounding volume.
+    NodeCandidate(DBVTNodeId),
+    /// A leaf, weighted by the exact distance to its object.
+    LeafCandidate(uint)
+}
+
+impl<B, BV: PointQuery> DBVT<B, BV> {
+    /// Finds all leaves which have their object containing a given point.
+    ///
+    /// # Arguments:
+    /// * `pt`      - the point.
+    /// * `dist_fn` - computes the exact distance between an object and a point. The object
+    ///               contains the point if this distance is zero.
+    /// * `out`     - will be filled with the leaves containing `pt`.
+    pub fn interferences_with_point(&self,
+                                    pt:      &Vect,
+                                    dist_fn: &mut |&B, &Vect| -> Scalar,
+                                    out:     &mut Vec<DBVTLeafId>) {
+        match self.root {
+            Some(r) => self.node_interferences_with_point(r, pt, dist_fn, out),
+            None    => { }
+        }
+    }
+
+    fn node_interferences_with_point(&self,
+                                     node:    DBVTNodeId,
+                                     pt:      &Vect,
+                                     dist_fn: &mut |&B, &Vect| -> Scalar,
+                                     out:     &mut Vec<DBVTLeafId>) {
+        match node {
+            InternalId(i) => {
+                let internal = self.internal(i);
+
+                if internal.bounding_volume.contains_point(pt) {
+                    self.node_interferences_with_point(internal.left, pt, dist_fn, out);
+                    self.node_interferences_with_point(internal.right, pt, dist_fn, out);
                 }
             },
-            _ => unreachable!() // combinations with Invalid
+            LeafId(i) => {
+                let leaf = self.leaf_at(i);
+
+                if leaf.bounding_volume.contains_point(pt) && (*dist_fn)(&leaf.object, pt) <= na::zero() {
+                    out.push(self.leaf_id(i))
+                }
+            }
         }
     }
-}
 
-/*
-impl<BV: 'static + BoundingVolume + RayCast<Scalar, Vect> + Translation<Vect>,
-     B:  'static,
-     Vect:  'static + AlgebraicVec<Scalar>,
-     Scalar:  Algebraic + Ord>
-DBVTNode<Vect, B, BV> {
-    fn interferences_with_ray(&self, ray: &Ray<Vect>, out: &mut Vec<Gc<RefCell<f<Vect, B, BV>>>) {
-        match (*self) {
-            Internal(ref i) => {
-                if i.bounding_volume.intersects_ray(ray) {
-                    i.left.interferences_with_ray(ray, out);
-                    i.right.interferences_with_ray(ray, out)
+    /// Finds the `k` leaves which have their objects closest to a given point, sorted by
+    /// increasing distance.
+    ///
+    /// # Arguments:
+    /// * `pt`      - the point.
+    /// * `k`       - the maximum number of leaves to collect.
+    /// * `dist_fn` - computes the exact distance between an object and a point. It must never be
+    ///               smaller than the distance between the point and the leaf bounding volume.
+    /// * `out`     - will be filled with the distance to and the leaf of each object found.
+    pub fn k_nearest_to_point(&self,
+                              pt:      &Vect,
+                              k:       uint,
+                              dist_fn: &mut |&B, &Vect| -> Scalar,
+                              out:     &mut Vec<(Scalar, DBVTLeafId)>) {
+        self.nearest_to_point_with_max_dist(pt, k, Bounded::max_value(), dist_fn, out)
+    }
+
+    /// Finds the leaf which has its object closest to a given point, among those closer than
+    /// `max_dist`.
+    ///
+    /// `dist_fn` computes the exact distance between an object and a point. It must never be
+    /// smaller than the distance between the point and the leaf bounding volume.
+    pub fn nearest_to_point_within_distance(&self,
+                                            pt:       &Vect,
+                                            max_dist: Scalar,
+                                            dist_fn:  &mut |&B, &Vect| -> Scalar)
+                                            -> Option<(Scalar, DBVTLeafId)> {
+        let mut res = Vec::with_capacity(1);
+
+        self.nearest_to_point_with_max_dist(pt, 1, max_dist, dist_fn, &mut res);
+
+        res.pop()
+    }
+
+    // Same best-first traversal as for the `BVT`.
+    fn nearest_to_point_with_max_dist(&self,
+                                      pt:       &Vect,
+                                      k:        uint,
+                                      max_dist: Scalar,
+                                      dist_fn:  &mut |&B, &Vect| -> Scalar,
+                                      out:      &mut Vec<(Scalar, DBVTLeafId)>) {
+        let mut queue = PriorityQueue::new();
+
+        match self.root {
+            Some(r) => {
+                let dist = self.node_bounding_volume(r).distance_to_point(pt);
+
+                if dist <= max_dist {
+                    queue.push(WeightedValue::new(dist, NodeCandidate(r)))
                 }
             },
-            Leaf(ref l) => {
-                if l.bounding_volume.intersects_ray(ray) {
-                    out.push(l.clone())
+            None => return
+        }
+
+        let mut num_found = 0u;
+
+        while num_found < k {
+            let curr = match queue.pop() {
+                Some(c) => c,
+                None    => break
+            };
+
+            match curr.value {
+                NodeCandidate(InternalId(i)) => {
+                    let (left, right) = (self.internal(i).left, self.internal(i).right);
+                    let left_dist     = self.node_bounding_volume(left).distance_to_point(pt);
+                    let right_dist    = self.node_bounding_volume(right).distance_to_point(pt);
+
+                    if left_dist <= max_dist {
+                        queue.push(WeightedValue::new(left_dist, NodeCandidate(left)))
+                    }
+
+                    if right_dist <= max_dist {
+                        queue.push(WeightedValue::new(right_dist, NodeCandidate(right)))
+                    }
+                },
+                NodeCandidate(LeafId(i)) => {
+                    let dist = (*dist_fn)(&self.leaf_at(i).object, pt);
+
+                    if dist <= max_dist {
+                        queue.push(WeightedValue::new(dist, LeafCandidate(i)))
+                    }
+                },
+                LeafCandidate(i) => {
+                    out.push((curr.cost, self.leaf_id(i)));
+                    num_found = num_found + 1;
                 }
+            }
+        }
+    }
+}
+
+impl<B, BV: BoundingVolume + SurfaceArea + Translation<Vect> + Clone> DBVT<B, BV> {
+    /// The sum of the surface areas of the bounding volumes of every internal node.
+    ///
+    /// The smaller, the faster the queries on this tree are expected to be.
+    pub fn total_internal_surface_area(&self) -> Scalar {
+        let mut res = na::zero::<Scalar>();
+
+        for internal in self.internals.iter() {
+            match *internal {
+                Some(ref internal) => res = res + internal.bounding_volume.surface_area(),
+                None               => { }
+            }
+        }
+
+        res
+    }
+
+    /// Runs one step of the incremental optimization of this tree.
+    ///
+    /// Up to `budget` internal nodes are visited, resuming where the previous step stopped. Each
+    /// visited node first has its bounding volume refitted if some leaf was removed below it.
+    /// Then, one of its children is swapped with one of its grand-children if this reduces the
+    /// surface area of the subtree. Leaf handles are not affected.
+    ///
+    /// Returns the number of rotations performed.
+    pub fn optimize_incremental(&mut self, budget: uint) -> uint {
+        let num_internals     = self.internals.len();
+        let mut num_rotations = 0u;
+
+        if num_internals == 0 {
+            return 0
+        }
+
+        for i in range(self.optimize_off, self.optimize_off + cmp::min(budget, num_internals)) {
+            let id = i % num_internals;
+
+            if self.internals.get(id).is_none() {
+                continue
+            }
+
+            self.shrink(id);
+
+            if self.rotate(id) {
+                num_rotations = num_rotations + 1;
+            }
+        }
+
+        self.optimize_off = (self.optimize_off + budget) % num_internals;
+
+        num_rotations
+    }
+
+    /// Refits the bounding volume of the internal node `i` if it is too large.
+    fn shrink(&mut self, i: uint) {
+        match self.internal(i).state {
+            UpToDate    => return,
+            NeedsShrink => { }
+        }
+
+        let (left, right, parent) = {
+            let internal = self.internal(i);
+
+            (internal.left, internal.right, internal.parent)
+        };
+
+        let bv = self.node_bounding_volume(left).merged(self.node_bounding_volume(right));
+
+        {
+            let internal = self.internal_mut(i);
+
+            internal.center          = bv.translation();
+            internal.bounding_volume = bv;
+            internal.state           = UpToDate;
+        }
+
+        // The parent may be smaller now.
+        match parent {
+            Some(p) => self.internal_mut(p).state = NeedsShrink,
+            None    => { }
+        }
+    }
+
+    /// Applies the best rotation at the internal node `i`, if any reduces its children surface
+    /// area.
+    fn rotate(&mut self, i: uint) -> bool {
+        let (left, right) = (self.internal(i).left, self.internal(i).right);
+
+        // (child of `i`, internal child of `i`, grand-child, new bounding volume of the internal
+        // child, surface area gain)
+        let mut best: Option<(DBVTNodeId, uint, DBVTNodeId, BV, Scalar)> = None;
+
+        for &(child, other) in [ (left, right), (right, left) ].iter() {
+            let c = match other {
+                InternalId(c) => c,
+                LeafId(_)     => continue
+            };
+
+            let (gleft, gright) = (self.internal(c).left, self.internal(c).right);
+            let curr_area       = self.internal(c).bounding_volume.surface_area();
+
+            // Swapping `child` with one grand-child makes it the sibling of the other one.
+            for &(grand_child, sibling) in [ (gleft, gright), (gright, gleft) ].iter() {
+                let bv   = self.node_bounding_volume(child).merged(self.node_bounding_volume(sibling));
+                let gain = curr_area - bv.surface_area();
+
+                let is_better = match best {
+                    Some((_, _, _, _, ref best_gain)) => gain > *best_gain,
+                    None                              => gain > na::zero()
+                };
+
+                if is_better {
+                    best = Some((child, c, grand_child, bv, gain))
+                }
+            }
+        }
+
+        match best {
+            Some((child, c, grand_child, bv, _)) => {
+                self.replace_child(i, child, grand_child);
+                self.replace_child(c, grand_child, child);
+                self.set_node_parent(grand_child, Some(i));
+                self.set_node_parent(child, Some(c));
+
+                let internal = self.internal_mut(c);
+
+                internal.center          = bv.translation();
+                internal.bounding_volume = bv;
+                internal.state           = UpToDate;
+
+                true
             },
-            Invalid => unreachable!()
+            None => false
         }
     }
 }
-*/
 
-// XXX: Drop should be implemented to invalidate the leaves parents when the tree is dropped.
+#[cfg(test, dim3, f64)]
+mod test {
+    use std::task;
+    use test::Bencher;
+    use nalgebra::na::Vec3;
+    use bounding_volume::AABB;
+    use partitioning::managed_dbvt::ManagedDBVT;
+    use super::{DBVT, DBVTLeafId};
+
+    // AABBs on a 10x10xN grid.
+    fn grid_aabbs(n: uint) -> Vec<AABB> {
+        Vec::from_fn(n, |i| {
+            let center = Vec3::new((i % 10) as f64, ((i / 10) % 10) as f64, (i / 100) as f64) * 2.0;
+
+            AABB::new(center - Vec3::new(0.5, 0.5, 0.5), center + Vec3::new(0.5, 0.5, 0.5))
+        })
+    }
+
+    #[test]
+    fn test_dbvt_insert_remove() {
+        let aabbs    = grid_aabbs(100);
+        let mut tree = DBVT::new();
+        let ids: Vec<DBVTLeafId> = aabbs.iter().enumerate().map(|(i, aabb)| tree.insert(i, aabb.clone())).collect();
+
+        assert_eq!(tree.len(), 100);
+
+        for (i, id) in ids.iter().enumerate() {
+            assert_eq!(tree.leaf(id.clone()).object, i);
+        }
+
+        // Remove every other leaf: the handles of the remaining ones are not affected.
+        for i in range(0u, 50) {
+            assert_eq!(tree.remove(ids.get(2 * i).clone()).object, 2 * i);
+        }
+
+        assert_eq!(tree.len(), 50);
+
+        for i in range(0u, 50) {
+            assert_eq!(tree.leaf(ids.get(2 * i + 1).clone()).object, 2 * i + 1);
+        }
+
+        // Every leaf intersects itself only once.
+        for i in range(0u, 50) {
+            let mut out = Vec::new();
+            let leaf    = tree.leaf(ids.get(2 * i + 1).clone()).clone();
+
+            tree.interferences_with_leaf(&leaf, &mut out);
+
+            assert_eq!(out.iter().filter(|id| **id == *ids.get(2 * i + 1)).count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_dbvt_removed_leaf_handle() {
+        let aabbs    = grid_aabbs(2);
+        let mut tree = DBVT::new();
+        let removed  = tree.insert(0u, aabbs.get(0).clone());
+        let _        = tree.insert(1u, aabbs.get(1).clone());
+
+        let _     = tree.remove(removed.clone());
+        let reuse = tree.insert(2u, aabbs.get(0).clone());
+
+        // The new leaf reuses the slot of the removed one, but not its handle.
+        assert!(reuse != removed);
+        assert_eq!(tree.leaf(reuse.clone()).object, 2);
+
+        let res = task::try(proc() {
+            let _ = tree.leaf(removed);
+        });
+
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_dbvt_optimize_incremental() {
+        let aabbs    = grid_aabbs(200);
+        let mut tree = DBVT::new();
+
+        assert_eq!(tree.depth(), 0);
+
+        // Alternate between both ends of the grid to get a poor hierarchy.
+        let ids: Vec<DBVTLeafId> = range(0u, 200).map(|i| {
+            let j = if i % 2 == 0 { i / 2 } else { 199 - i / 2 };
+            tree.insert(j, aabbs.get(j).clone())
+        }).collect();
+
+        // Remove some leaves so that some internal nodes need to be refitted.
+        for i in range(0u, 20) {
+            let _ = tree.remove(ids.get(10 * i).clone());
+        }
+
+        let area_before = tree.total_internal_surface_area();
+
+        for _ in range(0u, 10) {
+            let _ = tree.optimize_incremental(50);
+        }
+
+        assert!(tree.total_internal_surface_area() < area_before);
+        assert_eq!(tree.len(), 180);
+
+        // The tree still finds every interference.
+        for (i, id) in ids.iter().enumerate() {
+            if i % 10 != 0 {
+                let mut out = Vec::new();
+                let leaf    = tree.leaf(id.clone()).clone();
+
+                tree.interferences_with_leaf(&leaf, &mut out);
+
+                assert!(out.iter().any(|other| *other == *id));
+            }
+        }
+    }
+
+    #[bench]
+    fn bench_dbvt_insert_remove(bh: &mut Bencher) {
+        let aabbs    = grid_aabbs(1000);
+        let mut tree = DBVT::new();
+
+        bh.iter(|| {
+            let ids: Vec<DBVTLeafId> = aabbs.iter().enumerate().map(|(i, aabb)| tree.insert(i, aabb.clone())).collect();
+
+            for id in ids.move_iter() {
+                let _ = tree.remove(id);
+            }
+        })
+    }
+
+    // Same as `bench_dbvt_insert_remove`, with the pointer-based tree the `DBVT` replaced.
+    #[bench]
+    fn bench_managed_dbvt_insert_remove(bh: &mut Bencher) {
+        let aabbs    = grid_aabbs(1000);
+        let mut tree = ManagedDBVT::new();
+
+        bh.iter(|| {
+            let leaves: Vec<_> = aabbs.iter().enumerate().map(|(i, aabb)| tree.insert_new(i, aabb.clone())).collect();
+
+            for mut leaf in leaves.move_iter() {
+                tree.remove(&mut leaf);
+            }
+        })
+    }
+}
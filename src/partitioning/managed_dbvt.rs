@@ -0,0 +1,365 @@
+//! The pointer-based Dynamic Bounding Volume Tree the arena-based `DBVT` replaced.
+//!
+//! Leaves are garbage-collected and internal nodes are boxed and linked by raw pointers. Only the
+//! insertion and removal are kept: this is compiled for the benchmarks comparing both trees.
+
+use std::gc::{Gc, GC};
+use std::cell::RefCell;
+use std::ptr;
+use std::mem;
+use data::owned_allocation_cache::OwnedAllocationCache;
+use nalgebra::na::Translation;
+use nalgebra::na;
+use bounding_volume::BoundingVolume;
+use math::{Scalar, Vect};
+
+type Cache<B, BV> = OwnedAllocationCache<DBVTInternal<B, BV>>;
+
+/// A pointer-based Dynamic Bounding Volume Tree.
+pub struct ManagedDBVT<B, BV> {
+    cache: Cache<B, BV>,
+    tree:  Option<DBVTNode<B, BV>>,
+    len:   uint
+}
+
+impl<B, BV> ManagedDBVT<B, BV> {
+    /// Creates a new pointer-based Dynamic Bounding Volume Tree.
+    pub fn new() -> ManagedDBVT<B, BV> {
+        ManagedDBVT {
+            cache: OwnedAllocationCache::new(),
+            tree:  None,
+            len:   0
+        }
+    }
+}
+
+impl<BV: 'static + BoundingVolume + Translation<Vect> + Clone,
+     B:  'static + Clone>
+ManagedDBVT<B, BV> {
+    /// Removes a leaf from the tree. Fails if the tree is empty.
+    pub fn remove(&mut self, leaf: &mut Gc<RefCell<ManagedDBVTLeaf<B, BV>>>) {
+        let self_tree = self.tree.take().unwrap();
+
+        let mut bleaf = leaf.borrow_mut();
+        self.tree = bleaf.unlink(&mut self.cache, self_tree);
+        self.len  = self.len - 1;
+    }
+
+    /// Creates, inserts, and returns a new leaf with the given content.
+    pub fn insert_new(&mut self, b: B, bv: BV) -> Gc<RefCell<ManagedDBVTLeaf<B, BV>>> {
+        let leaf = box(GC) RefCell::new(ManagedDBVTLeaf::new(bv, b));
+
+        self.insert(leaf.clone());
+
+        leaf
+    }
+
+    /// Inserts a leaf to the tree.
+    pub fn insert(&mut self, leaf: Gc<RefCell<ManagedDBVTLeaf<B, BV>>>) {
+        let mut self_tree = None;
+        mem::swap(&mut self_tree, &mut self.tree);
+
+        self.tree = match self_tree {
+            None    => Some(Leaf(leaf)),
+            Some(t) => Some(Internal(t.insert(&mut self.cache, leaf)))
+        };
+
+        self.len = self.len + 1;
+    }
+}
+
+/// Node of the pointer-based Dynamic Bounding Volume Tree.
+enum DBVTNode<B, BV> {
+    Internal(Box<DBVTInternal<B, BV>>),
+    Leaf(Gc<RefCell<ManagedDBVTLeaf<B, BV>>>),
+    Invalid
+}
+
+/// Internal node of a pointer-based DBVT. An internal node always has two children.
+struct DBVTInternal<B, BV> {
+    /// The bounding volume of this node. It always encloses both its children bounding volumes.
+    bounding_volume: BV,
+    /// The center of this node bounding volume.
+    center:          Vect,
+    /// This node left child.
+    left:            DBVTNode<B, BV>,
+    /// This node right child.
+    right:           DBVTNode<B, BV>,
+    /// This node parent.
+    parent:          *mut DBVTInternal<B, BV>
+}
+
+impl<BV: Translation<Vect>, B> DBVTInternal<B, BV> {
+    /// Creates a new internal node.
+    fn new(bounding_volume: BV,
+           parent:          *mut DBVTInternal<B, BV>,
+           left:            DBVTNode<B, BV>,
+           right:           DBVTNode<B, BV>)
+           -> DBVTInternal<B, BV> {
+        DBVTInternal {
+            center:          bounding_volume.translation(),
+            bounding_volume: bounding_volume,
+            left:            left,
+            right:           right,
+            parent:          parent
+        }
+    }
+}
+
+/// State of a leaf.
+enum DBVTLeafState<B, BV> {
+    /// This leaf is the right child of another node.
+    RightChildOf(*mut DBVTInternal<B, BV>),
+    /// This leaf is the left child of another node.
+    LeftChildOf(*mut DBVTInternal<B, BV>),
+    /// This leaf is detached from any tree.
+    Detached
+}
+
+impl<B, BV> DBVTLeafState<B, BV> {
+    /// Indicates whether this leaf is detached.
+    #[inline]
+    fn is_detached(&self) -> bool {
+        match *self {
+            Detached => true,
+            _        => false
+        }
+    }
+
+    /// Returns a pointer to this leaf parent and `true` if it is the left child.
+    #[inline]
+    fn unwrap(self) -> (bool, *mut DBVTInternal<B, BV>) {
+        match self {
+            RightChildOf(p) => (false, p),
+            LeftChildOf(p)  => (true, p),
+            _               => fail!("Attempting to unwrap a detached node.")
+        }
+    }
+}
+
+/// Leaf of a pointer-based Dynamic Bounding Volume Tree.
+pub struct ManagedDBVTLeaf<B, BV> {
+    /// The bounding volume of this node.
+    pub bounding_volume: BV,
+    /// The center of this node bounding volume.
+    pub center:          Vect,
+    /// An user-defined object.
+    pub object:          B,
+    /// This node parent.
+    parent:              DBVTLeafState<B, BV>
+}
+
+impl<B, BV> DBVTNode<B, BV> {
+    fn take_internal(self) -> Box<DBVTInternal<B, BV>> {
+        match self {
+            Internal(i) => i,
+            _ => fail!("DBVT internal error: this is not an internal node.")
+        }
+    }
+
+    fn invalidate(&mut self) -> DBVTNode<B, BV> {
+        let mut res = Invalid;
+
+        mem::swap(&mut res, self);
+
+        res
+    }
+}
+
+impl<B, BV> DBVTInternal<B, BV> {
+    fn is_right_internal_node(&self, r: &mut DBVTInternal<B, BV>) -> bool
+    {
+        match self.right {
+            Internal(ref i) => &**i as *const DBVTInternal<B, BV> == &*r as *const DBVTInternal<B, BV>,
+            _ => false
+        }
+    }
+}
+
+impl<B: 'static, BV: Translation<Vect> + 'static> ManagedDBVTLeaf<B, BV> {
+    /// Creates a new leaf.
+    pub fn new(bounding_volume: BV, object: B) -> ManagedDBVTLeaf<B, BV> {
+        ManagedDBVTLeaf {
+            center:          bounding_volume.translation(),
+            bounding_volume: bounding_volume,
+            object:          object,
+            parent:          Detached
+        }
+    }
+
+    /// Removes this leaf from the tree.
+    ///
+    /// Returns the new root of the tree.
+    fn unlink(&mut self,
+              cache:     &mut Cache<B, BV>,
+              curr_root: DBVTNode<B, BV>) -> Option<DBVTNode<B, BV>> {
+        if !self.parent.is_detached() {
+            let (is_left, p) = mem::replace(&mut self.parent, Detached).unwrap();
+
+            let pp           = unsafe { (*p).parent };
+            let parent_left  = unsafe { (*p).left.invalidate() };
+            let parent_right = unsafe { (*p).right.invalidate() };
+
+            let mut other = if is_left { parent_right } else { parent_left };
+
+            if pp.is_not_null() {
+                let is_p_right_to_pp = unsafe { (*pp).is_right_internal_node(&mut *p) };
+                // we are far away from the root
+                unsafe {
+                    match other {
+                        Internal(ref mut i) => i.parent = pp,
+                        Leaf(ref mut l)     => {
+                            l.borrow_mut().deref_mut().parent =
+                                if is_p_right_to_pp { RightChildOf(pp) } else { LeftChildOf(pp) }
+                        },
+                        Invalid             => unreachable!()
+                    }
+
+                    if is_p_right_to_pp {
+                        mem::swap(&mut (*pp).right, &mut other);
+                        // NOTE: the children have already been invalidated before
+                        cache.retain(other.take_internal())
+                    }
+                    else {
+                        mem::swap(&mut (*pp).left, &mut other);
+                        // NOTE: the children have already been invalidated before
+                        cache.retain(other.take_internal())
+                    }
+                }
+
+                Some(curr_root)
+            }
+            else {
+                // the root changes to the other child
+                match other {
+                    Internal(ref mut i) => i.parent = ptr::mut_null(),
+                    Leaf(ref l)         => l.borrow_mut().deref_mut().parent = Detached,
+                    Invalid             => unreachable!()
+                }
+
+                Some(other)
+            }
+        }
+        else {
+            self.parent = Detached;
+
+            // the tree becomes empty
+            None
+        }
+    }
+}
+
+impl<BV: 'static + BoundingVolume, B: 'static> DBVTNode<B, BV> {
+    fn sqdist_to(&self, to: &Vect) -> Scalar {
+        match *self {
+            Internal(ref i) => na::sqnorm(&(i.center - *to)),
+            Leaf(ref l)     => {
+                let bl = l.borrow();
+                na::sqnorm(&(bl.center - *to))
+            },
+            Invalid         => unreachable!()
+        }
+    }
+}
+
+impl<BV: 'static + Translation<Vect> + BoundingVolume, B: 'static> DBVTInternal<B, BV> {
+    fn is_closest_to_left(&self, pt: &Vect) -> bool {
+        self.right.sqdist_to(pt) > self.left.sqdist_to(pt)
+    }
+}
+
+impl<BV: 'static + BoundingVolume + Translation<Vect> + Clone, B: 'static + Clone> DBVTNode<B, BV> {
+    /// Inserts a new leaf on this tree.
+    fn insert(self,
+              cache:     &mut Cache<B, BV>,
+              to_insert: Gc<RefCell<ManagedDBVTLeaf<B, BV>>>)
+              -> Box<DBVTInternal<B, BV>> {
+
+        let mut bto_insert = to_insert.borrow_mut();
+        let pto_insert     = bto_insert.deref_mut();
+
+        match self {
+            Internal(i) => {
+                let mut mut_internal = i;
+                let mut parent       = &mut *mut_internal as *mut DBVTInternal<B, BV>;
+
+                unsafe {
+                    (*parent).bounding_volume.merge(&pto_insert.bounding_volume);
+
+                    // iteratively go to the leaves
+                    let mut curr;
+                    let mut left;
+
+                    if (*parent).is_closest_to_left(&pto_insert.center) {
+                        curr = &mut (*parent).left as *mut DBVTNode<B, BV>;
+                        left = true;
+                    }
+                    else {
+                        curr = &mut (*parent).right as *mut DBVTNode<B, BV>;
+                        left = false;
+                    }
+
+                    loop {
+                        match *curr {
+                            Internal(ref mut ci) => {
+                                ci.bounding_volume.merge(&pto_insert.bounding_volume);
+
+                                if ci.is_closest_to_left(&pto_insert.center) {
+                                    curr = &mut ci.left as *mut DBVTNode<B, BV>;
+                                    left = true;
+                                }
+                                else {
+                                    curr = &mut ci.right as *mut DBVTNode<B, BV>;
+                                    left = false;
+                                }
+
+                                parent = &mut **ci as *mut DBVTInternal<B, BV>;
+                            },
+                            Leaf(ref l) => {
+                                let mut bl       = l.borrow_mut();
+                                let     pl       = bl.deref_mut();
+                                let mut internal = cache.alloc(DBVTInternal::new(
+                                    pl.bounding_volume.merged(&pto_insert.bounding_volume),
+                                    parent,
+                                    Leaf(l.clone()),
+                                    Leaf(to_insert.clone())));
+
+                                pl.parent         = LeftChildOf(&mut *internal as *mut DBVTInternal<B, BV>);
+                                pto_insert.parent = RightChildOf(&mut *internal as *mut DBVTInternal<B, BV>);
+
+                                if left {
+                                    (*parent).left = Internal(internal)
+                                }
+                                else {
+                                    (*parent).right = Internal(internal)
+                                }
+
+                                break;
+                            },
+                            Invalid => unreachable!()
+                        }
+                    }
+                }
+
+                mut_internal
+            },
+            Leaf(l) => {
+                let mut bl = l.borrow_mut();
+                let     pl = bl.deref_mut();
+
+                // create the root
+                let mut root = cache.alloc(DBVTInternal::new(
+                    pl.bounding_volume.merged(&pto_insert.bounding_volume),
+                    ptr::mut_null(),
+                    Leaf(l),
+                    Leaf(to_insert.clone())));
+
+                pl.parent         = LeftChildOf(&mut *root as *mut DBVTInternal<B, BV>);
+                pto_insert.parent = RightChildOf(&mut *root as *mut DBVTInternal<B, BV>);
+
+                root
+            },
+            Invalid => unreachable!()
+        }
+    }
+}
@@ -1,8 +1,8 @@
 //! Spatial partitioning tools.
 
-pub use partitioning::dbvt::{DBVT, DBVTLeaf, DBVTLeafState};
+pub use partitioning::dbvt::{DBVT, DBVTLeaf, DBVTLeafId};
 pub use partitioning::bvt::{BVT, kdtree_partitioner, kdtree_partitioner_with_centers,
-                            BinaryPartition, BVTNode, Internal, Leaf};
+                            sah_partitioner, BinaryPartition, BVTNode, Internal, Leaf};
 pub use partitioning::bvt_visitor::{BVTVisitor, RayInterferencesCollector, BoundingVolumeInterferencesCollector};
 pub use partitioning::bvtt_visitor::BVTTVisitor;
 
@@ -10,3 +10,6 @@ mod dbvt;
 mod bvt;
 mod bvt_visitor;
 mod bvtt_visitor;
+mod weighted_value;
+#[cfg(test, dim3, f64)]
+mod managed_dbvt;
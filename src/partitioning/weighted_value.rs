@@ -0,0 +1,53 @@
+use math::Scalar;
+
+/// A value with a cost, ordered such that the smallest cost has the greatest priority on a
+/// `PriorityQueue`.
+pub struct WeightedValue<T> {
+    /// The cost of the value.
+    pub cost:  Scalar,
+    /// The value.
+    pub value: T
+}
+
+impl<T> WeightedValue<T> {
+    /// Creates a new weighted value.
+    #[inline]
+    pub fn new(cost: Scalar, value: T) -> WeightedValue<T> {
+        WeightedValue {
+            cost:  cost,
+            value: value
+        }
+    }
+}
+
+impl<T> PartialEq for WeightedValue<T> {
+    #[inline]
+    fn eq(&self, other: &WeightedValue<T>) -> bool {
+        self.cost == other.cost
+    }
+}
+
+impl<T> Eq for WeightedValue<T> {
+}
+
+impl<T> PartialOrd for WeightedValue<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &WeightedValue<T>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for WeightedValue<T> {
+    #[inline]
+    fn cmp(&self, other: &WeightedValue<T>) -> Ordering {
+        if self.cost < other.cost {
+            Greater
+        }
+        else if self.cost > other.cost {
+            Less
+        }
+        else {
+            Equal
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! Point inclusion and projection.
+
+// types an traits
+#[doc(inline)]
+pub use point::point_query::{PointQuery, PointProjection};
+pub use point::point_query_dispatcher::{PointQueryDispatcher, PointQueryFn};
+
+// functions
+pub use point::point_implicit::implicit_project_point;
+pub use point::point_concave::concave_project_point;
+
+// modules
+#[doc(hidden)]
+pub mod point_query;
+mod point_query_dispatcher;
+mod point_aabb;
+mod point_bounding_sphere;
+mod point_ball;
+mod point_plane;
+mod point_box;
+mod point_implicit;
+mod point_concave;
+mod point_bezier_surface;
+#[dim3]
+mod point_torus;
@@ -0,0 +1,55 @@
+use std::num::Bounded;
+use nalgebra::na::Indexable;
+use nalgebra::na;
+use bounding_volume::AABB;
+use point::{PointQuery, PointProjection};
+use math::{Scalar, Vect};
+
+impl PointQuery for AABB {
+    fn project_point(&self, pt: &Vect, solid: bool) -> PointProjection {
+        let mut inside = true;
+        let mut proj   = pt.clone();
+
+        for i in range(0u, na::dim::<Vect>()) {
+            let (min, max, x) = (self.mins().at(i), self.maxs().at(i), pt.at(i));
+
+            if x < min {
+                inside = false;
+                proj.set(i, min)
+            }
+            else if x > max {
+                inside = false;
+                proj.set(i, max)
+            }
+        }
+
+        if !inside || solid {
+            return PointProjection::new(inside, proj)
+        }
+
+        // Move the point to the closest face.
+        let mut best_dist: Scalar = Bounded::max_value();
+        let mut best_axis         = 0u;
+        let mut best_coord        = na::zero();
+
+        for i in range(0u, na::dim::<Vect>()) {
+            let (min, max, x) = (self.mins().at(i), self.maxs().at(i), pt.at(i));
+
+            if x - min < best_dist {
+                best_dist  = x - min;
+                best_axis  = i;
+                best_coord = min;
+            }
+
+            if max - x < best_dist {
+                best_dist  = max - x;
+                best_axis  = i;
+                best_coord = max;
+            }
+        }
+
+        proj.set(best_axis, best_coord);
+
+        PointProjection::new(true, proj)
+    }
+}
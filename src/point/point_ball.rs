@@ -0,0 +1,39 @@
+use std::num::Zero;
+use nalgebra::na::Indexable;
+use nalgebra::na;
+use geom::Ball;
+use point::{PointQuery, PointProjection};
+use math::{Scalar, Vect};
+
+impl PointQuery for Ball {
+    #[inline]
+    fn project_point(&self, pt: &Vect, solid: bool) -> PointProjection {
+        let sqdist = na::sqnorm(pt);
+        let inside = sqdist <= self.radius() * self.radius();
+
+        if inside && solid {
+            PointProjection::new(true, pt.clone())
+        }
+        else if sqdist.is_zero() {
+            // Every point of the sphere is a valid projection.
+            let mut proj: Vect = na::zero();
+
+            proj.set(0, self.radius());
+
+            PointProjection::new(inside, proj)
+        }
+        else {
+            PointProjection::new(inside, *pt * (self.radius() / sqdist.sqrt()))
+        }
+    }
+
+    #[inline]
+    fn distance_to_point(&self, pt: &Vect) -> Scalar {
+        (na::norm(pt) - self.radius()).max(na::zero())
+    }
+
+    #[inline]
+    fn contains_point(&self, pt: &Vect) -> bool {
+        na::sqnorm(pt) <= self.radius() * self.radius()
+    }
+}
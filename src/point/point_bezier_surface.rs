@@ -0,0 +1,74 @@
+use nalgebra::na;
+use geom::BezierSurface;
+use narrow::surface_selector::HyperPlaneSurfaceSelector;
+use narrow::bezier_surface_ball;
+use point::{PointQuery, PointProjection};
+use math::Vect;
+
+/// The projection on a bézier surface is approximate.
+///
+/// The closest points inside of the parametric domain are found numerically, but the boundary of
+/// the surface is approximated by its four endpoints. Thus, a point whose projection lies on one
+/// of the boundary curves, but not at one of its ends, is projected either on the closest endpoint
+/// or on an interior point farther than the actual projection.
+impl PointQuery for BezierSurface {
+    // A surface has no interior: the point is never inside of it.
+    fn project_point(&self, pt: &Vect, _: bool) -> PointProjection {
+        let mut candidates = vec!(self.endpoint_00().clone(), self.endpoint_01().clone(),
+                                  self.endpoint_10().clone(), self.endpoint_11().clone());
+
+        let mut max_dist = na::norm(&(*pt - *candidates.get(0)));
+
+        for c in candidates.iter() {
+            max_dist = max_dist.min(na::norm(&(*pt - *c)));
+        }
+
+        // No point farther than the closest endpoint can be the projection.
+        let mut selector = HyperPlaneSurfaceSelector::new(max_dist);
+
+        bezier_surface_ball::closest_points(pt, self, 5, &mut selector, 15, &mut candidates);
+
+        let mut best = candidates.get(0).clone();
+
+        for c in candidates.iter() {
+            if na::sqnorm(&(*pt - *c)) < na::sqnorm(&(*pt - best)) {
+                best = c.clone()
+            }
+        }
+
+        PointProjection::new(false, best)
+    }
+}
+
+#[cfg(test, dim3, f64)]
+mod test {
+    use nalgebra::na::Vec3;
+    use nalgebra::na;
+    use geom::BezierSurface;
+    use point::PointQuery;
+
+    #[test]
+    fn test_project_point_on_bezier_surface() {
+        let control_points = vec!(
+            Vec3::new(-1.0f64, 0.0, -1.0), Vec3::new(0.0, 0.0, -1.0), Vec3::new(1.0, 0.0, -1.0),
+            Vec3::new(-1.0f64, 0.0,  0.0), Vec3::new(0.0, 0.0,  0.0), Vec3::new(1.0, 0.0,  0.0),
+            Vec3::new(-1.0f64, 0.0,  1.0), Vec3::new(0.0, 0.0,  1.0), Vec3::new(1.0, 0.0,  1.0));
+
+        let surface = BezierSurface::new(control_points, 3, 3);
+
+        let above = Vec3::new(0.25f64, 1.0, -0.3);
+        let proj  = surface.project_point(&above, true);
+        assert!(!proj.inside);
+        assert!(na::norm(&(proj.point - Vec3::new(0.25, 0.0, -0.3))) < 1.0e-3);
+        assert!((surface.distance_to_point(&above) - 1.0).abs() < 1.0e-3);
+
+        // A surface has no interior.
+        let on = Vec3::new(0.5f64, 0.0, 0.5);
+        assert!(!surface.contains_point(&on));
+        assert!(surface.distance_to_point(&on) < 1.0e-3);
+
+        // The closest point is an endpoint.
+        let corner = Vec3::new(3.0f64, 1.0, 3.0);
+        assert!(na::norm(&(surface.project_point(&corner, true).point - Vec3::new(1.0, 0.0, 1.0))) < 1.0e-3);
+    }
+}
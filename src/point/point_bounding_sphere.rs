@@ -0,0 +1,27 @@
+use nalgebra::na;
+use geom::Ball;
+use bounding_volume::BoundingSphere;
+use point::{PointQuery, PointProjection};
+use math::{Scalar, Vect};
+
+impl PointQuery for BoundingSphere {
+    #[inline]
+    fn project_point(&self, pt: &Vect, solid: bool) -> PointProjection {
+        let ls_pt    = *pt - *self.center();
+        let mut proj = Ball::new(self.radius()).project_point(&ls_pt, solid);
+
+        proj.point = proj.point + *self.center();
+
+        proj
+    }
+
+    #[inline]
+    fn distance_to_point(&self, pt: &Vect) -> Scalar {
+        (na::norm(&(*pt - *self.center())) - self.radius()).max(na::zero())
+    }
+
+    #[inline]
+    fn contains_point(&self, pt: &Vect) -> bool {
+        na::sqnorm(&(*pt - *self.center())) <= self.radius() * self.radius()
+    }
+}
@@ -0,0 +1,20 @@
+use std::num::Zero;
+use nalgebra::na;
+use bounding_volume::AABB;
+use geom::Cuboid;
+use implicit::HasMargin;
+use point::{PointQuery, PointProjection};
+use point;
+use math::Vect;
+
+impl PointQuery for Cuboid {
+    #[inline]
+    fn project_point(&self, pt: &Vect, solid: bool) -> PointProjection {
+        if self.margin().is_zero() {
+            AABB::new(-self.half_extents(), self.half_extents()).project_point(pt, solid)
+        }
+        else {
+            point::implicit_project_point(&na::one(), self, pt, solid)
+        }
+    }
+}
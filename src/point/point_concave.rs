@@ -0,0 +1,186 @@
+use std::num::Bounded;
+use nalgebra::na::{Transform, Indexable};
+use nalgebra::na;
+use bounding_volume::{BoundingVolume, LooseBoundingVolume, HasAABB, AABB};
+use geom::{ConcaveGeom, Compound, Mesh, HeightField};
+use point::{PointQuery, PointProjection, PointQueryDispatcher};
+use math::{Scalar, Vect, Matrix};
+
+/// Projects a point on a transformed concave geometry.
+///
+/// The point is projected on every sub-geometry which might be closer to it than the best
+/// projection found so far. Those are found by querying the sub-geometries intersecting a cube
+/// centered at the point, which is enlarged until it contains the best projection. The point is
+/// inside of the concave geometry if it is inside of one of its sub-geometries. The sub-geometries
+/// are projected on with `dispatcher`: those it cannot handle are ignored.
+///
+/// If `solid` is `false` and the point is inside of some sub-geometries, the closest point of the
+/// boundaries of those sub-geometries is returned. This might be a point inside of the concave
+/// geometry if its sub-geometries overlap.
+///
+/// Returns `None` if the concave geometry has no sub-geometry the point can be projected on.
+pub fn concave_project_point<G: ConcaveGeom>(dispatcher: &PointQueryDispatcher,
+                                             m:          &Matrix,
+                                             geom:       &G,
+                                             pt:         &Vect,
+                                             solid:      bool)
+                                             -> Option<PointProjection> {
+    let one: Matrix = na::one();
+    let aabb        = geom.aabb(&one);
+
+    // An empty concave geometry has an invalid AABB.
+    if aabb.mins().at(0) > aabb.maxs().at(0) {
+        return None
+    }
+
+    let ls_pt                 = m.inv_transform(pt);
+    let mut radius            = aabb.distance_to_point(&ls_pt).max(na::norm(&(*aabb.maxs() - *aabb.mins())) / na::cast(16.0f64));
+    let mut best_dist: Scalar = Bounded::max_value();
+    let mut best: Option<PointProjection> = None;
+    let mut parts             = Vec::new();
+
+    loop {
+        let query = AABB::new(ls_pt.clone(), ls_pt.clone()).loosened(radius);
+
+        geom.approx_interferences_with_aabb(&query, &mut parts);
+
+        for i in parts.iter() {
+            // The sub-geometries containing the point are never skipped: their AABB distance is zero.
+            if geom.aabb_at(*i).distance_to_point(&ls_pt) > best_dist {
+                continue
+            }
+
+            let proj = match geom.map_part_at(*i, |m, g| dispatcher.project_point(m, g, &ls_pt, solid)) {
+                Some(proj) => proj,
+                None       => continue
+            };
+
+            let dist   = na::norm(&(ls_pt - proj.point));
+            let inside = proj.inside || best.as_ref().map_or(false, |best| best.inside);
+
+            if dist < best_dist {
+                best_dist = dist;
+                best      = Some(PointProjection::new(inside, proj.point));
+            }
+            else {
+                match best {
+                    Some(ref mut best) => best.inside = inside,
+                    None               => { }
+                }
+            }
+        }
+
+        parts.clear();
+
+        // Any sub-geometry closer than the best projection has an AABB intersecting the query.
+        if best_dist <= radius || query.contains(&aabb) {
+            break
+        }
+
+        radius = radius * na::cast(2.0f64);
+    }
+
+    match best {
+        Some(mut proj) => {
+            proj.point = m.transform(&proj.point);
+
+            Some(proj)
+        },
+        None => None
+    }
+}
+
+// Projects a point on a concave geometry with the default dispatcher.
+//
+// An empty concave geometry is infinitely far from the point.
+fn project_point_with_default_dispatcher<G: ConcaveGeom>(geom: &G, pt: &Vect, solid: bool) -> PointProjection {
+    let dispatcher = PointQueryDispatcher::new_w_tls();
+
+    match concave_project_point(&*dispatcher, &na::one(), geom, pt, solid) {
+        Some(proj) => proj,
+        None       => PointProjection::new(false, Bounded::max_value())
+    }
+}
+
+impl PointQuery for Compound {
+    #[inline]
+    fn project_point(&self, pt: &Vect, solid: bool) -> PointProjection {
+        project_point_with_default_dispatcher(self, pt, solid)
+    }
+}
+
+impl PointQuery for Mesh {
+    #[inline]
+    fn project_point(&self, pt: &Vect, solid: bool) -> PointProjection {
+        project_point_with_default_dispatcher(self, pt, solid)
+    }
+}
+
+impl PointQuery for HeightField {
+    #[inline]
+    fn project_point(&self, pt: &Vect, solid: bool) -> PointProjection {
+        project_point_with_default_dispatcher(self, pt, solid)
+    }
+}
+
+#[cfg(test, dim3, f64)]
+mod test {
+    use sync::Arc;
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::{Mesh, Compound, CompoundData, Ball, Cuboid};
+    use point::PointQuery;
+
+    #[test]
+    fn test_project_point_on_mesh() {
+        let vertices = vec!(Vec3::new(-1.0f64, 0.0, -1.0), Vec3::new(1.0, 0.0, -1.0),
+                            Vec3::new(1.0, 0.0, 1.0), Vec3::new(-1.0, 0.0, 1.0));
+        let indices  = vec!(0u, 1, 2, 0, 2, 3);
+        let mesh     = Mesh::new_with_margin(Arc::new(vertices), Arc::new(indices), None, None, 0.0);
+
+        let above = Vec3::new(0.2f64, 1.0, 0.3);
+        let proj  = mesh.project_point(&above, true);
+        assert!(!proj.inside);
+        assert!(na::norm(&(proj.point - Vec3::new(0.2, 0.0, 0.3))) < 1.0e-3);
+        assert!((mesh.distance_to_point(&above) - 1.0).abs() < 1.0e-3);
+
+        // The point is far from the mesh AABB.
+        let aside = Vec3::new(30.0f64, 0.0, 0.5);
+        let proj  = mesh.project_point(&aside, true);
+        assert!(na::norm(&(proj.point - Vec3::new(1.0, 0.0, 0.5))) < 1.0e-3);
+
+        let m = Iso3::new(Vec3::new(0.0f64, 5.0, 0.0), na::zero());
+        assert!((mesh.distance_to_point_with_transform(&m, &above) - 4.0).abs() < 1.0e-3);
+    }
+
+    #[test]
+    fn test_project_point_on_compound() {
+        let mut data = CompoundData::new();
+        data.push_geom(Iso3::new(Vec3::new(-2.0f64, 0.0, 0.0), na::zero()), Ball::new(1.0f64), 1.0);
+        data.push_geom(Iso3::new(Vec3::new(2.0f64, 0.0, 0.0), na::zero()), Cuboid::new(Vec3::new(1.0f64, 1.0, 1.0)), 1.0);
+
+        let compound = Compound::new(data);
+
+        let between = Vec3::new(-0.5f64, 0.0, 0.0);
+        let proj    = compound.project_point(&between, true);
+        assert!(!proj.inside);
+        assert!(na::norm(&(proj.point - Vec3::new(-1.0, 0.0, 0.0))) < 1.0e-3);
+
+        let inside = Vec3::new(2.0f64, 0.5, 0.0);
+        assert!(compound.contains_point(&inside));
+        assert!(na::approx_eq(&compound.project_point(&inside, true).point, &inside));
+
+        let proj = compound.project_point(&inside, false);
+        assert!(proj.inside);
+        assert!(na::norm(&(proj.point - Vec3::new(2.0, 1.0, 0.0))) < 1.0e-3);
+    }
+
+    #[test]
+    fn test_project_point_on_empty_compound() {
+        let compound = Compound::new(CompoundData::new());
+        let pt       = Vec3::new(1.0f64, 2.0, 3.0);
+
+        assert!(!compound.contains_point(&pt));
+        assert!(compound.distance_to_point(&pt) > 1.0e100);
+    }
+}
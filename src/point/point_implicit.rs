@@ -0,0 +1,132 @@
+use std::num::Zero;
+use nalgebra::na::{Translation, Indexable, Norm};
+use nalgebra::na;
+use narrow::algorithm::simplex::Simplex;
+use narrow::algorithm::johnson_simplex::JohnsonSimplex;
+use narrow::algorithm::{gjk, epa};
+use geom::{AnnotatedPoint, Ball, Cylinder, Cone, Capsule, Convex, Segment, Triangle};
+use implicit::{Implicit, HasMargin};
+use implicit;
+use point::{PointQuery, PointProjection};
+use math::{Vect, Matrix};
+
+/// Projects a point on a transformed geometry using the GJK algorithm.
+///
+/// If the point is inside of the geometry and `solid` is `false`, the Expanding Polytope
+/// Algorithm is used to find the closest point of the geometry boundary.
+pub fn implicit_project_point<G: Implicit<Vect, Matrix>>(m:     &Matrix,
+                                                        geom:  &G,
+                                                        pt:    &Vect,
+                                                        solid: bool)
+                                                        -> PointProjection {
+    // The point is seen as a ball with a zero radius.
+    let point: Ball   = Ball::new(na::zero());
+    let mpoint        = na::append_translation(&na::one::<Matrix>(), pt);
+    let margin        = geom.margin();
+    let mut simplex: JohnsonSimplex<AnnotatedPoint> = JohnsonSimplex::new_w_tls();
+
+    let mut dir = m.translation() - *pt;
+
+    if dir.is_zero() {
+        dir.set(0, na::one());
+    }
+
+    simplex.reset(implicit::cso_support_point_without_margin(m, geom, &mpoint, &point, dir));
+
+    match gjk::closest_points_without_margin(m, geom, &mpoint, &point, &mut simplex) {
+        Some((p, _)) => {
+            let mut normal = *pt - p;
+            let dist       = normal.normalize();
+
+            if dist > margin {
+                return PointProjection::new(false, p + normal * margin)
+            }
+            else if solid {
+                return PointProjection::new(true, pt.clone())
+            }
+            else if !dist.is_zero() {
+                return PointProjection::new(true, p + normal * margin)
+            }
+        },
+        None => {
+            if solid {
+                return PointProjection::new(true, pt.clone())
+            }
+        }
+    }
+
+    // The point is inside of the geometry without its margin.
+    match epa::closest_points(m, geom, &mpoint, &point, &simplex) {
+        Some(contact) => PointProjection::new(true, contact.world1),
+        None          => PointProjection::new(true, pt.clone()) // FIXME: the EPA failed.
+    }
+}
+
+impl PointQuery for Cylinder {
+    #[inline]
+    fn project_point(&self, pt: &Vect, solid: bool) -> PointProjection {
+        implicit_project_point(&na::one(), self, pt, solid)
+    }
+}
+
+impl PointQuery for Cone {
+    #[inline]
+    fn project_point(&self, pt: &Vect, solid: bool) -> PointProjection {
+        implicit_project_point(&na::one(), self, pt, solid)
+    }
+}
+
+impl PointQuery for Capsule {
+    #[inline]
+    fn project_point(&self, pt: &Vect, solid: bool) -> PointProjection {
+        implicit_project_point(&na::one(), self, pt, solid)
+    }
+}
+
+impl PointQuery for Convex {
+    #[inline]
+    fn project_point(&self, pt: &Vect, solid: bool) -> PointProjection {
+        implicit_project_point(&na::one(), self, pt, solid)
+    }
+}
+
+impl PointQuery for Segment {
+    #[inline]
+    fn project_point(&self, pt: &Vect, solid: bool) -> PointProjection {
+        implicit_project_point(&na::one(), self, pt, solid)
+    }
+}
+
+impl PointQuery for Triangle {
+    #[inline]
+    fn project_point(&self, pt: &Vect, solid: bool) -> PointProjection {
+        implicit_project_point(&na::one(), self, pt, solid)
+    }
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use nalgebra::na::Vec3;
+    use nalgebra::na;
+    use geom::Cylinder;
+    use point::PointQuery;
+
+    #[test]
+    fn test_project_point_on_cylinder() {
+        let cylinder = Cylinder::new_with_margin(1.0f64, 1.0, 0.0);
+        let outside  = Vec3::new(3.0f64, 0.5, 0.0);
+        let inside   = Vec3::new(0.0f64, 0.8, 0.0);
+
+        let proj = cylinder.project_point(&outside, true);
+        assert!(!proj.inside);
+        assert!(na::norm(&(proj.point - Vec3::new(1.0, 0.5, 0.0))) < 1.0e-3);
+        assert!((cylinder.distance_to_point(&outside) - 2.0).abs() < 1.0e-3);
+
+        assert!(cylinder.contains_point(&inside));
+        assert!(na::approx_eq(&cylinder.project_point(&inside, true).point, &inside));
+
+        let proj = cylinder.project_point(&inside, false);
+        assert!(proj.inside);
+        assert!(na::norm(&(proj.point - Vec3::new(0.0, 1.0, 0.0))) < 1.0e-3);
+    }
+}
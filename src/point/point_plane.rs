@@ -0,0 +1,62 @@
+use nalgebra::na;
+use geom::Plane;
+use point::{PointQuery, PointProjection};
+use math::{Scalar, Vect};
+
+impl PointQuery for Plane {
+    #[inline]
+    fn project_point(&self, pt: &Vect, solid: bool) -> PointProjection {
+        let d      = na::dot(&self.normal(), pt);
+        let inside = d <= na::zero();
+
+        if inside && solid {
+            PointProjection::new(true, pt.clone())
+        }
+        else {
+            PointProjection::new(inside, *pt - self.normal() * d)
+        }
+    }
+
+    #[inline]
+    fn distance_to_point(&self, pt: &Vect) -> Scalar {
+        na::dot(&self.normal(), pt).max(na::zero())
+    }
+
+    #[inline]
+    fn contains_point(&self, pt: &Vect) -> bool {
+        na::dot(&self.normal(), pt) <= na::zero()
+    }
+}
+
+#[cfg(test, dim3, f64)]
+mod test {
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::Plane;
+    use point::PointQuery;
+
+    #[test]
+    fn test_project_point_on_plane() {
+        let plane   = Plane::new(Vec3::new(0.0f64, 1.0, 0.0));
+        let outside = Vec3::new(1.0f64, 2.0, 3.0);
+        let inside  = Vec3::new(1.0f64, -2.0, 3.0);
+
+        let proj = plane.project_point(&outside, true);
+        assert!(!proj.inside);
+        assert!(na::approx_eq(&proj.point, &Vec3::new(1.0, 0.0, 3.0)));
+        assert!(na::approx_eq(&plane.distance_to_point(&outside), &2.0));
+
+        assert!(plane.contains_point(&inside));
+        assert!(na::approx_eq(&plane.distance_to_point(&inside), &0.0));
+        assert!(na::approx_eq(&plane.project_point(&inside, true).point, &inside));
+
+        let proj = plane.project_point(&inside, false);
+        assert!(proj.inside);
+        assert!(na::approx_eq(&proj.point, &Vec3::new(1.0, 0.0, 3.0)));
+
+        let m = Iso3::new(Vec3::new(0.0f64, 1.0, 0.0), na::zero());
+        assert!(na::approx_eq(&plane.distance_to_point_with_transform(&m, &outside), &1.0));
+        assert!(na::approx_eq(&plane.project_point_with_transform(&m, &outside, true).point,
+                              &Vec3::new(1.0, 1.0, 3.0)));
+    }
+}
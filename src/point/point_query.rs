@@ -0,0 +1,81 @@
+//! Traits and structure needed to test and project points on geometries.
+
+use nalgebra::na::Transform;
+use nalgebra::na;
+use math::{Scalar, Vect, Matrix};
+
+/// Structure containing the result of the projection of a point on a geometry.
+#[deriving(Show, Clone)]
+pub struct PointProjection {
+    /// Whether or not the projected point is inside of the geometry.
+    pub inside: bool,
+
+    /// The projection of the point on the geometry.
+    ///
+    /// If the projection is solid and the point is inside of the geometry, this is the point
+    /// itself. Otherwise, this is the closest point of the geometry boundary.
+    pub point:  Vect
+}
+
+impl PointProjection {
+    #[inline]
+    /// Creates a new `PointProjection`.
+    pub fn new(inside: bool, point: Vect) -> PointProjection {
+        PointProjection {
+            inside: inside,
+            point:  point
+        }
+    }
+}
+
+/// Traits of objects which can be tested for point inclusion and projection.
+pub trait PointQuery {
+    /// Projects a point on this geometry.
+    ///
+    /// If `solid` is `true`, a point inside of the geometry is its own projection. Otherwise, it
+    /// is projected on the geometry boundary.
+    fn project_point(&self, pt: &Vect, solid: bool) -> PointProjection;
+
+    /// Computes the distance between a point and this geometry.
+    ///
+    /// This is zero if the point is inside of the geometry.
+    #[inline]
+    fn distance_to_point(&self, pt: &Vect) -> Scalar {
+        let proj = self.project_point(pt, true);
+
+        if proj.inside {
+            na::zero()
+        }
+        else {
+            na::norm(&(*pt - proj.point))
+        }
+    }
+
+    /// Tests whether a point is inside of this geometry.
+    #[inline]
+    fn contains_point(&self, pt: &Vect) -> bool {
+        self.project_point(pt, true).inside
+    }
+
+    /// Projects a point on this transformed geometry.
+    #[inline]
+    fn project_point_with_transform(&self, m: &Matrix, pt: &Vect, solid: bool) -> PointProjection {
+        let mut proj = self.project_point(&m.inv_transform(pt), solid);
+
+        proj.point = m.transform(&proj.point);
+
+        proj
+    }
+
+    /// Computes the distance between a point and this transformed geometry.
+    #[inline]
+    fn distance_to_point_with_transform(&self, m: &Matrix, pt: &Vect) -> Scalar {
+        self.distance_to_point(&m.inv_transform(pt))
+    }
+
+    /// Tests whether a point is inside of this transformed geometry.
+    #[inline]
+    fn contains_point_with_transform(&self, m: &Matrix, pt: &Vect) -> bool {
+        self.contains_point(&m.inv_transform(pt))
+    }
+}
@@ -0,0 +1,151 @@
+//! Point projection on any `Geom`.
+
+use std::intrinsics::TypeId;
+use std::any::{Any, AnyRefExt};
+use std::collections::HashMap;
+use std::local_data;
+use sync::Arc;
+use geom::{Geom, ConcaveGeom, Plane, Ball, Cuboid, Cone, Cylinder, Capsule, Convex, Triangle,
+           Segment, BezierSurface, Compound, Mesh, HeightField};
+// #[dim3]
+use geom::Torus;
+use point::{PointQuery, PointProjection, concave_project_point};
+use math::{Vect, Matrix};
+
+static KEY_POINT_QUERY_DISPATCHER: local_data::Key<Arc<PointQueryDispatcher>> = &local_data::Key;
+
+/// Function projecting a point on a transformed geometry.
+///
+/// The geometry has the type the function has been registered with. Returns `None` if the
+/// geometry has nothing to project the point on, e.g., if it is an empty concave geometry.
+pub type PointQueryFn = fn(&PointQueryDispatcher, &Matrix, &Geom, &Vect, bool) -> Option<PointProjection>;
+
+/// Dispatcher projecting points on any `Geom`.
+///
+/// A point projection function is registered for each type of geometry. This is what the parts
+/// of concave geometries are projected with, since a `Geom` is not required to implement
+/// `PointQuery`.
+pub struct PointQueryDispatcher {
+    point_query_fns: HashMap<TypeId, PointQueryFn>
+}
+
+impl PointQueryDispatcher {
+    /// Creates a new `PointQueryDispatcher` able to project points on every geometry supported by
+    /// `ncollide`.
+    pub fn new() -> PointQueryDispatcher {
+        let mut res = PointQueryDispatcher::new_without_default();
+
+        res.register_default_point_query::<Plane>();
+        res.register_default_point_query::<Ball>();
+        res.register_default_point_query::<Cuboid>();
+        res.register_default_point_query::<Cone>();
+        res.register_default_point_query::<Cylinder>();
+        res.register_default_point_query::<Capsule>();
+        res.register_default_point_query::<Convex>();
+        res.register_default_point_query::<Triangle>();
+        res.register_default_point_query::<Segment>();
+        res.register_default_point_query::<BezierSurface>();
+        res.register_default_torus_point_query();
+
+        res.register_default_concave_geom_point_query::<Compound>();
+        res.register_default_concave_geom_point_query::<Mesh>();
+        res.register_default_concave_geom_point_query::<HeightField>();
+
+        res
+    }
+
+    /// Creates a new `PointQueryDispatcher` without any registered point projection function.
+    pub fn new_without_default() -> PointQueryDispatcher {
+        PointQueryDispatcher {
+            point_query_fns: HashMap::new()
+        }
+    }
+
+    /// The default `PointQueryDispatcher`. It is created once per task, and shared afterward.
+    pub fn new_w_tls() -> Arc<PointQueryDispatcher> {
+        let dispatcher = KEY_POINT_QUERY_DISPATCHER.get().map(|d| d.clone());
+
+        match dispatcher {
+            Some(d) => d,
+            None    => {
+                let new_dispatcher = Arc::new(PointQueryDispatcher::new());
+                let _ = KEY_POINT_QUERY_DISPATCHER.replace(Some(new_dispatcher.clone()));
+                new_dispatcher
+            }
+        }
+    }
+
+    /// Registers a function projecting points on a given type of geometry.
+    pub fn register_point_query<G: 'static + Any>(&mut self, f: PointQueryFn) {
+        self.point_query_fns.insert(TypeId::of::<G>(), f);
+    }
+
+    /// Unregister the point projection function for a given type of geometry.
+    pub fn unregister_point_query<G: 'static + Any>(&mut self) {
+        self.point_query_fns.remove(&TypeId::of::<G>());
+    }
+
+    /// Registers the point projection function of a geometry implementing `PointQuery`.
+    pub fn register_default_point_query<G: 'static + PointQuery>(&mut self) {
+        self.register_point_query::<G>(point_query::<G>);
+    }
+
+    /// Registers the point projection function of a concave geometry.
+    ///
+    /// Its parts are projected on with this dispatcher.
+    pub fn register_default_concave_geom_point_query<G: 'static + ConcaveGeom>(&mut self) {
+        self.register_point_query::<G>(concave_geom_point_query::<G>);
+    }
+
+    /// Registers the point projection function of the torus.
+    #[dim3]
+    pub fn register_default_torus_point_query(&mut self) {
+        self.register_default_point_query::<Torus>();
+    }
+
+    /// Registers the point projection function of the torus.
+    ///
+    /// This does nothing in 2d and 4d: tori are defined in 3d only.
+    #[not_dim3]
+    pub fn register_default_torus_point_query(&mut self) {
+    }
+
+    /// Projects a point on a transformed geometry.
+    ///
+    /// Returns `None` if no point projection function is registered for the type of `g`, or if `g`
+    /// has nothing to project the point on.
+    ///
+    /// # Arguments:
+    /// * `m`     - the geometry transform.
+    /// * `g`     - the geometry.
+    /// * `pt`    - the point to project.
+    /// * `solid` - whether a point inside of the geometry is its own projection.
+    pub fn project_point(&self, m: &Matrix, g: &Geom, pt: &Vect, solid: bool) -> Option<PointProjection> {
+        match self.point_query_fns.find(&g.get_dyn_type_id()) {
+            Some(f) => (*f)(self, m, g, pt, solid),
+            None    => None
+        }
+    }
+}
+
+fn point_query<G: 'static + PointQuery>(_:     &PointQueryDispatcher,
+                                        m:     &Matrix,
+                                        g:     &Geom,
+                                        pt:    &Vect,
+                                        solid: bool)
+                                        -> Option<PointProjection> {
+    let g = g.downcast_ref::<G>().expect("Invalid geometry.");
+
+    Some(g.project_point_with_transform(m, pt, solid))
+}
+
+fn concave_geom_point_query<G: 'static + ConcaveGeom>(dispatcher: &PointQueryDispatcher,
+                                                      m:          &Matrix,
+                                                      g:          &Geom,
+                                                      pt:         &Vect,
+                                                      solid:      bool)
+                                                      -> Option<PointProjection> {
+    let g = g.downcast_ref::<G>().expect("Invalid geometry.");
+
+    concave_project_point(dispatcher, m, g, pt, solid)
+}
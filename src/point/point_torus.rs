@@ -0,0 +1,73 @@
+use std::num::Zero;
+use nalgebra::na::Vec3;
+use nalgebra::na;
+use geom::Torus;
+use point::{PointQuery, PointProjection};
+use math::Vect;
+
+impl PointQuery for Torus {
+    fn project_point(&self, pt: &Vect, solid: bool) -> PointProjection {
+        // Project on the core circle first.
+        let sqlen  = pt.x * pt.x + pt.z * pt.z;
+        let radial =
+            if sqlen.is_zero() {
+                // Every point of the core circle is at the same distance.
+                Vec3::new(na::one(), na::zero(), na::zero())
+            }
+            else {
+                Vec3::new(pt.x, na::zero(), pt.z) / sqlen.sqrt()
+            };
+
+        let core   = radial * self.major_radius();
+        let dcore  = *pt - core;
+        let sqdist = na::sqnorm(&dcore);
+        let inside = sqdist <= self.minor_radius() * self.minor_radius();
+
+        if inside && solid {
+            PointProjection::new(true, pt.clone())
+        }
+        else if sqdist.is_zero() {
+            PointProjection::new(true, core + radial * self.minor_radius())
+        }
+        else {
+            PointProjection::new(inside, core + dcore * (self.minor_radius() / sqdist.sqrt()))
+        }
+    }
+}
+
+#[cfg(test, f64)]
+mod test {
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::Torus;
+    use point::PointQuery;
+
+    #[test]
+    fn test_project_point_on_torus() {
+        let torus = Torus::new(2.0f64, 0.5);
+
+        let outside = Vec3::new(4.0f64, 0.0, 0.0);
+        let proj    = torus.project_point(&outside, true);
+        assert!(!proj.inside);
+        assert!(na::approx_eq(&proj.point, &Vec3::new(2.5, 0.0, 0.0)));
+        assert!(na::approx_eq(&torus.distance_to_point(&outside), &1.5));
+
+        // The center of the hole is at the same distance from every point of the core circle.
+        assert!(!torus.contains_point(&na::zero()));
+        assert!(na::approx_eq(&torus.distance_to_point(&na::zero()), &1.5));
+
+        let above = Vec3::new(0.0f64, 3.0, 0.0);
+        assert!(na::approx_eq(&torus.distance_to_point(&above), &(13.0f64.sqrt() - 0.5)));
+
+        let inside = Vec3::new(0.0f64, 0.2, -2.0);
+        assert!(torus.contains_point(&inside));
+        assert!(na::approx_eq(&torus.project_point(&inside, true).point, &inside));
+
+        let proj = torus.project_point(&inside, false);
+        assert!(proj.inside);
+        assert!(na::approx_eq(&proj.point, &Vec3::new(0.0, 0.5, -2.0)));
+
+        let m = Iso3::new(Vec3::new(0.0f64, 0.0, 10.0), na::zero());
+        assert!(torus.contains_point_with_transform(&m, &Vec3::new(2.2f64, 0.0, 10.0)));
+    }
+}
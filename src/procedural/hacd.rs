@@ -156,10 +156,13 @@ pub fn hacd(mesh:           TriMesh<Scalar, Vec3<Scalar>>,
 
     for vertex in dual_graph.move_iter() {
         if vertex.timestamp != Bounded::max_value() {
-            let mut chull = vertex.chull.unwrap();
+            let chull      = vertex.chull.unwrap();
+            let margin     = chull.margin();
+            let mut mesh   = chull.unwrap();
 
-            denormalize(chull.mesh_mut(), &center, &diag);
-            result.push(chull);
+            // Rebuilt so that its bounding sphere matches the denormalized vertices.
+            denormalize(&mut mesh, &center, &diag);
+            result.push(unsafe { Convex::new_with_convex_mesh(mesh, margin) });
             parts.push(vertex.parts.unwrap());
         }
     }
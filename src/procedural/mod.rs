@@ -34,6 +34,9 @@ mod to_trimesh {
     mod cylinder_to_trimesh;
     mod geom_with_margin_to_trimesh;
     mod mesh_to_trimesh;
+    mod height_field_to_trimesh;
+    #[dim3]
+    mod torus_to_trimesh;
     mod reflection_to_trimesh;
     mod triangle_to_trimesh;
 }
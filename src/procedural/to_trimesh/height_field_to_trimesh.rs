@@ -0,0 +1,37 @@
+use nalgebra::na::Vec3;
+use procedural::{ToTriMesh, TriMesh, UnifiedIndexBuffer};
+use geom::HeightField;
+use math::{Scalar, Vect};
+
+#[dim3]
+impl ToTriMesh<()> for HeightField {
+    // The margin is ignored.
+    fn to_trimesh(&self, _: ()) -> TriMesh<Scalar, Vect> {
+        let nrows = self.nrows();
+        let ncols = self.ncols();
+
+        let mut vertices = Vec::with_capacity(nrows * ncols);
+        let mut indices  = Vec::with_capacity(self.nparts());
+
+        for i in range(0u, nrows) {
+            for j in range(0u, ncols) {
+                vertices.push(self.point_at(i, j))
+            }
+        }
+
+        // Same triangulation as `HeightField::part_at`.
+        for i in range(0u, nrows - 1) {
+            for j in range(0u, ncols - 1) {
+                let p00 = (i * ncols + j) as u32;
+                let p01 = p00 + 1;
+                let p10 = p00 + ncols as u32;
+                let p11 = p10 + 1;
+
+                indices.push(Vec3::new(p00, p10, p11));
+                indices.push(Vec3::new(p00, p11, p01));
+            }
+        }
+
+        TriMesh::new(vertices, None, None, Some(UnifiedIndexBuffer(indices)))
+    }
+}
@@ -0,0 +1,10 @@
+use geom::Torus;
+use procedural::{ToTriMesh, TriMesh};
+use procedural;
+use math::{Scalar, Vect};
+
+impl ToTriMesh<(u32, u32)> for Torus {
+    fn to_trimesh(&self, (nmajor_subdiv, nminor_subdiv): (u32, u32)) -> TriMesh<Scalar, Vect> {
+        procedural::parametric_surface_uniform(self, nmajor_subdiv as uint, nminor_subdiv as uint)
+    }
+}
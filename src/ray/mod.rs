@@ -3,12 +3,15 @@
 // types an traits
 #[doc(inline)]
 pub use ray::ray::{Ray, RayCast, RayIntersection};
+pub use ray::shape_cast::{ShapeCastIntersection, ShapeCastDispatcher, ShapeCastFn};
 
 // functions
 pub use ray::ray_plane::plane_toi_with_ray;
 pub use ray::ray_implicit::implicit_toi_and_normal_with_ray;
 pub use ray::ray_ball::ball_toi_with_ray;
 pub use ray::ray_triangle::triangle_ray_intersection;
+pub use ray::shape_cast::{shape_cast_on_implicit, shape_cast_on_plane, shape_cast_on_torus,
+                          shape_cast_on_concave, shape_cast_with_rays};
 
 // modules
 #[doc(hidden)]
@@ -17,10 +20,16 @@ mod ray_plane;
 mod ray_ball;
 mod ray_box;
 mod ray_aabb;
+mod ray_obb;
+mod ray_kdop;
 mod ray_implicit;
 mod ray_triangle;
 mod ray_concave;
 mod ray_mesh;
+mod ray_height_field;
+#[dim3]
+mod ray_torus;
 mod ray_bvt;
 mod ray_bezier_surface;
 mod ray_bezier_curve;
+mod shape_cast;
@@ -0,0 +1,35 @@
+use std::num::Zero;
+use ray::{Ray, RayCast, RayIntersection};
+use geom::HeightField;
+
+impl RayCast for HeightField {
+    fn toi_and_normal_with_ray(&self, ray: &Ray, solid: bool) -> Option<RayIntersection> {
+        let solid = solid || self.margin().is_zero(); // The `solid` flag is useless if we have no margin.
+        let nparts_per_cell = self.nparts() / self.ncells();
+        let mut res: Option<RayIntersection> = None;
+
+        // The cells are traversed in the order the ray goes through them. Thus, the first cell
+        // with an intersection contains the closest one.
+        self.traverse_cells_with_ray(ray, |cell| {
+            for k in range(0u, nparts_per_cell) {
+                match self.part_at(cell * nparts_per_cell + k).toi_and_normal_with_ray(ray, solid) {
+                    Some(inter) => {
+                        let better = match res {
+                            Some(ref best) => inter.toi < best.toi,
+                            None           => true
+                        };
+
+                        if better {
+                            res = Some(inter)
+                        }
+                    },
+                    None => { }
+                }
+            }
+
+            res.is_none()
+        });
+
+        res
+    }
+}
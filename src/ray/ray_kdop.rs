@@ -0,0 +1,133 @@
+use std::num::{Zero, Bounded};
+use std::mem;
+use nalgebra::na;
+use ray::{Ray, RayCast, RayIntersection};
+use bounding_volume::KDOP;
+use math::{Scalar, Vect};
+
+impl RayCast for KDOP {
+    #[inline]
+    fn toi_and_normal_with_ray(&self, ray: &Ray, solid: bool) -> Option<RayIntersection> {
+        ray_kdop(self, ray, solid).map(|(t, n)| RayIntersection::new(t, n))
+    }
+}
+
+// Same as the ray-AABB intersection, except that the slabs are not orthogonal to the
+// coordinate axes.
+fn ray_kdop(kdop: &KDOP, ray: &Ray, solid: bool) -> Option<(Scalar, Vect)> {
+    let mut tmax: Scalar    = Bounded::max_value();
+    let mut tmin: Scalar    = -tmax;
+    let mut near_normal: Vect = na::zero();
+    let mut far_normal: Vect  = na::zero();
+
+    for (i, axis) in kdop.axes().iter().enumerate() {
+        let dir  = na::dot(&ray.dir, axis);
+        let orig = na::dot(&ray.orig, axis);
+
+        if dir.is_zero() {
+            if orig < kdop.mins()[i] || orig > kdop.maxs()[i] {
+                return None
+            }
+        }
+        else {
+            let _1: Scalar = na::one();
+            let denom = _1 / dir;
+            let mut inter_with_near_plane = (kdop.mins()[i] - orig) * denom;
+            let mut inter_with_far_plane  = (kdop.maxs()[i] - orig) * denom;
+
+            // The outward normals of the planes the ray enters and exits the slab through.
+            let near_plane_normal;
+            let far_plane_normal;
+
+            if inter_with_near_plane > inter_with_far_plane {
+                mem::swap(&mut inter_with_near_plane, &mut inter_with_far_plane);
+                near_plane_normal = axis.clone();
+                far_plane_normal  = -*axis;
+            }
+            else {
+                near_plane_normal = -*axis;
+                far_plane_normal  = axis.clone();
+            }
+
+            if inter_with_near_plane > tmin {
+                tmin        = inter_with_near_plane;
+                near_normal = near_plane_normal;
+            }
+
+            if inter_with_far_plane < tmax {
+                tmax       = inter_with_far_plane;
+                far_normal = far_plane_normal;
+            }
+
+            if tmin > tmax {
+                return None;
+            }
+        }
+    }
+
+    if tmin < na::zero() {
+        // the ray starts inside of the k-DOP
+        if solid {
+            Some((na::zero(), na::zero()))
+        }
+        else {
+            Some((tmax, na::normalize(&far_normal)))
+        }
+    }
+    else {
+        Some((tmin, na::normalize(&near_normal)))
+    }
+}
+
+#[cfg(test, dim3, f64)]
+mod test {
+    use nalgebra::na::Vec3;
+    use nalgebra::na;
+    use bounding_volume::{KDOP, KDOP14};
+    use bounding_volume;
+    use ray::{Ray, RayCast};
+
+    // The 14-DOP of the cube of half-extents 1.
+    fn cube_kdop() -> KDOP {
+        let mut pts = Vec::new();
+
+        for i in range(0u, 8) {
+            pts.push(Vec3::new(if i & 1 == 0 { -1.0f64 } else { 1.0 },
+                               if i & 2 == 0 { -1.0f64 } else { 1.0 },
+                               if i & 4 == 0 { -1.0f64 } else { 1.0 }));
+        }
+
+        bounding_volume::point_cloud_kdop(KDOP14, &na::one(), pts.as_slice())
+    }
+
+    #[test]
+    fn test_ray_kdop_from_outside() {
+        let kdop = cube_kdop();
+        let ray  = Ray::new(Vec3::new(3.0f64, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+
+        let inter = kdop.toi_and_normal_with_ray(&ray, true).unwrap();
+        assert!(na::approx_eq(&inter.toi, &2.0));
+        assert!(na::approx_eq(&inter.normal, &Vec3::new(1.0, 0.0, 0.0)));
+
+        let ray = Ray::new(Vec3::new(3.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(kdop.toi_and_normal_with_ray(&ray, true).is_none());
+    }
+
+    #[test]
+    fn test_ray_kdop_from_inside() {
+        let kdop = cube_kdop();
+
+        for &(dir, exit_normal) in [ (Vec3::new(1.0f64, 0.0, 0.0), Vec3::new(1.0f64, 0.0, 0.0)),
+                                     (Vec3::new(-1.0f64, 0.0, 0.0), Vec3::new(-1.0f64, 0.0, 0.0)) ].iter() {
+            let ray = Ray::new(Vec3::new(0.5f64, 0.0, 0.0), dir);
+
+            let inter = kdop.toi_and_normal_with_ray(&ray, true).unwrap();
+            assert!(na::approx_eq(&inter.toi, &0.0));
+
+            // The ray exits through the face with the outward normal `exit_normal`.
+            let inter = kdop.toi_and_normal_with_ray(&ray, false).unwrap();
+            assert!(na::approx_eq(&inter.toi, &(1.0 - 0.5 * dir.x)));
+            assert!(na::approx_eq(&inter.normal, &exit_normal));
+        }
+    }
+}
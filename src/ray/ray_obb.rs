@@ -0,0 +1,20 @@
+use ray::{Ray, RayCast, RayIntersection};
+use bounding_volume::OBB;
+use math::Scalar;
+
+impl RayCast for OBB {
+    #[inline]
+    fn toi_with_ray(&self, ray: &Ray, solid: bool) -> Option<Scalar> {
+        self.local_aabb().toi_with_transform_and_ray(self.m(), ray, solid)
+    }
+
+    #[inline]
+    fn toi_and_normal_with_ray(&self, ray: &Ray, solid: bool) -> Option<RayIntersection> {
+        self.local_aabb().toi_and_normal_with_transform_and_ray(self.m(), ray, solid)
+    }
+
+    #[inline]
+    fn toi_and_normal_and_uv_with_ray(&self, ray: &Ray, solid: bool) -> Option<RayIntersection> {
+        self.local_aabb().toi_and_normal_and_uv_with_transform_and_ray(self.m(), ray, solid)
+    }
+}
@@ -0,0 +1,127 @@
+use std::num::{Zero, Float};
+use nalgebra::na::Vec3;
+use nalgebra::na;
+use ray::{Ray, RayCast, RayIntersection};
+use ray;
+use geom::Torus;
+use utils;
+use math::{Scalar, Vect};
+
+impl RayCast for Torus {
+    fn toi_with_ray(&self, ray: &Ray, solid: bool) -> Option<Scalar> {
+        torus_toi_with_ray(self, ray, solid).val1()
+    }
+
+    fn toi_and_normal_with_ray(&self, ray: &Ray, solid: bool) -> Option<RayIntersection> {
+        let (inside, inter) = torus_toi_with_ray(self, ray, solid);
+
+        inter.map(|t| {
+            let normal = torus_normal(self, &(ray.orig + ray.dir * t));
+
+            RayIntersection::new(t, if inside { -normal } else { normal })
+        })
+    }
+}
+
+/// The outward normal of the torus surface at the point `pt`.
+fn torus_normal(torus: &Torus, pt: &Vect) -> Vect {
+    let radial = Vec3::new(pt.x, na::zero(), pt.z);
+    let sqlen  = na::sqnorm(&radial);
+
+    if sqlen.is_zero() {
+        // Only possible for a self-intersecting torus.
+        na::normalize(pt)
+    }
+    else {
+        let core = radial * (torus.major_radius() / sqlen.sqrt());
+
+        na::normalize(&(*pt - core))
+    }
+}
+
+/// Tests if `pt` is inside of the solid torus.
+fn torus_contains(torus: &Torus, pt: &Vect) -> bool {
+    let radial = (pt.x * pt.x + pt.z * pt.z).sqrt() - torus.major_radius();
+
+    radial * radial + pt.y * pt.y <= torus.minor_radius() * torus.minor_radius()
+}
+
+/// Computes the time of impact of a ray on a torus.
+///
+/// The first element of the result is `true` if the ray origin is inside of the torus.
+fn torus_toi_with_ray(torus: &Torus, ray: &Ray, solid: bool) -> (bool, Option<Scalar>) {
+    let major = torus.major_radius();
+    let minor = torus.minor_radius();
+
+    // The quartic is badly conditioned for far away origins: start from the bounding sphere.
+    let t0 = match ray::ball_toi_with_ray(na::zero(), major + minor, ray, true).val1() {
+        Some(t) => t,
+        None    => return (false, None)
+    };
+
+    let inside = t0.is_zero() && torus_contains(torus, &ray.orig);
+
+    if inside && solid {
+        return (true, Some(na::zero()))
+    }
+
+    let orig = ray.orig + ray.dir * t0;
+    let dir  = ray.dir;
+
+    // Substituting `orig + dir * t` into `(|p|² + R² - r²)² = 4R²(px² + pz²)`.
+    let _2: Scalar = na::cast(2.0f64);
+    let _4: Scalar = na::cast(4.0f64);
+    let sq_major   = major * major;
+    let g          = na::sqnorm(&dir);
+    let h          = _2 * na::dot(&orig, &dir);
+    let i          = na::sqnorm(&orig) + sq_major - minor * minor;
+    let j          = dir.x * dir.x + dir.z * dir.z;
+    let k          = _2 * (orig.x * dir.x + orig.z * dir.z);
+    let l          = orig.x * orig.x + orig.z * orig.z;
+
+    let mut roots = Vec::with_capacity(4);
+
+    utils::quartic_roots(g * g,
+                         _2 * g * h,
+                         h * h + _2 * g * i - _4 * sq_major * j,
+                         _2 * h * i - _4 * sq_major * k,
+                         i * i - _4 * sq_major * l,
+                         &mut roots);
+
+    // Roots slightly negative because of rounding errors are hits at the shifted origin.
+    let _eps: Scalar = Float::epsilon();
+    let tolerance    = -_eps.sqrt() * (major + minor) / g.sqrt();
+    let mut toi      = None;
+
+    for t in roots.iter() {
+        if *t >= tolerance && toi.map_or(true, |best| *t < best) {
+            toi = Some(*t)
+        }
+    }
+
+    (inside, toi.map(|t| t.max(na::zero()) + t0))
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use nalgebra::na::Vec3;
+    use nalgebra::na;
+    use geom::Torus;
+    use ray::{Ray, RayCast};
+
+    #[test]
+    fn test_ray_cast_on_torus() {
+        let torus = Torus::new(2.0f64, 0.5);
+
+        let side = Ray::new(Vec3::new(-5.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let top  = Ray::new(Vec3::new(2.0f64, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let hole = Ray::new(Vec3::new(0.0f64, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+
+        assert!(na::approx_eq(&torus.toi_with_ray(&side, true).unwrap(), &2.5));
+        assert!(na::approx_eq(&torus.toi_with_ray(&top, true).unwrap(), &4.5));
+        assert!(torus.toi_with_ray(&hole, true).is_none());
+
+        let n = torus.toi_and_normal_with_ray(&top, true).unwrap().normal;
+        assert!(na::approx_eq(&n, &Vec3::new(0.0, 1.0, 0.0)));
+    }
+}
@@ -0,0 +1,447 @@
+//! Casting of a whole implicit geometry along a direction.
+
+use std::intrinsics::TypeId;
+use std::any::{Any, AnyRefExt};
+use std::collections::HashMap;
+use nalgebra::na::Rotate;
+use nalgebra::na;
+use bounding_volume::BoundingVolume;
+use bounding_volume;
+use geom::{Geom, ConcaveGeom, Reflection, MinkowskiSum, Plane, Ball, Cuboid, Cone, Cylinder,
+           Capsule, Convex, Triangle, Segment, Compound, Mesh, HeightField, Torus,
+           DEFAULT_TORUS_NSUBDIVS};
+use implicit::Implicit;
+use ray::{Ray, RayCast};
+use math::{Scalar, Vect, Matrix};
+
+/// Structure containing the result of a successful shape cast.
+#[deriving(Show, Clone)]
+pub struct ShapeCastIntersection {
+    /// The time of impact of the cast geometry with the object. The cast geometry touches the
+    /// object after being translated by `dir * toi` where `dir` is the cast direction.
+    pub toi:    Scalar,
+
+    /// The normal of the object at the contact point. It points toward the cast geometry.
+    pub normal: Vect,
+
+    /// The contact point at the time of impact, in world space.
+    pub point:  Vect
+}
+
+impl ShapeCastIntersection {
+    #[inline]
+    /// Creates a new `ShapeCastIntersection`.
+    pub fn new(toi: Scalar, normal: Vect, point: Vect) -> ShapeCastIntersection {
+        ShapeCastIntersection {
+            toi:    toi,
+            normal: normal,
+            point:  point
+        }
+    }
+}
+
+/// Function casting an implicit geometry on a geometry.
+///
+/// The hit geometry has the type the function has been registered with.
+pub type ShapeCastFn = fn(&ShapeCastDispatcher, &Matrix, &Implicit<Vect, Matrix>, &Vect, &Matrix, &Geom)
+                          -> Option<ShapeCastIntersection>;
+
+/// Dispatcher casting implicit geometries on any `Geom`.
+///
+/// A shape cast function is registered for each type of hit geometry. Geometries no function is
+/// registered for are hit using `shape_cast_with_rays`.
+pub struct ShapeCastDispatcher {
+    shape_cast_fns: HashMap<TypeId, ShapeCastFn>
+}
+
+impl ShapeCastDispatcher {
+    /// Creates a new `ShapeCastDispatcher` able to cast implicit geometries on every geometry
+    /// supported by `ncollide`.
+    pub fn new() -> ShapeCastDispatcher {
+        let mut res = ShapeCastDispatcher::new_without_default();
+
+        res.register_shape_cast::<Plane>(plane_shape_cast);
+
+        res.register_default_implicit_shape_cast::<Ball>();
+        res.register_default_implicit_shape_cast::<Cuboid>();
+        res.register_default_implicit_shape_cast::<Cone>();
+        res.register_default_implicit_shape_cast::<Cylinder>();
+        res.register_default_implicit_shape_cast::<Capsule>();
+        res.register_default_implicit_shape_cast::<Convex>();
+        res.register_default_implicit_shape_cast::<Triangle>();
+        res.register_default_implicit_shape_cast::<Segment>();
+
+        res.register_shape_cast::<Torus>(torus_shape_cast);
+
+        res.register_default_concave_geom_shape_cast::<Compound>();
+        res.register_default_concave_geom_shape_cast::<Mesh>();
+        res.register_default_concave_geom_shape_cast::<HeightField>();
+
+        res
+    }
+
+    /// Creates a new `ShapeCastDispatcher` without any registered shape cast function.
+    pub fn new_without_default() -> ShapeCastDispatcher {
+        ShapeCastDispatcher {
+            shape_cast_fns: HashMap::new()
+        }
+    }
+
+    /// Registers a function casting implicit geometries on a given type of geometry.
+    pub fn register_shape_cast<G: 'static + Any>(&mut self, f: ShapeCastFn) {
+        self.shape_cast_fns.insert(TypeId::of::<G>(), f);
+    }
+
+    /// Unregister the shape cast function for a given type of geometry.
+    pub fn unregister_shape_cast<G: 'static + Any>(&mut self) {
+        self.shape_cast_fns.remove(&TypeId::of::<G>());
+    }
+
+    /// Registers the shape cast function on a given implicit geometry.
+    pub fn register_default_implicit_shape_cast<G: 'static + Implicit<Vect, Matrix>>(&mut self) {
+        self.register_shape_cast::<G>(implicit_shape_cast::<G>);
+    }
+
+    /// Registers the shape cast function on a given concave geometry.
+    pub fn register_default_concave_geom_shape_cast<G: 'static + ConcaveGeom>(&mut self) {
+        self.register_shape_cast::<G>(concave_geom_shape_cast::<G>);
+    }
+
+    /// Casts an implicit geometry on another geometry.
+    ///
+    /// If no shape cast function is registered for the type of `g2`, this falls back to
+    /// `shape_cast_with_rays`. Returns `None` if `g2` is not hit.
+    ///
+    /// # Arguments:
+    /// * `m1`  - the cast geometry transform.
+    /// * `g1`  - the cast geometry.
+    /// * `dir` - the direction of the cast.
+    /// * `m2`  - the hit geometry transform.
+    /// * `g2`  - the hit geometry.
+    pub fn shape_cast(&self,
+                      m1:  &Matrix,
+                      g1:  &Implicit<Vect, Matrix>,
+                      dir: &Vect,
+                      m2:  &Matrix,
+                      g2:  &Geom)
+                      -> Option<ShapeCastIntersection> {
+        match self.shape_cast_fns.find(&g2.get_dyn_type_id()) {
+            Some(f) => (*f)(self, m1, g1, dir, m2, g2),
+            None    => shape_cast_with_rays(m1, g1, dir, m2, g2)
+        }
+    }
+}
+
+/// Casts an implicit geometry on another implicit geometry.
+///
+/// This casts a ray on the Minkowski difference of both geometries.
+pub fn shape_cast_on_implicit<G1: Implicit<Vect, Matrix>, G2: Implicit<Vect, Matrix>>(
+                              m1:  &Matrix,
+                              g1:  &G1,
+                              dir: &Vect,
+                              m2:  &Matrix,
+                              g2:  &G2)
+                              -> Option<ShapeCastIntersection> {
+    let rg2 = Reflection::new(g2);
+    let cso = MinkowskiSum::new(m1, g1, m2, &rg2);
+
+    cso.toi_and_normal_with_ray(&Ray::new(na::zero(), -*dir), true).map(|inter| {
+        // The ray hits the CSO on a point `a - b` where `a` is the support point of `g1` along the
+        // CSO normal.
+        let m1_at_toi = na::append_translation(m1, &(*dir * inter.toi));
+        let point     = g1.support_point(&m1_at_toi, &inter.normal);
+
+        ShapeCastIntersection::new(inter.toi, -inter.normal, point)
+    })
+}
+
+/// Casts an implicit geometry on a plane.
+pub fn shape_cast_on_plane<G1: Implicit<Vect, Matrix>>(
+                           m1:     &Matrix,
+                           g1:     &G1,
+                           dir:    &Vect,
+                           mplane: &Matrix,
+                           plane:  &Plane)
+                           -> Option<ShapeCastIntersection> {
+    let plane_normal = mplane.rotate(&plane.normal());
+    let deepest      = g1.support_point(m1, &-plane_normal);
+
+    plane.toi_with_transform_and_ray(mplane, &Ray::new(deepest.clone(), dir.clone()), true).map(|toi| {
+        ShapeCastIntersection::new(toi, plane_normal, deepest + *dir * toi)
+    })
+}
+
+/// Casts an implicit geometry on a torus.
+///
+/// This is not an exact cast: the torus is approximated by a ring of `DEFAULT_TORUS_NSUBDIVS` (32)
+/// rounded segments inscribed in its core circle, like the `TorusImplicit` collision detector
+/// does.
+#[dim3]
+pub fn shape_cast_on_torus<G1: Implicit<Vect, Matrix>>(
+                           m1:     &Matrix,
+                           g1:     &G1,
+                           dir:    &Vect,
+                           mtorus: &Matrix,
+                           torus:  &Torus)
+                           -> Option<ShapeCastIntersection> {
+    let mut best: Option<ShapeCastIntersection> = None;
+
+    for i in range(0u, DEFAULT_TORUS_NSUBDIVS) {
+        let chord = torus.chord(DEFAULT_TORUS_NSUBDIVS, i);
+
+        match shape_cast_on_implicit(m1, g1, dir, mtorus, &chord) {
+            Some(inter) => {
+                if best.as_ref().map_or(true, |b| inter.toi < b.toi) {
+                    best = Some(inter)
+                }
+            },
+            None => { }
+        }
+    }
+
+    best
+}
+
+/// Casts an implicit geometry on a torus.
+///
+/// This always returns `None` in 2d and 4d: tori are defined in 3d only.
+#[not_dim3]
+pub fn shape_cast_on_torus<G1: Implicit<Vect, Matrix>>(_: &Matrix,
+                                                       _: &G1,
+                                                       _: &Vect,
+                                                       _: &Matrix,
+                                                       _: &Torus)
+                                                       -> Option<ShapeCastIntersection> {
+    None
+}
+
+/// Casts an implicit geometry on a concave geometry.
+///
+/// Every part of the concave geometry which might be hit is tested with the given dispatcher.
+pub fn shape_cast_on_concave<G2: ConcaveGeom>(dispatcher: &ShapeCastDispatcher,
+                                              m1:         &Matrix,
+                                              g1:         &Implicit<Vect, Matrix>,
+                                              dir:        &Vect,
+                                              m2:         &Matrix,
+                                              g2:         &G2)
+                                              -> Option<ShapeCastIntersection> {
+    let ls_m1    = na::inv(m2).expect("The transformation `m2` must be inversible.") * *m1;
+    let ls_dir   = m2.inv_rotate(dir);
+    let ls_aabb1 = bounding_volume::implicit_shape_aabb(&ls_m1, &g1);
+    let all      = ls_aabb1.merged(&g2.aabb(&na::one()));
+
+    // Once it has been moved by the diagonal of `all`, the AABB of `g1` cannot hit `g2` anymore.
+    let max_toi     = na::norm(&(*all.maxs() - *all.mins())) / na::norm(dir);
+    let swept_aabb1 = ls_aabb1.merged(&na::append_translation(&ls_aabb1, &(ls_dir * max_toi)));
+
+    let mut interferences = Vec::new();
+
+    g2.approx_interferences_with_aabb(&swept_aabb1, &mut interferences);
+
+    let mut best: Option<ShapeCastIntersection> = None;
+
+    for i in interferences.iter() {
+        let inter = g2.map_transformed_part_at(m2, *i, |m2, g2| dispatcher.shape_cast(m1, g1, dir, m2, g2));
+
+        match inter {
+            Some(inter) => {
+                let better = match best {
+                    Some(ref b) => inter.toi < b.toi,
+                    None        => true
+                };
+
+                if better {
+                    best = Some(inter)
+                }
+            },
+            None => { }
+        }
+    }
+
+    best
+}
+
+/// Casts an implicit geometry on any geometry using ray casting.
+///
+/// This is only an approximation: rays are cast on `g2` from the support points of `g1` along the
+/// cast direction and along the directions orthogonal to it. Thus, parts of `g2` passing between
+/// those rays are missed. It is used by the `ShapeCastDispatcher` for the geometries it has no
+/// shape cast function for.
+pub fn shape_cast_with_rays(m1:  &Matrix,
+                            g1:  &Implicit<Vect, Matrix>,
+                            dir: &Vect,
+                            m2:  &Matrix,
+                            g2:  &Geom)
+                            -> Option<ShapeCastIntersection> {
+    let mut origins = vec!(g1.support_point(m1, dir));
+
+    na::orthonormal_subspace_basis(&na::normalize(dir), |b| {
+        origins.push(g1.support_point(m1, &b));
+        origins.push(g1.support_point(m1, &-b));
+
+        true
+    });
+
+    let mut best: Option<ShapeCastIntersection> = None;
+
+    for orig in origins.iter() {
+        match g2.toi_and_normal_with_transform_and_ray(m2, &Ray::new(orig.clone(), dir.clone()), true) {
+            Some(inter) => {
+                if best.as_ref().map_or(true, |b| inter.toi < b.toi) {
+                    best = Some(ShapeCastIntersection::new(inter.toi, inter.normal, *orig + *dir * inter.toi))
+                }
+            },
+            None => { }
+        }
+    }
+
+    best
+}
+
+/*
+ *
+ * Shape cast functions
+ *
+ */
+fn plane_shape_cast(_:   &ShapeCastDispatcher,
+                    m1:  &Matrix,
+                    g1:  &Implicit<Vect, Matrix>,
+                    dir: &Vect,
+                    m2:  &Matrix,
+                    g2:  &Geom)
+                    -> Option<ShapeCastIntersection> {
+    shape_cast_on_plane(m1, &g1, dir, m2, g2.downcast_ref::<Plane>().expect("Invalid geometry."))
+}
+
+fn implicit_shape_cast<G: 'static + Implicit<Vect, Matrix>>(_:   &ShapeCastDispatcher,
+                                                            m1:  &Matrix,
+                                                            g1:  &Implicit<Vect, Matrix>,
+                                                            dir: &Vect,
+                                                            m2:  &Matrix,
+                                                            g2:  &Geom)
+                                                            -> Option<ShapeCastIntersection> {
+    shape_cast_on_implicit(m1, &g1, dir, m2, g2.downcast_ref::<G>().expect("Invalid geometry."))
+}
+
+fn torus_shape_cast(_:   &ShapeCastDispatcher,
+                    m1:  &Matrix,
+                    g1:  &Implicit<Vect, Matrix>,
+                    dir: &Vect,
+                    m2:  &Matrix,
+                    g2:  &Geom)
+                    -> Option<ShapeCastIntersection> {
+    shape_cast_on_torus(m1, &g1, dir, m2, g2.downcast_ref::<Torus>().expect("Invalid geometry."))
+}
+
+fn concave_geom_shape_cast<G: 'static + ConcaveGeom>(dispatcher: &ShapeCastDispatcher,
+                                                     m1:         &Matrix,
+                                                     g1:         &Implicit<Vect, Matrix>,
+                                                     dir:        &Vect,
+                                                     m2:         &Matrix,
+                                                     g2:         &Geom)
+                                                     -> Option<ShapeCastIntersection> {
+    shape_cast_on_concave(dispatcher, m1, g1, dir, m2, g2.downcast_ref::<G>().expect("Invalid geometry."))
+}
+
+#[cfg(test, dim3, f64)]
+mod test {
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::{Geom, Ball, Cuboid, Plane, Torus, Compound, CompoundData};
+    use implicit::Implicit;
+    use math::{Vect, Matrix};
+    use super::ShapeCastDispatcher;
+
+    #[test]
+    fn test_shape_cast_on_implicit() {
+        let dispatcher = ShapeCastDispatcher::new();
+        let ball       = Ball::new(1.0f64);
+        let ball       = &ball as &Implicit<Vect, Matrix>;
+        let cuboid     = Cuboid::new_with_margin(Vec3::new(1.0f64, 1.0, 1.0), 0.0);
+        let m1         = Iso3::new(na::zero(), na::zero());
+        let m2         = Iso3::new(Vec3::new(5.0f64, 0.0, 0.0), na::zero());
+
+        let inter = dispatcher.shape_cast(&m1, ball, &Vec3::x(), &m2, &cuboid as &Geom).unwrap();
+        assert!(na::approx_eq_eps(&inter.toi, &3.0, &1.0e-3));
+        assert!(na::approx_eq_eps(&inter.normal, &Vec3::new(-1.0, 0.0, 0.0), &1.0e-3));
+        assert!(na::approx_eq_eps(&inter.point, &Vec3::new(4.0, 0.0, 0.0), &1.0e-3));
+
+        assert!(dispatcher.shape_cast(&m1, ball, &-Vec3::x(), &m2, &cuboid as &Geom).is_none());
+    }
+
+    #[test]
+    fn test_shape_cast_on_plane() {
+        let dispatcher = ShapeCastDispatcher::new();
+        let ball       = Ball::new(1.0f64);
+        let ball       = &ball as &Implicit<Vect, Matrix>;
+        let plane      = Plane::new(Vec3::y());
+        let m1         = Iso3::new(Vec3::new(0.0f64, 5.0, 0.0), na::zero());
+        let m2         = Iso3::new(na::zero(), na::zero());
+
+        let inter = dispatcher.shape_cast(&m1, ball, &-Vec3::y(), &m2, &plane as &Geom).unwrap();
+        assert!(na::approx_eq(&inter.toi, &4.0));
+        assert!(na::approx_eq(&inter.normal, &Vec3::y()));
+        assert!(na::approx_eq(&inter.point, &na::zero()));
+
+        assert!(dispatcher.shape_cast(&m1, ball, &Vec3::y(), &m2, &plane as &Geom).is_none());
+    }
+
+    #[test]
+    fn test_shape_cast_on_compound() {
+        let mut data = CompoundData::new();
+        data.push_geom(Iso3::new(Vec3::new(-3.0f64, 0.0, 0.0), na::zero()), Ball::new(1.0), 1.0);
+        data.push_geom(Iso3::new(Vec3::new(3.0f64, 0.0, 0.0), na::zero()), Ball::new(1.0), 1.0);
+
+        let dispatcher = ShapeCastDispatcher::new();
+        let compound   = Compound::new(data);
+        let ball       = Ball::new(1.0f64);
+        let ball       = &ball as &Implicit<Vect, Matrix>;
+        let m1         = Iso3::new(Vec3::new(10.0f64, 0.0, 0.0), na::zero());
+        let m2         = Iso3::new(na::zero(), na::zero());
+
+        // The closest part is hit first.
+        let inter = dispatcher.shape_cast(&m1, ball, &-Vec3::x(), &m2, &compound as &Geom).unwrap();
+        assert!(na::approx_eq_eps(&inter.toi, &5.0, &1.0e-3));
+        assert!(na::approx_eq_eps(&inter.point, &Vec3::new(4.0, 0.0, 0.0), &1.0e-3));
+
+        // Between the parts.
+        let m1 = Iso3::new(Vec3::new(0.0f64, 10.0, 0.0), na::zero());
+        assert!(dispatcher.shape_cast(&m1, ball, &-Vec3::y(), &m2, &compound as &Geom).is_none());
+    }
+
+    #[test]
+    fn test_shape_cast_on_torus() {
+        let dispatcher = ShapeCastDispatcher::new();
+        let torus      = Torus::new(2.0f64, 0.5);
+        let ball       = Ball::new(0.5f64);
+        let ball       = &ball as &Implicit<Vect, Matrix>;
+        let m2         = Iso3::new(na::zero(), na::zero());
+
+        let m1    = Iso3::new(Vec3::new(2.0f64, 5.0, 0.0), na::zero());
+        let inter = dispatcher.shape_cast(&m1, ball, &-Vec3::y(), &m2, &torus as &Geom).unwrap();
+        assert!(na::approx_eq_eps(&inter.toi, &4.0, &1.0e-3));
+        assert!(na::approx_eq_eps(&inter.normal, &Vec3::y(), &1.0e-3));
+
+        // The ball passes through the hole of the torus.
+        let m1 = Iso3::new(Vec3::new(0.0f64, 5.0, 0.0), na::zero());
+        assert!(dispatcher.shape_cast(&m1, ball, &-Vec3::y(), &m2, &torus as &Geom).is_none());
+    }
+
+    #[test]
+    fn test_shape_cast_fallback() {
+        let mut dispatcher = ShapeCastDispatcher::new();
+        let ball           = Ball::new(1.0f64);
+        let ball           = &ball as &Implicit<Vect, Matrix>;
+        let cuboid         = Cuboid::new_with_margin(Vec3::new(1.0f64, 1.0, 1.0), 0.0);
+        let m1             = Iso3::new(na::zero(), na::zero());
+        let m2             = Iso3::new(Vec3::new(5.0f64, 0.0, 0.0), na::zero());
+
+        // Without a registered function, rays are cast from the support points of the ball.
+        dispatcher.unregister_shape_cast::<Cuboid>();
+
+        let inter = dispatcher.shape_cast(&m1, ball, &Vec3::x(), &m2, &cuboid as &Geom).unwrap();
+        assert!(na::approx_eq(&inter.toi, &3.0));
+        assert!(na::approx_eq(&inter.normal, &Vec3::new(-1.0, 0.0, 0.0)));
+        assert!(na::approx_eq(&inter.point, &Vec3::new(4.0, 0.0, 0.0)));
+    }
+}
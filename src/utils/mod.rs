@@ -17,6 +17,8 @@ pub use utils::any_private::AnyPrivate;
 pub use utils::as_bytes::AsBytes;
 pub use utils::cov::{cov, cov_and_center, center_reduce};
 pub use utils::sort::sort3;
+pub use utils::segment::{closest_point_on_segment, segment_segment_closest_points};
+pub use utils::polynomial::{quadratic_roots, cubic_roots, quartic_roots};
 
 
 pub mod symbolic;
@@ -35,3 +37,5 @@ pub mod any_private;
 pub mod as_bytes;
 mod cov;
 mod sort;
+mod segment;
+mod polynomial;
@@ -0,0 +1,198 @@
+use std::num::{Zero, Float};
+use nalgebra::na::Cast;
+use nalgebra::na;
+
+/// Computes the real roots of the quadratic polynomial `a * x^2 + b * x + c`.
+///
+/// The roots are pushed on `out`. A double root is pushed only once.
+pub fn quadratic_roots<N: Float + Cast<f64>>(a: N, b: N, c: N, out: &mut Vec<N>) {
+    if a.is_zero() {
+        if !b.is_zero() {
+            out.push(-c / b)
+        }
+
+        return
+    }
+
+    let _2:    N = na::cast(2.0f64);
+    let _4:    N = na::cast(4.0f64);
+    let delta    = b * b - _4 * a * c;
+
+    if delta.is_zero() {
+        out.push(-b / (_2 * a))
+    }
+    else if delta > na::zero() {
+        // Numerically stable formulation avoiding cancellations.
+        let sqrt_delta = delta.sqrt();
+        let q          = if b < na::zero() { (sqrt_delta - b) / _2 } else { -(b + sqrt_delta) / _2 };
+
+        out.push(q / a);
+
+        if !q.is_zero() {
+            out.push(c / q)
+        }
+    }
+}
+
+/// Computes the real roots of the monic cubic polynomial `x^3 + a * x^2 + b * x + c`.
+///
+/// The roots are pushed on `out`.
+pub fn cubic_roots<N: Float + Cast<f64>>(a: N, b: N, c: N, out: &mut Vec<N>) {
+    let _2:    N = na::cast(2.0f64);
+    let _3:    N = na::cast(3.0f64);
+    let _9:    N = na::cast(9.0f64);
+    let _27:   N = na::cast(27.0f64);
+    let _54:   N = na::cast(54.0f64);
+    let _2_pi: N = Float::two_pi();
+
+    let q  = (a * a - _3 * b) / _9;
+    let r  = (_2 * a * a * a - _9 * a * b + _27 * c) / _54;
+    let q3 = q * q * q;
+
+    if r * r < q3 {
+        // Three real roots.
+        let theta  = (r / q3.sqrt()).acos();
+        let sqrt_q = q.sqrt();
+
+        for k in range(0u, 3) {
+            let k: N = na::cast(k as f64);
+
+            out.push(-_2 * sqrt_q * ((theta + _2_pi * k) / _3).cos() - a / _3)
+        }
+    }
+    else {
+        // One real root.
+        let mut aa = (r.abs() + (r * r - q3).sqrt()).cbrt();
+
+        if r > na::zero() {
+            aa = -aa
+        }
+
+        let bb = if aa.is_zero() { na::zero() } else { q / aa };
+
+        out.push(aa + bb - a / _3)
+    }
+}
+
+/// Computes the real roots of the polynomial `a * x^4 + b * x^3 + c * x^2 + d * x + e`.
+///
+/// The roots are computed with Ferrari's method and then refined with a few Newton iterations.
+/// They are pushed on `out` in no particular order.
+pub fn quartic_roots<N: Float + Cast<f64>>(a: N, b: N, c: N, d: N, e: N, out: &mut Vec<N>) {
+    if a.is_zero() {
+        let mut roots = Vec::new();
+
+        if b.is_zero() {
+            quadratic_roots(c, d, e, out);
+        }
+        else {
+            cubic_roots(c / b, d / b, e / b, &mut roots);
+            out.push_all_move(roots);
+        }
+
+        return
+    }
+
+    let _eps: N = Float::epsilon();
+    let _2:   N = na::cast(2.0f64);
+    let _3:   N = na::cast(3.0f64);
+    let _4:   N = na::cast(4.0f64);
+    let _8:   N = na::cast(8.0f64);
+    let _16:  N = na::cast(16.0f64);
+    let _256: N = na::cast(256.0f64);
+
+    // Monic polynomial.
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+
+    // Depressed quartic y^4 + p * y^2 + q * y + r with x = y - b / 4.
+    let shift = b / _4;
+    let bb    = b * b;
+    let p     = c - _3 * bb / _8;
+    let q     = d - b * c / _2 + bb * b / _8;
+    let r     = e - b * d / _4 + bb * c / _16 - _3 * bb * bb / _256;
+
+    let start = out.len();
+
+    if q.abs() <= _eps {
+        // Biquadratic equation.
+        let mut zs = Vec::new();
+
+        quadratic_roots(na::one(), p, r, &mut zs);
+
+        for z in zs.iter() {
+            if *z > na::zero() {
+                let y = z.sqrt();
+
+                out.push(y - shift);
+                out.push(-y - shift);
+            }
+            else if z.is_zero() {
+                out.push(-shift);
+            }
+        }
+    }
+    else {
+        // Find the positive root `m` of the resolvent cubic.
+        let mut ms = Vec::new();
+
+        cubic_roots(p, p * p / _4 - r, -q * q / _8, &mut ms);
+
+        let mut m: N = na::zero();
+
+        for mi in ms.iter() {
+            if *mi > m {
+                m = *mi
+            }
+        }
+
+        if m <= na::zero() {
+            return
+        }
+
+        // y^4 + p * y^2 + q * y + r = (y^2 + p / 2 + m)^2 - 2m (y - q / (4m))^2
+        let sqrt_2m = (_2 * m).sqrt();
+        let k       = q / (_2 * sqrt_2m);
+        let mut ys  = Vec::new();
+
+        quadratic_roots(na::one(), -sqrt_2m, p / _2 + m + k, &mut ys);
+        quadratic_roots(na::one(), sqrt_2m, p / _2 + m - k, &mut ys);
+
+        for y in ys.iter() {
+            out.push(*y - shift)
+        }
+    }
+
+    // Polish the roots.
+    for x in out.mut_slice_from(start).mut_iter() {
+        for _ in range(0u, 2u) {
+            let f  = (((*x + b) * *x + c) * *x + d) * *x + e;
+            let df = ((_4 * *x + _3 * b) * *x + _2 * c) * *x + d;
+
+            if df.is_zero() {
+                break;
+            }
+
+            *x = *x - f / df;
+        }
+    }
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use super::quartic_roots;
+    use nalgebra::na;
+
+    #[test]
+    fn test_quartic_roots() {
+        // (x - 1)(x + 2)(x - 3)(x + 4) = x^4 + 2x^3 - 13x^2 - 14x + 24
+        let mut roots = Vec::new();
+
+        quartic_roots(1.0f64, 2.0, -13.0, -14.0, 24.0, &mut roots);
+
+        assert!(roots.len() == 4);
+
+        for expected in [ 1.0f64, -2.0, 3.0, -4.0 ].iter() {
+            assert!(roots.iter().any(|r| na::approx_eq(r, expected)));
+        }
+    }
+}
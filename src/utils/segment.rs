@@ -0,0 +1,78 @@
+use nalgebra::na::FloatVec;
+use nalgebra::na;
+
+#[inline]
+fn clamp01<N: Float>(t: N) -> N {
+    t.max(na::zero()).min(na::one())
+}
+
+/// Computes the point of the segment `[a, b]` closest to `p`.
+///
+/// Returns the closest point and its parameter `t` on the segment, i.e., the closest point is
+/// `a + (b - a) * t`.
+pub fn closest_point_on_segment<N: Float, V: FloatVec<N> + Clone>(p: &V, a: &V, b: &V) -> (V, N) {
+    let ab    = *b - *a;
+    let sqlen = na::sqnorm(&ab);
+
+    if sqlen <= Float::epsilon() {
+        (a.clone(), na::zero())
+    }
+    else {
+        let t = clamp01(na::dot(&(*p - *a), &ab) / sqlen);
+
+        (*a + ab * t, t)
+    }
+}
+
+/// Computes the closest points of the segments `[a1, b1]` and `[a2, b2]`.
+///
+/// If the segments are parallel, one of the pairs of closest points is returned.
+pub fn segment_segment_closest_points<N: Float, V: FloatVec<N> + Clone>(a1: &V, b1: &V, a2: &V, b2: &V)
+                                                                       -> (V, V) {
+    let _eps: N = Float::epsilon();
+
+    let d1 = *b1 - *a1;
+    let d2 = *b2 - *a2;
+    let r  = *a1 - *a2;
+    let a  = na::sqnorm(&d1);
+    let e  = na::sqnorm(&d2);
+    let f  = na::dot(&d2, &r);
+
+    let mut s: N;
+    let mut t: N;
+
+    if a <= _eps && e <= _eps {
+        s = na::zero();
+        t = na::zero();
+    }
+    else if a <= _eps {
+        s = na::zero();
+        t = clamp01(f / e);
+    }
+    else {
+        let c = na::dot(&d1, &r);
+
+        if e <= _eps {
+            t = na::zero();
+            s = clamp01(-c / a);
+        }
+        else {
+            let b     = na::dot(&d1, &d2);
+            let denom = a * e - b * b;
+
+            s = if denom > _eps { clamp01((b * f - c * e) / denom) } else { na::zero() };
+            t = (b * s + f) / e;
+
+            if t < na::zero() {
+                t = na::zero();
+                s = clamp01(-c / a);
+            }
+            else if t > na::one() {
+                t = na::one();
+                s = clamp01((b - c) / a);
+            }
+        }
+    }
+
+    (*a1 + d1 * s, *a2 + d2 * t)
+}
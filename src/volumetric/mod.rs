@@ -15,3 +15,5 @@ mod volumetric_cone;
 mod volumetric_capsule;
 mod volumetric_compound;
 mod volumetric_convex;
+#[dim3]
+mod volumetric_torus;
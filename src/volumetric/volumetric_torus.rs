@@ -0,0 +1,97 @@
+use std::num::Float;
+use nalgebra::na::Indexable;
+use nalgebra::na;
+use geom::Torus;
+use volumetric::Volumetric;
+use math::{Scalar, Vect, AngularInertia};
+
+impl Volumetric for Torus {
+    #[inline]
+    fn surface(&self) -> Scalar {
+        let _pi: Scalar = Float::pi();
+
+        _pi * _pi * self.major_radius() * self.minor_radius() * na::cast(4.0f64)
+    }
+
+    #[inline]
+    fn volume(&self) -> Scalar {
+        let _pi: Scalar = Float::pi();
+
+        _pi * _pi * self.major_radius() * self.minor_radius() * self.minor_radius() * na::cast(2.0f64)
+    }
+
+    #[inline]
+    fn center_of_mass(&self) -> Vect {
+        na::zero()
+    }
+
+    fn unit_angular_inertia(&self) -> AngularInertia {
+        let sq_major = self.major_radius() * self.major_radius();
+        let sq_minor = self.minor_radius() * self.minor_radius();
+        let diameter = sq_major / na::cast(2.0f64) + sq_minor * na::cast(5.0f64 / 8.0);
+        let axial    = sq_major + sq_minor * na::cast(3.0f64 / 4.0);
+
+        let mut res: AngularInertia = na::zero();
+
+        res.set((0, 0), diameter.clone());
+        res.set((1, 1), axial);
+        res.set((2, 2), diameter);
+
+        res
+    }
+}
+
+#[cfg(test, f64)]
+mod test {
+    use nalgebra::na::Indexable;
+    use nalgebra::na;
+    use geom::Torus;
+    use volumetric::Volumetric;
+
+    #[test]
+    fn test_torus_volumetric() {
+        let torus = Torus::new(2.0f64, 1.0);
+
+        // The surface is the derivative of the volume wrt. the minor radius.
+        let h     = 1.0e-3f64;
+        let thick = Torus::new(2.0f64, 1.0 + h).volume();
+        let thin  = Torus::new(2.0f64, 1.0 - h).volume();
+
+        assert!(na::approx_eq_eps(&((thick - thin) / (2.0 * h)), &torus.surface(), &1.0e-6));
+
+        // Integrate the volume and the inertia on a grid sampling the torus AABB.
+        let (nxz, ny) = (100u, 33u);
+        let dxz       = 6.0f64 / (nxz as f64);
+        let dy        = 2.0f64 / (ny as f64);
+
+        let mut n        = 0u;
+        let mut axial    = 0.0f64;
+        let mut diameter = 0.0f64;
+
+        for i in range(0u, nxz) {
+            let x = -3.0 + (i as f64 + 0.5) * dxz;
+
+            for j in range(0u, ny) {
+                let y = -1.0 + (j as f64 + 0.5) * dy;
+
+                for k in range(0u, nxz) {
+                    let z      = -3.0 + (k as f64 + 0.5) * dxz;
+                    let radial = (x * x + z * z).sqrt() - 2.0;
+
+                    if radial * radial + y * y <= 1.0 {
+                        n        = n + 1;
+                        axial    = axial + x * x + z * z;
+                        diameter = diameter + y * y + z * z;
+                    }
+                }
+            }
+        }
+
+        let inertia = torus.unit_angular_inertia();
+
+        assert!(na::approx_eq_eps(&((n as f64) * dxz * dxz * dy), &torus.volume(), &0.2));
+        assert!(na::approx_eq_eps(&(axial / (n as f64)), &inertia.at((1, 1)), &1.0e-2));
+        assert!(na::approx_eq_eps(&(diameter / (n as f64)), &inertia.at((0, 0)), &1.0e-2));
+        assert!(na::approx_eq_eps(&(diameter / (n as f64)), &inertia.at((2, 2)), &1.0e-2));
+    }
+}